@@ -0,0 +1,99 @@
+use ark_sqlx::providers::marketplace::FailedBlockRangeProvider;
+use ark_sqlx::providers::SqlxCtxPg;
+use std::future::Future;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+const RETRY_POLL_SECONDS: u64 = 10;
+const RETRY_BATCH_SIZE: i64 = 10;
+
+/// Records a block range that could not be indexed so that it is retried later.
+pub async fn register_failed_range(
+    client: &SqlxCtxPg,
+    chain_id: &str,
+    from_block: u64,
+    to_block: u64,
+    error: &str,
+) {
+    match FailedBlockRangeProvider::register_failure(client, chain_id, from_block, to_block, error)
+        .await
+    {
+        Ok(range) => warn!(
+            "Blocks range {} - {} queued for retry (attempt {}, next retry at {})",
+            from_block, to_block, range.attempts, range.next_retry_timestamp
+        ),
+        Err(e) => error!(
+            "Failed to queue blocks range {} - {} for retry: {}",
+            from_block, to_block, e
+        ),
+    }
+}
+
+/// Re-indexes the failed block ranges whose backoff has elapsed, forever.
+pub async fn retry_failed_ranges<F, Fut>(client: Arc<SqlxCtxPg>, chain_id: String, index_range: F)
+where
+    F: Fn(u64, u64) -> Fut,
+    Fut: Future<Output = Result<(), String>>,
+{
+    loop {
+        match FailedBlockRangeProvider::get_due_ranges(&client, &chain_id, RETRY_BATCH_SIZE).await {
+            Ok(ranges) => {
+                for range in ranges {
+                    let (from_block, to_block) = (range.from_block as u64, range.to_block as u64);
+                    info!(
+                        "Retrying blocks range {} - {} (attempt {})",
+                        from_block,
+                        to_block,
+                        range.attempts + 1
+                    );
+
+                    match index_range(from_block, to_block).await {
+                        Ok(_) => {
+                            if let Err(e) = FailedBlockRangeProvider::resolve(
+                                &client,
+                                range.failed_block_range_id,
+                            )
+                            .await
+                            {
+                                error!(
+                                    "Failed to resolve blocks range {} - {}: {}",
+                                    from_block, to_block, e
+                                );
+                            }
+                        }
+                        Err(e) => {
+                            register_failed_range(&client, &chain_id, from_block, to_block, &e)
+                                .await
+                        }
+                    }
+                }
+            }
+            Err(e) => error!("Failed to fetch block ranges to retry: {}", e),
+        }
+
+        tokio::time::sleep(tokio::time::Duration::from_secs(RETRY_POLL_SECONDS)).await;
+    }
+}
+
+/// Prints the block ranges that are still missing.
+pub async fn list_gaps(client: &SqlxCtxPg, chain_id: &str) -> anyhow::Result<()> {
+    let ranges = FailedBlockRangeProvider::get_open_ranges(client, chain_id).await?;
+    if ranges.is_empty() {
+        println!("No gaps for chain {}", chain_id);
+        return Ok(());
+    }
+
+    println!("from_block\tto_block\tattempts\tnext_retry\tlast_error");
+    for range in ranges {
+        println!(
+            "{}\t{}\t{}\t{}\t{}",
+            range.from_block,
+            range.to_block,
+            range.attempts,
+            range.next_retry_timestamp,
+            range.last_error.unwrap_or_default()
+        );
+    }
+
+    Ok(())
+}
@@ -1,5 +1,8 @@
+mod block_range_retry;
+mod reorg_detector;
 mod sana_observer;
 use anyhow::Result;
+use ark_sqlx::providers::SqlxCtxPg;
 use arkproject::{
     sana::storage::sqlx::PostgresStorage,
     sana::{Sana, SanaConfig},
@@ -7,8 +10,10 @@ use arkproject::{
 };
 use aws_config::BehaviorVersion;
 use chrono::Utc;
+use clap::{App, Arg};
 use dotenv::dotenv;
 use regex::Regex;
+use reorg_detector::{ReorgDetector, DEFAULT_REORG_DEPTH};
 use sana_observer::SanaObserver;
 use serde::Deserialize;
 use starknet::{
@@ -60,6 +65,14 @@ async fn main() -> Result<()> {
 
     init_logging();
 
+    let matches = App::new("ark-indexer-marketplace")
+        .arg(
+            Arg::with_name("list-gaps")
+                .long("list-gaps")
+                .help("Lists the block ranges that are still waiting to be re-indexed and exits"),
+        )
+        .get_matches();
+
     let rpc_url = env::var("RPC_PROVIDER").expect("RPC_PROVIDER must be set");
     let rpc_url_converted = Url::parse(&rpc_url).unwrap();
 
@@ -70,6 +83,11 @@ async fn main() -> Result<()> {
     let force_mode = std::env::var("FORCE_MODE").map_or(false, |val| val == "true");
     let is_head_of_chain = std::env::var("HEAD_OF_CHAIN").map_or(false, |val| val == "true");
 
+    let sqlx_client = Arc::new(SqlxCtxPg::new(&db_url).await?);
+    if matches.is_present("list-gaps") {
+        return block_range_retry::list_gaps(&sqlx_client, &chain_id).await;
+    }
+
     info!(
         "Starting Indexer. Version={:?}, Identifier={}, Force Mode={}",
         indexer_version, indexer_identifier, force_mode
@@ -88,7 +106,7 @@ async fn main() -> Result<()> {
         HttpTransport::new(rpc_url_converted.clone()),
     )));
 
-    let sana_task = Sana::new(
+    let sana_task = Arc::new(Sana::new(
         Arc::clone(&starknet_client),
         storage,
         Arc::clone(&sana_observer),
@@ -96,7 +114,7 @@ async fn main() -> Result<()> {
             indexer_version,
             indexer_identifier,
         },
-    );
+    ));
 
     if !is_head_of_chain {
         let from_value = env::var("FROM_BLOCK")
@@ -134,6 +152,40 @@ async fn main() -> Result<()> {
         }
     }
 
+    let reorg_depth = env::var("REORG_DEPTH")
+        .ok()
+        .and_then(|val| val.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_REORG_DEPTH);
+    let reorg_detector = ReorgDetector::new(
+        Arc::clone(&provider),
+        Arc::clone(&sqlx_client),
+        chain_id.clone(),
+        reorg_depth,
+    );
+
+    let retry_sana_task = Arc::clone(&sana_task);
+    let retry_chain_id = chain_id.clone();
+    tokio::spawn(block_range_retry::retry_failed_ranges(
+        Arc::clone(&sqlx_client),
+        chain_id.clone(),
+        move |from_block, to_block| {
+            let sana_task = Arc::clone(&retry_sana_task);
+            let chain_id = retry_chain_id.clone();
+            async move {
+                // The range may have been partially indexed before failing
+                sana_task
+                    .index_block_range(
+                        BlockId::Number(from_block),
+                        BlockId::Number(to_block),
+                        true,
+                        chain_id.as_str(),
+                    )
+                    .await
+                    .map_err(|e| e.to_string())
+            }
+        },
+    ));
+
     let sleep_secs = 1;
 
     let current_block = match provider.block_number().await {
@@ -194,6 +246,20 @@ async fn main() -> Result<()> {
                 continue;
             }
 
+            match reorg_detector.check(start).await {
+                Ok(Some(resume_from)) => {
+                    warn!("Re-indexing from block {resume_from} after reorg");
+                    from = resume_from;
+                    continue;
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    error!("Reorg check failed at block {start}: {e}");
+                    tokio::time::sleep(tokio::time::Duration::from_secs(sleep_secs)).await;
+                    continue;
+                }
+            }
+
             trace!("Fetching blocks {start} - {end}");
             match sana_task
                 .index_block_range(
@@ -207,6 +273,10 @@ async fn main() -> Result<()> {
                 Ok(_) => {
                     trace!("Blocks successfully indexed");
 
+                    if let Err(e) = reorg_detector.record(start, end).await {
+                        warn!("Failed to record block hashes {start} - {end}: {e}");
+                    }
+
                     if let Some(to) = to {
                         if end >= to {
                             trace!("`to` block was reached, exit.");
@@ -220,9 +290,16 @@ async fn main() -> Result<()> {
                 Err(e) => {
                     error!("Blocks indexing error: {}", e);
 
-                    // TODO: for now, any failure on the block range, we skip it.
-                    // Can be changed as needed.
-                    warn!("Skipping blocks range: {} - {}", start, end);
+                    // The range is re-indexed in the background, the head of
+                    // chain keeps moving forward.
+                    block_range_retry::register_failed_range(
+                        &sqlx_client,
+                        &chain_id,
+                        start,
+                        end,
+                        &e.to_string(),
+                    )
+                    .await;
                     from = end + 1;
                 }
             };
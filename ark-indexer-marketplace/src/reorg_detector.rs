@@ -0,0 +1,130 @@
+use anyhow::Result;
+use ark_sqlx::providers::marketplace::reorg::{IndexedBlockHash, ReorgProvider};
+use ark_sqlx::providers::SqlxCtxPg;
+use starknet::{
+    core::types::{BlockId, MaybePendingBlockWithTxHashes},
+    providers::{AnyProvider, Provider},
+};
+use std::sync::Arc;
+use tracing::{error, trace, warn};
+
+/// Number of indexed blocks kept to find the common ancestor of a reorg.
+pub const DEFAULT_REORG_DEPTH: u64 = 64;
+
+pub struct ReorgDetector {
+    provider: Arc<AnyProvider>,
+    client: Arc<SqlxCtxPg>,
+    chain_id: String,
+    depth: u64,
+}
+
+impl ReorgDetector {
+    pub fn new(
+        provider: Arc<AnyProvider>,
+        client: Arc<SqlxCtxPg>,
+        chain_id: String,
+        depth: u64,
+    ) -> Self {
+        Self {
+            provider,
+            client,
+            chain_id,
+            depth,
+        }
+    }
+
+    async fn fetch_block_hash(&self, block_number: u64) -> Result<IndexedBlockHash> {
+        match self
+            .provider
+            .get_block_with_tx_hashes(BlockId::Number(block_number))
+            .await?
+        {
+            MaybePendingBlockWithTxHashes::Block(block) => Ok(IndexedBlockHash {
+                block_number: block.block_number,
+                block_hash: block.block_hash.to_fixed_hex_string(),
+                parent_hash: block.parent_hash.to_fixed_hex_string(),
+                block_timestamp: block.timestamp,
+            }),
+            MaybePendingBlockWithTxHashes::PendingBlock(_) => {
+                Err(anyhow::anyhow!("Block {} is still pending", block_number))
+            }
+        }
+    }
+
+    /// Verifies that `block_number` extends the indexed chain. On a reorg, the
+    /// orphaned blocks are reverted and the first one of them is returned so
+    /// that indexing can resume from the common ancestor.
+    pub async fn check(&self, block_number: u64) -> Result<Option<u64>> {
+        let Some(previous_block_number) = block_number.checked_sub(1) else {
+            return Ok(None);
+        };
+
+        let Some(previous) =
+            ReorgProvider::get_block_hash(&self.client, &self.chain_id, previous_block_number)
+                .await?
+        else {
+            trace!("No hash recorded for block {previous_block_number}, skipping reorg check");
+            return Ok(None);
+        };
+
+        let block = self.fetch_block_hash(block_number).await?;
+        if block.parent_hash == previous.block_hash {
+            return Ok(None);
+        }
+
+        warn!(
+            "Reorg detected at block {}: parent hash {} does not match indexed hash {}",
+            block_number, block.parent_hash, previous.block_hash
+        );
+
+        let lowest_block_number = previous_block_number.saturating_sub(self.depth);
+        let mut first_orphaned = previous;
+        let mut ancestor_found = false;
+        while first_orphaned.block_number > lowest_block_number {
+            let candidate_number = first_orphaned.block_number - 1;
+            let Some(indexed) =
+                ReorgProvider::get_block_hash(&self.client, &self.chain_id, candidate_number)
+                    .await?
+            else {
+                break;
+            };
+
+            let canonical = self.fetch_block_hash(candidate_number).await?;
+            if canonical.block_hash == indexed.block_hash {
+                ancestor_found = true;
+                break;
+            }
+            first_orphaned = indexed;
+        }
+
+        if !ancestor_found {
+            error!(
+                "No common ancestor found within {} blocks, reverting from block {}",
+                self.depth, first_orphaned.block_number
+            );
+        }
+
+        ReorgProvider::revert_from_block(&self.client, &self.chain_id, first_orphaned.block_number)
+            .await?;
+
+        Ok(Some(first_orphaned.block_number))
+    }
+
+    /// Records the hashes of the indexed blocks and drops the ones that are
+    /// too old to be reorged.
+    pub async fn record(&self, from_block: u64, to_block: u64) -> Result<()> {
+        for block_number in from_block..=to_block {
+            let block = self.fetch_block_hash(block_number).await?;
+            ReorgProvider::insert_block_hash(&self.client, &self.chain_id, &block).await?;
+        }
+
+        ReorgProvider::prune_block_hashes(
+            &self.client,
+            &self.chain_id,
+            to_block.saturating_sub(self.depth),
+        )
+        .await?;
+
+        Ok(())
+    }
+}
@@ -1,19 +1,48 @@
-use ark_marketplace_api::handlers::token_handler::RefreshMetadataRequest;
+use ark_marketplace_api::handlers::token_handler::{
+    RefreshMetadataRequest, TokenBatchKey, TokensBatchRequest,
+};
 use ark_marketplace_api::handlers::{
-    collection_handler, default_handler, portfolio_handler, token_handler,
+    api_key_handler, chain_handler, collection_handler, default_handler, event_handler,
+    export_handler, graphql_handler, portfolio_handler, token_handler, wallet_session_handler,
+    watchlist_handler, webhook_handler,
 };
+use ark_marketplace_api::models::api_key::{ApiKey, ApiKeyRequest, CreatedApiKey};
+use ark_marketplace_api::models::chain::Chain;
 use ark_marketplace_api::models::collection::{
-    CollectionActivityData, CollectionData, CollectionFullData, CollectionPortfolioData,
-    CollectionSearchData, OwnerData,
+    CollectionActivityData, CollectionChartData, CollectionData, CollectionFullData,
+    CollectionOfferData, CollectionPortfolioData, CollectionSearchData, OfferDepthLevel, OrderBook,
+    OrderBookLevel, OwnerData,
 };
 use ark_marketplace_api::models::default::{LastSale, LiveAuction, PreviewNft, Trending};
-use ark_marketplace_api::models::portfolio::{OfferApiData, StatsData};
+use ark_marketplace_api::models::event::MarketplaceEvent;
+use ark_marketplace_api::models::export::{ActivityExportRow, ExportFormat};
+use ark_marketplace_api::models::portfolio::{
+    CollectionValuation, OfferApiData, PortfolioAmount, PortfolioValuation, PortfolioValuePoint,
+    StatsData,
+};
+use ark_marketplace_api::models::tax_report::{
+    LotMethod, TaxDisposal, TaxReport, TaxReportFormat, TaxReportSummary,
+};
 use ark_marketplace_api::models::token::{
-    Listing, TokenActivityData, TokenData, TokenDataListing, TokenEventType, TokenInformationData,
-    TokenMarketData, TokenOfferOneData, TokenPortfolioActivityData, TokenPortfolioData, TopOffer,
+    Listing, TokenActivityData, TokenBatchData, TokenData, TokenDataListing, TokenEventType,
+    TokenInformationData, TokenMarketData, TokenOfferOneData, TokenPortfolioActivityData,
+    TokenPortfolioData, TokenPriceStats, TokenSaleData, TopOffer,
+};
+use ark_marketplace_api::models::wallet_session::{
+    SessionToken, SignInChallenge, SignInChallengeRequest, SignInRequest,
 };
+use ark_marketplace_api::models::watchlist::{
+    WatchlistEventType, WatchlistFeedItem, WatchlistItem, WatchlistItemRequest,
+};
+use ark_marketplace_api::models::webhook::{
+    WebhookDeadLetter, WebhookSubscription, WebhookSubscriptionRequest,
+};
+use ark_marketplace_api::types::api_error::{ErrorCode, ErrorResponse};
+use ark_marketplace_api::types::api_key::{ApiKeyResponse, ApiKeysResponse, CreatedApiKeyResponse};
+use ark_marketplace_api::types::chain::ChainsResponse;
 use ark_marketplace_api::types::collection::{
-    AttributeValues, AttributesResponse, CollectionActivityResponse, CollectionPortfolioResponse,
+    AttributeValues, AttributesResponse, CollectionActivityResponse, CollectionChartsResponse,
+    CollectionOffersResponse, CollectionOrderBookResponse, CollectionPortfolioResponse,
     CollectionResponse, CollectionSearchResponse, CollectionsResponse,
 };
 use ark_marketplace_api::types::default::{
@@ -22,11 +51,18 @@ use ark_marketplace_api::types::default::{
 };
 use ark_marketplace_api::types::portfolio::{
     PortfolioActivityResponse, PortfolioOffersResponse, PortfolioStatsResponse,
-    TokensPortfolioResponse,
+    PortfolioValuationResponse, TaxReportResponse, TokensPortfolioResponse,
 };
 use ark_marketplace_api::types::token::{
-    TokenActivitiesResponse, TokenMarketDataResponse, TokenOffersResponse, TokenResponse,
-    TokensResponse,
+    TokenActivitiesResponse, TokenMarketDataResponse, TokenOffersResponse,
+    TokenPriceHistoryResponse, TokenResponse, TokensBatchResponse, TokensResponse,
+};
+use ark_marketplace_api::types::wallet_session::{SessionTokenResponse, SignInChallengeResponse};
+use ark_marketplace_api::types::watchlist::{
+    WatchlistFeedResponse, WatchlistItemResponse, WatchlistResponse,
+};
+use ark_marketplace_api::types::webhook::{
+    WebhookDeadLettersResponse, WebhookResponse, WebhooksResponse,
 };
 use utoipa::OpenApi;
 use utoipa_swagger_ui::{SwaggerUi, Url};
@@ -38,29 +74,70 @@ use utoipa_swagger_ui::{SwaggerUi, Url};
         default_handler::last_sales,
         default_handler::live_auctions,
         default_handler::trending,
+        chain_handler::get_chains,
         collection_handler::get_collection,
         collection_handler::get_collection_activity,
+        collection_handler::get_collection_charts,
+        collection_handler::get_collection_offers,
+        collection_handler::get_collection_order_book,
         collection_handler::get_portfolio_collections,
         collection_handler::search_collections,
         collection_handler::get_traits,
         collection_handler::get_collections,
         token_handler::get_tokens,
+        token_handler::get_tokens_batch,
         token_handler::get_token,
         token_handler::get_token_market,
         token_handler::get_token_offers,
         token_handler::get_tokens_portfolio,
         token_handler::get_token_activity,
+        token_handler::get_token_price_history,
         token_handler::post_refresh_token_metadata,
         portfolio_handler::get_activity,
         portfolio_handler::get_offers,
         portfolio_handler::get_stats,
+        portfolio_handler::get_valuation,
+        portfolio_handler::get_tax_report,
+        watchlist_handler::get_watchlist,
+        watchlist_handler::add_to_watchlist,
+        watchlist_handler::remove_from_watchlist,
+        watchlist_handler::get_feed,
+        wallet_session_handler::create_nonce,
+        wallet_session_handler::verify_signature,
+        wallet_session_handler::get_session,
+        event_handler::stream_events,
+        export_handler::export_collection_activity,
+        export_handler::export_portfolio_activity,
+        graphql_handler::post_graphql,
+        graphql_handler::get_graphql_schema,
+        webhook_handler::create_webhook,
+        webhook_handler::get_webhooks,
+        webhook_handler::get_webhook,
+        webhook_handler::update_webhook,
+        webhook_handler::delete_webhook,
+        webhook_handler::get_webhook_dead_letter_log,
+        api_key_handler::create_key,
+        api_key_handler::get_keys,
+        api_key_handler::revoke_key,
     ),
     components(schemas(
         HealthCheckResponse,
+        ErrorResponse,
+        ErrorCode,
+        Chain,
+        ChainsResponse,
         CollectionResponse,
         CollectionData,
         CollectionActivityResponse,
         CollectionActivityData,
+        CollectionChartData,
+        CollectionChartsResponse,
+        CollectionOfferData,
+        OfferDepthLevel,
+        CollectionOffersResponse,
+        OrderBook,
+        OrderBookLevel,
+        CollectionOrderBookResponse,
         CollectionPortfolioData,
         CollectionPortfolioResponse,
         CollectionSearchData,
@@ -76,7 +153,13 @@ use utoipa_swagger_ui::{SwaggerUi, Url};
         TokenDataListing,
         TokenMarketData,
         TokenMarketDataResponse,
+        TokenBatchKey,
+        TokensBatchRequest,
+        TokenBatchData,
+        TokensBatchResponse,
         TokenEventType,
+        ExportFormat,
+        ActivityExportRow,
         Listing,
         TopOffer,
         TokenOffersResponse,
@@ -85,6 +168,9 @@ use utoipa_swagger_ui::{SwaggerUi, Url};
         TokensPortfolioResponse,
         TokenActivityData,
         TokenActivitiesResponse,
+        TokenSaleData,
+        TokenPriceStats,
+        TokenPriceHistoryResponse,
         OfferApiData,
         TokenPortfolioActivityData,
         PortfolioActivityResponse,
@@ -92,6 +178,30 @@ use utoipa_swagger_ui::{SwaggerUi, Url};
         RefreshMetadataRequest,
         PortfolioStatsResponse,
         StatsData,
+        PortfolioValuationResponse,
+        PortfolioValuation,
+        PortfolioAmount,
+        CollectionValuation,
+        PortfolioValuePoint,
+        TaxReportResponse,
+        TaxReport,
+        TaxReportSummary,
+        TaxDisposal,
+        LotMethod,
+        TaxReportFormat,
+        WatchlistItem,
+        WatchlistItemRequest,
+        WatchlistEventType,
+        WatchlistFeedItem,
+        WatchlistResponse,
+        WatchlistItemResponse,
+        WatchlistFeedResponse,
+        SignInChallengeRequest,
+        SignInChallenge,
+        SignInChallengeResponse,
+        SignInRequest,
+        SessionToken,
+        SessionTokenResponse,
         CollectionFullData,
         LastSalesResponse,
         LastSale,
@@ -100,6 +210,19 @@ use utoipa_swagger_ui::{SwaggerUi, Url};
         PreviewNft,
         TrendingResponse,
         Trending,
+        MarketplaceEvent,
+        WebhookSubscription,
+        WebhookSubscriptionRequest,
+        WebhookDeadLetter,
+        WebhookResponse,
+        WebhooksResponse,
+        WebhookDeadLettersResponse,
+        ApiKey,
+        ApiKeyRequest,
+        CreatedApiKey,
+        ApiKeyResponse,
+        ApiKeysResponse,
+        CreatedApiKeyResponse,
     ))
 )]
 pub struct ApiDoc;
@@ -0,0 +1,118 @@
+use crate::models::api_key::{ApiKey, ApiKeyRequest, ApiQuota, CreatedApiKey};
+use async_trait::async_trait;
+use sqlx::Error;
+use sqlx::PgPool;
+
+const API_KEY_COLUMNS: &str = "
+    api_key_id,
+    key_prefix,
+    name,
+    tier,
+    requests_per_minute,
+    requests_per_day,
+    is_active,
+    created_timestamp,
+    revoked_timestamp
+";
+
+#[async_trait]
+pub trait DatabaseAccess: Send + Sync {
+    async fn create_api_key(&self, request: &ApiKeyRequest) -> Result<CreatedApiKey, Error>;
+
+    async fn get_api_keys(&self) -> Result<Vec<ApiKey>, Error>;
+
+    async fn revoke_api_key(&self, id: i32) -> Result<ApiKey, Error>;
+
+    async fn get_api_key_quota(&self, api_key: &str) -> Result<Option<ApiQuota>, Error>;
+
+    async fn get_tier_quota(&self, tier: &str) -> Result<ApiQuota, Error>;
+}
+
+#[async_trait]
+impl DatabaseAccess for PgPool {
+    async fn create_api_key(&self, request: &ApiKeyRequest) -> Result<CreatedApiKey, Error> {
+        let query = format!(
+            "
+            WITH new_key AS (
+                SELECT 'ark_' || replace(gen_random_uuid()::text || gen_random_uuid()::text, '-', '') AS api_key
+            )
+            INSERT INTO api_key (key_hash, key_prefix, name, tier, requests_per_minute, requests_per_day)
+            SELECT encode(sha256(convert_to(api_key, 'UTF8')), 'hex'), left(api_key, 12), $1, $2, $3, $4
+            FROM new_key
+            RETURNING {}, (SELECT api_key FROM new_key) AS api_key
+            ",
+            API_KEY_COLUMNS
+        );
+
+        sqlx::query_as::<_, CreatedApiKey>(&query)
+            .bind(&request.name)
+            .bind(&request.tier)
+            .bind(request.requests_per_minute)
+            .bind(request.requests_per_day)
+            .fetch_one(self)
+            .await
+    }
+
+    async fn get_api_keys(&self) -> Result<Vec<ApiKey>, Error> {
+        let query = format!(
+            "
+            SELECT {}
+            FROM api_key
+            ORDER BY api_key_id
+            ",
+            API_KEY_COLUMNS
+        );
+
+        sqlx::query_as::<_, ApiKey>(&query).fetch_all(self).await
+    }
+
+    async fn revoke_api_key(&self, id: i32) -> Result<ApiKey, Error> {
+        let query = format!(
+            "
+            UPDATE api_key
+            SET is_active = FALSE,
+                revoked_timestamp = COALESCE(revoked_timestamp, EXTRACT(EPOCH FROM NOW())::BIGINT)
+            WHERE api_key_id = $1
+            RETURNING {}
+            ",
+            API_KEY_COLUMNS
+        );
+
+        sqlx::query_as::<_, ApiKey>(&query)
+            .bind(id)
+            .fetch_one(self)
+            .await
+    }
+
+    async fn get_api_key_quota(&self, api_key: &str) -> Result<Option<ApiQuota>, Error> {
+        let query = "
+            SELECT
+                api_key.api_key_id,
+                api_key.tier,
+                COALESCE(api_key.requests_per_minute, api_key_tier.requests_per_minute) AS requests_per_minute,
+                COALESCE(api_key.requests_per_day, api_key_tier.requests_per_day) AS requests_per_day
+            FROM api_key
+            INNER JOIN api_key_tier ON api_key_tier.tier = api_key.tier
+            WHERE api_key.key_hash = encode(sha256(convert_to($1, 'UTF8')), 'hex')
+                AND api_key.is_active = TRUE
+        ";
+
+        sqlx::query_as::<_, ApiQuota>(query)
+            .bind(api_key)
+            .fetch_optional(self)
+            .await
+    }
+
+    async fn get_tier_quota(&self, tier: &str) -> Result<ApiQuota, Error> {
+        let query = "
+            SELECT NULL::INTEGER AS api_key_id, tier, requests_per_minute, requests_per_day
+            FROM api_key_tier
+            WHERE tier = $1
+        ";
+
+        sqlx::query_as::<_, ApiQuota>(query)
+            .bind(tier)
+            .fetch_one(self)
+            .await
+    }
+}
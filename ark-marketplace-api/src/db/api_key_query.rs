@@ -0,0 +1,36 @@
+use crate::db::api_key_db_access;
+use crate::models::api_key::{ApiKey, ApiKeyRequest, ApiQuota, CreatedApiKey};
+
+pub async fn create_api_key<D: api_key_db_access::DatabaseAccess + Sync>(
+    db_access: &D,
+    request: &ApiKeyRequest,
+) -> Result<CreatedApiKey, sqlx::Error> {
+    db_access.create_api_key(request).await
+}
+
+pub async fn get_api_keys<D: api_key_db_access::DatabaseAccess + Sync>(
+    db_access: &D,
+) -> Result<Vec<ApiKey>, sqlx::Error> {
+    db_access.get_api_keys().await
+}
+
+pub async fn revoke_api_key<D: api_key_db_access::DatabaseAccess + Sync>(
+    db_access: &D,
+    id: i32,
+) -> Result<ApiKey, sqlx::Error> {
+    db_access.revoke_api_key(id).await
+}
+
+pub async fn get_api_key_quota<D: api_key_db_access::DatabaseAccess + Sync>(
+    db_access: &D,
+    api_key: &str,
+) -> Result<Option<ApiQuota>, sqlx::Error> {
+    db_access.get_api_key_quota(api_key).await
+}
+
+pub async fn get_tier_quota<D: api_key_db_access::DatabaseAccess + Sync>(
+    db_access: &D,
+    tier: &str,
+) -> Result<ApiQuota, sqlx::Error> {
+    db_access.get_tier_quota(tier).await
+}
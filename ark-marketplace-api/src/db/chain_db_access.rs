@@ -0,0 +1,23 @@
+use crate::models::chain::Chain;
+use async_trait::async_trait;
+use sqlx::Error;
+use sqlx::PgPool;
+
+#[async_trait]
+pub trait DatabaseAccess: Send + Sync {
+    async fn get_enabled_chains(&self) -> Result<Vec<Chain>, Error>;
+}
+
+#[async_trait]
+impl DatabaseAccess for PgPool {
+    async fn get_enabled_chains(&self) -> Result<Vec<Chain>, Error> {
+        let query = "
+            SELECT chain_id, name, is_default
+            FROM chain
+            WHERE is_enabled = TRUE
+            ORDER BY is_default DESC, chain_id
+        ";
+
+        sqlx::query_as::<_, Chain>(query).fetch_all(self).await
+    }
+}
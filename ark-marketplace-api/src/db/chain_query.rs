@@ -0,0 +1,8 @@
+use crate::db::chain_db_access;
+use crate::models::chain::Chain;
+
+pub async fn get_enabled_chains<D: chain_db_access::DatabaseAccess + Sync>(
+    db_access: &D,
+) -> Result<Vec<Chain>, sqlx::Error> {
+    db_access.get_enabled_chains().await
+}
@@ -1,15 +1,24 @@
 use crate::models::collection::{
-    CollectionActivityData, CollectionActivityDataDB, CollectionData, CollectionFloorPrice,
-    CollectionFullData, CollectionPortfolioData, CollectionSearchData, OwnerData,
+    CollectionActivityData, CollectionActivityDataDB, CollectionChartData, CollectionData,
+    CollectionFloorPrice, CollectionFullData, CollectionKey, CollectionOfferDataDB,
+    CollectionPortfolioData, CollectionSearchData, OfferDepthLevelDB, OrderBookLevelDB, OwnerData,
 };
 use crate::models::default::Currency;
 use crate::models::token::{
-    Listing, ListingRaw, TokenActivityData, TokenActivityDataDB, TokenData, TokenDataListing,
-    TokenEventType, TokenInformationData, TokenMarketData, TokenOfferOneDataDB, TokenOneData,
-    TokenPortfolioData, TopOffer, TopOfferQueryResult,
+    Listing, ListingRaw, TokenActivityData, TokenActivityDataDB, TokenBatchData, TokenData,
+    TokenDataListing, TokenDetailsData, TokenEventType, TokenInformationData, TokenKey,
+    TokenMarketData, TokenOfferOneDataDB, TokenOffersData, TokenOneData, TokenOwnershipStatsDB,
+    TokenPortfolioData, TokenSaleDB, TokenSaleData, TopOffer, TopOfferQueryResult,
 };
+use crate::types::chart_interval::ChartInterval;
+use crate::utils::currency_utils::normalize_currency_amount;
+use crate::utils::cursor_utils::Cursor;
 use crate::utils::db_utils::event_type_list;
-use crate::utils::sql_utils::{generate_order_by_clause, generate_order_by_clause_collections};
+use crate::utils::sql_utils::{
+    activity_keyset, collection_offers_keyset, escape_like_pattern,
+    generate_order_by_clause_collections, token_offers_keyset, tokens_keyset, COLLECTION_SORTS,
+    DIRECTIONS, TIME_RANGES,
+};
 use async_trait::async_trait;
 use serde_json::Value as JsonValue;
 use sqlx::types::BigDecimal;
@@ -41,6 +50,78 @@ struct TokenDataDB {
     pub owner: Option<String>,
     pub currency_address: Option<String>,
     pub buy_in_progress: Option<bool>,
+    pub rarity_rank: Option<i64>,
+    pub rarity_score: Option<BigDecimal>,
+    pub cursor_values: String,
+}
+
+#[derive(FromRow)]
+struct CollectionDataByKeyDB {
+    chain_id: String,
+    #[sqlx(flatten)]
+    collection: CollectionData,
+}
+
+#[derive(FromRow)]
+struct TokenOfferByKeyDB {
+    contract_address: String,
+    chain_id: String,
+    token_id: String,
+    total_count: i64,
+    #[sqlx(flatten)]
+    offer: TokenOfferOneDataDB,
+}
+
+#[derive(FromRow)]
+struct TokenDetailsDataDB {
+    collection_address: String,
+    chain_id: String,
+    token_id: String,
+    owner: Option<String>,
+    price: Option<BigDecimal>,
+    last_price: Option<BigDecimal>,
+    metadata: Option<JsonValue>,
+    metadata_updated_at: Option<i64>,
+    buy_in_progress: Option<bool>,
+    rarity_rank: Option<i64>,
+    rarity_score: Option<BigDecimal>,
+    listing_type: Option<String>,
+    listing_orderhash: Option<String>,
+    listing_start_amount: Option<String>,
+    listing_end_amount: Option<String>,
+    listing_start_date: Option<i64>,
+    listing_end_date: Option<i64>,
+    listing_currency_address: Option<String>,
+    top_bid_order_hash: Option<String>,
+    top_bid_amount: Option<BigDecimal>,
+    top_bid_start_date: Option<i64>,
+    top_bid_end_date: Option<i64>,
+    top_bid_currency_address: Option<String>,
+}
+
+#[derive(FromRow)]
+struct TokenBatchDataDB {
+    chain_id: String,
+    #[sqlx(flatten)]
+    token: TokenInformationData,
+    floor: Option<BigDecimal>,
+    created_timestamp: Option<i64>,
+    updated_timestamp: Option<i64>,
+    is_listed: Option<bool>,
+    has_offer: Option<bool>,
+    buy_in_progress: Option<bool>,
+    listing_type: Option<String>,
+    listing_orderhash: Option<String>,
+    listing_start_amount: Option<String>,
+    listing_end_amount: Option<String>,
+    listing_start_date: Option<i64>,
+    listing_end_date: Option<i64>,
+    listing_currency_address: Option<String>,
+    top_bid_order_hash: Option<String>,
+    top_bid_amount: Option<BigDecimal>,
+    top_bid_start_date: Option<i64>,
+    top_bid_end_date: Option<i64>,
+    top_bid_currency_address: Option<String>,
 }
 
 #[async_trait]
@@ -64,7 +145,7 @@ pub trait DatabaseAccess: Send + Sync {
         &self,
         contract_address: &str,
         chain_id: &str,
-        page: i64,
+        cursor: Option<&Cursor>,
         items_per_page: i64,
         buy_now: bool,
         sort: Option<String>,
@@ -72,7 +153,7 @@ pub trait DatabaseAccess: Send + Sync {
         sort_value: Option<String>,
         token_ids: Option<Vec<String>>,
         token_id: Option<String>,
-    ) -> Result<(Vec<TokenData>, bool, i64), Error>;
+    ) -> Result<(Vec<TokenData>, Option<String>, i64), Error>;
 
     async fn get_token_data(
         &self,
@@ -81,6 +162,17 @@ pub trait DatabaseAccess: Send + Sync {
         token_id: &str,
     ) -> Result<TokenInformationData, Error>;
 
+    /// Fetches several tokens in one query, keyed by token. Missing tokens are
+    /// absent from the map.
+    async fn get_tokens_data_by_keys(
+        &self,
+        keys: &[TokenKey],
+    ) -> Result<HashMap<TokenKey, TokenDetailsData>, Error>;
+
+    /// Fetches the information and market data of several tokens in one
+    /// query, in the order of `keys`. Missing tokens are skipped.
+    async fn get_tokens_batch_data(&self, keys: &[TokenKey]) -> Result<Vec<TokenBatchData>, Error>;
+
     async fn get_token_marketdata(
         &self,
         contract_address: &str,
@@ -104,9 +196,17 @@ pub trait DatabaseAccess: Send + Sync {
         contract_address: &str,
         chain_id: &str,
         token_id: &str,
-        page: i64,
+        cursor: Option<&Cursor>,
         items_per_page: i64,
-    ) -> Result<(Vec<TokenOfferOneDataDB>, bool, i64), Error>;
+    ) -> Result<(Vec<TokenOfferOneDataDB>, Option<String>, i64), Error>;
+
+    /// Fetches the first `items_per_token` offers of several tokens in one
+    /// query, keyed by token. Tokens without offers are absent from the map.
+    async fn get_token_offers_data_by_keys(
+        &self,
+        keys: &[TokenKey],
+        items_per_token: i64,
+    ) -> Result<HashMap<TokenKey, TokenOffersData>, Error>;
 
     async fn get_collections_data(
         &self,
@@ -136,15 +236,22 @@ pub trait DatabaseAccess: Send + Sync {
         chain_id: &str,
     ) -> Result<CollectionData, Error>;
 
+    /// Fetches several collections in one query, keyed by collection. Missing
+    /// collections are absent from the map.
+    async fn get_collections_data_by_keys(
+        &self,
+        keys: &[CollectionKey],
+    ) -> Result<HashMap<CollectionKey, CollectionData>, Error>;
+
     async fn get_collection_activity_data(
         &self,
         contract_address: &str,
         chain_id: &str,
-        page: i64,
+        cursor: Option<&Cursor>,
         items_per_page: i64,
         direction: &str,
         types: &Option<Vec<TokenEventType>>,
-    ) -> Result<(Vec<CollectionActivityData>, bool, i64), Error>;
+    ) -> Result<(Vec<CollectionActivityData>, Option<String>, i64), Error>;
 
     async fn get_collection_floor_price(
         &self,
@@ -152,6 +259,42 @@ pub trait DatabaseAccess: Send + Sync {
         chain_id: &str,
     ) -> Result<CollectionFloorPrice, Error>;
 
+    async fn get_collection_offers_data(
+        &self,
+        contract_address: &str,
+        chain_id: &str,
+        cursor: Option<&Cursor>,
+        items_per_page: i64,
+    ) -> Result<(Vec<CollectionOfferDataDB>, Option<String>, i64), Error>;
+
+    async fn get_collection_offers_depth(
+        &self,
+        contract_address: &str,
+        chain_id: &str,
+        levels: i64,
+    ) -> Result<Vec<OfferDepthLevelDB>, Error>;
+
+    async fn get_collection_offers_acceptable_tokens(
+        &self,
+        offer_ids: &[i32],
+        user_address: &str,
+    ) -> Result<HashMap<i32, Vec<String>>, Error>;
+
+    async fn get_collection_order_book_levels(
+        &self,
+        contract_address: &str,
+        chain_id: &str,
+    ) -> Result<Vec<OrderBookLevelDB>, Error>;
+
+    async fn get_collection_charts_data(
+        &self,
+        contract_address: &str,
+        chain_id: &str,
+        interval: ChartInterval,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<CollectionChartData>, Error>;
+
     async fn get_currency(
         &self,
         currencies: Vec<Currency>,
@@ -171,6 +314,20 @@ pub trait DatabaseAccess: Send + Sync {
         types: &Option<Vec<TokenEventType>>,
     ) -> Result<(Vec<TokenActivityData>, bool, i64), Error>;
 
+    async fn get_token_sales_data(
+        &self,
+        contract_address: &str,
+        chain_id: &str,
+        token_id: &str,
+    ) -> Result<Vec<TokenSaleData>, Error>;
+
+    async fn get_token_ownership_stats(
+        &self,
+        contract_address: &str,
+        chain_id: &str,
+        token_id: &str,
+    ) -> Result<TokenOwnershipStatsDB, Error>;
+
     async fn flush_all_data(&self) -> Result<u64, Error>;
 }
 
@@ -315,8 +472,7 @@ impl DatabaseAccess for PgPool {
                     .await?;
 
                 // Search for collections matching the query
-                let collection_query = format!(
-                    "SELECT
+                let collection_query = "SELECT
                          contract.contract_address as address,
                          contract_image AS image,
                          contract_name AS name,
@@ -324,15 +480,15 @@ impl DatabaseAccess for PgPool {
                          is_verified AS is_verified
                      FROM
                          contract
-                     WHERE contract_name ILIKE '%{}%' OR contract.contract_address ILIKE '%{}%'
+                     WHERE contract_name ILIKE $1 OR contract.contract_address ILIKE $1
                      ORDER BY token_count desc, is_verified desc, contract_name
-                     LIMIT {}
-                     ",
-                    cleaned, cleaned, items
-                );
+                     LIMIT $2
+                     ";
 
                 collections =
-                    sqlx::query_as::<sqlx::Postgres, CollectionSearchData>(&collection_query)
+                    sqlx::query_as::<sqlx::Postgres, CollectionSearchData>(collection_query)
+                        .bind(format!("%{}%", escape_like_pattern(cleaned)))
+                        .bind(items)
                         .fetch_all(self)
                         .await?;
             }
@@ -356,26 +512,21 @@ impl DatabaseAccess for PgPool {
 
         let count = total_count.count.unwrap_or(0);
 
-        const ALLOWED_SORTS: &[&str] = &[
-            "floor_price",
-            "floor_percentage",
-            "volume",
-            "top_bid",
-            "number_of_sales",
-            "marketcap",
-            "listed",
-        ];
-        const ALLOWED_DIRECTIONS: &[&str] = &["asc", "desc"];
-        if !ALLOWED_SORTS.contains(&sort) || !ALLOWED_DIRECTIONS.contains(&direction) {
-            tracing::error!("get_collections_data: Invalid sort or direction");
+        // These are written in the query, the handler only lets whitelisted
+        // values through
+        if !COLLECTION_SORTS.contains(&sort)
+            || !DIRECTIONS.contains(&direction)
+            || !TIME_RANGES.contains(&time_range)
+        {
+            return Err(Error::Protocol(format!(
+                "get_collections_data: invalid sort {}, direction {} or time range {}",
+                sort, direction, time_range
+            )));
         }
         let order_by_clause = generate_order_by_clause_collections(sort, direction);
 
-        let contract_timestamp_clause: String = if time_range.is_empty() {
-            String::new()
-        } else {
-            format!(" AND contract_marketdata.timerange = '{}'", time_range)
-        };
+        let contract_timestamp_clause =
+            format!(" AND contract_marketdata.timerange = '{}'", time_range);
 
         let sql_query = format!(
             "SELECT
@@ -637,16 +788,78 @@ impl DatabaseAccess for PgPool {
         Ok(collection_data)
     }
 
+    async fn get_collections_data_by_keys(
+        &self,
+        keys: &[CollectionKey],
+    ) -> Result<HashMap<CollectionKey, CollectionData>, Error> {
+        let (contract_addresses, chain_ids): (Vec<String>, Vec<String>) = keys
+            .iter()
+            .map(|key| (key.contract_address.clone(), key.chain_id.clone()))
+            .unzip();
+
+        let collections_data = sqlx::query_as::<_, CollectionDataByKeyDB>(
+            r#"
+             SELECT
+                 contract.chain_id,
+                 contract.contract_address as address,
+                 CASE
+                     WHEN contract_image = '' THEN NULL
+                     ELSE contract_image
+                 END AS image,
+                 contract_name AS name,
+                 contract.floor_price AS floor,
+                 volume_7d_eth,
+                 contract.top_bid AS top_offer,
+                 sales_7d,
+                 marketcap,
+                 token_listed_count AS listed_items,
+                 listed_percentage,
+                 token_count,
+                 owner_count,
+                 total_volume,
+                 total_sales,
+                 floor_7d_percentage,
+                 is_verified,
+                 deployed_timestamp,
+                 website,
+                 twitter,
+                 discord,
+                 description,
+                 market_data_enabled
+             FROM contract
+             INNER JOIN UNNEST($1::TEXT[], $2::TEXT[]) AS k(contract_address, chain_id)
+                 ON k.contract_address = contract.contract_address
+                 AND k.chain_id = contract.chain_id
+             "#,
+        )
+        .bind(contract_addresses)
+        .bind(chain_ids)
+        .fetch_all(self)
+        .await?;
+
+        Ok(collections_data
+            .into_iter()
+            .map(|row| {
+                let key = CollectionKey {
+                    contract_address: row.collection.address.clone(),
+                    chain_id: row.chain_id,
+                };
+                (key, row.collection)
+            })
+            .collect())
+    }
+
     async fn get_collection_activity_data(
         &self,
         contract_address: &str,
         chain_id: &str,
-        page: i64,
+        cursor: Option<&Cursor>,
         items_per_page: i64,
         direction: &str,
         types: &Option<Vec<TokenEventType>>,
-    ) -> Result<(Vec<CollectionActivityData>, bool, i64), Error> {
-        let offset = (page - 1) * items_per_page;
+    ) -> Result<(Vec<CollectionActivityData>, Option<String>, i64), Error> {
+        let keyset = activity_keyset(direction);
+        let (cursor_condition, cursor_binds) = keyset.after_condition(cursor, 3);
         let types_filter = match types {
             None => String::from(""),
             Some(values) => {
@@ -731,26 +944,34 @@ impl DatabaseAccess for PgPool {
                 contract.contract_address as address,
                 {},
                 {},
+                {},
                 {}
             {}
-            ORDER BY te.block_timestamp {}
-            LIMIT {} OFFSET {}
+                {}
+            ORDER BY {}
+            LIMIT {}
             ",
             price_select_part,
             from_select_part,
             to_select_part,
+            keyset.cursor_values_select(),
             common_sql_query,
-            direction,
-            items_per_page,
-            offset,
+            cursor_condition,
+            keyset.order_by(),
+            items_per_page + 1,
         );
 
-        let collection_activity_data_db: Vec<CollectionActivityDataDB> =
-            sqlx::query_as(&activity_sql_query)
-                .bind(contract_address)
-                .bind(chain_id)
-                .fetch_all(self)
-                .await?;
+        let mut activity_query = sqlx::query_as::<_, CollectionActivityDataDB>(&activity_sql_query)
+            .bind(contract_address)
+            .bind(chain_id);
+        for value in cursor_binds {
+            activity_query = activity_query.bind(value);
+        }
+        let (collection_activity_data_db, next_cursor) = keyset.paginate(
+            activity_query.fetch_all(self).await?,
+            items_per_page,
+            |activity| &activity.cursor_values,
+        );
 
         let currencies: Vec<Currency> = sqlx::query_as!(
             Currency,
@@ -783,11 +1004,87 @@ impl DatabaseAccess for PgPool {
             })
             .collect();
 
-        // Calculate if there is another page
-        let total_pages = (count + items_per_page - 1) / items_per_page;
-        let has_next_page = page < total_pages;
+        Ok((collection_activity_data, next_cursor, count))
+    }
+
+    async fn get_collection_charts_data(
+        &self,
+        contract_address: &str,
+        chain_id: &str,
+        interval: ChartInterval,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<CollectionChartData>, Error> {
+        // Buckets without snapshot or sale are kept so that the series has no
+        // gap; their prices are null and their volume is zero. Sales are
+        // aggregated on their ETH value so that every currency is comparable,
+        // the ones that could not be priced are left out.
+        let charts_query = "
+            WITH buckets AS (
+                SELECT generate_series(
+                    date_trunc($3, to_timestamp($4) AT TIME ZONE 'UTC'),
+                    to_timestamp($5) AT TIME ZONE 'UTC',
+                    make_interval(secs => $6)
+                ) AS bucket
+            ),
+            floors AS (
+                SELECT
+                    date_trunc($3, to_timestamp(timestamp) AT TIME ZONE 'UTC') AS bucket,
+                    (ARRAY_AGG(floor ORDER BY timestamp ASC))[1] AS floor_open,
+                    MAX(floor) AS floor_high,
+                    MIN(floor) AS floor_low,
+                    (ARRAY_AGG(floor ORDER BY timestamp DESC))[1] AS floor_close
+                FROM floor_collection
+                WHERE contract_address = $1
+                  AND chain_id = $2
+                  AND timestamp BETWEEN $4 AND $5
+                  AND floor > 0
+                GROUP BY 1
+            ),
+            sales AS (
+                SELECT
+                    date_trunc($3, to_timestamp(block_timestamp) AT TIME ZONE 'UTC') AS bucket,
+                    (ARRAY_AGG(eth_amount::NUMERIC ORDER BY block_timestamp ASC))[1] AS sale_open,
+                    MAX(eth_amount::NUMERIC) AS sale_high,
+                    MIN(eth_amount::NUMERIC) AS sale_low,
+                    (ARRAY_AGG(eth_amount::NUMERIC ORDER BY block_timestamp DESC))[1] AS sale_close,
+                    SUM(eth_amount::NUMERIC) AS volume,
+                    COUNT(*) AS sales
+                FROM token_event
+                WHERE contract_address = $1
+                  AND chain_id = $2
+                  AND event_type = 'Executed'
+                  AND eth_amount IS NOT NULL
+                  AND block_timestamp BETWEEN $4 AND $5
+                GROUP BY 1
+            )
+            SELECT
+                EXTRACT(EPOCH FROM buckets.bucket)::BIGINT AS timestamp,
+                floors.floor_open,
+                floors.floor_high,
+                floors.floor_low,
+                floors.floor_close,
+                sales.sale_open,
+                sales.sale_high,
+                sales.sale_low,
+                sales.sale_close,
+                COALESCE(sales.volume, 0) AS volume,
+                COALESCE(sales.sales, 0) AS sales
+            FROM buckets
+            LEFT JOIN floors ON floors.bucket = buckets.bucket
+            LEFT JOIN sales ON sales.bucket = buckets.bucket
+            ORDER BY buckets.bucket
+        ";
 
-        Ok((collection_activity_data, has_next_page, count))
+        sqlx::query_as::<_, CollectionChartData>(charts_query)
+            .bind(contract_address)
+            .bind(chain_id)
+            .bind(interval.date_trunc_unit())
+            .bind(from)
+            .bind(to)
+            .bind(interval.seconds() as f64)
+            .fetch_all(self)
+            .await
     }
 
     async fn get_collection_floor_price(
@@ -805,6 +1102,206 @@ impl DatabaseAccess for PgPool {
         Ok(floor_price)
     }
 
+    async fn get_collection_offers_data(
+        &self,
+        contract_address: &str,
+        chain_id: &str,
+        cursor: Option<&Cursor>,
+        items_per_page: i64,
+    ) -> Result<(Vec<CollectionOfferDataDB>, Option<String>, i64), Error> {
+        let keyset = collection_offers_keyset();
+        let (cursor_condition, cursor_binds) = keyset.after_condition(cursor, 5);
+        let current_time: i64 = match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+            Ok(d) => d.as_secs().try_into().unwrap(),
+            Err(_) => 0,
+        };
+
+        let count = sqlx::query_as::<_, Count>(
+            "SELECT COUNT(*) AS total
+            FROM collection_offer
+            WHERE collection_offer.contract_address = $1
+                AND collection_offer.chain_id = $2
+                AND collection_offer.status = 'PLACED'
+                AND collection_offer.end_date > $3",
+        )
+        .bind(contract_address)
+        .bind(chain_id)
+        .bind(current_time)
+        .fetch_one(self)
+        .await?
+        .total;
+
+        let collection_offers_query = format!(
+            "SELECT
+                collection_offer_id AS offer_id,
+                hex_to_decimal(offer_amount) AS amount,
+                hex_to_decimal(offer_quantity) AS quantity,
+                offer_maker AS source,
+                end_date AS expire_at,
+                order_hash AS hash,
+                currency_address,
+                {}
+            FROM collection_offer
+            WHERE collection_offer.contract_address = $1
+                AND collection_offer.chain_id = $2
+                AND collection_offer.status = 'PLACED'
+                AND collection_offer.end_date > $3
+                {}
+            ORDER BY {}
+            LIMIT $4
+            ",
+            keyset.cursor_values_select(),
+            cursor_condition,
+            keyset.order_by()
+        );
+
+        let mut collection_offers_query =
+            sqlx::query_as::<_, CollectionOfferDataDB>(&collection_offers_query)
+                .bind(contract_address)
+                .bind(chain_id)
+                .bind(current_time)
+                .bind(items_per_page + 1);
+        for value in cursor_binds {
+            collection_offers_query = collection_offers_query.bind(value);
+        }
+        let (collection_offers_data, next_cursor) = keyset.paginate(
+            collection_offers_query.fetch_all(self).await?,
+            items_per_page,
+            |offer| &offer.cursor_values,
+        );
+
+        Ok((collection_offers_data, next_cursor, count))
+    }
+
+    async fn get_collection_offers_depth(
+        &self,
+        contract_address: &str,
+        chain_id: &str,
+        levels: i64,
+    ) -> Result<Vec<OfferDepthLevelDB>, Error> {
+        let current_time: i64 = match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+            Ok(d) => d.as_secs().try_into().unwrap(),
+            Err(_) => 0,
+        };
+
+        sqlx::query_as::<_, OfferDepthLevelDB>(
+            "SELECT
+                hex_to_decimal(offer_amount) AS price,
+                currency_address,
+                COUNT(*) AS offer_count,
+                SUM(hex_to_decimal(offer_quantity)) AS quantity
+            FROM collection_offer
+            WHERE contract_address = $1
+                AND chain_id = $2
+                AND status = 'PLACED'
+                AND end_date > $3
+            GROUP BY hex_to_decimal(offer_amount), currency_address
+            ORDER BY price DESC NULLS LAST
+            LIMIT $4",
+        )
+        .bind(contract_address)
+        .bind(chain_id)
+        .bind(current_time)
+        .bind(levels)
+        .fetch_all(self)
+        .await
+    }
+
+    async fn get_collection_offers_acceptable_tokens(
+        &self,
+        offer_ids: &[i32],
+        user_address: &str,
+    ) -> Result<HashMap<i32, Vec<String>>, Error> {
+        if offer_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        // Collection offers can be accepted against any token of the
+        // collection, except by their own maker.
+        let rows = sqlx::query(
+            "SELECT
+                o.collection_offer_id AS offer_id,
+                ARRAY_AGG(t.token_id ORDER BY LENGTH(t.token_id), t.token_id) AS token_ids
+            FROM collection_offer o
+            INNER JOIN token t
+                ON t.contract_address = o.contract_address
+                AND t.chain_id = o.chain_id
+            WHERE o.collection_offer_id = ANY($1)
+                AND t.current_owner = $2
+                AND t.is_burned = false
+                AND o.offer_maker <> $2
+            GROUP BY o.collection_offer_id",
+        )
+        .bind(offer_ids)
+        .bind(user_address)
+        .fetch_all(self)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| Ok((row.try_get("offer_id")?, row.try_get("token_ids")?)))
+            .collect()
+    }
+
+    async fn get_collection_order_book_levels(
+        &self,
+        contract_address: &str,
+        chain_id: &str,
+    ) -> Result<Vec<OrderBookLevelDB>, Error> {
+        let current_time: i64 = match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+            Ok(d) => d.as_secs().try_into().unwrap(),
+            Err(_) => 0,
+        };
+
+        // Asks are the fixed price listings, bids the token offers and the
+        // collection-wide offers, which can be accepted by any token.
+        sqlx::query_as::<_, OrderBookLevelDB>(
+            "SELECT
+                false AS is_bid,
+                COALESCE(token.listing_currency_address, '') AS currency_address,
+                hex_to_decimal(token.listing_start_amount) AS price,
+                COUNT(*) AS order_count,
+                COUNT(*)::NUMERIC AS quantity
+            FROM token
+            WHERE token.contract_address = $1
+                AND token.chain_id = $2
+                AND token.listing_start_amount IS NOT NULL
+                AND token.listing_type != 'Auction'
+                AND (token.listing_end_date IS NULL OR token.listing_end_date > $3)
+            GROUP BY 2, 3
+            UNION ALL
+            SELECT
+                true AS is_bid,
+                token_offer.currency_address,
+                hex_to_decimal(token_offer.offer_amount) AS price,
+                COUNT(*) AS order_count,
+                COUNT(*)::NUMERIC AS quantity
+            FROM token_offer
+            WHERE token_offer.contract_address = $1
+                AND token_offer.chain_id = $2
+                AND token_offer.status = 'PLACED'
+                AND token_offer.end_date > $3
+            GROUP BY 2, 3
+            UNION ALL
+            SELECT
+                true AS is_bid,
+                collection_offer.currency_address,
+                hex_to_decimal(collection_offer.offer_amount) AS price,
+                COUNT(*) AS order_count,
+                SUM(hex_to_decimal(collection_offer.offer_quantity)) AS quantity
+            FROM collection_offer
+            WHERE collection_offer.contract_address = $1
+                AND collection_offer.chain_id = $2
+                AND collection_offer.status = 'PLACED'
+                AND collection_offer.end_date > $3
+            GROUP BY 2, 3",
+        )
+        .bind(contract_address)
+        .bind(chain_id)
+        .bind(current_time)
+        .fetch_all(self)
+        .await
+    }
+
     async fn get_currencies(&self) -> Result<Vec<Currency>, Error> {
         let currencies: Vec<Currency> = sqlx::query_as!(
             Currency,
@@ -988,9 +1485,9 @@ impl DatabaseAccess for PgPool {
     ) -> Result<TokenInformationData, Error> {
         let token_data: TokenInformationData = sqlx::query_as!(
             TokenInformationData,
-            "
+            r#"
                     SELECT
-                        token_id,
+                        token.token_id,
                         token.contract_address as collection_address,
                         hex_to_decimal(token.listing_start_amount) as price,
                         hex_to_decimal(token.last_price) as last_price,
@@ -999,15 +1496,20 @@ impl DatabaseAccess for PgPool {
                         c.contract_name as collection_name,
                         token.metadata as metadata,
                         c.contract_image as collection_image,
-                        metadata_updated_at,
-                        metadata_status
+                        token.metadata_updated_at,
+                        token.metadata_status,
+                        token_rarity.rarity_rank as "rarity_rank?",
+                        token_rarity.information_content_score as "rarity_score?"
                     FROM token
                     INNER JOIN contract as c ON c.contract_address = token.contract_address
                         AND c.chain_id = token.chain_id
+                    LEFT JOIN token_rarity ON token_rarity.contract_address = token.contract_address
+                        AND token_rarity.chain_id = token.chain_id
+                        AND token_rarity.token_id = token.token_id
                     WHERE token.contract_address = $1
                       AND token.chain_id = $2
                       AND token.token_id = $3
-                    ",
+                    "#,
             contract_address,
             chain_id,
             token_id
@@ -1018,11 +1520,248 @@ impl DatabaseAccess for PgPool {
         Ok(token_data)
     }
 
+    async fn get_tokens_data_by_keys(
+        &self,
+        keys: &[TokenKey],
+    ) -> Result<HashMap<TokenKey, TokenDetailsData>, Error> {
+        let mut contract_addresses = Vec::with_capacity(keys.len());
+        let mut chain_ids = Vec::with_capacity(keys.len());
+        let mut token_ids = Vec::with_capacity(keys.len());
+        for key in keys {
+            contract_addresses.push(key.contract_address.clone());
+            chain_ids.push(key.chain_id.clone());
+            token_ids.push(key.token_id.clone());
+        }
+
+        let tokens_data = sqlx::query_as::<_, TokenDetailsDataDB>(
+            r#"
+                SELECT
+                    token.contract_address as collection_address,
+                    token.chain_id,
+                    token.token_id,
+                    token.current_owner as owner,
+                    hex_to_decimal(token.listing_start_amount) as price,
+                    hex_to_decimal(token.last_price) as last_price,
+                    token.metadata,
+                    token.metadata_updated_at,
+                    token.buy_in_progress,
+                    token_rarity.rarity_rank,
+                    token_rarity.information_content_score as rarity_score,
+                    token.listing_type,
+                    token.listing_orderhash,
+                    token.listing_start_amount,
+                    token.listing_end_amount,
+                    token.listing_start_date,
+                    token.listing_end_date,
+                    token.listing_currency_address,
+                    token.top_bid_order_hash,
+                    token.top_bid_amount,
+                    token.top_bid_start_date,
+                    token.top_bid_end_date,
+                    token.top_bid_currency_address
+                FROM token
+                INNER JOIN UNNEST($1::TEXT[], $2::TEXT[], $3::TEXT[])
+                    AS k(contract_address, chain_id, token_id)
+                    ON k.contract_address = token.contract_address
+                    AND k.chain_id = token.chain_id
+                    AND k.token_id = token.token_id
+                LEFT JOIN token_rarity ON token_rarity.contract_address = token.contract_address
+                    AND token_rarity.chain_id = token.chain_id
+                    AND token_rarity.token_id = token.token_id
+            "#,
+        )
+        .bind(contract_addresses)
+        .bind(chain_ids)
+        .bind(token_ids)
+        .fetch_all(self)
+        .await?;
+
+        let currencies = self.get_currencies().await?;
+
+        let mut tokens = HashMap::with_capacity(tokens_data.len());
+        for token in tokens_data {
+            let listing = match token.listing_start_amount {
+                Some(start_amount) => Some(Listing {
+                    is_auction: Some(
+                        token.listing_type.as_deref() == Some(LISTING_TYPE_AUCTION_STR),
+                    ),
+                    order_hash: token.listing_orderhash,
+                    start_amount: Some(start_amount),
+                    end_amount: token.listing_end_amount,
+                    start_date: token.listing_start_date,
+                    end_date: token.listing_end_date,
+                    currency: self
+                        .get_currency(currencies.clone(), token.listing_currency_address)
+                        .await,
+                }),
+                None => None,
+            };
+
+            let top_offer = match token.top_bid_order_hash {
+                Some(order_hash) => Some(TopOffer {
+                    order_hash: Some(order_hash),
+                    amount: token.top_bid_amount,
+                    start_date: token.top_bid_start_date,
+                    end_date: token.top_bid_end_date,
+                    currency: self
+                        .get_currency(currencies.clone(), token.top_bid_currency_address)
+                        .await,
+                }),
+                None => None,
+            };
+
+            let key = TokenKey {
+                contract_address: token.collection_address.clone(),
+                chain_id: token.chain_id.clone(),
+                token_id: token.token_id.clone(),
+            };
+            tokens.insert(
+                key,
+                TokenDetailsData {
+                    collection_address: token.collection_address,
+                    chain_id: token.chain_id,
+                    token_id: token.token_id,
+                    owner: token.owner,
+                    price: token.price,
+                    last_price: token.last_price,
+                    metadata: token.metadata,
+                    metadata_updated_at: token.metadata_updated_at,
+                    buy_in_progress: token.buy_in_progress,
+                    rarity_rank: token.rarity_rank,
+                    rarity_score: token.rarity_score,
+                    listing,
+                    top_offer,
+                },
+            );
+        }
+
+        Ok(tokens)
+    }
+
+    async fn get_tokens_batch_data(&self, keys: &[TokenKey]) -> Result<Vec<TokenBatchData>, Error> {
+        let mut contract_addresses = Vec::with_capacity(keys.len());
+        let mut chain_ids = Vec::with_capacity(keys.len());
+        let mut token_ids = Vec::with_capacity(keys.len());
+        for key in keys {
+            contract_addresses.push(key.contract_address.clone());
+            chain_ids.push(key.chain_id.clone());
+            token_ids.push(key.token_id.clone());
+        }
+
+        let tokens_data = sqlx::query_as::<_, TokenBatchDataDB>(
+            r#"
+                SELECT
+                    token.chain_id,
+                    token.token_id,
+                    token.contract_address as collection_address,
+                    hex_to_decimal(token.listing_start_amount) as price,
+                    hex_to_decimal(token.last_price) as last_price,
+                    token.top_bid_amount as top_offer,
+                    token.current_owner as owner,
+                    c.contract_name as collection_name,
+                    token.metadata,
+                    c.contract_image as collection_image,
+                    token.metadata_updated_at,
+                    token.metadata_status,
+                    token_rarity.rarity_rank,
+                    token_rarity.information_content_score as rarity_score,
+                    c.floor_price as floor,
+                    token.listing_timestamp as created_timestamp,
+                    token.updated_timestamp,
+                    (token.listing_start_amount IS NOT NULL) as is_listed,
+                    token.has_bid as has_offer,
+                    token.buy_in_progress,
+                    token.listing_type,
+                    token.listing_orderhash,
+                    token.listing_start_amount,
+                    token.listing_end_amount,
+                    token.listing_start_date,
+                    token.listing_end_date,
+                    token.listing_currency_address,
+                    token.top_bid_order_hash,
+                    token.top_bid_amount,
+                    token.top_bid_start_date,
+                    token.top_bid_end_date,
+                    token.top_bid_currency_address
+                FROM UNNEST($1::TEXT[], $2::TEXT[], $3::TEXT[])
+                    WITH ORDINALITY AS k(contract_address, chain_id, token_id, position)
+                INNER JOIN token ON token.contract_address = k.contract_address
+                    AND token.chain_id = k.chain_id
+                    AND token.token_id = k.token_id
+                INNER JOIN contract as c ON c.contract_address = token.contract_address
+                    AND c.chain_id = token.chain_id
+                LEFT JOIN token_rarity ON token_rarity.contract_address = token.contract_address
+                    AND token_rarity.chain_id = token.chain_id
+                    AND token_rarity.token_id = token.token_id
+                ORDER BY k.position
+            "#,
+        )
+        .bind(contract_addresses)
+        .bind(chain_ids)
+        .bind(token_ids)
+        .fetch_all(self)
+        .await?;
+
+        let currencies = self.get_currencies().await?;
+
+        let mut tokens = Vec::with_capacity(tokens_data.len());
+        for token in tokens_data {
+            let listing = match token.listing_start_amount {
+                Some(start_amount) => Some(Listing {
+                    is_auction: Some(
+                        token.listing_type.as_deref() == Some(LISTING_TYPE_AUCTION_STR),
+                    ),
+                    order_hash: token.listing_orderhash,
+                    start_amount: Some(start_amount),
+                    end_amount: token.listing_end_amount,
+                    start_date: token.listing_start_date,
+                    end_date: token.listing_end_date,
+                    currency: self
+                        .get_currency(currencies.clone(), token.listing_currency_address)
+                        .await,
+                }),
+                None => None,
+            };
+
+            let top_offer = match token.top_bid_order_hash {
+                Some(order_hash) => Some(TopOffer {
+                    order_hash: Some(order_hash),
+                    amount: token.top_bid_amount,
+                    start_date: token.top_bid_start_date,
+                    end_date: token.top_bid_end_date,
+                    currency: self
+                        .get_currency(currencies.clone(), token.top_bid_currency_address)
+                        .await,
+                }),
+                None => None,
+            };
+
+            tokens.push(TokenBatchData {
+                chain_id: token.chain_id,
+                market_data: TokenMarketData {
+                    owner: token.token.owner.clone(),
+                    floor: token.floor,
+                    created_timestamp: token.created_timestamp,
+                    updated_timestamp: token.updated_timestamp,
+                    is_listed: token.is_listed,
+                    has_offer: token.has_offer,
+                    buy_in_progress: token.buy_in_progress,
+                    top_offer,
+                    listing,
+                    last_price: token.token.last_price.clone(),
+                },
+                token: token.token,
+            });
+        }
+
+        Ok(tokens)
+    }
+
     async fn get_tokens_data(
         &self,
         contract_address: &str,
         chain_id: &str,
-        page: i64,
+        cursor: Option<&Cursor>,
         items_per_page: i64,
         buy_now: bool,
         sort: Option<String>,
@@ -1030,11 +1769,11 @@ impl DatabaseAccess for PgPool {
         sort_value: Option<String>,
         token_ids: Option<Vec<String>>,
         token_id: Option<String>,
-    ) -> Result<(Vec<TokenData>, bool, i64), Error> {
+    ) -> Result<(Vec<TokenData>, Option<String>, i64), Error> {
         let sort_field = sort.as_deref().unwrap_or("price");
         let sort_direction = direction.as_deref().unwrap_or("asc");
-        let order_by = generate_order_by_clause(sort_field, sort_direction, sort_value.as_deref());
-        let count = 0;
+        let keyset = tokens_keyset(sort_field, sort_direction, sort_value.as_deref());
+        let (cursor_condition, cursor_binds) = keyset.after_condition(cursor, 5);
 
         // Additional condition for token_id if it's provided
         let token_id_condition = if let Some(ref id) = token_id {
@@ -1128,25 +1867,40 @@ impl DatabaseAccess for PgPool {
                    token.metadata as metadata,
                    current_owner as owner,
                    token.listing_currency_address as currency_address,
-                   token.buy_in_progress
+                   token.buy_in_progress,
+                   token_rarity.rarity_rank,
+                   token_rarity.information_content_score as rarity_score,
+                   {}
                FROM token
+               LEFT JOIN token_rarity ON token_rarity.contract_address = token.contract_address
+                   AND token_rarity.chain_id = token.chain_id
+                   AND token_rarity.token_id = token.token_id
                WHERE token.contract_address = $1
                    AND token.chain_id = $2
                    AND ($3 = false OR (token.listing_start_amount IS NOT NULL AND token.listing_type != 'Auction'))
-                   {} {}
+                   {} {} {}
                ORDER BY {}
-               LIMIT $4 OFFSET $5",
-            token_ids_condition, token_id_condition, order_by
+               LIMIT $4",
+            keyset.cursor_values_select(),
+            token_ids_condition,
+            token_id_condition,
+            cursor_condition,
+            keyset.order_by()
         );
 
-        let token_data_query_result: Vec<TokenDataDB> = sqlx::query_as(&tokens_data_query)
+        let mut tokens_data_query = sqlx::query_as::<_, TokenDataDB>(&tokens_data_query)
             .bind(contract_address)
             .bind(chain_id)
             .bind(buy_now)
-            .bind(items_per_page)
-            .bind((page - 1) * items_per_page)
-            .fetch_all(self)
-            .await?;
+            .bind(items_per_page + 1);
+        for value in cursor_binds {
+            tokens_data_query = tokens_data_query.bind(value);
+        }
+        let (token_data_query_result, next_cursor) = keyset.paginate(
+            tokens_data_query.fetch_all(self).await?,
+            items_per_page,
+            |token_data| &token_data.cursor_values,
+        );
 
         let currencies = self.get_currencies().await?;
         let currencies_map: HashMap<String, Currency> = currencies
@@ -1180,13 +1934,12 @@ impl DatabaseAccess for PgPool {
                 metadata: token_data.metadata,
                 owner: token_data.owner,
                 buy_in_progress: token_data.buy_in_progress,
+                rarity_rank: token_data.rarity_rank,
+                rarity_score: token_data.rarity_score,
             })
             .collect();
 
-        // Calculate if there is another page
-        let total_pages = (count + items_per_page - 1) / items_per_page;
-        let has_next_page = page < total_pages;
-        Ok((tokens_data, has_next_page, token_count))
+        Ok((tokens_data, next_cursor, token_count))
     }
 
     async fn get_tokens_portfolio_data(
@@ -1304,11 +2057,12 @@ impl DatabaseAccess for PgPool {
         contract_address: &str,
         chain_id: &str,
         token_id: &str,
-        page: i64,
+        cursor: Option<&Cursor>,
         items_per_page: i64,
-    ) -> Result<(Vec<TokenOfferOneDataDB>, bool, i64), Error> {
+    ) -> Result<(Vec<TokenOfferOneDataDB>, Option<String>, i64), Error> {
         // FIXME: pagination assume that all offers used the same currency
-        let offset = (page - 1) * items_per_page;
+        let keyset = token_offers_keyset();
+        let (cursor_condition, cursor_binds) = keyset.after_condition(cursor, 6);
         let current_time: i64 = match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
             Ok(d) => d.as_secs().try_into().unwrap(),
             Err(_) => 0,
@@ -1332,38 +2086,131 @@ impl DatabaseAccess for PgPool {
 
         let count = total_count.count.unwrap_or(0);
 
-        let token_offers_data = sqlx::query_as!(
-            TokenOfferOneDataDB,
+        let token_offers_query = format!(
             "SELECT
                 token_offer_id AS offer_id,
                 hex_to_decimal(offer_amount) AS amount,
                 offer_maker AS source,
                 end_date AS expire_at,
                 order_hash as hash,
-                currency_address
+                currency_address,
+                {}
             FROM token_offer
             WHERE token_offer.contract_address = $1
                 AND token_offer.chain_id = $2
                 AND token_offer.token_id = $3
                 AND token_offer.status = 'PLACED'
                 AND end_date > $4
-            ORDER BY amount DESC, expire_at ASC
-            LIMIT $5 OFFSET $6
+                {}
+            ORDER BY {}
+            LIMIT $5
             ",
-            contract_address,
-            chain_id,
-            token_id,
-            current_time,
+            keyset.cursor_values_select(),
+            cursor_condition,
+            keyset.order_by()
+        );
+
+        let mut token_offers_query = sqlx::query_as::<_, TokenOfferOneDataDB>(&token_offers_query)
+            .bind(contract_address)
+            .bind(chain_id)
+            .bind(token_id)
+            .bind(current_time)
+            .bind(items_per_page + 1);
+        for value in cursor_binds {
+            token_offers_query = token_offers_query.bind(value);
+        }
+        let (token_offers_data, next_cursor) = keyset.paginate(
+            token_offers_query.fetch_all(self).await?,
             items_per_page,
-            offset
-        )
-        .fetch_all(self)
-        .await?;
+            |offer| &offer.cursor_values,
+        );
 
-        let total_pages = (count + items_per_page - 1) / items_per_page;
-        let has_next_page = page < total_pages;
+        Ok((token_offers_data, next_cursor, count))
+    }
 
-        Ok((token_offers_data, has_next_page, count))
+    async fn get_token_offers_data_by_keys(
+        &self,
+        keys: &[TokenKey],
+        items_per_token: i64,
+    ) -> Result<HashMap<TokenKey, TokenOffersData>, Error> {
+        let mut contract_addresses = Vec::with_capacity(keys.len());
+        let mut chain_ids = Vec::with_capacity(keys.len());
+        let mut token_ids = Vec::with_capacity(keys.len());
+        for key in keys {
+            contract_addresses.push(key.contract_address.clone());
+            chain_ids.push(key.chain_id.clone());
+            token_ids.push(key.token_id.clone());
+        }
+        let current_time: i64 = match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+            Ok(d) => d.as_secs().try_into().unwrap(),
+            Err(_) => 0,
+        };
+
+        let keyset = token_offers_keyset();
+        let token_offers_query = format!(
+            "SELECT contract_address, chain_id, token_id, total_count, offer_id, amount, source,
+                expire_at, hash, currency_address, cursor_values
+            FROM (
+                SELECT
+                    token_offer.contract_address,
+                    token_offer.chain_id,
+                    token_offer.token_id,
+                    token_offer_id AS offer_id,
+                    hex_to_decimal(offer_amount) AS amount,
+                    offer_maker AS source,
+                    end_date AS expire_at,
+                    order_hash as hash,
+                    currency_address,
+                    {},
+                    ROW_NUMBER() OVER token_offers AS position,
+                    COUNT(*) OVER (PARTITION BY token_offer.contract_address, token_offer.chain_id, token_offer.token_id) AS total_count
+                FROM token_offer
+                INNER JOIN UNNEST($1::TEXT[], $2::TEXT[], $3::TEXT[])
+                    AS k(contract_address, chain_id, token_id)
+                    ON k.contract_address = token_offer.contract_address
+                    AND k.chain_id = token_offer.chain_id
+                    AND k.token_id = token_offer.token_id
+                WHERE token_offer.status = 'PLACED'
+                    AND end_date > $4
+                WINDOW token_offers AS (
+                    PARTITION BY token_offer.contract_address, token_offer.chain_id, token_offer.token_id
+                    ORDER BY {}
+                )
+            ) offers
+            WHERE position <= $5
+            ORDER BY position
+            ",
+            keyset.cursor_values_select(),
+            keyset.order_by()
+        );
+
+        let rows = sqlx::query_as::<_, TokenOfferByKeyDB>(&token_offers_query)
+            .bind(contract_addresses)
+            .bind(chain_ids)
+            .bind(token_ids)
+            .bind(current_time)
+            .bind(items_per_token)
+            .fetch_all(self)
+            .await?;
+
+        let mut token_offers: HashMap<TokenKey, TokenOffersData> = HashMap::new();
+        for row in rows {
+            let key = TokenKey {
+                contract_address: row.contract_address,
+                chain_id: row.chain_id,
+                token_id: row.token_id,
+            };
+            token_offers
+                .entry(key)
+                .or_insert_with(|| TokenOffersData {
+                    offers: Vec::new(),
+                    total_count: row.total_count,
+                })
+                .offers
+                .push(row.offer);
+        }
+
+        Ok(token_offers)
     }
 
     async fn get_token_activity_data(
@@ -1566,4 +2413,134 @@ impl DatabaseAccess for PgPool {
 
         Ok((token_activity_data, has_next_page, count))
     }
+
+    async fn get_token_sales_data(
+        &self,
+        contract_address: &str,
+        chain_id: &str,
+        token_id: &str,
+    ) -> Result<Vec<TokenSaleData>, Error> {
+        let sales_query = format!(
+            "
+            SELECT
+                hex_to_decimal(amount) AS amount,
+                eth_amount::NUMERIC AS eth_amount,
+                currency_address,
+                from_address AS from,
+                to_address AS to,
+                block_timestamp AS time_stamp,
+                transaction_hash
+            FROM token_event
+            WHERE contract_address = $1
+                AND chain_id = $2
+                AND token_id = $3
+                AND event_type IN ({})
+            ORDER BY block_timestamp DESC
+            ",
+            event_type_list(&[TokenEventType::Sale])
+        );
+
+        let sales_db: Vec<TokenSaleDB> = sqlx::query_as(&sales_query)
+            .bind(contract_address)
+            .bind(chain_id)
+            .bind(token_id)
+            .fetch_all(self)
+            .await?;
+
+        let currencies: Vec<Currency> = sqlx::query_as!(
+            Currency,
+            r#"SELECT currency_address as contract, symbol, decimals FROM public.currency_mapping"#
+        )
+        .fetch_all(self)
+        .await?;
+
+        let sales = sales_db
+            .into_iter()
+            .map(|sale| {
+                let currency = currencies
+                    .iter()
+                    .find(|c| c.contract == sale.currency_address)
+                    .cloned()
+                    .unwrap_or_default();
+                let decimals = currency.decimals.unwrap_or(18);
+
+                TokenSaleData {
+                    price: sale
+                        .amount
+                        .map(|amount| normalize_currency_amount(amount, decimals)),
+                    eth_price: sale
+                        .eth_amount
+                        .map(|eth_amount| normalize_currency_amount(eth_amount, 18)),
+                    currency,
+                    from: sale.from,
+                    to: sale.to,
+                    time_stamp: sale.time_stamp,
+                    transaction_hash: sale.transaction_hash,
+                }
+            })
+            .collect();
+
+        Ok(sales)
+    }
+
+    async fn get_token_ownership_stats(
+        &self,
+        contract_address: &str,
+        chain_id: &str,
+        token_id: &str,
+    ) -> Result<TokenOwnershipStatsDB, Error> {
+        // A sale is usually recorded both as a transfer and as an executed
+        // order, so consecutive events to the same address are one holding.
+        let ownership_query = format!(
+            "
+            WITH ownership_change AS (
+                SELECT
+                    to_address,
+                    block_timestamp,
+                    LAG(to_address) OVER (ORDER BY block_timestamp, token_event_id) AS previous_owner
+                FROM token_event
+                WHERE contract_address = $1
+                    AND chain_id = $2
+                    AND token_id = $3
+                    AND event_type IN ({})
+                    AND to_address IS NOT NULL
+            ),
+            holding AS (
+                SELECT
+                    to_address,
+                    LEAD(block_timestamp) OVER (ORDER BY block_timestamp) - block_timestamp AS hold_time
+                FROM ownership_change
+                WHERE previous_owner IS DISTINCT FROM to_address
+            )
+            SELECT
+                (SELECT AVG(hold_time)::BIGINT FROM holding) AS average_hold_time,
+                (
+                    SELECT COUNT(DISTINCT owner)
+                    FROM (
+                        SELECT to_address AS owner FROM holding
+                        UNION
+                        SELECT token.current_owner
+                    ) AS owners
+                ) AS owner_count
+            FROM token
+            WHERE token.contract_address = $1
+                AND token.chain_id = $2
+                AND token.token_id = $3
+            ",
+            event_type_list(&[
+                TokenEventType::Mint,
+                TokenEventType::Transfer,
+                TokenEventType::Sale,
+            ])
+        );
+
+        let stats: TokenOwnershipStatsDB = sqlx::query_as(&ownership_query)
+            .bind(contract_address)
+            .bind(chain_id)
+            .bind(token_id)
+            .fetch_one(self)
+            .await?;
+
+        Ok(stats)
+    }
 }
@@ -1,5 +1,4 @@
 use crate::db::db_access::LISTING_TYPE_AUCTION_STR;
-use crate::handlers::utils::CHAIN_ID;
 use crate::models::default::{
     CollectionInfo, Currency, LastSale, LastSaleDB, LiveAuction, PreviewNft, Trending,
 };
@@ -13,7 +12,7 @@ pub trait DatabaseAccess: Send + Sync {
     async fn get_last_sales(&self) -> Result<Vec<LastSale>, Error>;
     async fn get_live_auctions(&self) -> Result<Vec<LiveAuction>, Error>;
 
-    async fn get_trending(&self, time_range: &str) -> Result<Vec<Trending>, Error>;
+    async fn get_trending(&self, time_range: &str, chain_id: &str) -> Result<Vec<Trending>, Error>;
 }
 
 #[async_trait]
@@ -114,7 +113,7 @@ impl DatabaseAccess for PgPool {
         Ok(live_auctions)
     }
 
-    async fn get_trending(&self, time_range: &str) -> Result<Vec<Trending>, Error> {
+    async fn get_trending(&self, time_range: &str, chain_id: &str) -> Result<Vec<Trending>, Error> {
         let contract_timestamp_clause: String = if time_range.is_empty() {
             String::new()
         } else {
@@ -131,7 +130,8 @@ impl DatabaseAccess for PgPool {
                     FROM
                      contract
                      INNER JOIN contract_marketdata on contract.contract_address = contract_marketdata.contract_address and contract.chain_id = contract_marketdata.chain_id {}
-                     WHERE contract_marketdata.volume > 0
+                     WHERE contract.chain_id = $1
+                       AND contract_marketdata.volume > 0
                GROUP BY contract.contract_address, contract.chain_id, floor_difference, volume
                ORDER BY VOLUME DESC
                LIMIT 5
@@ -139,8 +139,10 @@ impl DatabaseAccess for PgPool {
             contract_timestamp_clause,
         );
 
-        let mut collection_data: Vec<CollectionInfo> =
-            sqlx::query_as(&sql_query).fetch_all(self).await?;
+        let mut collection_data: Vec<CollectionInfo> = sqlx::query_as(&sql_query)
+            .bind(chain_id)
+            .fetch_all(self)
+            .await?;
 
         // Check if we have less than 5 results and fill up if necessary
         if collection_data.len() < 5 {
@@ -166,17 +168,18 @@ impl DatabaseAccess for PgPool {
                     INNER JOIN contract_marketdata ON contract.contract_address = contract_marketdata.contract_address
                     AND contract.chain_id = contract_marketdata.chain_id
                 WHERE
-                    contract_marketdata.volume > 0
-                    AND contract.contract_address NOT IN ({})
+                    contract.chain_id = $1
+                    AND contract_marketdata.volume > 0
+                    AND contract.contract_address <> ALL($2)
                 GROUP BY
                     contract.contract_address, contract.chain_id, floor_difference, volume
                 ORDER BY volume DESC
                 LIMIT {}",
-                existing_addresses.iter().map(|_| "?").collect::<Vec<_>>().join(", "),
                 missing_count
             );
             // Execute the query to fetch additional collections
             let additional_collections: Vec<CollectionInfo> = sqlx::query_as(&additional_sql_query)
+                .bind(chain_id)
                 .bind(existing_addresses)
                 .fetch_all(self)
                 .await?;
@@ -200,7 +203,7 @@ impl DatabaseAccess for PgPool {
 
             let preview_nfts: Vec<PreviewNft> = sqlx::query_as(preview_nft_sql)
                 .bind(&collection.collection_address)
-                .bind(CHAIN_ID)
+                .bind(chain_id)
                 .fetch_all(self)
                 .await?;
 
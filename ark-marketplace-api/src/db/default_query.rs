@@ -19,6 +19,7 @@ pub async fn get_live_auctions<D: default_db_access::DatabaseAccess + Sync>(
 pub async fn get_trending<D: default_db_access::DatabaseAccess + Sync>(
     db_access: &D,
     time_range: &str,
+    chain_id: &str,
 ) -> Result<Vec<Trending>, sqlx::Error> {
-    db_access.get_trending(time_range).await
+    db_access.get_trending(time_range, chain_id).await
 }
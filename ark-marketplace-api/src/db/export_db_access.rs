@@ -0,0 +1,201 @@
+use crate::models::default::Currency;
+use crate::models::export::{
+    ActivityExportFilter, ActivityExportRow, ActivityExportRowDB, ActivityExportScope,
+};
+use crate::models::token::TokenEventType;
+use crate::utils::currency_utils::normalize_currency_amount;
+use crate::utils::db_utils::event_type_list;
+use crate::utils::sql_utils::historical_usd_price_join;
+use bigdecimal::RoundingMode;
+use futures_util::stream::{self, BoxStream, StreamExt};
+use sqlx::{Error, PgPool, Postgres, Transaction};
+
+/// Rows read from the cursor at each fetch, and so the size of the chunks
+/// streamed to the client.
+const EXPORT_FETCH_SIZE: i64 = 1000;
+
+/// Longest a single fetch may run, and longest the client may leave the
+/// cursor idle between two chunks, before Postgres ends the export.
+const EXPORT_STATEMENT_TIMEOUT: &str = "30s";
+const EXPORT_IDLE_TIMEOUT: &str = "60s";
+
+pub trait DatabaseAccess: Send + Sync {
+    /// Streams the events of the export in batches, oldest first. The rows
+    /// are read from a server-side cursor so that a large export is never
+    /// loaded in memory, which keeps a connection busy until the stream is
+    /// consumed or dropped.
+    fn stream_activity_export(
+        &self,
+        filter: ActivityExportFilter,
+    ) -> BoxStream<'static, Result<Vec<ActivityExportRow>, Error>>;
+}
+
+enum ExportCursor {
+    Pending(PgPool, ActivityExportFilter),
+    Open(Transaction<'static, Postgres>),
+}
+
+impl DatabaseAccess for PgPool {
+    fn stream_activity_export(
+        &self,
+        filter: ActivityExportFilter,
+    ) -> BoxStream<'static, Result<Vec<ActivityExportRow>, Error>> {
+        let cursor = ExportCursor::Pending(self.clone(), filter);
+        stream::try_unfold(cursor, |cursor| async move {
+            let mut tx = match cursor {
+                ExportCursor::Pending(pool, filter) => open_export_cursor(&pool, &filter).await?,
+                ExportCursor::Open(tx) => tx,
+            };
+
+            let rows = sqlx::query_as::<_, ActivityExportRowDB>(&format!(
+                "FETCH {} FROM activity_export",
+                EXPORT_FETCH_SIZE
+            ))
+            .persistent(false)
+            .fetch_all(&mut *tx)
+            .await?;
+            if rows.is_empty() {
+                tx.commit().await?;
+                return Ok(None);
+            }
+
+            let rows = rows.into_iter().map(to_export_row).collect();
+            Ok(Some((rows, ExportCursor::Open(tx))))
+        })
+        .boxed()
+    }
+}
+
+async fn open_export_cursor(
+    pool: &PgPool,
+    filter: &ActivityExportFilter,
+) -> Result<Transaction<'static, Postgres>, Error> {
+    let (scope_condition, scope_address) = match &filter.scope {
+        ActivityExportScope::Collection { contract_address } => {
+            ("te.contract_address = $2", contract_address)
+        }
+        ActivityExportScope::User { user_address } => {
+            ("(te.from_address = $2 OR te.to_address = $2)", user_address)
+        }
+    };
+    let types_filter = match &filter.types {
+        None => String::from(""),
+        Some(values) => format!("AND te.event_type IN ({})", event_type_list(values)),
+    };
+    let fulfill = event_type_list(&[TokenEventType::Fulfill]);
+    let historical_price_join = historical_usd_price_join(
+        "historical_price",
+        &format!(
+            "COALESCE(currency_mapping.symbol, '{}')",
+            Currency::default().symbol.unwrap_or_default()
+        ),
+        "te.block_timestamp",
+    );
+
+    let declare_sql_query = format!(
+        "
+        DECLARE activity_export NO SCROLL CURSOR FOR
+        SELECT
+            te.block_timestamp AS time_stamp,
+            to_char(
+                to_timestamp(te.block_timestamp) AT TIME ZONE 'UTC',
+                'YYYY-MM-DD\"T\"HH24:MI:SS\"Z\"'
+            ) AS date,
+            CASE
+                WHEN te.event_type = 'Executed' THEN 'Sale'
+                ELSE te.event_type
+            END AS activity_type,
+            te.contract_address AS collection_address,
+            contract.contract_name AS collection_name,
+            te.token_id,
+            CASE
+                WHEN te.event_type IN ({fulfill}) THEN token_offer.from_address
+                ELSE te.from_address
+            END AS from,
+            CASE
+                WHEN te.event_type IN ({fulfill}) THEN token_offer.to_address
+                ELSE te.to_address
+            END AS to,
+            CASE
+                WHEN te.event_type IN ({fulfill}) THEN hex_to_decimal(token_offer.offer_amount)
+                ELSE hex_to_decimal(te.amount)
+            END AS amount,
+            te.currency_address,
+            currency_mapping.symbol AS currency_symbol,
+            currency_mapping.decimals AS currency_decimals,
+            COALESCE(te.currency_usd_price, historical_price.usd_price) AS currency_usd_price,
+            te.transaction_hash
+        FROM token_event te
+        LEFT JOIN token_offer ON te.order_hash = token_offer.order_hash
+        LEFT JOIN contract ON te.contract_address = contract.contract_address
+            AND te.chain_id = contract.chain_id
+        LEFT JOIN currency_mapping ON currency_mapping.currency_address = te.currency_address
+            AND currency_mapping.chain_id = te.chain_id
+        {historical_price_join}
+        WHERE te.chain_id = $1
+            AND {scope_condition}
+            AND te.block_timestamp BETWEEN $3 AND $4
+            {types_filter}
+        ORDER BY te.block_timestamp, te.token_event_id
+        "
+    );
+
+    let mut tx = pool.begin().await?;
+    sqlx::query(&format!(
+        "SET LOCAL statement_timeout = '{}'",
+        EXPORT_STATEMENT_TIMEOUT
+    ))
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query(&format!(
+        "SET LOCAL idle_in_transaction_session_timeout = '{}'",
+        EXPORT_IDLE_TIMEOUT
+    ))
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query(&declare_sql_query)
+        .bind(&filter.chain_id)
+        .bind(scope_address)
+        .bind(filter.from)
+        .bind(filter.to)
+        .persistent(false)
+        .execute(&mut *tx)
+        .await?;
+    Ok(tx)
+}
+
+/// Amounts without a known currency are in ETH, as on the other activity
+/// routes. The USD price falls back to the price of the currency on the day
+/// of the event when none was recorded with it.
+fn to_export_row(row: ActivityExportRowDB) -> ActivityExportRow {
+    let (currency, decimals) = match (&row.amount, row.currency_decimals) {
+        (None, _) => (None, 0),
+        (Some(_), Some(decimals)) => (row.currency_symbol, decimals),
+        (Some(_), None) => (Currency::default().symbol, 18),
+    };
+    let price = row
+        .amount
+        .map(|amount| normalize_currency_amount(amount, decimals));
+    let usd_price = match (&price, &row.currency_usd_price) {
+        (Some(price), Some(currency_usd_price)) => {
+            Some((price * currency_usd_price).with_scale_round(2, RoundingMode::HalfEven))
+        }
+        _ => None,
+    };
+
+    ActivityExportRow {
+        time_stamp: row.time_stamp,
+        date: row.date,
+        activity_type: row.activity_type,
+        collection_address: row.collection_address,
+        collection_name: row.collection_name,
+        token_id: row.token_id,
+        from: row.from,
+        to: row.to,
+        price,
+        currency,
+        currency_address: row.currency_address,
+        usd_price,
+        transaction_hash: row.transaction_hash,
+    }
+}
@@ -0,0 +1,10 @@
+use crate::db::export_db_access;
+use crate::models::export::{ActivityExportFilter, ActivityExportRow};
+use futures_util::stream::BoxStream;
+
+pub fn stream_activity_export<D: export_db_access::DatabaseAccess + Sync>(
+    db_access: &D,
+    filter: ActivityExportFilter,
+) -> BoxStream<'static, Result<Vec<ActivityExportRow>, sqlx::Error>> {
+    db_access.stream_activity_export(filter)
+}
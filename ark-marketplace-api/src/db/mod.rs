@@ -1,6 +1,18 @@
+pub mod api_key_db_access;
+pub mod api_key_query;
+pub mod chain_db_access;
+pub mod chain_query;
 pub mod db_access;
 pub mod default_db_access;
 pub mod default_query;
+pub mod export_db_access;
+pub mod export_query;
 pub mod portfolio_db_access;
 pub mod portfolio_query;
 pub mod query;
+pub mod wallet_session_db_access;
+pub mod wallet_session_query;
+pub mod watchlist_db_access;
+pub mod watchlist_query;
+pub mod webhook_db_access;
+pub mod webhook_query;
@@ -1,12 +1,18 @@
 use crate::models::default::Currency;
-use crate::models::portfolio::{OfferData, StatsData};
+use crate::models::portfolio::{
+    CollectionValuationDB, OfferData, PortfolioValueDB, RealizedPnlDB, StatsData,
+};
+use crate::models::tax_report::TaxEventDB;
 use crate::models::token::{
     TokenEventType, TokenPortfolioActivityData, TokenPortfolioActivityDataDB,
 };
+use crate::types::chart_interval::ChartInterval;
 use crate::types::offer_type::OfferType;
 use std::time::SystemTime;
 
+use crate::utils::cursor_utils::Cursor;
 use crate::utils::db_utils::event_type_list;
+use crate::utils::sql_utils::{activity_keyset, historical_usd_price_join};
 use async_trait::async_trait;
 use sqlx::Error;
 use sqlx::FromRow;
@@ -18,6 +24,18 @@ struct Count {
     total: i64,
 }
 
+// Cost of a token for the user receiving it in the event `te`: the price
+// paid for a sale, nothing for a mint and unknown for a transfer. The latest
+// of these events before the user's sale or ownership is the acquisition,
+// the sale winning over the transfer it comes with.
+const ACQUISITION_EVENTS: &str = "'Executed', 'Mint', 'Transfer'";
+const ACQUISITION_COST: &str = "
+    CASE
+        WHEN te.event_type = 'Executed' THEN te.eth_amount::NUMERIC
+        WHEN te.event_type = 'Mint' THEN 0
+    END";
+const ACQUISITION_ORDER: &str = "te.block_timestamp DESC, (te.event_type = 'Executed') DESC";
+
 #[async_trait]
 #[allow(clippy::too_many_arguments)]
 pub trait DatabaseAccess: Send + Sync {
@@ -25,11 +43,11 @@ pub trait DatabaseAccess: Send + Sync {
         &self,
         chain_id: &str,
         user_address: &str,
-        page: i64,
+        cursor: Option<&Cursor>,
         items_per_page: i64,
         direction: &str,
         types: &Option<Vec<TokenEventType>>,
-    ) -> Result<(Vec<TokenPortfolioActivityData>, bool, i64), Error>;
+    ) -> Result<(Vec<TokenPortfolioActivityData>, Option<String>, i64), Error>;
 
     async fn get_offers_data(
         &self,
@@ -41,6 +59,35 @@ pub trait DatabaseAccess: Send + Sync {
     ) -> Result<(Vec<OfferData>, bool, i64), Error>;
 
     async fn get_stats_data(&self, chain_id: &str, user_address: &str) -> Result<StatsData, Error>;
+
+    async fn get_collections_valuation(
+        &self,
+        chain_id: &str,
+        user_address: &str,
+    ) -> Result<Vec<CollectionValuationDB>, Error>;
+
+    async fn get_realized_pnl(
+        &self,
+        chain_id: &str,
+        user_address: &str,
+    ) -> Result<RealizedPnlDB, Error>;
+
+    async fn get_value_history(
+        &self,
+        chain_id: &str,
+        user_address: &str,
+        interval: ChartInterval,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<PortfolioValueDB>, Error>;
+
+    /// Tokens received and sent by the user until `to`, oldest first.
+    async fn get_tax_events(
+        &self,
+        chain_id: &str,
+        user_address: &str,
+        to: i64,
+    ) -> Result<Vec<TaxEventDB>, Error>;
 }
 
 #[async_trait]
@@ -49,12 +96,13 @@ impl DatabaseAccess for PgPool {
         &self,
         chain_id: &str,
         user_address: &str,
-        page: i64,
+        cursor: Option<&Cursor>,
         items_per_page: i64,
         direction: &str,
         types: &Option<Vec<TokenEventType>>,
-    ) -> Result<(Vec<TokenPortfolioActivityData>, bool, i64), Error> {
-        let offset = (page - 1) * items_per_page;
+    ) -> Result<(Vec<TokenPortfolioActivityData>, Option<String>, i64), Error> {
+        let keyset = activity_keyset(direction);
+        let (cursor_condition, cursor_binds) = keyset.after_condition(cursor, 3);
 
         let types_filter = match types {
             None => String::from(""),
@@ -149,26 +197,35 @@ impl DatabaseAccess for PgPool {
                 contract.is_verified as collection_is_verified,
                 {},
                 {},
+                {},
                 {}
             {}
-            ORDER BY te.block_timestamp {}
-            LIMIT {} OFFSET {}
+                {}
+            ORDER BY {}
+            LIMIT {}
             ",
             price_select_part,
             from_select_part,
             to_select_part,
+            keyset.cursor_values_select(),
             from_sql_query,
-            direction,
-            items_per_page,
-            offset,
+            cursor_condition,
+            keyset.order_by(),
+            items_per_page + 1,
         );
 
-        let token_activity_data_db: Vec<TokenPortfolioActivityDataDB> =
-            sqlx::query_as(&activity_sql_query)
+        let mut activity_query =
+            sqlx::query_as::<_, TokenPortfolioActivityDataDB>(&activity_sql_query)
                 .bind(chain_id)
-                .bind(user_address)
-                .fetch_all(self)
-                .await?;
+                .bind(user_address);
+        for value in cursor_binds {
+            activity_query = activity_query.bind(value);
+        }
+        let (token_activity_data_db, next_cursor) = keyset.paginate(
+            activity_query.fetch_all(self).await?,
+            items_per_page,
+            |activity| &activity.cursor_values,
+        );
 
         let currencies: Vec<Currency> = sqlx::query_as!(
             Currency,
@@ -203,11 +260,7 @@ impl DatabaseAccess for PgPool {
             })
             .collect();
 
-        // Calculate if there is another page
-        let total_pages = (count + items_per_page - 1) / items_per_page;
-        let has_next_page = page < total_pages;
-
-        Ok((token_activity_data, has_next_page, count))
+        Ok((token_activity_data, next_cursor, count))
     }
 
     async fn get_offers_data(
@@ -319,4 +372,265 @@ impl DatabaseAccess for PgPool {
 
         Ok(result)
     }
+
+    async fn get_collections_valuation(
+        &self,
+        chain_id: &str,
+        user_address: &str,
+    ) -> Result<Vec<CollectionValuationDB>, Error> {
+        let query = format!(
+            "
+            WITH held AS (
+                SELECT token.contract_address, token.token_id, token.top_bid_amount
+                FROM token
+                WHERE token.chain_id = $1
+                  AND token.current_owner = $2
+                  AND token.is_burned = false
+            ),
+            acquisitions AS (
+                SELECT DISTINCT ON (te.contract_address, te.token_id)
+                    te.contract_address,
+                    te.token_id,
+                    {acquisition_cost} AS cost
+                FROM token_event te
+                JOIN held
+                  ON held.contract_address = te.contract_address
+                 AND held.token_id = te.token_id
+                WHERE te.chain_id = $1
+                  AND te.to_address = $2
+                  AND te.event_type IN ({acquisition_events})
+                ORDER BY te.contract_address, te.token_id, {acquisition_order}
+            )
+            SELECT
+                contract.contract_address AS collection_address,
+                contract.contract_name AS collection_name,
+                contract.contract_image AS collection_image,
+                COUNT(*) AS token_count,
+                contract.floor_price,
+                contract.top_bid,
+                contract.floor_price * COUNT(*) AS value_at_floor,
+                SUM(GREATEST(held.top_bid_amount, contract.top_bid)) AS value_at_top_bid,
+                SUM(acquisitions.cost) AS cost_basis,
+                COUNT(*) FILTER (WHERE acquisitions.cost IS NULL) AS tokens_without_cost_basis,
+                SUM(contract.floor_price - acquisitions.cost) AS unrealized_pnl
+            FROM held
+            JOIN contract
+              ON contract.contract_address = held.contract_address
+             AND contract.chain_id = $1
+            LEFT JOIN acquisitions
+              ON acquisitions.contract_address = held.contract_address
+             AND acquisitions.token_id = held.token_id
+            GROUP BY
+                contract.contract_address,
+                contract.contract_name,
+                contract.contract_image,
+                contract.floor_price,
+                contract.top_bid
+            ORDER BY value_at_floor DESC NULLS LAST, token_count DESC
+            ",
+            acquisition_cost = ACQUISITION_COST,
+            acquisition_events = ACQUISITION_EVENTS,
+            acquisition_order = ACQUISITION_ORDER,
+        );
+
+        sqlx::query_as::<_, CollectionValuationDB>(&query)
+            .bind(chain_id)
+            .bind(user_address)
+            .fetch_all(self)
+            .await
+    }
+
+    async fn get_realized_pnl(
+        &self,
+        chain_id: &str,
+        user_address: &str,
+    ) -> Result<RealizedPnlDB, Error> {
+        let query = format!(
+            "
+            SELECT
+                COUNT(*) AS sale_count,
+                COUNT(*) FILTER (
+                    WHERE acquisition.cost IS NULL OR sale.eth_amount IS NULL
+                ) AS sales_without_cost_basis,
+                SUM(sale.eth_amount::NUMERIC - acquisition.cost) AS realized_pnl,
+                SUM(
+                    (sale.eth_amount::NUMERIC - acquisition.cost) / 1e18
+                    * COALESCE(
+                        CASE WHEN COALESCE(sale.currency_address, $3) = $3 THEN sale.currency_usd_price END,
+                        eth_price.usd_price
+                    )
+                ) AS realized_pnl_usd
+            FROM token_event sale
+            LEFT JOIN LATERAL (
+                SELECT {acquisition_cost} AS cost
+                FROM token_event te
+                WHERE te.chain_id = sale.chain_id
+                  AND te.contract_address = sale.contract_address
+                  AND te.token_id = sale.token_id
+                  AND te.to_address = $2
+                  AND te.event_type IN ({acquisition_events})
+                  AND te.block_timestamp < sale.block_timestamp
+                ORDER BY {acquisition_order}
+                LIMIT 1
+            ) acquisition ON true
+            {eth_price_join}
+            WHERE sale.chain_id = $1
+              AND sale.from_address = $2
+              AND sale.event_type = 'Executed'
+            ",
+            acquisition_cost = ACQUISITION_COST,
+            acquisition_events = ACQUISITION_EVENTS,
+            acquisition_order = ACQUISITION_ORDER,
+            eth_price_join = historical_usd_price_join("eth_price", "'ETH'", "sale.block_timestamp"),
+        );
+
+        sqlx::query_as::<_, RealizedPnlDB>(&query)
+            .bind(chain_id)
+            .bind(user_address)
+            .bind(Currency::default().contract)
+            .fetch_one(self)
+            .await
+    }
+
+    async fn get_value_history(
+        &self,
+        chain_id: &str,
+        user_address: &str,
+        interval: ChartInterval,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<PortfolioValueDB>, Error> {
+        // The owner of a token at the end of a bucket is the recipient of its
+        // last ownership event, the floor the last snapshot of its collection.
+        let query = "
+            WITH buckets AS (
+                SELECT
+                    bucket,
+                    EXTRACT(EPOCH FROM bucket + make_interval(secs => $6))::BIGINT AS bucket_end
+                FROM generate_series(
+                    date_trunc($3, to_timestamp($4) AT TIME ZONE 'UTC'),
+                    to_timestamp($5) AT TIME ZONE 'UTC',
+                    make_interval(secs => $6)
+                ) AS bucket
+            ),
+            ownership AS (
+                SELECT
+                    contract_address,
+                    token_id,
+                    block_timestamp,
+                    to_address = $2 AS is_owner
+                FROM token_event
+                WHERE chain_id = $1
+                  AND (to_address = $2 OR from_address = $2)
+                  AND event_type IN ('Mint', 'Transfer', 'Burn')
+            ),
+            holdings AS (
+                SELECT buckets.bucket, held.contract_address, COUNT(*) AS token_count
+                FROM buckets
+                CROSS JOIN LATERAL (
+                    SELECT DISTINCT ON (contract_address, token_id)
+                        contract_address,
+                        is_owner
+                    FROM ownership
+                    WHERE block_timestamp < buckets.bucket_end
+                    ORDER BY contract_address, token_id, block_timestamp DESC
+                ) held
+                WHERE held.is_owner
+                GROUP BY buckets.bucket, held.contract_address
+            )
+            SELECT
+                EXTRACT(EPOCH FROM buckets.bucket)::BIGINT AS timestamp,
+                COALESCE(SUM(holdings.token_count), 0)::BIGINT AS token_count,
+                SUM(holdings.token_count * floor.floor) AS value_at_floor
+            FROM buckets
+            LEFT JOIN holdings ON holdings.bucket = buckets.bucket
+            LEFT JOIN LATERAL (
+                SELECT floor_collection.floor
+                FROM floor_collection
+                WHERE floor_collection.contract_address = holdings.contract_address
+                  AND floor_collection.chain_id = $1
+                  AND floor_collection.timestamp < buckets.bucket_end
+                  AND floor_collection.floor > 0
+                ORDER BY floor_collection.timestamp DESC
+                LIMIT 1
+            ) floor ON true
+            GROUP BY buckets.bucket
+            ORDER BY buckets.bucket
+        ";
+
+        sqlx::query_as::<_, PortfolioValueDB>(query)
+            .bind(chain_id)
+            .bind(user_address)
+            .bind(interval.date_trunc_unit())
+            .bind(from)
+            .bind(to)
+            .bind(interval.seconds() as f64)
+            .fetch_all(self)
+            .await
+    }
+
+    async fn get_tax_events(
+        &self,
+        chain_id: &str,
+        user_address: &str,
+        to: i64,
+    ) -> Result<Vec<TaxEventDB>, Error> {
+        // A sale comes with the transfer of the token, only the sale is kept.
+        // Trades are valued at the USD price recorded with the sale, else at
+        // the last price of its currency recorded in the day before.
+        let query = format!(
+            "
+            SELECT *
+            FROM (
+                SELECT DISTINCT ON (te.contract_address, te.token_id, te.block_timestamp, is_acquisition)
+                    te.contract_address,
+                    contract.contract_name AS collection_name,
+                    te.token_id,
+                    te.event_type,
+                    te.block_timestamp,
+                    te.to_address = $2 AS is_acquisition,
+                    CASE WHEN te.event_type = 'Executed' THEN hex_to_decimal(te.amount) END AS amount,
+                    hex_to_decimal(te.royalty_amount) AS royalty_amount,
+                    hex_to_decimal(te.marketplace_fee_amount) AS marketplace_fee_amount,
+                    currency_mapping.symbol AS currency_symbol,
+                    currency_mapping.decimals AS currency_decimals,
+                    COALESCE(te.currency_usd_price, price.usd_price) AS currency_usd_price,
+                    te.transaction_hash
+                FROM token_event te
+                LEFT JOIN contract ON te.contract_address = contract.contract_address
+                    AND te.chain_id = contract.chain_id
+                LEFT JOIN currency_mapping ON currency_mapping.currency_address = te.currency_address
+                    AND currency_mapping.chain_id = te.chain_id
+                {price_join}
+                WHERE te.chain_id = $1
+                  AND (te.from_address = $2 OR te.to_address = $2)
+                  AND te.from_address IS DISTINCT FROM te.to_address
+                  AND te.event_type IN ('Executed', 'Mint', 'Transfer', 'Burn')
+                  AND te.block_timestamp <= $3
+                ORDER BY
+                    te.contract_address,
+                    te.token_id,
+                    te.block_timestamp,
+                    is_acquisition,
+                    (te.event_type = 'Executed') DESC
+            ) events
+            ORDER BY block_timestamp, is_acquisition DESC
+        ",
+            price_join = historical_usd_price_join(
+                "price",
+                &format!(
+                    "COALESCE(currency_mapping.symbol, '{}')",
+                    Currency::default().symbol.unwrap_or_default()
+                ),
+                "te.block_timestamp",
+            ),
+        );
+
+        sqlx::query_as::<_, TaxEventDB>(&query)
+            .bind(chain_id)
+            .bind(user_address)
+            .bind(to)
+            .fetch_all(self)
+            .await
+    }
 }
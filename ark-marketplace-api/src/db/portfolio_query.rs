@@ -1,23 +1,28 @@
 use crate::db::portfolio_db_access;
-use crate::models::portfolio::{OfferData, StatsData};
+use crate::models::portfolio::{
+    CollectionValuationDB, OfferData, PortfolioValueDB, RealizedPnlDB, StatsData,
+};
+use crate::models::tax_report::TaxEventDB;
 use crate::models::token::{TokenEventType, TokenPortfolioActivityData};
+use crate::types::chart_interval::ChartInterval;
 use crate::types::offer_type::OfferType;
+use crate::utils::cursor_utils::Cursor;
 
 #[allow(clippy::too_many_arguments)]
 pub async fn get_activity_data<D: portfolio_db_access::DatabaseAccess + Sync>(
     db_access: &D,
     chain_id: &str,
     user_address: &str,
-    page: i64,
+    cursor: Option<&Cursor>,
     items_per_page: i64,
     direction: &str,
     types: &Option<Vec<TokenEventType>>,
-) -> Result<(Vec<TokenPortfolioActivityData>, bool, i64), sqlx::Error> {
+) -> Result<(Vec<TokenPortfolioActivityData>, Option<String>, i64), sqlx::Error> {
     db_access
         .get_activity_data(
             chain_id,
             user_address,
-            page,
+            cursor,
             items_per_page,
             direction,
             types,
@@ -47,3 +52,43 @@ pub async fn get_stats_data<D: portfolio_db_access::DatabaseAccess + Sync>(
 ) -> Result<StatsData, sqlx::Error> {
     db_access.get_stats_data(chain_id, user_address).await
 }
+
+pub async fn get_collections_valuation<D: portfolio_db_access::DatabaseAccess + Sync>(
+    db_access: &D,
+    chain_id: &str,
+    user_address: &str,
+) -> Result<Vec<CollectionValuationDB>, sqlx::Error> {
+    db_access
+        .get_collections_valuation(chain_id, user_address)
+        .await
+}
+
+pub async fn get_realized_pnl<D: portfolio_db_access::DatabaseAccess + Sync>(
+    db_access: &D,
+    chain_id: &str,
+    user_address: &str,
+) -> Result<RealizedPnlDB, sqlx::Error> {
+    db_access.get_realized_pnl(chain_id, user_address).await
+}
+
+pub async fn get_value_history<D: portfolio_db_access::DatabaseAccess + Sync>(
+    db_access: &D,
+    chain_id: &str,
+    user_address: &str,
+    interval: ChartInterval,
+    from: i64,
+    to: i64,
+) -> Result<Vec<PortfolioValueDB>, sqlx::Error> {
+    db_access
+        .get_value_history(chain_id, user_address, interval, from, to)
+        .await
+}
+
+pub async fn get_tax_events<D: portfolio_db_access::DatabaseAccess + Sync>(
+    db_access: &D,
+    chain_id: &str,
+    user_address: &str,
+    to: i64,
+) -> Result<Vec<TaxEventDB>, sqlx::Error> {
+    db_access.get_tax_events(chain_id, user_address, to).await
+}
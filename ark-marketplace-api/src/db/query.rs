@@ -1,18 +1,23 @@
 use crate::db::db_access::DatabaseAccess;
 use crate::models::collection::{
-    CollectionActivityData, CollectionData, CollectionFloorPrice, CollectionFullData,
-    CollectionPortfolioData, CollectionSearchData, OwnerDataCompleted,
+    CollectionActivityData, CollectionChartData, CollectionData, CollectionFloorPrice,
+    CollectionFullData, CollectionKey, CollectionOfferDataDB, CollectionPortfolioData,
+    CollectionSearchData, OfferDepthLevelDB, OrderBookLevelDB, OwnerDataCompleted,
 };
 use crate::models::default::Currency;
 use crate::models::token::{
-    TokenActivityData, TokenData, TokenEventType, TokenInformationData, TokenMarketData,
-    TokenOfferOneDataDB, TokenPortfolioData,
+    TokenActivityData, TokenBatchData, TokenData, TokenDetailsData, TokenEventType,
+    TokenInformationData, TokenKey, TokenMarketData, TokenOfferOneDataDB, TokenOffersData,
+    TokenOwnershipStatsDB, TokenPortfolioData, TokenSaleData,
 };
+use crate::types::chart_interval::ChartInterval;
+use crate::utils::cursor_utils::Cursor;
 use crate::utils::http_utils::{
     get_address_from_starknet_id, get_image_from_starknet_address, get_starknet_id_from_address,
 };
 use redis::AsyncCommands;
 use regex::Regex;
+use std::collections::HashMap;
 
 pub async fn get_collections_data<D: DatabaseAccess + Sync>(
     db_access: &D,
@@ -93,7 +98,7 @@ pub async fn get_collection_data<D: DatabaseAccess + Sync>(
     chain_id: &str,
 ) -> Result<CollectionData, sqlx::Error> {
     // Generate a unique key for this query based on buy_now value
-    let cache_key = format!("collection_{}", contract_address);
+    let cache_key = format!("collection_{}_{}", contract_address, chain_id);
     // Try to get the data from Redis
     let cached_data: Option<String> = redis_conn.get(&cache_key).await.unwrap_or(None);
 
@@ -141,16 +146,16 @@ pub async fn get_collection_activity_data<D: DatabaseAccess + Sync>(
     db_access: &D,
     contract_address: &str,
     chain_id: &str,
-    page: i64,
+    cursor: Option<&Cursor>,
     items_per_page: i64,
     direction: &str,
     types: &Option<Vec<TokenEventType>>,
-) -> Result<(Vec<CollectionActivityData>, bool, i64), sqlx::Error> {
+) -> Result<(Vec<CollectionActivityData>, Option<String>, i64), sqlx::Error> {
     let collection_activity_data = db_access
         .get_collection_activity_data(
             contract_address,
             chain_id,
-            page,
+            cursor,
             items_per_page,
             direction,
             types,
@@ -160,6 +165,19 @@ pub async fn get_collection_activity_data<D: DatabaseAccess + Sync>(
     Ok(collection_activity_data)
 }
 
+pub async fn get_collection_charts_data<D: DatabaseAccess + Sync>(
+    db_access: &D,
+    contract_address: &str,
+    chain_id: &str,
+    interval: ChartInterval,
+    from: i64,
+    to: i64,
+) -> Result<Vec<CollectionChartData>, sqlx::Error> {
+    db_access
+        .get_collection_charts_data(contract_address, chain_id, interval, from, to)
+        .await
+}
+
 pub async fn get_collection_floor_price<D: DatabaseAccess + Sync>(
     db_access: &D,
     contract_address: &str,
@@ -170,6 +188,101 @@ pub async fn get_collection_floor_price<D: DatabaseAccess + Sync>(
         .await
 }
 
+pub async fn get_collection_offers_data<D: DatabaseAccess + Sync>(
+    db_access: &D,
+    contract_address: &str,
+    chain_id: &str,
+    cursor: Option<&Cursor>,
+    items_per_page: i64,
+) -> Result<(Vec<CollectionOfferDataDB>, Option<String>, i64), sqlx::Error> {
+    db_access
+        .get_collection_offers_data(contract_address, chain_id, cursor, items_per_page)
+        .await
+}
+
+pub async fn get_collection_offers_depth<D: DatabaseAccess + Sync>(
+    db_access: &D,
+    contract_address: &str,
+    chain_id: &str,
+    levels: i64,
+) -> Result<Vec<OfferDepthLevelDB>, sqlx::Error> {
+    db_access
+        .get_collection_offers_depth(contract_address, chain_id, levels)
+        .await
+}
+
+pub async fn get_collection_offers_acceptable_tokens<D: DatabaseAccess + Sync>(
+    db_access: &D,
+    offer_ids: &[i32],
+    user_address: &str,
+) -> Result<HashMap<i32, Vec<String>>, sqlx::Error> {
+    db_access
+        .get_collection_offers_acceptable_tokens(offer_ids, user_address)
+        .await
+}
+
+pub async fn get_collection_order_book_levels<D: DatabaseAccess + Sync>(
+    db_access: &D,
+    redis_conn: &mut redis::aio::MultiplexedConnection,
+    contract_address: &str,
+    chain_id: &str,
+    disable_cache: bool,
+) -> Result<Vec<OrderBookLevelDB>, sqlx::Error> {
+    // The levels are cached before bucketing, so that every bucket size is
+    // served from the same entry
+    let cache_key = format!("order_book_{}_{}", contract_address, chain_id);
+    // Try to get the data from Redis
+    let cached_data: Option<String> = redis_conn.get(&cache_key).await.unwrap_or(None);
+
+    match (cached_data, disable_cache) {
+        (Some(data), false) => {
+            // If the data is in the cache and caching is not disabled, deserialize it and return it
+            match serde_json::from_str::<Vec<OrderBookLevelDB>>(&data) {
+                Ok(levels) => Ok(levels),
+                Err(e) => {
+                    tracing::error!("Failed to deserialize data from Redis: {}", e);
+                    Err(sqlx::Error::Configuration(e.into()))
+                }
+            }
+        }
+        _ => {
+            // If the data is not in the cache or caching is disabled, get it from the database
+            let levels = db_access
+                .get_collection_order_book_levels(contract_address, chain_id)
+                .await?;
+
+            // Spawn a new task to cache the data in Redis for future requests
+            if !disable_cache {
+                let levels_clone = levels.clone();
+                let cache_key_clone = cache_key.clone();
+                let mut redis_conn_clone = redis_conn.clone();
+                tokio::spawn(async move {
+                    let levels_string = match serde_json::to_string(&levels_clone) {
+                        Ok(string) => string,
+                        Err(e) => {
+                            tracing::error!("Failed to serialize data to Redis: {}", e);
+                            return;
+                        }
+                    };
+                    let _: () = redis_conn_clone
+                        .set_ex(&cache_key_clone, levels_string, 60)
+                        .await
+                        .unwrap_or(());
+                });
+            }
+
+            Ok(levels)
+        }
+    }
+}
+
+pub async fn get_collections_data_by_keys<D: DatabaseAccess + Sync>(
+    db_access: &D,
+    keys: &[CollectionKey],
+) -> Result<HashMap<CollectionKey, CollectionData>, sqlx::Error> {
+    db_access.get_collections_data_by_keys(keys).await
+}
+
 #[allow(clippy::too_many_arguments)]
 pub async fn get_token_data<D: DatabaseAccess + Sync>(
     db_access: &D,
@@ -182,13 +295,27 @@ pub async fn get_token_data<D: DatabaseAccess + Sync>(
         .await
 }
 
+pub async fn get_tokens_data_by_keys<D: DatabaseAccess + Sync>(
+    db_access: &D,
+    keys: &[TokenKey],
+) -> Result<HashMap<TokenKey, TokenDetailsData>, sqlx::Error> {
+    db_access.get_tokens_data_by_keys(keys).await
+}
+
+pub async fn get_tokens_batch_data<D: DatabaseAccess + Sync>(
+    db_access: &D,
+    keys: &[TokenKey],
+) -> Result<Vec<TokenBatchData>, sqlx::Error> {
+    db_access.get_tokens_batch_data(keys).await
+}
+
 #[allow(clippy::too_many_arguments)]
 pub async fn get_tokens_data<D: DatabaseAccess + Sync>(
     db_access: &D,
     redis_conn: &mut redis::aio::MultiplexedConnection,
     contract_address: &str,
     chain_id: &str,
-    page: i64,
+    cursor: Option<&Cursor>,
     items_per_page: i64,
     buy_now: bool,
     sort: &str,
@@ -197,16 +324,24 @@ pub async fn get_tokens_data<D: DatabaseAccess + Sync>(
     disable_cache: bool,
     token_ids: Option<Vec<String>>,
     token_id: Option<String>,
-) -> Result<(Vec<TokenData>, bool, i64), sqlx::Error> {
+) -> Result<(Vec<TokenData>, Option<String>, i64), sqlx::Error> {
+    // Only the first page is cached, the next ones depend on the cursor
+    let disable_cache = disable_cache || cursor.is_some();
     // Generate a unique key for this query based on buy_now value
     let cache_key = if buy_now {
         if direction == "asc" {
-            format!("listed_tokens_asc_{}_page_{}", contract_address, page)
+            format!(
+                "listed_tokens_asc_{}_{}_first_page",
+                contract_address, chain_id
+            )
         } else {
-            format!("listed_tokens_desc_{}_page_{}", contract_address, page)
+            format!(
+                "listed_tokens_desc_{}_{}_first_page",
+                contract_address, chain_id
+            )
         }
     } else {
-        format!("all_tokens_{}_page_{}", contract_address, page)
+        format!("all_tokens_{}_{}_first_page", contract_address, chain_id)
     };
     // Try to get the data from Redis
     let cached_data: Option<String> = redis_conn.get(&cache_key).await.unwrap_or(None);
@@ -214,7 +349,7 @@ pub async fn get_tokens_data<D: DatabaseAccess + Sync>(
     match (cached_data, disable_cache) {
         (Some(data), false) => {
             // If the data is in the cache and caching is not disabled, deserialize it and return it
-            match serde_json::from_str::<(Vec<TokenData>, bool, i64)>(&data) {
+            match serde_json::from_str::<(Vec<TokenData>, Option<String>, i64)>(&data) {
                 Ok(tokens_data) => Ok(tokens_data),
                 Err(e) => {
                     tracing::error!("Failed to deserialize data from Redis: {}", e);
@@ -228,7 +363,7 @@ pub async fn get_tokens_data<D: DatabaseAccess + Sync>(
                 .get_tokens_data(
                     contract_address,
                     chain_id,
-                    page,
+                    cursor,
                     items_per_page,
                     buy_now,
                     Some(sort.to_string()),
@@ -281,19 +416,19 @@ pub async fn get_tokens_data_by_id<D: DatabaseAccess + Sync>(
     db_access: &D,
     contract_address: &str,
     chain_id: &str,
-    page: i64,
+    cursor: Option<&Cursor>,
     items_per_page: i64,
     buy_now: bool,
     sort: &str,
     direction: &str,
     sort_value: Option<String>,
     token_ids: Option<Vec<String>>,
-) -> Result<(Vec<TokenData>, bool, i64), sqlx::Error> {
+) -> Result<(Vec<TokenData>, Option<String>, i64), sqlx::Error> {
     let tokens_data = db_access
         .get_tokens_data(
             contract_address,
             chain_id,
-            page,
+            cursor,
             items_per_page,
             buy_now,
             Some(sort.to_string()),
@@ -337,11 +472,21 @@ pub async fn get_token_offers_data<D: DatabaseAccess + Sync>(
     contract_address: &str,
     chain_id: &str,
     token_id: &str,
-    page: i64,
+    cursor: Option<&Cursor>,
     items_per_page: i64,
-) -> Result<(Vec<TokenOfferOneDataDB>, bool, i64), sqlx::Error> {
+) -> Result<(Vec<TokenOfferOneDataDB>, Option<String>, i64), sqlx::Error> {
     db_access
-        .get_token_offers_data(contract_address, chain_id, token_id, page, items_per_page)
+        .get_token_offers_data(contract_address, chain_id, token_id, cursor, items_per_page)
+        .await
+}
+
+pub async fn get_token_offers_data_by_keys<D: DatabaseAccess + Sync>(
+    db_access: &D,
+    keys: &[TokenKey],
+    items_per_token: i64,
+) -> Result<HashMap<TokenKey, TokenOffersData>, sqlx::Error> {
+    db_access
+        .get_token_offers_data_by_keys(keys, items_per_token)
         .await
 }
 
@@ -402,3 +547,25 @@ pub async fn refresh_token_metadata<D: DatabaseAccess + Sync>(
         .refresh_token_metadata(contract_address, chain_id, token_id)
         .await
 }
+
+pub async fn get_token_sales_data<D: DatabaseAccess + Sync>(
+    db_access: &D,
+    contract_address: &str,
+    chain_id: &str,
+    token_id: &str,
+) -> Result<Vec<TokenSaleData>, sqlx::Error> {
+    db_access
+        .get_token_sales_data(contract_address, chain_id, token_id)
+        .await
+}
+
+pub async fn get_token_ownership_stats<D: DatabaseAccess + Sync>(
+    db_access: &D,
+    contract_address: &str,
+    chain_id: &str,
+    token_id: &str,
+) -> Result<TokenOwnershipStatsDB, sqlx::Error> {
+    db_access
+        .get_token_ownership_stats(contract_address, chain_id, token_id)
+        .await
+}
@@ -0,0 +1,67 @@
+use crate::models::wallet_session::WalletNonce;
+use async_trait::async_trait;
+use sqlx::Error;
+use sqlx::PgPool;
+
+#[async_trait]
+pub trait DatabaseAccess: Send + Sync {
+    async fn create_wallet_nonce(&self, nonce: &WalletNonce) -> Result<(), Error>;
+
+    async fn consume_wallet_nonce(
+        &self,
+        nonce: &str,
+        user_address: &str,
+        now: i64,
+    ) -> Result<WalletNonce, Error>;
+}
+
+#[async_trait]
+impl DatabaseAccess for PgPool {
+    /// Also drops the nonces that expired without being used.
+    async fn create_wallet_nonce(&self, nonce: &WalletNonce) -> Result<(), Error> {
+        sqlx::query("DELETE FROM wallet_nonce WHERE expiration_time < $1")
+            .bind(nonce.issued_at)
+            .execute(self)
+            .await?;
+
+        sqlx::query(
+            "
+            INSERT INTO wallet_nonce (nonce, user_address, chain_id, issued_at, expiration_time)
+            VALUES ($1, $2, $3, $4, $5)
+            ",
+        )
+        .bind(&nonce.nonce)
+        .bind(&nonce.user_address)
+        .bind(&nonce.chain_id)
+        .bind(nonce.issued_at)
+        .bind(nonce.expiration_time)
+        .execute(self)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Deletes the nonce so that a signature can't be replayed, failing with
+    /// `RowNotFound` when it wasn't issued to `user_address` or expired.
+    async fn consume_wallet_nonce(
+        &self,
+        nonce: &str,
+        user_address: &str,
+        now: i64,
+    ) -> Result<WalletNonce, Error> {
+        sqlx::query_as::<_, WalletNonce>(
+            "
+            DELETE FROM wallet_nonce
+            WHERE nonce = $1
+                AND user_address = $2
+                AND expiration_time >= $3
+            RETURNING nonce, user_address, chain_id, issued_at, expiration_time
+            ",
+        )
+        .bind(nonce)
+        .bind(user_address)
+        .bind(now)
+        .fetch_one(self)
+        .await
+    }
+}
@@ -0,0 +1,20 @@
+use crate::db::wallet_session_db_access;
+use crate::models::wallet_session::WalletNonce;
+
+pub async fn create_wallet_nonce<D: wallet_session_db_access::DatabaseAccess + Sync>(
+    db_access: &D,
+    nonce: &WalletNonce,
+) -> Result<(), sqlx::Error> {
+    db_access.create_wallet_nonce(nonce).await
+}
+
+pub async fn consume_wallet_nonce<D: wallet_session_db_access::DatabaseAccess + Sync>(
+    db_access: &D,
+    nonce: &str,
+    user_address: &str,
+    now: i64,
+) -> Result<WalletNonce, sqlx::Error> {
+    db_access
+        .consume_wallet_nonce(nonce, user_address, now)
+        .await
+}
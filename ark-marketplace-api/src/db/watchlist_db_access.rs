@@ -0,0 +1,404 @@
+use crate::models::default::Currency;
+use crate::models::watchlist::{
+    WatchlistEventType, WatchlistFeedItem, WatchlistFeedItemDB, WatchlistItem,
+};
+use crate::utils::cursor_utils::Cursor;
+use crate::utils::sql_utils::watchlist_feed_keyset;
+use async_trait::async_trait;
+use sqlx::Error;
+use sqlx::FromRow;
+use sqlx::PgPool;
+
+#[derive(FromRow)]
+struct Count {
+    total: i64,
+}
+
+/// How far back the feed goes, which bounds the events scanned per page.
+const WATCHLIST_FEED_WINDOW_SECONDS: i64 = 30 * 24 * 60 * 60;
+
+const WATCHLIST_ITEM_SELECT: &str = "
+    SELECT
+        watchlist_item.watchlist_item_id,
+        watchlist_item.user_address,
+        watchlist_item.contract_address,
+        watchlist_item.chain_id,
+        watchlist_item.token_id,
+        contract.contract_name AS collection_name,
+        contract.contract_image AS collection_image,
+        contract.floor_price AS floor,
+        token.metadata,
+        watchlist_item.created_timestamp
+    FROM watchlist_item
+    LEFT JOIN contract
+        ON contract.contract_address = watchlist_item.contract_address
+        AND contract.chain_id = watchlist_item.chain_id
+    LEFT JOIN token
+        ON token.contract_address = watchlist_item.contract_address
+        AND token.chain_id = watchlist_item.chain_id
+        AND token.token_id = watchlist_item.token_id
+";
+
+#[async_trait]
+pub trait DatabaseAccess: Send + Sync {
+    async fn get_watchlist_items(
+        &self,
+        chain_id: &str,
+        user_address: &str,
+    ) -> Result<Vec<WatchlistItem>, Error>;
+
+    async fn count_watchlist_items(&self, user_address: &str) -> Result<i64, Error>;
+
+    async fn add_watchlist_item(
+        &self,
+        chain_id: &str,
+        user_address: &str,
+        contract_address: &str,
+        token_id: Option<&str>,
+    ) -> Result<WatchlistItem, Error>;
+
+    async fn delete_watchlist_item(&self, user_address: &str, id: i32) -> Result<(), Error>;
+
+    async fn get_watchlist_feed(
+        &self,
+        chain_id: &str,
+        user_address: &str,
+        cursor: Option<&Cursor>,
+        items_per_page: i64,
+        types: &Option<Vec<WatchlistEventType>>,
+    ) -> Result<(Vec<WatchlistFeedItem>, Option<String>), Error>;
+}
+
+#[async_trait]
+impl DatabaseAccess for PgPool {
+    async fn get_watchlist_items(
+        &self,
+        chain_id: &str,
+        user_address: &str,
+    ) -> Result<Vec<WatchlistItem>, Error> {
+        let query = format!(
+            "
+            {}
+            WHERE watchlist_item.user_address = $1
+                AND watchlist_item.chain_id = $2
+            ORDER BY watchlist_item.created_timestamp DESC, watchlist_item.watchlist_item_id DESC
+            ",
+            WATCHLIST_ITEM_SELECT
+        );
+
+        sqlx::query_as::<_, WatchlistItem>(&query)
+            .bind(user_address)
+            .bind(chain_id)
+            .fetch_all(self)
+            .await
+    }
+
+    async fn count_watchlist_items(&self, user_address: &str) -> Result<i64, Error> {
+        let total_count: Count =
+            sqlx::query_as("SELECT COUNT(*) AS total FROM watchlist_item WHERE user_address = $1")
+                .bind(user_address)
+                .fetch_one(self)
+                .await?;
+        Ok(total_count.total)
+    }
+
+    /// Following an item twice returns the existing entry. Fails with
+    /// `RowNotFound` when the collection or the token isn't indexed.
+    async fn add_watchlist_item(
+        &self,
+        chain_id: &str,
+        user_address: &str,
+        contract_address: &str,
+        token_id: Option<&str>,
+    ) -> Result<WatchlistItem, Error> {
+        let watchlist_item_id: i32 = sqlx::query_scalar(
+            "
+            INSERT INTO watchlist_item (user_address, contract_address, chain_id, token_id)
+            SELECT $1, contract.contract_address, contract.chain_id, $4
+            FROM contract
+            WHERE contract.contract_address = $2
+                AND contract.chain_id = $3
+                AND (
+                    $4::TEXT IS NULL
+                    OR EXISTS (
+                        SELECT 1
+                        FROM token
+                        WHERE token.contract_address = contract.contract_address
+                            AND token.chain_id = contract.chain_id
+                            AND token.token_id = $4
+                    )
+                )
+            ON CONFLICT (user_address, chain_id, contract_address, COALESCE(token_id, ''))
+            DO UPDATE SET user_address = EXCLUDED.user_address
+            RETURNING watchlist_item_id
+            ",
+        )
+        .bind(user_address)
+        .bind(contract_address)
+        .bind(chain_id)
+        .bind(token_id)
+        .fetch_one(self)
+        .await?;
+
+        let query = format!(
+            "
+            {}
+            WHERE watchlist_item.watchlist_item_id = $1
+            ",
+            WATCHLIST_ITEM_SELECT
+        );
+
+        sqlx::query_as::<_, WatchlistItem>(&query)
+            .bind(watchlist_item_id)
+            .fetch_one(self)
+            .await
+    }
+
+    async fn delete_watchlist_item(&self, user_address: &str, id: i32) -> Result<(), Error> {
+        let result = sqlx::query(
+            "DELETE FROM watchlist_item WHERE watchlist_item_id = $1 AND user_address = $2",
+        )
+        .bind(id)
+        .bind(user_address)
+        .execute(self)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(Error::RowNotFound);
+        }
+
+        Ok(())
+    }
+
+    /// Merges the listings, offers and sales of the followed tokens, and the
+    /// floor drops and collection offers of the followed collections, over
+    /// the last `WATCHLIST_FEED_WINDOW_SECONDS`. A listing is a price drop
+    /// when the seller listed the same token higher before.
+    async fn get_watchlist_feed(
+        &self,
+        chain_id: &str,
+        user_address: &str,
+        cursor: Option<&Cursor>,
+        items_per_page: i64,
+        types: &Option<Vec<WatchlistEventType>>,
+    ) -> Result<(Vec<WatchlistFeedItem>, Option<String>), Error> {
+        let keyset = watchlist_feed_keyset();
+        let (cursor_condition, cursor_binds) = keyset.after_condition(cursor, 4);
+        let since = chrono::Utc::now().timestamp() - WATCHLIST_FEED_WINDOW_SECONDS;
+
+        let types_filter = match types {
+            None => String::from(""),
+            Some(values) => format!(
+                "AND feed.event_type IN ({})",
+                values
+                    .iter()
+                    .map(|v| format!("'{}'", v.to_db_string()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        };
+
+        let feed_sql_query = format!(
+            "
+            WITH watched AS (
+                SELECT contract_address, token_id
+                FROM watchlist_item
+                WHERE user_address = $1
+                    AND chain_id = $2
+            ),
+            feed AS (
+                SELECT
+                    te.token_event_id AS feed_id,
+                    CASE
+                        WHEN te.event_type IN ('Executed', 'Sale') THEN 'SALE'
+                        WHEN te.event_type = 'Offer' THEN 'NEW_OFFER'
+                        WHEN te.previous_price > te.price THEN 'PRICE_DROP'
+                        ELSE 'LISTING'
+                    END AS event_type,
+                    te.contract_address,
+                    te.token_id,
+                    te.price,
+                    CASE
+                        WHEN te.previous_price > te.price THEN te.previous_price
+                    END AS previous_price,
+                    te.currency_address,
+                    te.from_address,
+                    te.to_address,
+                    te.block_timestamp AS timestamp,
+                    te.transaction_hash
+                FROM (
+                    SELECT
+                        token_event.*,
+                        hex_to_decimal(token_event.amount) AS price,
+                        previous_listing.price AS previous_price
+                    FROM token_event
+                    LEFT JOIN LATERAL (
+                        SELECT hex_to_decimal(previous.amount) AS price
+                        FROM token_event previous
+                        WHERE token_event.event_type = 'Listing'
+                            AND previous.contract_address = token_event.contract_address
+                            AND previous.chain_id = token_event.chain_id
+                            AND previous.token_id = token_event.token_id
+                            AND previous.from_address = token_event.from_address
+                            AND previous.event_type = 'Listing'
+                            AND (previous.block_timestamp, previous.token_event_id)
+                                < (token_event.block_timestamp, token_event.token_event_id)
+                        ORDER BY previous.block_timestamp DESC, previous.token_event_id DESC
+                        LIMIT 1
+                    ) previous_listing ON true
+                    WHERE token_event.chain_id = $2
+                        AND token_event.event_type IN ('Listing', 'Offer', 'Executed', 'Sale')
+                        AND token_event.contract_address IN (SELECT contract_address FROM watched)
+                        AND token_event.block_timestamp >= $3
+                ) te
+                WHERE EXISTS (
+                    SELECT 1
+                    FROM watched
+                    WHERE watched.contract_address = te.contract_address
+                        AND (watched.token_id IS NULL OR watched.token_id = te.token_id)
+                )
+                UNION ALL
+                SELECT
+                    'collection_offer_' || collection_offer.collection_offer_id,
+                    'NEW_OFFER',
+                    collection_offer.contract_address,
+                    NULL,
+                    hex_to_decimal(collection_offer.offer_amount),
+                    NULL,
+                    collection_offer.currency_address,
+                    collection_offer.offer_maker,
+                    NULL,
+                    collection_offer.offer_timestamp,
+                    NULL
+                FROM collection_offer
+                WHERE collection_offer.chain_id = $2
+                    AND collection_offer.contract_address IN (
+                        SELECT contract_address FROM watched WHERE token_id IS NULL
+                    )
+                    AND collection_offer.offer_timestamp >= $3
+                UNION ALL
+                SELECT
+                    'floor_' || fc.contract_address || '_' || fc.timestamp,
+                    'PRICE_DROP',
+                    fc.contract_address,
+                    NULL,
+                    fc.floor,
+                    fc.previous_floor,
+                    NULL,
+                    NULL,
+                    NULL,
+                    fc.timestamp,
+                    NULL
+                FROM (
+                    SELECT
+                        floor_collection.*,
+                        previous.floor AS previous_floor
+                    FROM floor_collection
+                    LEFT JOIN LATERAL (
+                        SELECT previous.floor
+                        FROM floor_collection previous
+                        WHERE previous.contract_address = floor_collection.contract_address
+                            AND previous.chain_id = floor_collection.chain_id
+                            AND previous.timestamp < floor_collection.timestamp
+                        ORDER BY previous.timestamp DESC
+                        LIMIT 1
+                    ) previous ON true
+                    WHERE floor_collection.chain_id = $2
+                        AND floor_collection.contract_address IN (
+                            SELECT contract_address FROM watched WHERE token_id IS NULL
+                        )
+                        AND floor_collection.timestamp >= $3
+                ) fc
+                WHERE fc.floor > 0
+                    AND fc.floor < fc.previous_floor
+            )
+            SELECT
+                feed.event_type,
+                feed.contract_address,
+                feed.token_id,
+                contract.contract_name AS collection_name,
+                token.metadata,
+                feed.price,
+                feed.previous_price,
+                feed.currency_address,
+                feed.from_address,
+                feed.to_address,
+                feed.timestamp,
+                feed.transaction_hash,
+                {}
+            FROM feed
+            LEFT JOIN contract
+                ON contract.contract_address = feed.contract_address
+                AND contract.chain_id = $2
+            LEFT JOIN token
+                ON token.contract_address = feed.contract_address
+                AND token.chain_id = $2
+                AND token.token_id = feed.token_id
+            WHERE TRUE
+                {}
+                {}
+            ORDER BY {}
+            LIMIT {}
+            ",
+            keyset.cursor_values_select(),
+            types_filter,
+            cursor_condition,
+            keyset.order_by(),
+            items_per_page + 1,
+        );
+
+        let mut feed_query = sqlx::query_as::<_, WatchlistFeedItemDB>(&feed_sql_query)
+            .bind(user_address)
+            .bind(chain_id)
+            .bind(since);
+        for value in cursor_binds {
+            feed_query = feed_query.bind(value);
+        }
+        let (feed_items_db, next_cursor) =
+            keyset.paginate(feed_query.fetch_all(self).await?, items_per_page, |item| {
+                &item.cursor_values
+            });
+
+        let currencies: Vec<Currency> = sqlx::query_as!(
+            Currency,
+            r#"SELECT currency_address as contract, symbol, decimals FROM public.currency_mapping"#
+        )
+        .fetch_all(self)
+        .await?;
+
+        let feed_items = feed_items_db
+            .into_iter()
+            .filter_map(|item| {
+                let event_type = match item.event_type.parse::<WatchlistEventType>() {
+                    Ok(event_type) => event_type,
+                    Err(e) => {
+                        tracing::error!("{}", e);
+                        return None;
+                    }
+                };
+                let currency = currencies
+                    .iter()
+                    .find(|c| c.contract == item.currency_address)
+                    .cloned()
+                    .unwrap_or_default();
+
+                Some(WatchlistFeedItem {
+                    event_type,
+                    contract_address: item.contract_address,
+                    token_id: item.token_id,
+                    collection_name: item.collection_name,
+                    metadata: item.metadata,
+                    price: item.price,
+                    previous_price: item.previous_price,
+                    currency,
+                    from: item.from_address,
+                    to: item.to_address,
+                    timestamp: item.timestamp,
+                    transaction_hash: item.transaction_hash,
+                })
+            })
+            .collect();
+
+        Ok((feed_items, next_cursor))
+    }
+}
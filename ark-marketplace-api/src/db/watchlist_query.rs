@@ -0,0 +1,51 @@
+use crate::db::watchlist_db_access;
+use crate::models::watchlist::{WatchlistEventType, WatchlistFeedItem, WatchlistItem};
+use crate::utils::cursor_utils::Cursor;
+
+pub async fn get_watchlist_items<D: watchlist_db_access::DatabaseAccess + Sync>(
+    db_access: &D,
+    chain_id: &str,
+    user_address: &str,
+) -> Result<Vec<WatchlistItem>, sqlx::Error> {
+    db_access.get_watchlist_items(chain_id, user_address).await
+}
+
+pub async fn count_watchlist_items<D: watchlist_db_access::DatabaseAccess + Sync>(
+    db_access: &D,
+    user_address: &str,
+) -> Result<i64, sqlx::Error> {
+    db_access.count_watchlist_items(user_address).await
+}
+
+pub async fn add_watchlist_item<D: watchlist_db_access::DatabaseAccess + Sync>(
+    db_access: &D,
+    chain_id: &str,
+    user_address: &str,
+    contract_address: &str,
+    token_id: Option<&str>,
+) -> Result<WatchlistItem, sqlx::Error> {
+    db_access
+        .add_watchlist_item(chain_id, user_address, contract_address, token_id)
+        .await
+}
+
+pub async fn delete_watchlist_item<D: watchlist_db_access::DatabaseAccess + Sync>(
+    db_access: &D,
+    user_address: &str,
+    id: i32,
+) -> Result<(), sqlx::Error> {
+    db_access.delete_watchlist_item(user_address, id).await
+}
+
+pub async fn get_watchlist_feed<D: watchlist_db_access::DatabaseAccess + Sync>(
+    db_access: &D,
+    chain_id: &str,
+    user_address: &str,
+    cursor: Option<&Cursor>,
+    items_per_page: i64,
+    types: &Option<Vec<WatchlistEventType>>,
+) -> Result<(Vec<WatchlistFeedItem>, Option<String>), sqlx::Error> {
+    db_access
+        .get_watchlist_feed(chain_id, user_address, cursor, items_per_page, types)
+        .await
+}
@@ -0,0 +1,206 @@
+use crate::models::webhook::{WebhookDeadLetter, WebhookSubscription, WebhookSubscriptionRequest};
+use async_trait::async_trait;
+use sqlx::Error;
+use sqlx::FromRow;
+use sqlx::PgPool;
+
+#[derive(FromRow)]
+struct Count {
+    total: i64,
+}
+
+const WEBHOOK_SUBSCRIPTION_COLUMNS: &str = "
+    webhook_subscription_id,
+    url,
+    secret,
+    contract_address,
+    chain_id,
+    event_types,
+    is_active,
+    created_timestamp,
+    updated_timestamp
+";
+
+#[async_trait]
+pub trait DatabaseAccess: Send + Sync {
+    async fn create_webhook_subscription(
+        &self,
+        subscription: &WebhookSubscriptionRequest,
+    ) -> Result<WebhookSubscription, Error>;
+
+    async fn get_webhook_subscriptions(
+        &self,
+        contract_address: Option<&str>,
+    ) -> Result<Vec<WebhookSubscription>, Error>;
+
+    async fn get_webhook_subscription(&self, id: i32) -> Result<WebhookSubscription, Error>;
+
+    async fn update_webhook_subscription(
+        &self,
+        id: i32,
+        subscription: &WebhookSubscriptionRequest,
+    ) -> Result<WebhookSubscription, Error>;
+
+    async fn delete_webhook_subscription(&self, id: i32) -> Result<(), Error>;
+
+    async fn get_webhook_dead_letters(
+        &self,
+        id: i32,
+        page: i64,
+        items_per_page: i64,
+    ) -> Result<(Vec<WebhookDeadLetter>, bool, i64), Error>;
+}
+
+#[async_trait]
+impl DatabaseAccess for PgPool {
+    async fn create_webhook_subscription(
+        &self,
+        subscription: &WebhookSubscriptionRequest,
+    ) -> Result<WebhookSubscription, Error> {
+        let query = format!(
+            "
+            INSERT INTO webhook_subscription (url, contract_address, chain_id, event_types, is_active)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING {}
+            ",
+            WEBHOOK_SUBSCRIPTION_COLUMNS
+        );
+
+        sqlx::query_as::<_, WebhookSubscription>(&query)
+            .bind(&subscription.url)
+            .bind(&subscription.contract_address)
+            .bind(&subscription.chain_id)
+            .bind(subscription.event_types.clone().unwrap_or_default())
+            .bind(subscription.is_active.unwrap_or(true))
+            .fetch_one(self)
+            .await
+    }
+
+    async fn get_webhook_subscriptions(
+        &self,
+        contract_address: Option<&str>,
+    ) -> Result<Vec<WebhookSubscription>, Error> {
+        let query = format!(
+            "
+            SELECT {}
+            FROM webhook_subscription
+            WHERE ($1::TEXT IS NULL OR contract_address = $1)
+            ORDER BY webhook_subscription_id
+            ",
+            WEBHOOK_SUBSCRIPTION_COLUMNS
+        );
+
+        sqlx::query_as::<_, WebhookSubscription>(&query)
+            .bind(contract_address)
+            .fetch_all(self)
+            .await
+    }
+
+    async fn get_webhook_subscription(&self, id: i32) -> Result<WebhookSubscription, Error> {
+        let query = format!(
+            "
+            SELECT {}
+            FROM webhook_subscription
+            WHERE webhook_subscription_id = $1
+            ",
+            WEBHOOK_SUBSCRIPTION_COLUMNS
+        );
+
+        sqlx::query_as::<_, WebhookSubscription>(&query)
+            .bind(id)
+            .fetch_one(self)
+            .await
+    }
+
+    async fn update_webhook_subscription(
+        &self,
+        id: i32,
+        subscription: &WebhookSubscriptionRequest,
+    ) -> Result<WebhookSubscription, Error> {
+        let query = format!(
+            "
+            UPDATE webhook_subscription
+            SET url = $2,
+                contract_address = $3,
+                chain_id = $4,
+                event_types = $5,
+                is_active = $6,
+                updated_timestamp = EXTRACT(EPOCH FROM NOW())::BIGINT
+            WHERE webhook_subscription_id = $1
+            RETURNING {}
+            ",
+            WEBHOOK_SUBSCRIPTION_COLUMNS
+        );
+
+        sqlx::query_as::<_, WebhookSubscription>(&query)
+            .bind(id)
+            .bind(&subscription.url)
+            .bind(&subscription.contract_address)
+            .bind(&subscription.chain_id)
+            .bind(subscription.event_types.clone().unwrap_or_default())
+            .bind(subscription.is_active.unwrap_or(true))
+            .fetch_one(self)
+            .await
+    }
+
+    async fn delete_webhook_subscription(&self, id: i32) -> Result<(), Error> {
+        let result =
+            sqlx::query("DELETE FROM webhook_subscription WHERE webhook_subscription_id = $1")
+                .bind(id)
+                .execute(self)
+                .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(Error::RowNotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn get_webhook_dead_letters(
+        &self,
+        id: i32,
+        page: i64,
+        items_per_page: i64,
+    ) -> Result<(Vec<WebhookDeadLetter>, bool, i64), Error> {
+        let total_count: Count = sqlx::query_as(
+            "
+            SELECT COUNT(*) AS total
+            FROM webhook_dead_letter
+            WHERE webhook_subscription_id = $1
+            ",
+        )
+        .bind(id)
+        .fetch_one(self)
+        .await?;
+        let count = total_count.total;
+
+        let dead_letters = sqlx::query_as::<_, WebhookDeadLetter>(
+            "
+            SELECT
+                webhook_dead_letter_id,
+                webhook_subscription_id,
+                event_type,
+                order_hash,
+                payload,
+                attempts,
+                last_error,
+                created_timestamp
+            FROM webhook_dead_letter
+            WHERE webhook_subscription_id = $1
+            ORDER BY created_timestamp DESC
+            LIMIT $2 OFFSET $3
+            ",
+        )
+        .bind(id)
+        .bind(items_per_page)
+        .bind((page - 1) * items_per_page)
+        .fetch_all(self)
+        .await?;
+
+        let total_pages = (count + items_per_page - 1) / items_per_page;
+        let has_next_page = page < total_pages;
+
+        Ok((dead_letters, has_next_page, count))
+    }
+}
@@ -0,0 +1,51 @@
+use crate::db::webhook_db_access;
+use crate::models::webhook::{WebhookDeadLetter, WebhookSubscription, WebhookSubscriptionRequest};
+
+pub async fn create_webhook_subscription<D: webhook_db_access::DatabaseAccess + Sync>(
+    db_access: &D,
+    subscription: &WebhookSubscriptionRequest,
+) -> Result<WebhookSubscription, sqlx::Error> {
+    db_access.create_webhook_subscription(subscription).await
+}
+
+pub async fn get_webhook_subscriptions<D: webhook_db_access::DatabaseAccess + Sync>(
+    db_access: &D,
+    contract_address: Option<&str>,
+) -> Result<Vec<WebhookSubscription>, sqlx::Error> {
+    db_access.get_webhook_subscriptions(contract_address).await
+}
+
+pub async fn get_webhook_subscription<D: webhook_db_access::DatabaseAccess + Sync>(
+    db_access: &D,
+    id: i32,
+) -> Result<WebhookSubscription, sqlx::Error> {
+    db_access.get_webhook_subscription(id).await
+}
+
+pub async fn update_webhook_subscription<D: webhook_db_access::DatabaseAccess + Sync>(
+    db_access: &D,
+    id: i32,
+    subscription: &WebhookSubscriptionRequest,
+) -> Result<WebhookSubscription, sqlx::Error> {
+    db_access
+        .update_webhook_subscription(id, subscription)
+        .await
+}
+
+pub async fn delete_webhook_subscription<D: webhook_db_access::DatabaseAccess + Sync>(
+    db_access: &D,
+    id: i32,
+) -> Result<(), sqlx::Error> {
+    db_access.delete_webhook_subscription(id).await
+}
+
+pub async fn get_webhook_dead_letters<D: webhook_db_access::DatabaseAccess + Sync>(
+    db_access: &D,
+    id: i32,
+    page: i64,
+    items_per_page: i64,
+) -> Result<(Vec<WebhookDeadLetter>, bool, i64), sqlx::Error> {
+    db_access
+        .get_webhook_dead_letters(id, page, items_per_page)
+        .await
+}
@@ -0,0 +1,115 @@
+use crate::db::query::{
+    get_collections_data_by_keys, get_currencies, get_token_offers_data_by_keys,
+    get_tokens_data_by_keys,
+};
+use crate::models::collection::{CollectionData, CollectionKey};
+use crate::models::default::Currency;
+use crate::models::token::{TokenDetailsData, TokenKey, TokenOffersData};
+use async_graphql::dataloader::{DataLoader, HashMapCache, Loader};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+
+pub type CollectionDataLoader = DataLoader<CollectionLoader, HashMapCache>;
+pub type TokenDataLoader = DataLoader<TokenLoader, HashMapCache>;
+pub type TokenOffersDataLoader = DataLoader<TokenOffersLoader, HashMapCache>;
+
+/// Largest page of offers of a token, the `first` limit of
+/// `TokenDetailsData::offers`. The batch lookup reads one more offer
+/// per token to know whether there is a next page.
+pub const MAX_TOKEN_OFFERS_PAGE: i64 = 100;
+
+/// Batches the collections requested while resolving a query into one
+/// database query.
+pub struct CollectionLoader {
+    db_pool: PgPool,
+}
+
+impl CollectionLoader {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+}
+
+impl Loader<CollectionKey> for CollectionLoader {
+    type Value = CollectionData;
+    type Error = Arc<sqlx::Error>;
+
+    async fn load(
+        &self,
+        keys: &[CollectionKey],
+    ) -> Result<HashMap<CollectionKey, Self::Value>, Self::Error> {
+        get_collections_data_by_keys(&self.db_pool, keys)
+            .await
+            .map_err(Arc::new)
+    }
+}
+
+/// Batches the tokens requested while resolving a query into one database
+/// query.
+pub struct TokenLoader {
+    db_pool: PgPool,
+}
+
+impl TokenLoader {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+}
+
+impl Loader<TokenKey> for TokenLoader {
+    type Value = TokenDetailsData;
+    type Error = Arc<sqlx::Error>;
+
+    async fn load(&self, keys: &[TokenKey]) -> Result<HashMap<TokenKey, Self::Value>, Self::Error> {
+        get_tokens_data_by_keys(&self.db_pool, keys)
+            .await
+            .map_err(Arc::new)
+    }
+}
+
+/// Batches the first page of offers of the tokens requested while resolving
+/// a query into one database query.
+pub struct TokenOffersLoader {
+    db_pool: PgPool,
+}
+
+impl TokenOffersLoader {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+}
+
+impl Loader<TokenKey> for TokenOffersLoader {
+    type Value = TokenOffersData;
+    type Error = Arc<sqlx::Error>;
+
+    async fn load(&self, keys: &[TokenKey]) -> Result<HashMap<TokenKey, Self::Value>, Self::Error> {
+        get_token_offers_data_by_keys(&self.db_pool, keys, MAX_TOKEN_OFFERS_PAGE + 1)
+            .await
+            .map_err(Arc::new)
+    }
+}
+
+/// Currencies read at most once per request.
+pub struct RequestCurrencies {
+    db_pool: PgPool,
+    currencies: OnceCell<Vec<Currency>>,
+}
+
+impl RequestCurrencies {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self {
+            db_pool,
+            currencies: OnceCell::new(),
+        }
+    }
+
+    pub async fn get(&self) -> Result<&[Currency], sqlx::Error> {
+        self.currencies
+            .get_or_try_init(|| get_currencies(&self.db_pool))
+            .await
+            .map(Vec::as_slice)
+    }
+}
@@ -0,0 +1,59 @@
+pub mod loaders;
+pub mod query;
+pub mod types;
+
+use crate::managers::chain_registry::ChainRegistry;
+use async_graphql::dataloader::HashMapCache;
+use async_graphql::{EmptyMutation, EmptySubscription, Request, Schema};
+use loaders::{
+    CollectionDataLoader, CollectionLoader, RequestCurrencies, TokenDataLoader, TokenLoader,
+    TokenOffersDataLoader, TokenOffersLoader,
+};
+use query::QueryRoot;
+use sqlx::PgPool;
+
+pub type MarketplaceSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// Deepest nesting of selections accepted in a query.
+pub const MAX_QUERY_DEPTH: usize = 10;
+
+/// Highest complexity accepted for a query, lists counting once per
+/// requested item.
+pub const MAX_QUERY_COMPLEXITY: usize = 5000;
+
+pub fn build_schema(db_pool: PgPool, chains: ChainRegistry) -> MarketplaceSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data(db_pool)
+        .data(chains)
+        .limit_depth(MAX_QUERY_DEPTH)
+        .limit_complexity(MAX_QUERY_COMPLEXITY)
+        .finish()
+}
+
+/// Attaches the data loaders and the currencies to a request. They are
+/// created per request so that their cache never outlives it.
+pub fn with_loaders(request: Request, db_pool: &PgPool) -> Request {
+    request
+        .data(CollectionDataLoader::with_cache(
+            CollectionLoader::new(db_pool.clone()),
+            tokio::spawn,
+            HashMapCache::default(),
+        ))
+        .data(TokenDataLoader::with_cache(
+            TokenLoader::new(db_pool.clone()),
+            tokio::spawn,
+            HashMapCache::default(),
+        ))
+        .data(TokenOffersDataLoader::with_cache(
+            TokenOffersLoader::new(db_pool.clone()),
+            tokio::spawn,
+            HashMapCache::default(),
+        ))
+        .data(RequestCurrencies::new(db_pool.clone()))
+}
+
+/// Logs a database error and hides its details from the client.
+pub(crate) fn internal_error(context: &str, err: impl std::fmt::Display) -> async_graphql::Error {
+    tracing::error!("error query {}: {}", context, err);
+    async_graphql::Error::new("Internal server error")
+}
@@ -0,0 +1,50 @@
+use super::types::{load_collection, load_token, Collection, Portfolio};
+use crate::managers::chain_registry::ChainRegistry;
+use crate::models::token::TokenDetailsData;
+use crate::utils::http_utils::normalize_address;
+use async_graphql::{Context, Object, Result};
+
+pub struct QueryRoot;
+
+fn resolve_chain_id(ctx: &Context<'_>, chain_id: Option<&str>) -> Result<String> {
+    ctx.data_unchecked::<ChainRegistry>()
+        .resolve(chain_id)
+        .map_err(async_graphql::Error::new)
+}
+
+#[Object]
+impl QueryRoot {
+    /// Collection by contract address, on the default chain unless `chainId` is set
+    async fn collection(
+        &self,
+        ctx: &Context<'_>,
+        address: String,
+        chain_id: Option<String>,
+    ) -> Result<Option<Collection>> {
+        let chain_id = resolve_chain_id(ctx, chain_id.as_deref())?;
+        load_collection(ctx, &normalize_address(&address), &chain_id).await
+    }
+
+    async fn token(
+        &self,
+        ctx: &Context<'_>,
+        address: String,
+        token_id: String,
+        chain_id: Option<String>,
+    ) -> Result<Option<TokenDetailsData>> {
+        let chain_id = resolve_chain_id(ctx, chain_id.as_deref())?;
+        load_token(ctx, &normalize_address(&address), &chain_id, &token_id).await
+    }
+
+    async fn portfolio(
+        &self,
+        ctx: &Context<'_>,
+        address: String,
+        chain_id: Option<String>,
+    ) -> Result<Portfolio> {
+        Ok(Portfolio {
+            address: normalize_address(&address),
+            chain_id: resolve_chain_id(ctx, chain_id.as_deref())?,
+        })
+    }
+}
@@ -0,0 +1,444 @@
+use super::internal_error;
+use super::loaders::{
+    CollectionDataLoader, RequestCurrencies, TokenDataLoader, TokenOffersDataLoader,
+};
+use crate::db::db_access::DatabaseAccess;
+use crate::db::portfolio_query::{get_activity_data, get_stats_data};
+use crate::db::query::{get_collection_activity_data, get_token_offers_data};
+use crate::models::collection::{CollectionActivityData, CollectionData, CollectionKey};
+use crate::models::default::Currency;
+use crate::models::token::{
+    TokenDetailsData, TokenEventType, TokenKey, TokenOfferOneData, TokenPortfolioActivityData,
+};
+use crate::models::wallet_session::WalletSession;
+use crate::utils::currency_utils::compute_floor_difference;
+use crate::utils::cursor_utils::{Cursor, Keyset};
+use crate::utils::sql_utils::{activity_keyset, token_offers_keyset, tokens_keyset};
+use async_graphql::{
+    ComplexObject, Context, Enum, Error, Object, OutputType, Result, SimpleObject,
+};
+use bigdecimal::BigDecimal;
+use sqlx::PgPool;
+
+/// A page of a list, `next_cursor` being passed as `after` to read the
+/// following one.
+#[derive(SimpleObject)]
+#[graphql(concrete(name = "TokenPage", params(TokenDetailsData)))]
+#[graphql(concrete(name = "ActivityPage", params(Activity)))]
+#[graphql(concrete(name = "OfferPage", params(TokenOfferOneData)))]
+pub struct Page<T: OutputType> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+    pub total_count: i64,
+}
+
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+pub enum SortDirection {
+    Asc,
+    Desc,
+}
+
+impl SortDirection {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Asc => "asc",
+            Self::Desc => "desc",
+        }
+    }
+}
+
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+pub enum TokenSort {
+    Price,
+    Rarity,
+}
+
+impl TokenSort {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Price => "price",
+            Self::Rarity => "rarity",
+        }
+    }
+}
+
+fn decode_after(keyset: &Keyset, after: Option<&str>) -> Result<Option<Cursor>> {
+    after
+        .map(|after| keyset.decode_cursor(after))
+        .transpose()
+        .map_err(async_graphql::Error::new)
+}
+
+pub(crate) async fn load_collection(
+    ctx: &Context<'_>,
+    contract_address: &str,
+    chain_id: &str,
+) -> Result<Option<Collection>> {
+    let key = CollectionKey {
+        contract_address: contract_address.to_string(),
+        chain_id: chain_id.to_string(),
+    };
+    let collection = ctx
+        .data_unchecked::<CollectionDataLoader>()
+        .load_one(key)
+        .await
+        .map_err(|err| internal_error("load collection", err))?;
+
+    Ok(collection.map(|data| Collection::new(chain_id.to_string(), data)))
+}
+
+pub(crate) async fn load_token(
+    ctx: &Context<'_>,
+    contract_address: &str,
+    chain_id: &str,
+    token_id: &str,
+) -> Result<Option<TokenDetailsData>> {
+    let key = TokenKey {
+        contract_address: contract_address.to_string(),
+        chain_id: chain_id.to_string(),
+        token_id: token_id.to_string(),
+    };
+    ctx.data_unchecked::<TokenDataLoader>()
+        .load_one(key)
+        .await
+        .map_err(|err| internal_error("load token", err))
+}
+
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct Collection {
+    pub chain_id: String,
+    #[graphql(flatten)]
+    pub data: CollectionData,
+}
+
+impl Collection {
+    pub fn new(chain_id: String, data: CollectionData) -> Self {
+        Self { chain_id, data }
+    }
+}
+
+#[ComplexObject]
+impl Collection {
+    #[graphql(complexity = "first as usize * child_complexity")]
+    async fn tokens(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 20, validator(minimum = 1, maximum = 100))] first: i32,
+        after: Option<String>,
+        #[graphql(default_with = "TokenSort::Price")] sort: TokenSort,
+        #[graphql(default_with = "SortDirection::Asc")] direction: SortDirection,
+        #[graphql(default)] buy_now: bool,
+    ) -> Result<Page<TokenDetailsData>> {
+        let keyset = tokens_keyset(sort.as_str(), direction.as_str(), None);
+        let cursor = decode_after(&keyset, after.as_deref())?;
+
+        let (tokens, next_cursor, total_count) = ctx
+            .data_unchecked::<PgPool>()
+            .get_tokens_data(
+                &self.data.address,
+                &self.chain_id,
+                cursor.as_ref(),
+                first as i64,
+                buy_now,
+                Some(sort.as_str().to_string()),
+                Some(direction.as_str().to_string()),
+                None,
+                None,
+                None,
+            )
+            .await
+            .map_err(|err| internal_error("get_tokens_data", err))?;
+
+        let keys: Vec<TokenKey> = tokens
+            .into_iter()
+            .filter_map(|token| {
+                Some(TokenKey {
+                    contract_address: token.collection_address?,
+                    chain_id: self.chain_id.clone(),
+                    token_id: token.token_id?,
+                })
+            })
+            .collect();
+        let mut details = ctx
+            .data_unchecked::<TokenDataLoader>()
+            .load_many(keys.iter().cloned())
+            .await
+            .map_err(|err| internal_error("load tokens", err))?;
+
+        Ok(Page {
+            items: keys.iter().filter_map(|key| details.remove(key)).collect(),
+            next_cursor,
+            total_count,
+        })
+    }
+
+    #[graphql(complexity = "first as usize * child_complexity")]
+    async fn activity(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 20, validator(minimum = 1, maximum = 100))] first: i32,
+        after: Option<String>,
+        #[graphql(default_with = "SortDirection::Desc")] direction: SortDirection,
+        types: Option<Vec<TokenEventType>>,
+    ) -> Result<Page<Activity>> {
+        let keyset = activity_keyset(direction.as_str());
+        let cursor = decode_after(&keyset, after.as_deref())?;
+
+        let (activities, next_cursor, total_count) = get_collection_activity_data(
+            ctx.data_unchecked::<PgPool>(),
+            &self.data.address,
+            &self.chain_id,
+            cursor.as_ref(),
+            first as i64,
+            direction.as_str(),
+            &types,
+        )
+        .await
+        .map_err(|err| internal_error("get_collection_activity_data", err))?;
+
+        Ok(Page {
+            items: activities
+                .into_iter()
+                .map(|activity| Activity::from_collection(&self.chain_id, activity))
+                .collect(),
+            next_cursor,
+            total_count,
+        })
+    }
+}
+
+#[ComplexObject]
+impl TokenDetailsData {
+    async fn collection(&self, ctx: &Context<'_>) -> Result<Option<Collection>> {
+        load_collection(ctx, &self.collection_address, &self.chain_id).await
+    }
+
+    #[graphql(complexity = "first as usize * child_complexity")]
+    async fn offers(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 20, validator(minimum = 1, maximum = 100))] first: i32,
+        after: Option<String>,
+    ) -> Result<Page<TokenOfferOneData>> {
+        let keyset = token_offers_keyset();
+        let cursor = decode_after(&keyset, after.as_deref())?;
+
+        // The first pages of the tokens of a query are read in one batch,
+        // the following ones token by token.
+        let (offers, next_cursor, total_count) = match cursor {
+            None => {
+                let key = TokenKey {
+                    contract_address: self.collection_address.clone(),
+                    chain_id: self.chain_id.clone(),
+                    token_id: self.token_id.clone(),
+                };
+                let token_offers = ctx
+                    .data_unchecked::<TokenOffersDataLoader>()
+                    .load_one(key)
+                    .await
+                    .map_err(|err| internal_error("get_token_offers_data_by_keys", err))?;
+                match token_offers {
+                    Some(token_offers) => {
+                        let (offers, next_cursor) =
+                            keyset.paginate(token_offers.offers, first as i64, |offer| {
+                                &offer.cursor_values
+                            });
+                        (offers, next_cursor, token_offers.total_count)
+                    }
+                    None => (vec![], None, 0),
+                }
+            }
+            Some(cursor) => get_token_offers_data(
+                ctx.data_unchecked::<PgPool>(),
+                &self.collection_address,
+                &self.chain_id,
+                &self.token_id,
+                Some(&cursor),
+                first as i64,
+            )
+            .await
+            .map_err(|err| internal_error("get_token_offers_data", err))?,
+        };
+
+        let currencies = ctx
+            .data_unchecked::<RequestCurrencies>()
+            .get()
+            .await
+            .map_err(|err| internal_error("get_currencies", err))?;
+        let floor = load_collection(ctx, &self.collection_address, &self.chain_id)
+            .await?
+            .and_then(|collection| collection.data.floor);
+
+        let items = offers
+            .into_iter()
+            .map(|offer| TokenOfferOneData {
+                offer_id: offer.offer_id,
+                price: offer.amount.clone(),
+                currency: currencies
+                    .iter()
+                    .find(|c| c.contract.as_ref() == Some(&offer.currency_address))
+                    .cloned()
+                    .unwrap_or_default(),
+                floor_difference: compute_floor_difference(
+                    offer.amount,
+                    offer.currency_address,
+                    floor.clone(),
+                ),
+                source: offer.source,
+                expire_at: offer.expire_at,
+                hash: offer.hash,
+            })
+            .collect();
+
+        Ok(Page {
+            items,
+            next_cursor,
+            total_count,
+        })
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct Activity {
+    pub activity_type: TokenEventType,
+    pub price: Option<BigDecimal>,
+    pub currency: Option<Currency>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub timestamp: i64,
+    pub transaction_hash: Option<String>,
+    pub token_id: Option<String>,
+    pub collection_address: Option<String>,
+    pub chain_id: String,
+}
+
+impl Activity {
+    fn from_collection(chain_id: &str, activity: CollectionActivityData) -> Self {
+        Self {
+            activity_type: activity.activity_type,
+            price: activity.price,
+            currency: activity.currency,
+            from: activity.from,
+            to: activity.to,
+            timestamp: activity.time_stamp,
+            transaction_hash: activity.transaction_hash,
+            token_id: activity.token_id,
+            collection_address: Some(activity.address),
+            chain_id: chain_id.to_string(),
+        }
+    }
+
+    fn from_portfolio(chain_id: &str, activity: TokenPortfolioActivityData) -> Self {
+        Self {
+            activity_type: activity.activity_type,
+            price: activity.price,
+            currency: Some(activity.currency),
+            from: activity.from,
+            to: activity.to,
+            timestamp: activity.time_stamp,
+            transaction_hash: activity.transaction_hash,
+            token_id: activity.token_id,
+            collection_address: activity.collection_address,
+            chain_id: chain_id.to_string(),
+        }
+    }
+}
+
+#[ComplexObject]
+impl Activity {
+    async fn token(&self, ctx: &Context<'_>) -> Result<Option<TokenDetailsData>> {
+        match (&self.collection_address, &self.token_id) {
+            (Some(collection_address), Some(token_id)) => {
+                load_token(ctx, collection_address, &self.chain_id, token_id).await
+            }
+            _ => Ok(None),
+        }
+    }
+
+    async fn collection(&self, ctx: &Context<'_>) -> Result<Option<Collection>> {
+        match &self.collection_address {
+            Some(collection_address) => {
+                load_collection(ctx, collection_address, &self.chain_id).await
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+pub struct Portfolio {
+    pub address: String,
+    pub chain_id: String,
+}
+
+impl Portfolio {
+    /// Same rule as the `wallet_session_guard` of the REST routes, the
+    /// session being attached to the request by the GraphQL handler.
+    fn check_session(&self, ctx: &Context<'_>) -> Result<()> {
+        match ctx.data_opt::<WalletSession>() {
+            Some(session) if session.sub == self.address => Ok(()),
+            Some(_) => Err(Error::new("The session belongs to another address")),
+            None => Err(Error::new("Missing wallet session")),
+        }
+    }
+}
+
+#[Object]
+impl Portfolio {
+    async fn address(&self) -> &str {
+        &self.address
+    }
+
+    async fn chain_id(&self) -> &str {
+        &self.chain_id
+    }
+
+    /// Value of the tokens held, at their collection floor price. Requires
+    /// the wallet session of the address
+    async fn total_value(&self, ctx: &Context<'_>) -> Result<Option<BigDecimal>> {
+        self.check_session(ctx)?;
+        let stats = get_stats_data(
+            ctx.data_unchecked::<PgPool>(),
+            &self.chain_id,
+            &self.address,
+        )
+        .await
+        .map_err(|err| internal_error("get_stats_data", err))?;
+        Ok(stats.total_value)
+    }
+
+    #[graphql(complexity = "first as usize * child_complexity")]
+    async fn activity(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 20, validator(minimum = 1, maximum = 100))] first: i32,
+        after: Option<String>,
+        #[graphql(default_with = "SortDirection::Desc")] direction: SortDirection,
+        types: Option<Vec<TokenEventType>>,
+    ) -> Result<Page<Activity>> {
+        let keyset = activity_keyset(direction.as_str());
+        let cursor = decode_after(&keyset, after.as_deref())?;
+
+        let (activities, next_cursor, total_count) = get_activity_data(
+            ctx.data_unchecked::<PgPool>(),
+            &self.chain_id,
+            &self.address,
+            cursor.as_ref(),
+            first as i64,
+            direction.as_str(),
+            &types,
+        )
+        .await
+        .map_err(|err| internal_error("get_activity_data", err))?;
+
+        Ok(Page {
+            items: activities
+                .into_iter()
+                .map(|activity| Activity::from_portfolio(&self.chain_id, activity))
+                .collect(),
+            next_cursor,
+            total_count,
+        })
+    }
+}
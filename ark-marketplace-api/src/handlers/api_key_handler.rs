@@ -0,0 +1,110 @@
+use crate::db::api_key_query::{create_api_key, get_api_keys, revoke_api_key};
+use crate::managers::api_key_manager::ANONYMOUS_TIER;
+use crate::models::api_key::ApiKeyRequest;
+use crate::types::api_error::ApiError;
+use actix_web::{web, HttpResponse};
+use serde_json::json;
+use sqlx::PgPool;
+use std::sync::Arc;
+
+fn validate_api_key_request(request: &ApiKeyRequest) -> Result<(), ApiError> {
+    if request.name.trim().is_empty() {
+        return Err(ApiError::InvalidBody(
+            "API key name can't be empty".to_string(),
+        ));
+    }
+
+    if request.tier == ANONYMOUS_TIER {
+        return Err(ApiError::InvalidBody(format!(
+            "Tier {} can't be given to a key",
+            ANONYMOUS_TIER
+        )));
+    }
+
+    for (field, quota) in [
+        ("requests_per_minute", request.requests_per_minute),
+        ("requests_per_day", request.requests_per_day),
+    ] {
+        if quota.is_some_and(|quota| quota <= 0) {
+            return Err(ApiError::InvalidBody(format!(
+                "{} must be greater than 0",
+                field
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_api_key_error(err: sqlx::Error) -> ApiError {
+    match err {
+        sqlx::Error::Database(e) if e.is_foreign_key_violation() => {
+            ApiError::InvalidBody("Unknown API key tier".to_string())
+        }
+        err => ApiError::database("api key")(err),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api-keys",
+    tag = "API keys",
+    request_body = ApiKeyRequest,
+    responses(
+        (status = 200, description = "Create an API key, the key is only returned here", body = CreatedApiKeyResponse),
+        (status = 400, description = "Malformed API key", body = ErrorResponse),
+        (status = 422, description = "Invalid API key", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+    )
+)]
+pub async fn create_key(
+    body: web::Json<ApiKeyRequest>,
+    db_pools: web::Data<Arc<[PgPool; 2]>>,
+) -> Result<HttpResponse, ApiError> {
+    let request = body.into_inner();
+    validate_api_key_request(&request)?;
+
+    let api_key = create_api_key(&db_pools[1], &request)
+        .await
+        .map_err(handle_api_key_error)?;
+    Ok(HttpResponse::Ok().json(json!({ "data": api_key })))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api-keys",
+    tag = "API keys",
+    responses(
+        (status = 200, description = "List API keys", body = ApiKeysResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+    )
+)]
+pub async fn get_keys(db_pools: web::Data<Arc<[PgPool; 2]>>) -> Result<HttpResponse, ApiError> {
+    let api_keys = get_api_keys(&db_pools[1])
+        .await
+        .map_err(handle_api_key_error)?;
+    Ok(HttpResponse::Ok().json(json!({ "data": api_keys })))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api-keys/{id}",
+    tag = "API keys",
+    responses(
+        (status = 200, description = "Revoke an API key", body = ApiKeyResponse),
+        (status = 404, description = "Data not found", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+    ),
+    params(
+        ("id" = i32, Path, description = "The API key id"),
+    )
+)]
+pub async fn revoke_key(
+    path: web::Path<i32>,
+    db_pools: web::Data<Arc<[PgPool; 2]>>,
+) -> Result<HttpResponse, ApiError> {
+    let api_key = revoke_api_key(&db_pools[1], path.into_inner())
+        .await
+        .map_err(handle_api_key_error)?;
+    Ok(HttpResponse::Ok().json(json!({ "data": api_key })))
+}
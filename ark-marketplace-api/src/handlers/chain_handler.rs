@@ -0,0 +1,21 @@
+use crate::managers::chain_registry::ChainRegistry;
+use actix_web::{get, web};
+use actix_web::{HttpResponse, Responder};
+use serde_json::json;
+
+#[utoipa::path(
+    tag = "Chains",
+    responses(
+        (status = 200, description = "Get the chains supported by the API", body = ChainsResponse),
+    )
+)]
+#[get("/chains")]
+pub async fn get_chains(chains: web::Data<ChainRegistry>) -> impl Responder {
+    HttpResponse::Ok().json(json!({
+        "data": chains.chains(),
+    }))
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_chains);
+}
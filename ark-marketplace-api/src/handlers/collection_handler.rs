@@ -1,14 +1,27 @@
-use super::utils::extract_page_params;
-use super::utils::CHAIN_ID;
+use super::utils::{
+    check_page_params, extract_chain_id, extract_chart_range, extract_cursor_params, parse_address,
+    parse_direction, parse_one_of, parse_query, resolve_chain_id,
+};
 use crate::db::query::{
-    get_collection_activity_data, get_collection_data, get_collections_data,
-    get_portfolio_collections_data, search_collections_data,
+    get_collection_activity_data, get_collection_charts_data, get_collection_data,
+    get_collection_floor_price, get_collection_offers_acceptable_tokens,
+    get_collection_offers_data, get_collection_offers_depth, get_collection_order_book_levels,
+    get_collections_data, get_currencies, get_portfolio_collections_data, search_collections_data,
 };
+use crate::managers::chain_registry::ChainRegistry;
 use crate::managers::elasticsearch_manager::ElasticsearchManager;
+use crate::models::collection::{CollectionOfferData, OfferDepthLevel};
+use crate::models::default::Currency;
 use crate::models::token::TokenEventType;
-use crate::utils::http_utils::normalize_address;
+use crate::types::api_error::ApiError;
+use crate::utils::currency_utils::compute_floor_difference;
+use crate::utils::order_book_utils::build_order_books;
+use crate::utils::sql_utils::{
+    activity_keyset, collection_offers_keyset, COLLECTION_SORTS, TIME_RANGES,
+};
 use actix_web::get;
-use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use actix_web::{web, HttpRequest, HttpResponse};
+use bigdecimal::{BigDecimal, Zero};
 use redis::aio::MultiplexedConnection;
 use serde::Deserialize;
 use serde_json::json;
@@ -38,11 +51,38 @@ struct ActivityQueryParameters {
     types: Option<Vec<TokenEventType>>,
 }
 
+#[derive(Deserialize)]
+struct CollectionOffersQueryParameters {
+    user: Option<String>,
+    depth: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct OrderBookQueryParameters {
+    levels: Option<i64>,
+    bucket_size: Option<String>,
+    disable_cache: Option<String>,
+}
+
+/// Most price levels returned per side of an order book.
+const MAX_DEPTH_LEVELS: i64 = 100;
+
+fn check_depth_levels(parameter: &str, levels: i64) -> Result<i64, ApiError> {
+    if !(1..=MAX_DEPTH_LEVELS).contains(&levels) {
+        return Err(ApiError::invalid_parameter(
+            parameter,
+            format!("{} must be between 1 and {}", parameter, MAX_DEPTH_LEVELS),
+        ));
+    }
+    Ok(levels)
+}
+
 #[utoipa::path(
     tag = "Collections",
     responses(
         (status = 200, description = "Get collections", body = CollectionsResponse),
-        (status = 400, description = "Data not found", body = String),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 404, description = "Data not found", body = ErrorResponse),
     ),
     params(
         ("page" = Option<i32>, Query, description = "Page number for pagination, defaults to 1"),
@@ -56,33 +96,48 @@ struct ActivityQueryParameters {
 pub async fn get_collections(
     query_params: web::Query<CollectionQueryParameters>,
     db_pools: web::Data<Arc<[PgPool; 2]>>,
-) -> impl Responder {
-    let page = query_params.page.unwrap_or(1);
-    let items_per_page = query_params.items_per_page.unwrap_or(100);
-    let time_range = query_params.time_range.as_deref().unwrap_or("1d");
-    let sort = query_params.sort.as_deref().unwrap_or("volume");
-    let direction = query_params.direction.as_deref().unwrap_or("desc");
+) -> Result<HttpResponse, ApiError> {
+    let (page, items_per_page) = check_page_params(
+        query_params.page.unwrap_or(1),
+        query_params.items_per_page.unwrap_or(100),
+    )?;
+    let time_range = parse_one_of(
+        "time_range",
+        query_params.time_range.as_deref(),
+        TIME_RANGES,
+        "1d",
+    )?;
+    let sort = parse_one_of(
+        "sort",
+        query_params.sort.as_deref(),
+        COLLECTION_SORTS,
+        "volume",
+    )?;
+    let direction = parse_direction(query_params.direction.as_deref(), "desc")?;
 
     let db_access = &db_pools[0];
-    match get_collections_data(db_access, page, items_per_page, time_range, sort, direction).await {
-        Err(sqlx::Error::RowNotFound) => HttpResponse::NotFound().body("data not found"),
-        Ok((collections_data, has_next_page, count)) => HttpResponse::Ok().json(json!({
-            "data": collections_data,
-            "count": count,
-            "next_page": if has_next_page { Some(page + 1) } else { None }
-        })),
-        Err(err) => {
-            tracing::error!("error query get_collections: {}", err);
-            HttpResponse::InternalServerError().finish()
-        }
-    }
+    let (collections_data, has_next_page, count) =
+        get_collections_data(db_access, page, items_per_page, time_range, sort, direction)
+            .await
+            .map_err(ApiError::database("get_collections"))?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "data": collections_data,
+        "count": count,
+        "next_page": if has_next_page { Some(page + 1) } else { None }
+    })))
 }
 
 #[utoipa::path(
     tag = "Collections",
     responses(
         (status = 200, description = "Get collection data", body = CollectionResponse),
-        (status = 400, description = "Data not found", body = String),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 404, description = "Data not found", body = ErrorResponse),
+    ),
+    params(
+        ("contract_address" = String, Path, description = "The contract address of the collection"),
+        ("chain_id" = String, Path, description = "The blockchain chain ID"),
     )
 )]
 #[get("/collections/{contract_address}/{chain_id}")]
@@ -90,40 +145,41 @@ pub async fn get_collection(
     path: web::Path<(String, String)>,
     db_pools: web::Data<Arc<[PgPool; 2]>>,
     redis_con: web::Data<Arc<Mutex<MultiplexedConnection>>>,
-) -> impl Responder {
+    chains: web::Data<ChainRegistry>,
+) -> Result<HttpResponse, ApiError> {
     let (contract_address, chain_id) = path.into_inner();
-    let normalized_address = normalize_address(&contract_address);
+    let normalized_address = parse_address("contract_address", &contract_address)?;
+    let chain_id = resolve_chain_id(&chains, Some(&chain_id))?;
 
     let db_access = &db_pools[0];
     let mut redis_con_ref = redis_con.get_ref().lock().await;
-    match get_collection_data(
+    let collection_data = get_collection_data(
         db_access,
         &mut redis_con_ref,
         &normalized_address,
         &chain_id,
     )
     .await
-    {
-        Err(sqlx::Error::RowNotFound) => HttpResponse::NotFound().body("data not found"),
-        Ok(collection_data) => HttpResponse::Ok().json(json!({
-            "data": collection_data,
-        })),
-        Err(err) => {
-            tracing::error!("error query get_collection: {}", err);
-            HttpResponse::InternalServerError().finish()
-        }
-    }
+    .map_err(ApiError::database("get_collection"))?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "data": collection_data,
+    })))
 }
 
 #[utoipa::path(
     tag = "Collections",
     responses(
         (status = 200, description = "Get collection activity", body = CollectionActivityResponse),
-        (status = 400, description = "Data not found", body = String),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 404, description = "Data not found", body = ErrorResponse),
     ),
     params(
-        ("page" = Option<i32>, Query, description = "Page number for pagination, defaults to 1"),
+        ("cursor" = Option<String>, Query, description = "Cursor of the page, as returned in `next_cursor`"),
         ("items_per_page" = Option<i32>, Query, description = "Number of items per page, defaults to 100"),
+        ("direction" = Option<String>, Query, description = "Sort direction by date, 'asc' or 'desc', defaults to 'desc'"),
+        ("types" = Option<Vec<TokenEventType>>, Query, description = "Only return events of these types"),
+        ("chain_id" = Option<String>, Query, description = "The blockchain chain ID, defaults to the default chain"),
     )
 )]
 #[get("/collections/{contract_address}/activity")]
@@ -131,55 +187,279 @@ pub async fn get_collection_activity(
     req: HttpRequest,
     path: web::Path<String>,
     db_pools: web::Data<Arc<[PgPool; 2]>>,
-) -> impl Responder {
+    chains: web::Data<ChainRegistry>,
+) -> Result<HttpResponse, ApiError> {
     let contract_address = path.into_inner();
-    let normalized_address = normalize_address(&contract_address);
-
-    let (page, items_per_page) = match extract_page_params(req.query_string(), 1, 100) {
-        Err(msg) => return HttpResponse::BadRequest().json(msg),
-        Ok((page, items_per_page)) => (page, items_per_page),
-    };
+    let normalized_address = parse_address("contract_address", &contract_address)?;
+    let chain_id = extract_chain_id(req.query_string(), &chains)?;
 
-    let params = serde_qs::from_str::<ActivityQueryParameters>(req.query_string());
-    if let Err(e) = params {
-        let msg = format!("Error when parsing query parameters: {}", e);
-        tracing::error!(msg);
-        return HttpResponse::BadRequest().json(msg);
-    }
-    let params = params.unwrap();
-    let direction = params.direction.as_deref().unwrap_or("desc");
+    let params = parse_query::<ActivityQueryParameters>(req.query_string())?;
+    let direction = parse_direction(params.direction.as_deref(), "desc")?;
+    let (cursor, items_per_page) =
+        extract_cursor_params(req.query_string(), &activity_keyset(direction), 100)?;
 
     let db_access = &db_pools[0];
-
-    match get_collection_activity_data(
+    let (collection_data, next_cursor, collection_count) = get_collection_activity_data(
         db_access,
         &normalized_address,
-        CHAIN_ID,
-        page,
+        &chain_id,
+        cursor.as_ref(),
         items_per_page,
         direction,
         &params.types,
     )
     .await
-    {
-        Err(sqlx::Error::RowNotFound) => HttpResponse::NotFound().body("data not found"),
-        Ok((collection_data, has_next_page, collection_count)) => HttpResponse::Ok().json(json!({
-            "data": collection_data,
-            "collection_count": collection_count,
-            "next_page": if has_next_page { Some(page + 1) } else { None }
-        })),
-        Err(err) => {
-            tracing::error!("error query get_collection_activity: {}", err);
-            HttpResponse::InternalServerError().finish()
+    .map_err(ApiError::database("get_collection_activity"))?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "data": collection_data,
+        "collection_count": collection_count,
+        "next_cursor": next_cursor
+    })))
+}
+
+#[utoipa::path(
+    tag = "Collections",
+    responses(
+        (status = 200, description = "Get floor, sales and volume charts of a collection, sale prices and volume in wei of ETH", body = CollectionChartsResponse),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+    ),
+    params(
+        ("address" = String, Path, description = "The contract address of the collection"),
+        ("chain_id" = String, Path, description = "The blockchain chain ID"),
+        ("interval" = Option<String>, Query, description = "Bucket size, '1h', '1d' or '1w', defaults to '1d'"),
+        ("from" = Option<i64>, Query, description = "Start of the range as a unix timestamp, defaults to 7 days, 90 days or a year before 'to' depending on the interval"),
+        ("to" = Option<i64>, Query, description = "End of the range as a unix timestamp, defaults to now"),
+    )
+)]
+#[get("/collections/{address}/{chain_id}/charts")]
+pub async fn get_collection_charts(
+    req: HttpRequest,
+    path: web::Path<(String, String)>,
+    db_pools: web::Data<Arc<[PgPool; 2]>>,
+    chains: web::Data<ChainRegistry>,
+) -> Result<HttpResponse, ApiError> {
+    let (contract_address, chain_id) = path.into_inner();
+    let normalized_address = parse_address("address", &contract_address)?;
+    let chain_id = resolve_chain_id(&chains, Some(&chain_id))?;
+
+    let (interval, from, to) = extract_chart_range(req.query_string())?;
+
+    let db_access = &db_pools[0];
+    let charts_data = get_collection_charts_data(
+        db_access,
+        &normalized_address,
+        &chain_id,
+        interval,
+        from,
+        to,
+    )
+    .await
+    .map_err(ApiError::database("get_collection_charts_data"))?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "data": charts_data,
+        "interval": interval.as_str(),
+        "from": from,
+        "to": to,
+    })))
+}
+
+#[utoipa::path(
+    tag = "Collections",
+    responses(
+        (status = 200, description = "Get the active offers placed on a collection", body = CollectionOffersResponse),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 404, description = "Data not found", body = ErrorResponse),
+    ),
+    params(
+        ("address" = String, Path, description = "The contract address of the collection"),
+        ("chain_id" = String, Path, description = "The blockchain chain ID"),
+        ("cursor" = Option<String>, Query, description = "Cursor of the page, as returned in `next_cursor`"),
+        ("items_per_page" = Option<i32>, Query, description = "Number of items per page, defaults to 100"),
+        ("user" = Option<String>, Query, description = "Address whose tokens each offer can be accepted against are returned in `acceptable_token_ids`"),
+        ("depth" = Option<i32>, Query, description = "Number of price levels of the depth, defaults to 20"),
+    )
+)]
+#[get("/collections/{address}/{chain_id}/offers")]
+pub async fn get_collection_offers(
+    req: HttpRequest,
+    path: web::Path<(String, String)>,
+    db_pools: web::Data<Arc<[PgPool; 2]>>,
+    chains: web::Data<ChainRegistry>,
+) -> Result<HttpResponse, ApiError> {
+    let (contract_address, chain_id) = path.into_inner();
+    let normalized_address = parse_address("address", &contract_address)?;
+    let chain_id = resolve_chain_id(&chains, Some(&chain_id))?;
+
+    let params = parse_query::<CollectionOffersQueryParameters>(req.query_string())?;
+    let user_address = params
+        .user
+        .as_deref()
+        .map(|user| parse_address("user", user))
+        .transpose()?;
+    let depth = check_depth_levels("depth", params.depth.unwrap_or(20))?;
+    let (cursor, items_per_page) =
+        extract_cursor_params(req.query_string(), &collection_offers_keyset(), 100)?;
+
+    let db_access = &db_pools[0];
+    let floor_price = get_collection_floor_price(db_access, &normalized_address, &chain_id)
+        .await
+        .map_err(ApiError::database("get_collection_floor_price"))?;
+
+    let (offers_data, next_cursor, count) = get_collection_offers_data(
+        db_access,
+        &normalized_address,
+        &chain_id,
+        cursor.as_ref(),
+        items_per_page,
+    )
+    .await
+    .map_err(ApiError::database("get_collection_offers_data"))?;
+
+    let depth_data = get_collection_offers_depth(db_access, &normalized_address, &chain_id, depth)
+        .await
+        .map_err(ApiError::database("get_collection_offers_depth"))?;
+
+    let mut acceptable_tokens = match &user_address {
+        Some(user_address) => {
+            let offer_ids: Vec<i32> = offers_data.iter().map(|offer| offer.offer_id).collect();
+            get_collection_offers_acceptable_tokens(db_access, &offer_ids, user_address)
+                .await
+                .map_err(ApiError::database(
+                    "get_collection_offers_acceptable_tokens",
+                ))?
         }
-    }
+        None => HashMap::new(),
+    };
+
+    let currencies = get_currencies(db_access)
+        .await
+        .map_err(ApiError::database("get_currencies"))?;
+    let find_currency = |address: &str| -> Currency {
+        currencies
+            .iter()
+            .find(|c| c.contract.as_deref() == Some(address))
+            .cloned()
+            .unwrap_or_default()
+    };
+
+    let offers_data: Vec<CollectionOfferData> = offers_data
+        .into_iter()
+        .map(|data| CollectionOfferData {
+            offer_id: data.offer_id,
+            price: data.amount.clone(),
+            quantity: data.quantity,
+            currency: find_currency(&data.currency_address),
+            floor_difference: compute_floor_difference(
+                data.amount,
+                data.currency_address,
+                floor_price.value.clone(),
+            ),
+            source: data.source,
+            expire_at: data.expire_at,
+            hash: data.hash,
+            acceptable_token_ids: user_address
+                .as_ref()
+                .map(|_| acceptable_tokens.remove(&data.offer_id).unwrap_or_default()),
+        })
+        .collect();
+
+    let depth_data: Vec<OfferDepthLevel> = depth_data
+        .into_iter()
+        .map(|level| OfferDepthLevel {
+            price: level.price,
+            currency: find_currency(&level.currency_address),
+            offer_count: level.offer_count,
+            quantity: level.quantity,
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(json!({
+        "data": offers_data,
+        "depth": depth_data,
+        "count": count,
+        "next_cursor": next_cursor
+    })))
+}
+
+#[utoipa::path(
+    tag = "Collections",
+    responses(
+        (status = 200, description = "Get the bid and ask ladders of a collection", body = CollectionOrderBookResponse),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+    ),
+    params(
+        ("address" = String, Path, description = "The contract address of the collection"),
+        ("chain_id" = String, Path, description = "The blockchain chain ID"),
+        ("levels" = Option<i32>, Query, description = "Number of price levels per side, defaults to 20"),
+        ("bucket_size" = Option<String>, Query, description = "Width of the price buckets in currency units, e.g. '0.01', orders are not bucketed by default"),
+        ("disable_cache" = Option<String>, Query, description = "'true' to bypass the cache"),
+    )
+)]
+#[get("/collections/{address}/{chain_id}/depth")]
+pub async fn get_collection_order_book(
+    req: HttpRequest,
+    path: web::Path<(String, String)>,
+    db_pools: web::Data<Arc<[PgPool; 2]>>,
+    redis_con: web::Data<Arc<Mutex<MultiplexedConnection>>>,
+    chains: web::Data<ChainRegistry>,
+) -> Result<HttpResponse, ApiError> {
+    let (contract_address, chain_id) = path.into_inner();
+    let normalized_address = parse_address("address", &contract_address)?;
+    let chain_id = resolve_chain_id(&chains, Some(&chain_id))?;
+
+    let params = parse_query::<OrderBookQueryParameters>(req.query_string())?;
+    let levels = check_depth_levels("levels", params.levels.unwrap_or(20))?;
+    let bucket_size = params
+        .bucket_size
+        .as_deref()
+        .map(|bucket_size| match bucket_size.parse::<BigDecimal>() {
+            Ok(size) if size > BigDecimal::zero() => Ok(size),
+            _ => Err(ApiError::invalid_parameter(
+                "bucket_size",
+                format!(
+                    "Invalid bucket_size: {}, expected a positive number",
+                    bucket_size
+                ),
+            )),
+        })
+        .transpose()?;
+    let disable_cache = params.disable_cache.as_deref() == Some("true");
+
+    let db_access = &db_pools[0];
+    let mut redis_con_ref = redis_con.get_ref().lock().await;
+    let order_book_levels = get_collection_order_book_levels(
+        db_access,
+        &mut redis_con_ref,
+        &normalized_address,
+        &chain_id,
+        disable_cache,
+    )
+    .await
+    .map_err(ApiError::database("get_collection_order_book_levels"))?;
+
+    let currencies = get_currencies(db_access)
+        .await
+        .map_err(ApiError::database("get_currencies"))?;
+    let order_books = build_order_books(
+        order_book_levels,
+        &currencies,
+        bucket_size.as_ref(),
+        levels as usize,
+    );
+
+    Ok(HttpResponse::Ok().json(json!({
+        "data": order_books,
+    })))
 }
 
 #[utoipa::path(
     tag = "Portfolio",
     responses(
         (status = 200, description = "Get portfolio collections", body = CollectionPortfolioResponse),
-        (status = 400, description = "Data not found", body = String),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 404, description = "Data not found", body = ErrorResponse),
     ),
     params(
         ("page" = Option<i32>, Query, description = "Page number for pagination, defaults to 1"),
@@ -191,28 +471,29 @@ pub async fn get_portfolio_collections(
     query_parameters: web::Query<PortfolioCollectionQueryParameters>,
     path: web::Path<String>,
     db_pools: web::Data<Arc<[PgPool; 2]>>,
-) -> impl Responder {
-    let page = query_parameters.page.unwrap_or(1);
-    let items_per_page = query_parameters.items_per_page.unwrap_or(100);
+) -> Result<HttpResponse, ApiError> {
+    let (page, items_per_page) = check_page_params(
+        query_parameters.page.unwrap_or(1),
+        query_parameters.items_per_page.unwrap_or(100),
+    )?;
     let user_address = path.into_inner();
-    let normalized_address = normalize_address(&user_address);
+    let normalized_address = parse_address("user_address", &user_address)?;
 
     let db_access = &db_pools[0];
-    match get_portfolio_collections_data(db_access, &normalized_address, page, items_per_page).await
-    {
-        Err(sqlx::Error::RowNotFound) => HttpResponse::NotFound().body("data not found"),
-        Ok((collection_data, has_next_page, collection_count)) => HttpResponse::Ok().json(json!({
-            "data": collection_data,
-            "collection_count": collection_count,
-            "next_page": if has_next_page { Some(page + 1) } else { None }
-        })),
-        Err(err) => {
-            tracing::error!("error query get_portfolio_collections_data: {}", err);
-            HttpResponse::InternalServerError().finish()
-        }
-    }
+    let (collection_data, has_next_page, collection_count) =
+        get_portfolio_collections_data(db_access, &normalized_address, page, items_per_page)
+            .await
+            .map_err(ApiError::database("get_portfolio_collections_data"))?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "data": collection_data,
+        "collection_count": collection_count,
+        "next_page": if has_next_page { Some(page + 1) } else { None }
+    })))
 }
 
+const MAX_SEARCH_LIMIT: i64 = 100;
+
 #[derive(Deserialize)]
 pub struct SearchQuery {
     q: Option<String>,
@@ -223,76 +504,83 @@ pub struct SearchQuery {
     tag = "Collections",
     responses(
         (status = 200, description = "Search in a collection", body = CollectionSearchResponse),
-        (status = 400, description = "Data not found", body = String),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
     ),
     params(
         ("q" = String, Query, description = "Can be a starknetId or a starknet user address"),
+        ("limit" = Option<i64>, Query, description = "Most collections returned, between 1 and 100, defaults to 8"),
     )
 )]
 #[get("/collections/search")]
 pub async fn search_collections(
     query_parameters: web::Query<SearchQuery>,
     db_pools: web::Data<Arc<[PgPool; 2]>>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
     let query_search = query_parameters.q.as_deref();
     let db_access = &db_pools[0];
     let items = query_parameters.limit.unwrap_or(8);
+    if !(1..=MAX_SEARCH_LIMIT).contains(&items) {
+        return Err(ApiError::invalid_parameter(
+            "limit",
+            format!("limit must be between 1 and {}", MAX_SEARCH_LIMIT),
+        ));
+    }
 
-    match search_collections_data(
+    let (collection_data, owner_data) = search_collections_data(
         db_access,
         query_search.unwrap_or("").to_lowercase().as_str(),
         items,
     )
     .await
-    {
-        Err(sqlx::Error::RowNotFound) => HttpResponse::NotFound().body("data not found"),
-        Ok((collection_data, owner_data)) => HttpResponse::Ok().json(json!({
-        "data": {
-            "collections": collection_data,
-            "accounts": owner_data
-        }
-        })),
-        Err(err) => {
-            tracing::error!("error query search_collections_data: {}", err);
-            HttpResponse::InternalServerError().finish()
-        }
+    .map_err(ApiError::database("search_collections_data"))?;
+
+    Ok(HttpResponse::Ok().json(json!({
+    "data": {
+        "collections": collection_data,
+        "accounts": owner_data
     }
+    })))
 }
 
 #[utoipa::path(
     tag = "Collections",
     responses(
         (status = 200, description = "Get traits in a collection", body = AttributesResponse),
-        (status = 400, description = "Data not found", body = String),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+    ),
+    params(
+        ("chain_id" = Option<String>, Query, description = "The blockchain chain ID, defaults to the default chain"),
     )
 )]
 #[get("/collections/{address}/traits")]
 pub async fn get_traits(
+    req: HttpRequest,
     path: web::Path<String>,
     es_data: web::Data<HashMap<String, String>>,
-) -> impl Responder {
+    chains: web::Data<ChainRegistry>,
+) -> Result<HttpResponse, ApiError> {
     let contract_address = path.into_inner();
+    let chain_id = extract_chain_id(req.query_string(), &chains)?;
     let elasticsearch_manager = ElasticsearchManager::new(es_data.get_ref().clone());
 
-    let normalized_address = normalize_address(&contract_address);
-    let result = elasticsearch_manager
-        .get_attributes_for_collection(&normalized_address, CHAIN_ID)
-        .await;
-
-    match result {
-        Ok(json_response) => HttpResponse::Ok().json(json!({
-            "data": json_response
-        })),
-        Err(e) => HttpResponse::InternalServerError().json(json!({
-            "error": format!("Failed to retrieve data: {}", e)
-        })),
-    }
+    let normalized_address = parse_address("address", &contract_address)?;
+    let json_response = elasticsearch_manager
+        .get_attributes_for_collection(&normalized_address, &chain_id)
+        .await
+        .map_err(|e| ApiError::internal("Failed to retrieve collection traits", e))?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "data": json_response
+    })))
 }
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(get_collections)
         .service(get_traits)
         .service(get_collection_activity)
+        .service(get_collection_charts)
+        .service(get_collection_offers)
+        .service(get_collection_order_book)
         .service(get_collection)
         .service(get_portfolio_collections)
         .service(search_collections);
@@ -1,7 +1,10 @@
+use super::utils::extract_chain_id;
 use crate::db::default_query::{get_last_sales, get_live_auctions, get_trending};
+use crate::managers::chain_registry::ChainRegistry;
+use crate::types::api_error::ApiError;
 use crate::types::default::{HealthCheckResponse, HealthCheckResponseV1};
 use actix_web::{get, web};
-use actix_web::{HttpResponse, Responder};
+use actix_web::{HttpRequest, HttpResponse, Responder};
 use serde_json::json;
 use sqlx::PgPool;
 use std::sync::Arc;
@@ -43,68 +46,70 @@ pub async fn health_check_v1() -> impl Responder {
     tag = "Collections",
     responses(
         (status = 200, description = "Get the 12 last sales", body = LastSalesResponse),
-        (status = 400, description = "Data not found", body = String),
+        (status = 404, description = "Data not found", body = ErrorResponse),
     )
 )]
 #[get("/last-sales")]
-pub async fn last_sales(db_pools: web::Data<Arc<[PgPool; 2]>>) -> impl Responder {
+pub async fn last_sales(db_pools: web::Data<Arc<[PgPool; 2]>>) -> Result<HttpResponse, ApiError> {
     let db_access = &db_pools[0];
-    match get_last_sales(db_access).await {
-        Err(sqlx::Error::RowNotFound) => HttpResponse::NotFound().body("data not found"),
-        Ok(data) => HttpResponse::Ok().json(json!({
-            "data": data,
-        })),
-        Err(err) => {
-            tracing::error!("error query last_sales: {}", err);
-            HttpResponse::InternalServerError().finish()
-        }
-    }
+    let data = get_last_sales(db_access)
+        .await
+        .map_err(ApiError::database("last_sales"))?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "data": data,
+    })))
 }
 
 #[utoipa::path(
     tag = "Collections",
     responses(
         (status = 200, description = "Get the 6 last live auctions", body = LiveAuctionsResponse),
-        (status = 400, description = "Data not found", body = String),
+        (status = 404, description = "Data not found", body = ErrorResponse),
     )
 )]
 #[get("/live-auctions")]
-pub async fn live_auctions(db_pools: web::Data<Arc<[PgPool; 2]>>) -> impl Responder {
+pub async fn live_auctions(
+    db_pools: web::Data<Arc<[PgPool; 2]>>,
+) -> Result<HttpResponse, ApiError> {
     let db_access = &db_pools[0];
-    match get_live_auctions(db_access).await {
-        Err(sqlx::Error::RowNotFound) => HttpResponse::NotFound().body("data not found"),
-        Ok(data) => HttpResponse::Ok().json(json!({
-            "data": data,
-        })),
-        Err(err) => {
-            tracing::error!("error query live_auctions: {}", err);
-            HttpResponse::InternalServerError().finish()
-        }
-    }
+    let data = get_live_auctions(db_access)
+        .await
+        .map_err(ApiError::database("live_auctions"))?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "data": data,
+    })))
 }
 
 #[utoipa::path(
     tag = "Collections",
     responses(
         (status = 200, description = "Get the 6 last live auctions", body = TrendingResponse),
-        (status = 400, description = "Data not found", body = String),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 404, description = "Data not found", body = ErrorResponse),
+    ),
+    params(
+        ("chain_id" = Option<String>, Query, description = "The blockchain chain ID, defaults to the default chain"),
     )
 )]
 #[get("/trending")]
-pub async fn trending(db_pools: web::Data<Arc<[PgPool; 2]>>) -> impl Responder {
+pub async fn trending(
+    req: HttpRequest,
+    db_pools: web::Data<Arc<[PgPool; 2]>>,
+    chains: web::Data<ChainRegistry>,
+) -> Result<HttpResponse, ApiError> {
+    let chain_id = extract_chain_id(req.query_string(), &chains)?;
     let db_access = &db_pools[0];
     // if we need later we can pass the timerange parameter to the url.
     const TIME_RANGE: &str = "7d";
-    match get_trending(db_access, TIME_RANGE).await {
-        Err(sqlx::Error::RowNotFound) => HttpResponse::NotFound().body("data not found"),
-        Ok(data) => HttpResponse::Ok().json(json!({
-            "data": data,
-        })),
-        Err(err) => {
-            tracing::error!("error query trending: {}", err);
-            HttpResponse::InternalServerError().finish()
-        }
-    }
+    let data = get_trending(db_access, TIME_RANGE, &chain_id)
+        .await
+        .map_err(ApiError::database("trending"))?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "data": data,
+    })))
 }
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
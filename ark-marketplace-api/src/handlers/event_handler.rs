@@ -0,0 +1,133 @@
+use super::utils::{parse_address, parse_query, parse_token_id};
+use crate::managers::event_stream_manager::EventStreamManager;
+use crate::models::event::MarketplaceEvent;
+use crate::models::token::TokenEventType;
+use crate::types::api_error::ApiError;
+use crate::utils::http_utils::normalize_address;
+use actix_web::http::header;
+use actix_web::{get, web, HttpRequest, HttpResponse};
+use serde::Deserialize;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::{timeout, Duration};
+
+const KEEP_ALIVE_SECONDS: u64 = 15;
+
+#[derive(Deserialize, Debug)]
+struct EventStreamQueryParameters {
+    collection: Option<String>,
+    token_id: Option<String>,
+    user: Option<String>,
+    types: Option<Vec<TokenEventType>>,
+}
+
+impl EventStreamQueryParameters {
+    fn normalized(self) -> Result<Self, ApiError> {
+        Ok(Self {
+            collection: self
+                .collection
+                .as_deref()
+                .map(|collection| parse_address("collection", collection))
+                .transpose()?,
+            token_id: self.token_id.as_deref().map(parse_token_id).transpose()?,
+            user: self
+                .user
+                .as_deref()
+                .map(|user| parse_address("user", user))
+                .transpose()?,
+            ..self
+        })
+    }
+
+    fn matches(&self, event: &MarketplaceEvent) -> bool {
+        if let Some(collection) = &self.collection {
+            if normalize_address(&event.collection_address) != *collection {
+                return false;
+            }
+        }
+
+        if let Some(token_id) = &self.token_id {
+            if event.token_id != *token_id {
+                return false;
+            }
+        }
+
+        if let Some(user) = &self.user {
+            let is_involved = [&event.from, &event.to]
+                .iter()
+                .any(|address| address.as_deref().map(normalize_address).as_ref() == Some(user));
+            if !is_involved {
+                return false;
+            }
+        }
+
+        match &self.types {
+            Some(types) if !types.is_empty() => types.contains(&event.activity_type),
+            _ => true,
+        }
+    }
+}
+
+#[utoipa::path(
+    tag = "Events",
+    responses(
+        (status = 200, description = "Server-Sent Events stream of marketplace activity", content_type = "text/event-stream", body = MarketplaceEvent),
+        (status = 400, description = "Invalid query parameters", body = ErrorResponse),
+    ),
+    params(
+        ("collection" = Option<String>, Query, description = "Only stream events of this collection"),
+        ("token_id" = Option<String>, Query, description = "Only stream events of this token"),
+        ("user" = Option<String>, Query, description = "Only stream events where this address is the sender or the recipient"),
+        ("types" = Option<Vec<TokenEventType>>, Query, description = "Only stream events of these types"),
+    )
+)]
+#[get("/events/stream")]
+pub async fn stream_events(
+    req: HttpRequest,
+    event_stream: web::Data<EventStreamManager>,
+) -> Result<HttpResponse, ApiError> {
+    let params = parse_query::<EventStreamQueryParameters>(req.query_string())?.normalized()?;
+
+    let receiver = event_stream.subscribe();
+    let stream =
+        futures_util::stream::unfold((receiver, params), |(mut receiver, params)| async move {
+            loop {
+                let chunk = match timeout(Duration::from_secs(KEEP_ALIVE_SECONDS), receiver.recv())
+                    .await
+                {
+                    // Comment lines keep proxies from closing idle connections
+                    Err(_) => ": keep-alive\n\n".to_string(),
+                    Ok(Ok(event)) => {
+                        if !params.matches(&event) {
+                            continue;
+                        }
+                        match serde_json::to_string(&event) {
+                            Ok(data) => format!("event: activity\ndata: {}\n\n", data),
+                            Err(e) => {
+                                tracing::error!("Failed to serialize event: {}", e);
+                                continue;
+                            }
+                        }
+                    }
+                    Ok(Err(RecvError::Lagged(skipped))) => {
+                        tracing::warn!("Event stream client lagged, {} events skipped", skipped);
+                        continue;
+                    }
+                    Ok(Err(RecvError::Closed)) => return None,
+                };
+
+                return Some((
+                    Ok::<_, actix_web::Error>(web::Bytes::from(chunk)),
+                    (receiver, params),
+                ));
+            }
+        });
+
+    Ok(HttpResponse::Ok()
+        .insert_header((header::CONTENT_TYPE, "text/event-stream"))
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        .streaming(stream))
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(stream_events);
+}
@@ -0,0 +1,187 @@
+use super::utils::{extract_chain_id, parse_address, parse_query, resolve_chain_id};
+use crate::db::export_query::stream_activity_export;
+use crate::managers::chain_registry::ChainRegistry;
+use crate::models::export::{
+    ActivityExportFilter, ActivityExportRow, ActivityExportScope, ExportFormat,
+};
+use crate::models::token::TokenEventType;
+use crate::types::api_error::ApiError;
+use actix_web::http::header::CONTENT_DISPOSITION;
+use actix_web::web::Bytes;
+use actix_web::{get, web, HttpRequest, HttpResponse};
+use chrono::Utc;
+use futures_util::stream::{self, StreamExt};
+use serde::Deserialize;
+use sqlx::postgres::PgPool;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+/// Each export keeps a read connection for the whole download, so only a
+/// few of them run at once to leave the pool to the other routes.
+const MAX_CONCURRENT_EXPORTS: usize = 4;
+static EXPORT_SLOTS: Semaphore = Semaphore::const_new(MAX_CONCURRENT_EXPORTS);
+
+#[derive(Deserialize, Debug)]
+struct ExportQueryParameters {
+    format: Option<ExportFormat>,
+    from: Option<i64>,
+    to: Option<i64>,
+    types: Option<Vec<TokenEventType>>,
+}
+
+/// Reads the format and the filter of an export, the range defaulting to the
+/// whole history.
+fn extract_export_params(
+    query_string: &str,
+    scope: ActivityExportScope,
+    chain_id: String,
+) -> Result<(ExportFormat, ActivityExportFilter), ApiError> {
+    let params = parse_query::<ExportQueryParameters>(query_string)?;
+    let to = params.to.unwrap_or_else(|| Utc::now().timestamp());
+    let from = params.from.unwrap_or(0);
+    if from > to {
+        return Err(ApiError::invalid_parameter(
+            "from",
+            "'from' must not be greater than 'to'",
+        ));
+    }
+
+    let filter = ActivityExportFilter {
+        scope,
+        chain_id,
+        from,
+        to,
+        types: params.types,
+    };
+    Ok((params.format.unwrap_or(ExportFormat::Csv), filter))
+}
+
+/// Streams the rows as they are read from the database. The status is sent
+/// before the first row, so a failure past this point ends the download
+/// early instead of returning an error response.
+fn export_response(
+    format: ExportFormat,
+    file_name: &str,
+    db_pool: &PgPool,
+    filter: ActivityExportFilter,
+) -> Result<HttpResponse, ApiError> {
+    // Released when the stream is dropped, at the end of the download or
+    // when the client goes away.
+    let slot = EXPORT_SLOTS.try_acquire().map_err(|_| {
+        ApiError::RateLimited("Too many exports in progress, retry later".to_string())
+    })?;
+
+    let header = match format {
+        ExportFormat::Csv => Some(Ok(Bytes::from(format!(
+            "{}\n",
+            ActivityExportRow::CSV_HEADER
+        )))),
+        ExportFormat::Ndjson => None,
+    };
+    let rows = stream_activity_export(db_pool, filter).map(move |batch| {
+        let _slot = &slot;
+        let batch = batch.map_err(|e| ApiError::internal("error streaming activity export", e))?;
+        let mut chunk = String::new();
+        for row in batch {
+            match format {
+                ExportFormat::Csv => chunk.push_str(&row.to_csv_record()),
+                ExportFormat::Ndjson => chunk.push_str(
+                    &serde_json::to_string(&row)
+                        .map_err(|e| ApiError::internal("error serializing activity export", e))?,
+                ),
+            }
+            chunk.push('\n');
+        }
+        Ok::<_, actix_web::Error>(Bytes::from(chunk))
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type(format.content_type())
+        .insert_header((
+            CONTENT_DISPOSITION,
+            format!(
+                "attachment; filename=\"{}.{}\"",
+                file_name,
+                format.extension()
+            ),
+        ))
+        .streaming(stream::iter(header).chain(rows)))
+}
+
+#[utoipa::path(
+    tag = "Collections",
+    responses(
+        (status = 200, description = "Activity of a collection as a CSV or NDJSON file, oldest first", body = ActivityExportRow, content_type = ["text/csv", "application/x-ndjson"]),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 429, description = "Too many exports in progress", body = ErrorResponse),
+    ),
+    params(
+        ("address" = String, Path, description = "The contract address of the collection"),
+        ("chain_id" = String, Path, description = "The blockchain chain ID"),
+        ("format" = Option<ExportFormat>, Query, description = "'csv' or 'ndjson', defaults to 'csv'"),
+        ("from" = Option<i64>, Query, description = "Start of the range as a unix timestamp, defaults to the first event"),
+        ("to" = Option<i64>, Query, description = "End of the range as a unix timestamp, defaults to now"),
+        ("types" = Option<Vec<TokenEventType>>, Query, description = "Only export events of these types"),
+    )
+)]
+#[get("/collections/{address}/{chain_id}/activity/export")]
+pub async fn export_collection_activity(
+    req: HttpRequest,
+    path: web::Path<(String, String)>,
+    db_pools: web::Data<Arc<[PgPool; 2]>>,
+    chains: web::Data<ChainRegistry>,
+) -> Result<HttpResponse, ApiError> {
+    let (contract_address, chain_id) = path.into_inner();
+    let normalized_address = parse_address("address", &contract_address)?;
+    let chain_id = resolve_chain_id(&chains, Some(&chain_id))?;
+    let file_name = format!("activity-{}", normalized_address);
+    let (format, filter) = extract_export_params(
+        req.query_string(),
+        ActivityExportScope::Collection {
+            contract_address: normalized_address,
+        },
+        chain_id,
+    )?;
+
+    export_response(format, &file_name, &db_pools[0], filter)
+}
+
+#[utoipa::path(
+    tag = "Portfolio",
+    responses(
+        (status = 200, description = "Activity of a wallet as a CSV or NDJSON file, oldest first", body = ActivityExportRow, content_type = ["text/csv", "application/x-ndjson"]),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 429, description = "Too many exports in progress", body = ErrorResponse),
+    ),
+    params(
+        ("user_address" = String, Path, description = "Address of the user"),
+        ("format" = Option<ExportFormat>, Query, description = "'csv' or 'ndjson', defaults to 'csv'"),
+        ("from" = Option<i64>, Query, description = "Start of the range as a unix timestamp, defaults to the first event"),
+        ("to" = Option<i64>, Query, description = "End of the range as a unix timestamp, defaults to now"),
+        ("types" = Option<Vec<TokenEventType>>, Query, description = "Only export events of these types"),
+        ("chain_id" = Option<String>, Query, description = "The blockchain chain ID, defaults to the default chain"),
+    )
+)]
+#[get("/portfolio/{user_address}/activity/export")]
+pub async fn export_portfolio_activity(
+    req: HttpRequest,
+    path: web::Path<String>,
+    db_pools: web::Data<Arc<[PgPool; 2]>>,
+    chains: web::Data<ChainRegistry>,
+) -> Result<HttpResponse, ApiError> {
+    let user_address = parse_address("user_address", &path.into_inner())?;
+    let chain_id = extract_chain_id(req.query_string(), &chains)?;
+    let file_name = format!("activity-{}", user_address);
+    let (format, filter) = extract_export_params(
+        req.query_string(),
+        ActivityExportScope::User { user_address },
+        chain_id,
+    )?;
+
+    export_response(format, &file_name, &db_pools[0], filter)
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(export_collection_activity)
+        .service(export_portfolio_activity);
+}
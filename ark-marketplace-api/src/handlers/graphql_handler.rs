@@ -0,0 +1,50 @@
+use crate::graphql::{with_loaders, MarketplaceSchema};
+use crate::managers::wallet_session_manager::WalletSessionManager;
+use crate::routes::auth::bearer_token;
+use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
+use sqlx::postgres::PgPool;
+use std::sync::Arc;
+
+#[utoipa::path(
+    tag = "GraphQL",
+    request_body(content = Object, description = "GraphQL request, with `query`, `variables` and `operationName`. The private fields of a portfolio need the wallet session of its address as a bearer token"),
+    responses(
+        (status = 200, description = "GraphQL response, errors are reported in its `errors` field", body = Object),
+    )
+)]
+#[post("/graphql")]
+pub async fn post_graphql(
+    req: HttpRequest,
+    request: web::Json<async_graphql::Request>,
+    schema: web::Data<MarketplaceSchema>,
+    db_pools: web::Data<Arc<[PgPool; 2]>>,
+) -> impl Responder {
+    let mut request = with_loaders(request.into_inner(), &db_pools[0]);
+    // The private fields of a portfolio check this session, an invalid token
+    // is treated as a missing one
+    let session = req
+        .app_data::<web::Data<WalletSessionManager>>()
+        .zip(bearer_token(req.headers()))
+        .and_then(|(manager, token)| manager.decode_session(token).ok());
+    if let Some(session) = session {
+        request = request.data(session);
+    }
+    HttpResponse::Ok().json(schema.execute(request).await)
+}
+
+#[utoipa::path(
+    tag = "GraphQL",
+    responses(
+        (status = 200, description = "GraphQL schema in SDL", body = String),
+    )
+)]
+#[get("/graphql/schema")]
+pub async fn get_graphql_schema(schema: web::Data<MarketplaceSchema>) -> impl Responder {
+    HttpResponse::Ok()
+        .content_type("text/plain; charset=utf-8")
+        .body(schema.sdl())
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(post_graphql).service(get_graphql_schema);
+}
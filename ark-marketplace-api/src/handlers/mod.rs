@@ -1,5 +1,13 @@
+pub mod api_key_handler;
+pub mod chain_handler;
 pub mod collection_handler;
 pub mod default_handler;
+pub mod event_handler;
+pub mod export_handler;
+pub mod graphql_handler;
 pub mod portfolio_handler;
 pub mod token_handler;
 pub mod utils;
+pub mod wallet_session_handler;
+pub mod watchlist_handler;
+pub mod webhook_handler;
@@ -1,39 +1,69 @@
-use super::utils::extract_page_params;
-use super::utils::CHAIN_ID;
-use crate::db::portfolio_query::{get_activity_data, get_offers_data, get_stats_data};
-use crate::models::portfolio::OfferApiData;
+use super::utils::{
+    extract_chain_id, extract_chart_range, extract_cursor_params, extract_page_params,
+    parse_address, parse_direction, parse_query,
+};
+use crate::db::portfolio_query::{
+    get_activity_data, get_collections_valuation, get_offers_data, get_realized_pnl,
+    get_stats_data, get_tax_events, get_value_history,
+};
+use crate::managers::chain_registry::ChainRegistry;
+use crate::managers::price_manager::PriceManager;
+use crate::models::portfolio::{
+    CollectionValuation, OfferApiData, PortfolioAmount, PortfolioValuation, PortfolioValuePoint,
+};
+use crate::models::tax_report::{LotMethod, TaxDisposal, TaxReportFormat};
 use crate::models::token::TokenEventType;
+use crate::routes::auth::wallet_session_guard;
+use crate::types::api_error::ApiError;
 use crate::types::offer_type::OfferType;
-use crate::utils::currency_utils::compute_floor_difference;
-use crate::utils::http_utils::normalize_address;
-use actix_web::{get, web, HttpRequest, HttpResponse, Responder};
+use crate::utils::currency_utils::{compute_floor_difference, normalize_currency_amount};
+use crate::utils::sql_utils::activity_keyset;
+use crate::utils::tax_utils::build_tax_report;
+use actix_web::http::header::CONTENT_DISPOSITION;
+use actix_web::middleware::from_fn;
+use actix_web::{get, web, HttpRequest, HttpResponse};
+use bigdecimal::{BigDecimal, RoundingMode};
+use chrono::{Datelike, NaiveDate, Utc};
 use serde::Deserialize;
 use serde_json::json;
-use serde_qs;
 use sqlx::postgres::PgPool;
-use std::collections::HashMap;
 use std::str::FromStr;
 use std::sync::Arc;
 
 #[derive(Deserialize, Debug)]
 struct ActivityQueryParameters {
-    page: Option<i64>,
-    items_per_page: Option<i64>,
     direction: Option<String>,
     types: Option<Vec<TokenEventType>>,
 }
 
+#[derive(Deserialize, Debug)]
+struct TaxReportQueryParameters {
+    year: Option<i32>,
+    method: Option<LotMethod>,
+    format: Option<TaxReportFormat>,
+}
+
+#[derive(Deserialize, Debug)]
+struct OffersQueryParameters {
+    #[serde(rename = "type")]
+    offer_type: Option<String>,
+}
+
 #[utoipa::path(
     tag = "Portfolio",
     responses(
         (status = 200, description = "Get activity for a portfolio", body = PortfolioActivityResponse),
-        (status = 400, description = "Data not found", body = String),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 404, description = "Data not found", body = ErrorResponse),
     ),
     params(
         ("user_address" = String, Path, description = "Address of the user"),
 
-        ("page" = Option<i32>, Query, description = "Page number for pagination, defaults to 1"),
+        ("cursor" = Option<String>, Query, description = "Cursor of the page, as returned in `next_cursor`"),
         ("items_per_page" = Option<i32>, Query, description = "Number of items per page, defaults to 100"),
+        ("direction" = Option<String>, Query, description = "Sort direction by date, 'asc' or 'desc', defaults to 'desc'"),
+        ("types" = Option<Vec<TokenEventType>>, Query, description = "Only return events of these types"),
+        ("chain_id" = Option<String>, Query, description = "The blockchain chain ID, defaults to the default chain"),
     )
 )]
 #[get("/portfolio/{user_address}/activity")]
@@ -41,105 +71,86 @@ pub async fn get_activity(
     req: HttpRequest,
     path: web::Path<String>,
     db_pools: web::Data<Arc<[PgPool; 2]>>,
-) -> impl Responder {
+    chains: web::Data<ChainRegistry>,
+) -> Result<HttpResponse, ApiError> {
     let user_address = path.into_inner();
-    let normalized_address = normalize_address(&user_address);
+    let normalized_address = parse_address("user_address", &user_address)?;
     let db_access = &db_pools[0];
 
-    let params = serde_qs::from_str::<ActivityQueryParameters>(req.query_string());
-    if let Err(e) = params {
-        let msg = format!("Error when parsing query parameters: {}", e);
-        tracing::error!(msg);
-        return HttpResponse::BadRequest().json(msg);
-    }
-    let params = params.unwrap();
+    let chain_id = extract_chain_id(req.query_string(), &chains)?;
 
-    let page = params.page.unwrap_or(1);
-    let items_per_page = params.items_per_page.unwrap_or(100);
-    let direction = params.direction.as_deref().unwrap_or("desc");
-    let (token_activity_data, has_next_page, count) = match get_activity_data(
+    let params = parse_query::<ActivityQueryParameters>(req.query_string())?;
+    let direction = parse_direction(params.direction.as_deref(), "desc")?;
+    let (cursor, items_per_page) =
+        extract_cursor_params(req.query_string(), &activity_keyset(direction), 100)?;
+
+    let (token_activity_data, next_cursor, count) = get_activity_data(
         db_access,
-        CHAIN_ID,
+        &chain_id,
         &normalized_address,
-        page,
+        cursor.as_ref(),
         items_per_page,
         direction,
         &params.types,
     )
     .await
-    {
-        Err(sqlx::Error::RowNotFound) => return HttpResponse::NotFound().body("data not found"),
-        Ok((token_activity_data, has_next_page, count)) => {
-            (token_activity_data, has_next_page, count)
-        }
-        Err(err) => {
-            tracing::error!("error query portfolio get_activity: {}", err);
-            return HttpResponse::InternalServerError().finish();
-        }
-    };
-    HttpResponse::Ok().json(json!({
+    .map_err(ApiError::database("portfolio get_activity"))?;
+
+    Ok(HttpResponse::Ok().json(json!({
         "data": token_activity_data,
-        "next_page": if has_next_page { Some(page + 1)} else { None },
+        "next_cursor": next_cursor,
         "count": count,
-    }))
+    })))
 }
 
 #[utoipa::path(
     tag = "Portfolio",
     responses(
         (status = 200, description = "Get offers for a portfolio", body = PortfolioOffersResponse),
-        (status = 400, description = "Data not found", body = String),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 404, description = "Data not found", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid wallet session", body = ErrorResponse),
+        (status = 403, description = "Session of another wallet", body = ErrorResponse),
     ),
     params(
         ("user_address" = String, Path, description = "Address of the user"),
         ("type" = Option<String>, Query, description = "'made' or 'received' to filter either made or received offers"),
         ("page" = Option<i32>, Query, description = "Page number for pagination, defaults to 1"),
         ("items_per_page" = Option<i32>, Query, description = "Number of items per page, defaults to 100"),
+        ("chain_id" = Option<String>, Query, description = "The blockchain chain ID, defaults to the default chain"),
     )
 )]
-#[get("/portfolio/{user_address}/offers")]
+#[get(
+    "/portfolio/{user_address}/offers",
+    wrap = "from_fn(wallet_session_guard)"
+)]
 pub async fn get_offers(
     req: HttpRequest,
     path: web::Path<String>,
     db_pools: web::Data<Arc<[PgPool; 2]>>,
-) -> impl Responder {
+    chains: web::Data<ChainRegistry>,
+) -> Result<HttpResponse, ApiError> {
     let user_address = path.into_inner();
-    let normalized_address = normalize_address(&user_address);
+    let normalized_address = parse_address("user_address", &user_address)?;
     let db_access = &db_pools[0];
 
-    let (page, items_per_page) = match extract_page_params(req.query_string(), 1, 100) {
-        Err(msg) => return HttpResponse::BadRequest().json(msg),
-        Ok((page, items_per_page)) => (page, items_per_page),
-    };
+    let chain_id = extract_chain_id(req.query_string(), &chains)?;
+    let (page, items_per_page) = extract_page_params(req.query_string(), 1, 100)?;
 
-    let query_string = req.query_string();
-    let query_params: HashMap<String, String> = serde_urlencoded::from_str(query_string).unwrap();
+    let params = parse_query::<OffersQueryParameters>(req.query_string())?;
+    let type_offer = OfferType::from_str(params.offer_type.as_deref().unwrap_or(""))
+        .map_err(|e| ApiError::invalid_parameter("type", e.to_string()))?;
 
-    let type_offer_str = query_params.get("type").map(|s| s.as_str()).unwrap_or("");
-    let type_offer = match OfferType::from_str(type_offer_str) {
-        Ok(t) => t,
-        Err(_) => return HttpResponse::BadRequest().json("Invalid type"),
-    };
-
-    let (token_offers_data, has_next_page, count) = match get_offers_data(
+    let (token_offers_data, has_next_page, count) = get_offers_data(
         db_access,
-        CHAIN_ID,
+        &chain_id,
         &normalized_address,
         page,
         items_per_page,
         type_offer,
     )
     .await
-    {
-        Err(sqlx::Error::RowNotFound) => return HttpResponse::NotFound().body("data not found"),
-        Ok((token_activity_data, has_next_page, count)) => {
-            (token_activity_data, has_next_page, count)
-        }
-        Err(err) => {
-            tracing::error!("error query portfolio getoffers: {}", err);
-            return HttpResponse::InternalServerError().finish();
-        }
-    };
+    .map_err(ApiError::database("portfolio getoffers"))?;
 
     let token_offers_data: Vec<OfferApiData> = token_offers_data
         .iter()
@@ -166,48 +177,273 @@ pub async fn get_offers(
         })
         .collect();
 
-    HttpResponse::Ok().json(json!({
+    Ok(HttpResponse::Ok().json(json!({
         "data": token_offers_data,
         "next_page": if has_next_page { Some(page + 1)} else { None },
         "count": count,
-    }))
+    })))
 }
 
 #[utoipa::path(
     tag = "Portfolio",
     responses(
         (status = 200, description = "Get stats for a portfolio", body = PortfolioStatsResponse),
-        (status = 400, description = "Data not found", body = String),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 404, description = "Data not found", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid wallet session", body = ErrorResponse),
+        (status = 403, description = "Session of another wallet", body = ErrorResponse),
     ),
     params(
         ("user_address" = String, Path, description = "Address of the user"),
+        ("chain_id" = Option<String>, Query, description = "The blockchain chain ID, defaults to the default chain"),
     )
 )]
-#[get("/portfolio/{user_address}/stats")]
+#[get(
+    "/portfolio/{user_address}/stats",
+    wrap = "from_fn(wallet_session_guard)"
+)]
 pub async fn get_stats(
+    req: HttpRequest,
     path: web::Path<String>,
     db_pools: web::Data<Arc<[PgPool; 2]>>,
-) -> impl Responder {
+    chains: web::Data<ChainRegistry>,
+) -> Result<HttpResponse, ApiError> {
     let user_address = path.into_inner();
-    let normalized_address = normalize_address(&user_address);
+    let normalized_address = parse_address("user_address", &user_address)?;
     let db_access = &db_pools[0];
 
-    let stats_data = match get_stats_data(db_access, CHAIN_ID, &normalized_address).await {
-        Err(sqlx::Error::RowNotFound) => return HttpResponse::NotFound().body("data not found"),
-        Ok(stats_data) => stats_data,
-        Err(err) => {
-            tracing::error!("error query get_stats: {}", err);
-            return HttpResponse::InternalServerError().finish();
+    let chain_id = extract_chain_id(req.query_string(), &chains)?;
+
+    let stats_data = get_stats_data(db_access, &chain_id, &normalized_address)
+        .await
+        .map_err(ApiError::database("get_stats"))?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "data": stats_data,
+    })))
+}
+
+/// Converts an amount in wei into ETH and USD.
+fn to_portfolio_amount(wei: Option<BigDecimal>, eth_usd: Option<&BigDecimal>) -> PortfolioAmount {
+    let eth = wei.map(|wei| normalize_currency_amount(wei, 18));
+    let usd = match (&eth, eth_usd) {
+        (Some(eth), Some(eth_usd)) => {
+            Some((eth * eth_usd).with_scale_round(2, RoundingMode::HalfEven))
         }
+        _ => None,
     };
+    PortfolioAmount { eth, usd }
+}
 
-    HttpResponse::Ok().json(json!({
-        "data": stats_data,
-    }))
+fn sum_amounts<'a>(amounts: impl Iterator<Item = &'a Option<BigDecimal>>) -> Option<BigDecimal> {
+    amounts.flatten().fold(None, |total, amount| {
+        Some(total.unwrap_or_else(|| BigDecimal::from(0)) + amount)
+    })
+}
+
+#[utoipa::path(
+    tag = "Portfolio",
+    responses(
+        (status = 200, description = "Get the value, cost basis and PnL of a portfolio", body = PortfolioValuationResponse),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid wallet session", body = ErrorResponse),
+        (status = 403, description = "Session of another wallet", body = ErrorResponse),
+    ),
+    params(
+        ("user_address" = String, Path, description = "Address of the user"),
+        ("chain_id" = Option<String>, Query, description = "The blockchain chain ID, defaults to the default chain"),
+        ("interval" = Option<String>, Query, description = "Bucket size of the value history, '1h', '1d' or '1w', defaults to '1d'"),
+        ("from" = Option<i64>, Query, description = "Start of the value history as a unix timestamp, defaults to 7 days, 90 days or a year before 'to' depending on the interval"),
+        ("to" = Option<i64>, Query, description = "End of the value history as a unix timestamp, defaults to now"),
+    )
+)]
+#[get(
+    "/portfolio/{user_address}/valuation",
+    wrap = "from_fn(wallet_session_guard)"
+)]
+pub async fn get_valuation(
+    req: HttpRequest,
+    path: web::Path<String>,
+    db_pools: web::Data<Arc<[PgPool; 2]>>,
+    chains: web::Data<ChainRegistry>,
+    price_manager: web::Data<PriceManager>,
+) -> Result<HttpResponse, ApiError> {
+    let user_address = path.into_inner();
+    let normalized_address = parse_address("user_address", &user_address)?;
+    let chain_id = extract_chain_id(req.query_string(), &chains)?;
+    let (interval, from, to) = extract_chart_range(req.query_string())?;
+
+    let db_access = &db_pools[0];
+    let collections = get_collections_valuation(db_access, &chain_id, &normalized_address)
+        .await
+        .map_err(ApiError::database("get_collections_valuation"))?;
+    let realized = get_realized_pnl(db_access, &chain_id, &normalized_address)
+        .await
+        .map_err(ApiError::database("get_realized_pnl"))?;
+    let history = get_value_history(
+        db_access,
+        &chain_id,
+        &normalized_address,
+        interval,
+        from,
+        to,
+    )
+    .await
+    .map_err(ApiError::database("get_value_history"))?;
+
+    // USD values are left empty rather than failing when the price engine
+    // is down
+    let eth_usd = price_manager
+        .get_usd_prices()
+        .await
+        .and_then(|prices| BigDecimal::from_str(&prices.eth_usd.to_string()).ok());
+    let eth_usd = eth_usd.as_ref();
+
+    let valuation = PortfolioValuation {
+        value_at_floor: to_portfolio_amount(
+            sum_amounts(collections.iter().map(|c| &c.value_at_floor)),
+            eth_usd,
+        ),
+        value_at_top_bid: to_portfolio_amount(
+            sum_amounts(collections.iter().map(|c| &c.value_at_top_bid)),
+            eth_usd,
+        ),
+        cost_basis: to_portfolio_amount(
+            sum_amounts(collections.iter().map(|c| &c.cost_basis)),
+            eth_usd,
+        ),
+        unrealized_pnl: to_portfolio_amount(
+            sum_amounts(collections.iter().map(|c| &c.unrealized_pnl)),
+            eth_usd,
+        ),
+        realized_pnl: PortfolioAmount {
+            eth: realized
+                .realized_pnl
+                .map(|wei| normalize_currency_amount(wei, 18)),
+            usd: realized
+                .realized_pnl_usd
+                .map(|usd| usd.with_scale_round(2, RoundingMode::HalfEven)),
+        },
+        sale_count: realized.sale_count,
+        sales_without_cost_basis: realized.sales_without_cost_basis,
+        eth_usd: eth_usd.cloned(),
+        collections: collections
+            .into_iter()
+            .map(|collection| CollectionValuation {
+                collection_address: collection.collection_address,
+                collection_name: collection.collection_name,
+                collection_image: collection.collection_image,
+                token_count: collection.token_count,
+                floor_price: collection
+                    .floor_price
+                    .map(|price| normalize_currency_amount(price, 18)),
+                top_bid: collection
+                    .top_bid
+                    .map(|price| normalize_currency_amount(price, 18)),
+                value_at_floor: to_portfolio_amount(collection.value_at_floor, eth_usd),
+                value_at_top_bid: to_portfolio_amount(collection.value_at_top_bid, eth_usd),
+                cost_basis: to_portfolio_amount(collection.cost_basis, eth_usd),
+                unrealized_pnl: to_portfolio_amount(collection.unrealized_pnl, eth_usd),
+                tokens_without_cost_basis: collection.tokens_without_cost_basis,
+            })
+            .collect(),
+        history: history
+            .into_iter()
+            .map(|point| PortfolioValuePoint {
+                timestamp: point.timestamp,
+                token_count: point.token_count,
+                value_at_floor: point
+                    .value_at_floor
+                    .map(|value| normalize_currency_amount(value, 18)),
+            })
+            .collect(),
+    };
+
+    Ok(HttpResponse::Ok().json(json!({
+        "data": valuation,
+        "interval": interval.as_str(),
+        "from": from,
+        "to": to,
+    })))
+}
+
+/// Unix timestamps of the first and last second of `year`, in UTC.
+fn year_range(year: i32) -> Option<(i64, i64)> {
+    let start = NaiveDate::from_ymd_opt(year, 1, 1)?.and_hms_opt(0, 0, 0)?;
+    let end = NaiveDate::from_ymd_opt(year + 1, 1, 1)?.and_hms_opt(0, 0, 0)?;
+    Some((start.and_utc().timestamp(), end.and_utc().timestamp() - 1))
+}
+
+#[utoipa::path(
+    tag = "Portfolio",
+    responses(
+        (status = 200, description = "Sales and burns of the year matched with their acquisitions, as JSON with a summary or as a CSV file of the disposals", body = TaxReportResponse, content_type = ["application/json", "text/csv"]),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid wallet session", body = ErrorResponse),
+        (status = 403, description = "Session of another wallet", body = ErrorResponse),
+    ),
+    params(
+        ("user_address" = String, Path, description = "Address of the user"),
+        ("year" = Option<i32>, Query, description = "Tax year, in UTC, defaults to the current year"),
+        ("method" = Option<LotMethod>, Query, description = "Lot selection among the tokens held in the collection, 'fifo', 'lifo' or 'hifo', defaults to 'fifo'"),
+        ("format" = Option<TaxReportFormat>, Query, description = "'json' or 'csv', defaults to 'json'"),
+        ("chain_id" = Option<String>, Query, description = "The blockchain chain ID, defaults to the default chain"),
+    )
+)]
+#[get(
+    "/portfolio/{user_address}/tax-report",
+    wrap = "from_fn(wallet_session_guard)"
+)]
+pub async fn get_tax_report(
+    req: HttpRequest,
+    path: web::Path<String>,
+    db_pools: web::Data<Arc<[PgPool; 2]>>,
+    chains: web::Data<ChainRegistry>,
+) -> Result<HttpResponse, ApiError> {
+    let user_address = path.into_inner();
+    let normalized_address = parse_address("user_address", &user_address)?;
+    let chain_id = extract_chain_id(req.query_string(), &chains)?;
+
+    let params = parse_query::<TaxReportQueryParameters>(req.query_string())?;
+    let year = params.year.unwrap_or_else(|| Utc::now().year());
+    let (from, to) = year_range(year)
+        .filter(|(from, _)| *from <= Utc::now().timestamp())
+        .ok_or_else(|| ApiError::invalid_parameter("year", "'year' must not be in the future"))?;
+    let method = params.method.unwrap_or(LotMethod::Fifo);
+
+    let events = get_tax_events(&db_pools[0], &chain_id, &normalized_address, to)
+        .await
+        .map_err(ApiError::database("get_tax_events"))?;
+    let report = build_tax_report(events, year, method, from, to);
+
+    match params.format.unwrap_or(TaxReportFormat::Json) {
+        TaxReportFormat::Json => Ok(HttpResponse::Ok().json(json!({
+            "data": report,
+        }))),
+        TaxReportFormat::Csv => {
+            let mut body = format!("{}\n", TaxDisposal::CSV_HEADER);
+            for disposal in &report.disposals {
+                body.push_str(&disposal.to_csv_record());
+                body.push('\n');
+            }
+            Ok(HttpResponse::Ok()
+                .content_type("text/csv; charset=utf-8")
+                .insert_header((
+                    CONTENT_DISPOSITION,
+                    format!(
+                        "attachment; filename=\"tax-report-{}-{}.csv\"",
+                        normalized_address, year
+                    ),
+                ))
+                .body(body))
+        }
+    }
 }
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(get_activity)
         .service(get_offers)
-        .service(get_stats);
+        .service(get_stats)
+        .service(get_valuation)
+        .service(get_tax_report);
 }
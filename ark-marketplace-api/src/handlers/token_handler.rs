@@ -1,33 +1,36 @@
-use super::utils::extract_page_params;
-use super::utils::CHAIN_ID;
+use super::utils::{
+    check_cursor_params, check_page_params, extract_cursor_params, parse_address, parse_direction,
+    parse_one_of, parse_query, parse_token_id, resolve_chain_id,
+};
 use crate::db::db_access::DatabaseAccess;
 use crate::db::query::get_currencies;
 use crate::db::query::{
     flush_all_data_query, get_collection_floor_price, get_token_activity_data, get_token_data,
-    get_token_marketdata, get_token_offers_data, get_tokens_data, get_tokens_portfolio_data,
-    refresh_token_metadata,
+    get_token_marketdata, get_token_offers_data, get_token_ownership_stats, get_token_sales_data,
+    get_tokens_batch_data, get_tokens_data, get_tokens_portfolio_data, refresh_token_metadata,
 };
+use crate::managers::chain_registry::ChainRegistry;
 use crate::managers::elasticsearch_manager::ElasticsearchManager;
 use crate::models::token::TokenOfferOneData;
-use crate::models::token::{TokenEventType, TokenInformationData};
+use crate::models::token::{TokenEventType, TokenInformationData, TokenKey, TokenPriceStats};
+use crate::types::api_error::ApiError;
 use crate::utils::currency_utils::compute_floor_difference;
-use crate::utils::http_utils::normalize_address;
+use crate::utils::sql_utils::{token_offers_keyset, tokens_keyset, TOKEN_SORTS};
 use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
 use chrono::Utc;
 use redis::aio::MultiplexedConnection;
 use serde::Deserialize;
 use serde_json::json;
-use serde_qs;
 use sqlx::PgPool;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use tracing::error;
 use urlencoding::decode;
 
 #[derive(Deserialize)]
 pub struct QueryParameters {
     page: Option<i64>,
+    cursor: Option<String>,
     items_per_page: Option<i64>,
     buy_now: Option<String>,
     sort: Option<String>,
@@ -48,31 +51,63 @@ struct ActivityQueryParameters {
     // range ?
 }
 
+#[derive(Deserialize, Debug)]
+struct PriceHistoryQueryParameters {
+    limit: Option<usize>,
+}
+
 fn extract_query_params(
     query_parameters: &web::Query<QueryParameters>,
-) -> (i64, i64, bool, &str, &str) {
-    let page = query_parameters.page.unwrap_or(1);
-    let items_per_page = query_parameters.items_per_page.unwrap_or(100);
+) -> Result<(i64, i64, bool, &'static str, &'static str), ApiError> {
+    let (page, items_per_page) = check_page_params(
+        query_parameters.page.unwrap_or(1),
+        query_parameters.items_per_page.unwrap_or(100),
+    )?;
     let buy_now = query_parameters.buy_now.as_deref() == Some("true");
-    let sort = query_parameters.sort.as_deref().unwrap_or("price");
-    let direction = query_parameters.direction.as_deref().unwrap_or("asc");
-    (page, items_per_page, buy_now, sort, direction)
+    let sort = parse_one_of(
+        "sort",
+        query_parameters.sort.as_deref(),
+        TOKEN_SORTS,
+        "price",
+    )?;
+    let direction = parse_direction(query_parameters.direction.as_deref(), "asc")?;
+    Ok((page, items_per_page, buy_now, sort, direction))
+}
+
+/// Reads the trait values of the `filters` parameter, an URL encoded JSON
+/// object such as `{"traits":{"Background":["Blue"]}}`.
+fn parse_trait_filters(filters: &str) -> Result<Option<HashMap<String, Vec<String>>>, ApiError> {
+    let decoded_filters = decode(filters)
+        .map_err(|e| ApiError::InvalidFilters(format!("Failed to decode filters: {}", e)))?;
+    let filters_map: HashMap<String, serde_json::Value> = serde_json::from_str(&decoded_filters)
+        .map_err(|e| ApiError::InvalidFilters(format!("Failed to parse filters: {}", e)))?;
+
+    filters_map
+        .get("traits")
+        .map(|traits| {
+            serde_json::from_value(traits.clone())
+                .map_err(|e| ApiError::InvalidFilters(format!("Failed to parse traits: {}", e)))
+        })
+        .transpose()
 }
 
 #[utoipa::path(
     tag = "Tokens",
     responses(
         (status = 200, description = "Get tokens", body = TokensResponse),
-        (status = 400, description = "Data not found", body = String),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 404, description = "Data not found", body = ErrorResponse),
+        (status = 422, description = "Invalid filters", body = ErrorResponse),
     ),
     params(
         ("address" = String, Path, description = "The contract address of the collection"),
         ("chain_id" = String, Path, description = "The blockchain chain ID"),
-        ("page" = Option<i32>, Query, description = "Page number for pagination, defaults to 1"),
+        ("cursor" = Option<String>, Query, description = "Cursor of the page, as returned in `next_cursor`"),
         ("items_per_page" = Option<i32>, Query, description = "Number of items per page, defaults to 100"),
-        ("search" = Option<String>, Query, description = "Filter by token id"),
+        ("search" = Option<String>, Query, description = "Filter by token id, only digits are accepted"),
+        ("filters" = Option<String>, Query, description = "URL encoded JSON object filtering by traits, e.g. '{\"traits\":{\"Background\":[\"Blue\"]}}'"),
         ("buy_now" = Option<String>, Query, description = "Filter tokens by 'buy now' status"),
-        ("sort" = Option<String>, Query, description = "Sort field, defaults to 'price'"),
+        ("sort" = Option<String>, Query, description = "Sort field, 'price', 'owner' or 'rarity', defaults to 'price'"),
         ("direction" = Option<String>, Query, description = "Sort direction, 'asc' or 'desc', defaults to 'asc'"),
         ("sort_value" = Option<String>, Query, description = "Specific value for sorting, used to refine results")
     )
@@ -84,24 +119,44 @@ pub async fn get_tokens(
     db_pools: web::Data<Arc<[PgPool; 2]>>,
     redis_con: web::Data<Arc<Mutex<MultiplexedConnection>>>,
     es_data: web::Data<HashMap<String, String>>,
-) -> impl Responder {
-    let page = query_parameters.page.unwrap_or(1);
-    let items_per_page = query_parameters.items_per_page.unwrap_or(100);
+    chains: web::Data<ChainRegistry>,
+) -> Result<HttpResponse, ApiError> {
     let (contract_address, chain_id) = path.into_inner();
-    let normalized_address = normalize_address(&contract_address);
+    let normalized_address = parse_address("address", &contract_address)?;
+    let chain_id = resolve_chain_id(&chains, Some(&chain_id))?;
     let buy_now = query_parameters.buy_now.as_deref() == Some("true");
-    let sort = query_parameters.sort.as_deref().unwrap_or("price");
-    let direction = query_parameters.direction.as_deref().unwrap_or("asc");
+    let sort = parse_one_of(
+        "sort",
+        query_parameters.sort.as_deref(),
+        TOKEN_SORTS,
+        "price",
+    )?;
+    let direction = parse_direction(query_parameters.direction.as_deref(), "asc")?;
     let search = query_parameters.search.as_deref().unwrap_or("");
+    if !search.is_empty() {
+        parse_token_id(search).map_err(|_| {
+            ApiError::invalid_parameter("search", "search must only contain digits")
+        })?;
+    }
     let mut disable_cache = query_parameters.disable_cache.as_deref() == Some("true");
     let sort_value = query_parameters
         .sort_value
         .as_deref()
         .map(|s| s.to_string());
-    // disable cache
-    if sort_value.is_some() {
+    // disable cache, cached pages are sorted by price
+    if sort_value.is_some() || sort != "price" {
         disable_cache = true;
     }
+    let (cursor, items_per_page) = check_cursor_params(
+        query_parameters.cursor.as_deref(),
+        query_parameters.items_per_page.unwrap_or(100),
+        &tokens_keyset(sort, direction, sort_value.as_deref()),
+    )?;
+    let trait_filters = match query_parameters.filters.as_deref() {
+        Some(filters) if !filters.is_empty() => parse_trait_filters(filters)?,
+        _ => None,
+    };
+
     let db_access = &db_pools[0];
     let mut redis_con_ref = redis_con.get_ref().lock().await;
     let mut token_ids = None;
@@ -113,46 +168,29 @@ pub async fn get_tokens(
             token_id = Some(parsed_token_id)
         }
         Err(_) => {
-            error!("get_tokens: error parsing search field");
+            tracing::error!("get_tokens: error parsing search field");
         }
     }
 
-    if let Some(filters_param) = &query_parameters.filters {
-        if !filters_param.is_empty() {
-            let decoded_filters = decode(filters_param).expect("Failed to decode filters");
-            let filters_map: HashMap<String, serde_json::Value> =
-                serde_json::from_str(&decoded_filters).expect("Failed to parse JSON");
-
-            if let Some(traits) = filters_map.get("traits") {
-                // for now we dont want to cache results with traits
-                disable_cache = true;
-                let traits_map: HashMap<String, Vec<String>> =
-                    serde_json::from_value(traits.clone()).expect("Failed to parse traits JSON");
-
-                let elasticsearch_manager = ElasticsearchManager::new(es_data.get_ref().clone());
-
-                let result = elasticsearch_manager
-                    .search_tokens_by_traits(&normalized_address, CHAIN_ID, traits_map)
-                    .await;
-
-                token_ids = match result {
-                    Ok(token_ids) => Some(token_ids),
-                    Err(e) => {
-                        return HttpResponse::InternalServerError().json(json!({
-                            "error": format!("Failed to retrieve data: {}", e)
-                        }))
-                    }
-                };
-            }
-        }
+    if let Some(traits_map) = trait_filters {
+        // for now we dont want to cache results with traits
+        disable_cache = true;
+        let elasticsearch_manager = ElasticsearchManager::new(es_data.get_ref().clone());
+
+        token_ids = Some(
+            elasticsearch_manager
+                .search_tokens_by_traits(&normalized_address, &chain_id, traits_map)
+                .await
+                .map_err(|e| ApiError::internal("Failed to search tokens by traits", e))?,
+        );
     }
 
-    match get_tokens_data(
+    let (collection_data, next_cursor, token_count) = get_tokens_data(
         db_access,
         &mut redis_con_ref,
         &normalized_address,
         &chain_id,
-        page,
+        cursor.as_ref(),
         items_per_page,
         buy_now,
         sort,
@@ -163,28 +201,24 @@ pub async fn get_tokens(
         token_id,
     )
     .await
-    {
-        Err(sqlx::Error::RowNotFound) => HttpResponse::NotFound().body("data not found"),
-        Ok((ref collection_data, _, _)) if collection_data.is_empty() => {
-            HttpResponse::NotFound().body("data not found")
-        }
-        Ok((collection_data, _has_next_page, token_count)) => HttpResponse::Ok().json(json!({
-            "data": collection_data,
-            "token_count": token_count,
-            "next_page": /*if has_next_page { Some(page + 1) } else { None }*/ page + 1
-        })),
-        Err(err) => {
-            tracing::error!("error query get_tokens_data: {}", err);
-            HttpResponse::InternalServerError().finish()
-        }
+    .map_err(ApiError::database("get_tokens_data"))?;
+
+    if collection_data.is_empty() {
+        return Err(ApiError::NotFound);
     }
+    Ok(HttpResponse::Ok().json(json!({
+        "data": collection_data,
+        "token_count": token_count,
+        "next_cursor": next_cursor
+    })))
 }
 
 #[utoipa::path(
     tag = "Tokens",
     responses(
         (status = 200, description = "Get a token information", body = TokensResponse),
-        (status = 400, description = "Data not found", body = String),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 404, description = "Data not found", body = ErrorResponse),
     ),
     params(
         ("address" = String, Path, description = "The contract address of the collection"),
@@ -196,28 +230,97 @@ pub async fn get_tokens(
 pub async fn get_token(
     path: web::Path<(String, String, String)>,
     db_pools: web::Data<Arc<[PgPool; 2]>>,
-) -> impl Responder {
+    chains: web::Data<ChainRegistry>,
+) -> Result<HttpResponse, ApiError> {
     let (contract_address, chain_id, token_id) = path.into_inner();
-    let normalized_address = normalize_address(&contract_address);
+    let normalized_address = parse_address("address", &contract_address)?;
+    let chain_id = resolve_chain_id(&chains, Some(&chain_id))?;
+    let token_id = parse_token_id(&token_id)?;
 
     let db_access = &db_pools[0];
-    match get_token_data(db_access, &normalized_address, &chain_id, &token_id).await {
-        Err(sqlx::Error::RowNotFound) => HttpResponse::NotFound().body("data not found"),
-        Ok(token_data) => HttpResponse::Ok().json(json!({
-            "data": token_data,
-        })),
-        Err(err) => {
-            tracing::error!("error query get_tokens_data: {}", err);
-            HttpResponse::InternalServerError().finish()
-        }
+    let token_data = get_token_data(db_access, &normalized_address, &chain_id, &token_id)
+        .await
+        .map_err(ApiError::database("get_token_data"))?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "data": token_data,
+    })))
+}
+
+/// Most tokens that can be looked up in one batch.
+const MAX_BATCH_TOKENS: usize = 200;
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct TokenBatchKey {
+    pub contract_address: String,
+    pub token_id: String,
+    /// Defaults to the default chain
+    pub chain_id: Option<String>,
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct TokensBatchRequest {
+    pub tokens: Vec<TokenBatchKey>,
+}
+
+fn parse_token_key(chains: &ChainRegistry, token: &TokenBatchKey) -> Result<TokenKey, ApiError> {
+    Ok(TokenKey {
+        contract_address: parse_address("contract_address", &token.contract_address)?,
+        chain_id: resolve_chain_id(chains, token.chain_id.as_deref())?,
+        token_id: parse_token_id(&token.token_id)?,
+    })
+}
+
+#[utoipa::path(
+    tag = "Tokens",
+    request_body = TokensBatchRequest,
+    responses(
+        (status = 200, description = "Information and market data of the tokens found, in the requested order", body = TokensBatchResponse),
+        (status = 400, description = "Malformed request", body = ErrorResponse),
+        (status = 422, description = "Invalid token or too many tokens", body = ErrorResponse),
+    )
+)]
+#[post("/tokens/batch")]
+pub async fn get_tokens_batch(
+    body: web::Json<TokensBatchRequest>,
+    db_pools: web::Data<Arc<[PgPool; 2]>>,
+    chains: web::Data<ChainRegistry>,
+) -> Result<HttpResponse, ApiError> {
+    let tokens = body.into_inner().tokens;
+    if tokens.is_empty() {
+        return Err(ApiError::InvalidBody("No tokens to look up".to_string()));
+    }
+    if tokens.len() > MAX_BATCH_TOKENS {
+        return Err(ApiError::InvalidBody(format!(
+            "Too many tokens, at most {} can be looked up at once",
+            MAX_BATCH_TOKENS
+        )));
     }
+
+    let keys = tokens
+        .iter()
+        .enumerate()
+        .map(|(index, token)| {
+            parse_token_key(&chains, token)
+                .map_err(|e| ApiError::InvalidBody(format!("tokens[{}]: {}", index, e)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let tokens = get_tokens_batch_data(&db_pools[0], &keys)
+        .await
+        .map_err(ApiError::database("get_tokens_batch_data"))?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "data": tokens,
+    })))
 }
 
 #[utoipa::path(
     tag = "Tokens",
     responses(
         (status = 200, description = "Get a token marketdata", body = TokenMarketDataResponse),
-        (status = 400, description = "Data not found", body = String),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 404, description = "Data not found", body = ErrorResponse),
     ),
     params(
         ("address" = String, Path, description = "The contract address of the collection"),
@@ -229,34 +332,39 @@ pub async fn get_token(
 pub async fn get_token_market(
     path: web::Path<(String, String, String)>,
     db_pools: web::Data<Arc<[PgPool; 2]>>,
-) -> impl Responder {
+    chains: web::Data<ChainRegistry>,
+) -> Result<HttpResponse, ApiError> {
     let (contract_address, chain_id, token_id) = path.into_inner();
-    let normalized_address = normalize_address(&contract_address);
+    let normalized_address = parse_address("address", &contract_address)?;
+    let chain_id = resolve_chain_id(&chains, Some(&chain_id))?;
+    let token_id = parse_token_id(&token_id)?;
 
     let db_access = &db_pools[0];
-    match get_token_marketdata(db_access, &normalized_address, &chain_id, &token_id).await {
-        Err(sqlx::Error::RowNotFound) => HttpResponse::NotFound().body("data not found"),
-        Ok(token_data) => HttpResponse::Ok().json(json!({
-            "data": token_data,
-        })),
-        Err(err) => {
-            tracing::error!("error query get_tokens_data: {}", err);
-            HttpResponse::InternalServerError().finish()
-        }
-    }
+    let token_data = get_token_marketdata(db_access, &normalized_address, &chain_id, &token_id)
+        .await
+        .map_err(ApiError::database("get_token_marketdata"))?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "data": token_data,
+    })))
 }
 
 #[utoipa::path(
     tag = "Portfolio",
     responses(
         (status = 200, description = "Get tokens in a portfolio", body = TokensPortfolioResponse),
-        (status = 400, description = "Data not found", body = String),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 404, description = "Data not found", body = ErrorResponse),
     ),
     params(
         ("user_address" = String, Path, description = "The user address"),
 
         ("page" = Option<i32>, Query, description = "Page number for pagination, defaults to 1"),
         ("items_per_page" = Option<i32>, Query, description = "Number of items per page, defaults to 100"),
+        ("collection" = Option<String>, Query, description = "Only return tokens of this collection"),
+        ("buy_now" = Option<String>, Query, description = "Filter tokens by 'buy now' status"),
+        ("sort" = Option<String>, Query, description = "Sort field, 'price', 'owner' or 'rarity', defaults to 'price'"),
+        ("direction" = Option<String>, Query, description = "Sort direction, 'asc' or 'desc', defaults to 'asc'"),
     )
 )]
 #[get("/portfolio/{user_address}")]
@@ -264,16 +372,18 @@ pub async fn get_tokens_portfolio(
     path: web::Path<String>,
     query_parameters: web::Query<QueryParameters>,
     db_pools: web::Data<Arc<[PgPool; 2]>>,
-) -> impl Responder {
-    let (page, items_per_page, buy_now, sort, direction) = extract_query_params(&query_parameters);
-    let collection = query_parameters.collection.as_deref().unwrap_or("");
+) -> Result<HttpResponse, ApiError> {
+    let (page, items_per_page, buy_now, sort, direction) = extract_query_params(&query_parameters)?;
+    let collection = match query_parameters.collection.as_deref() {
+        Some(collection) if !collection.is_empty() => parse_address("collection", collection)?,
+        _ => String::new(),
+    };
 
     let user_address = path.into_inner().to_lowercase();
-    let normalized_address = normalize_address(&user_address);
+    let normalized_address = parse_address("user_address", &user_address)?;
 
     let db_access = &db_pools[0];
-
-    match get_tokens_portfolio_data(
+    let (collection_data, has_next_page, token_count) = get_tokens_portfolio_data(
         db_access,
         &normalized_address,
         page,
@@ -281,34 +391,30 @@ pub async fn get_tokens_portfolio(
         buy_now,
         sort,
         direction,
-        collection,
+        &collection,
     )
     .await
-    {
-        Err(sqlx::Error::RowNotFound) => HttpResponse::NotFound().body("data not found"),
-        Ok((collection_data, has_next_page, token_count)) => HttpResponse::Ok().json(json!({
-            "data": collection_data,
-            "token_count": token_count,
-            "next_page": if has_next_page { Some(page + 1) } else { None }
-        })),
-        Err(err) => {
-            tracing::error!("error query portfolio token: {}", err);
-            HttpResponse::InternalServerError().finish()
-        }
-    }
+    .map_err(ApiError::database("portfolio token"))?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "data": collection_data,
+        "token_count": token_count,
+        "next_page": if has_next_page { Some(page + 1) } else { None }
+    })))
 }
 
 #[utoipa::path(
     tag = "Tokens",
     responses(
         (status = 200, description = "Get token offers", body = TokenOffersResponse),
-        (status = 400, description = "Data not found", body = String),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 404, description = "Data not found", body = ErrorResponse),
     ),
     params(
         ("address" = String, Path, description = "The contract address of the collection"),
         ("chain_id" = String, Path, description = "The blockchain chain ID"),
         ("token_id" = String, Path, description = "The token ID"),
-        ("page" = Option<i32>, Query, description = "Page number for pagination, defaults to 1"),
+        ("cursor" = Option<String>, Query, description = "Cursor of the page, as returned in `next_cursor`"),
         ("items_per_page" = Option<i32>, Query, description = "Number of items per page, defaults to 100"),
     )
 )]
@@ -317,52 +423,35 @@ pub async fn get_token_offers(
     req: HttpRequest,
     path: web::Path<(String, String, String)>,
     db_pools: web::Data<Arc<[PgPool; 2]>>,
-) -> impl Responder {
+    chains: web::Data<ChainRegistry>,
+) -> Result<HttpResponse, ApiError> {
     let (contract_address, chain_id, token_id) = path.into_inner();
-    let normalized_address = normalize_address(&contract_address);
+    let normalized_address = parse_address("address", &contract_address)?;
+    let chain_id = resolve_chain_id(&chains, Some(&chain_id))?;
+    let token_id = parse_token_id(&token_id)?;
 
-    let (page, items_per_page) = match extract_page_params(req.query_string(), 1, 100) {
-        Err(msg) => return HttpResponse::BadRequest().json(msg),
-        Ok((page, items_per_page)) => (page, items_per_page),
-    };
+    let (cursor, items_per_page) =
+        extract_cursor_params(req.query_string(), &token_offers_keyset(), 100)?;
 
     let db_access = &db_pools[0];
-    let floor_price = match get_collection_floor_price(db_access, &normalized_address, &chain_id)
+    let floor_price = get_collection_floor_price(db_access, &normalized_address, &chain_id)
         .await
-    {
-        Err(sqlx::Error::RowNotFound) => return HttpResponse::NotFound().body("data not found"),
-        Ok(floor_price) => floor_price,
-        Err(err) => {
-            tracing::error!("error query get_collection_floor_price: {}", err);
-            return HttpResponse::InternalServerError().finish();
-        }
-    };
+        .map_err(ApiError::database("get_collection_floor_price"))?;
 
-    let (token_offers_data, has_next_page, count) = match get_token_offers_data(
+    let (token_offers_data, next_cursor, count) = get_token_offers_data(
         db_access,
         &normalized_address,
         &chain_id,
         &token_id,
-        page,
+        cursor.as_ref(),
         items_per_page,
     )
     .await
-    {
-        Err(sqlx::Error::RowNotFound) => return HttpResponse::NotFound().body("data not found"),
-        Ok((token_offers_data, has_next_page, count)) => (token_offers_data, has_next_page, count),
-        Err(err) => {
-            tracing::error!("error query get_token_offers_data: {}", err);
-            return HttpResponse::InternalServerError().finish();
-        }
-    };
+    .map_err(ApiError::database("get_token_offers_data"))?;
 
-    let currencies = match get_currencies(db_access).await {
-        Ok(currencies) => currencies,
-        Err(err) => {
-            tracing::error!("error query get_currencies: {}", err);
-            return HttpResponse::InternalServerError().finish();
-        }
-    };
+    let currencies = get_currencies(db_access)
+        .await
+        .map_err(ApiError::database("get_currencies"))?;
 
     let token_offers_data: Vec<TokenOfferOneData> = token_offers_data
         .into_iter()
@@ -389,18 +478,19 @@ pub async fn get_token_offers(
         })
         .collect();
 
-    HttpResponse::Ok().json(json!({
+    Ok(HttpResponse::Ok().json(json!({
         "data": token_offers_data,
         "count": count,
-        "next_page": if has_next_page { Some(page + 1) } else { None }
-    }))
+        "next_cursor": next_cursor
+    })))
 }
 
 #[utoipa::path(
     tag = "Tokens",
     responses(
         (status = 200, description = "Get token activities", body = TokenActivitiesResponse),
-        (status = 400, description = "Data not found", body = String),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 404, description = "Data not found", body = ErrorResponse),
     ),
     params(
         ("address" = String, Path, description = "The contract address of the collection"),
@@ -409,6 +499,8 @@ pub async fn get_token_offers(
 
         ("page" = Option<i32>, Query, description = "Page number for pagination, defaults to 1"),
         ("items_per_page" = Option<i32>, Query, description = "Number of items per page, defaults to 100"),
+        ("direction" = Option<String>, Query, description = "Sort direction by date, 'asc' or 'desc', defaults to 'desc'"),
+        ("types" = Option<Vec<TokenEventType>>, Query, description = "Only return events of these types"),
     )
 )]
 #[get("/tokens/{address}/{chain_id}/{token_id}/activity")]
@@ -416,23 +508,21 @@ pub async fn get_token_activity(
     req: HttpRequest,
     path: web::Path<(String, String, String)>,
     db_pools: web::Data<Arc<[PgPool; 2]>>,
-) -> impl Responder {
+    chains: web::Data<ChainRegistry>,
+) -> Result<HttpResponse, ApiError> {
     let (contract_address, chain_id, token_id) = path.into_inner();
-    let normalized_address = normalize_address(&contract_address);
+    let normalized_address = parse_address("address", &contract_address)?;
+    let chain_id = resolve_chain_id(&chains, Some(&chain_id))?;
+    let token_id = parse_token_id(&token_id)?;
     let db_access = &db_pools[0];
 
-    let params = serde_qs::from_str::<ActivityQueryParameters>(req.query_string());
-    if let Err(e) = params {
-        let msg = format!("Error when parsing query parameters: {}", e);
-        tracing::error!(msg);
-        return HttpResponse::BadRequest().json(msg);
-    }
-    let params = params.unwrap();
-
-    let page = params.page.unwrap_or(1);
-    let items_per_page = params.items_per_page.unwrap_or(100);
-    let direction = params.direction.as_deref().unwrap_or("desc");
-    let (token_activity_data, has_next_page, count) = match get_token_activity_data(
+    let params = parse_query::<ActivityQueryParameters>(req.query_string())?;
+    let (page, items_per_page) = check_page_params(
+        params.page.unwrap_or(1),
+        params.items_per_page.unwrap_or(100),
+    )?;
+    let direction = parse_direction(params.direction.as_deref(), "desc")?;
+    let (token_activity_data, has_next_page, count) = get_token_activity_data(
         db_access,
         &normalized_address,
         &chain_id,
@@ -443,27 +533,91 @@ pub async fn get_token_activity(
         &params.types,
     )
     .await
-    {
-        Err(sqlx::Error::RowNotFound) => return HttpResponse::NotFound().body("data not found"),
-        Ok((token_activity_data, has_next_page, count)) => {
-            (token_activity_data, has_next_page, count)
-        }
-        Err(err) => {
-            tracing::error!("error query get_token_activity_data: {}", err);
-            return HttpResponse::InternalServerError().finish();
-        }
-    };
-    HttpResponse::Ok().json(json!({
+    .map_err(ApiError::database("get_token_activity_data"))?;
+
+    Ok(HttpResponse::Ok().json(json!({
         "data": token_activity_data,
         "next_page": if has_next_page { Some(page + 1)} else { None },
         "count": count,
-    }))
+    })))
+}
+
+#[utoipa::path(
+    tag = "Tokens",
+    responses(
+        (status = 200, description = "Get token sales and price statistics", body = TokenPriceHistoryResponse),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 404, description = "Token not found", body = ErrorResponse),
+    ),
+    params(
+        ("address" = String, Path, description = "The contract address of the collection"),
+        ("chain_id" = String, Path, description = "The blockchain chain ID"),
+        ("token_id" = String, Path, description = "The token ID"),
+        ("limit" = Option<usize>, Query, description = "Only return the last N sales, statistics still cover every sale"),
+    )
+)]
+#[get("/tokens/{address}/{chain_id}/{token_id}/price-history")]
+pub async fn get_token_price_history(
+    req: HttpRequest,
+    path: web::Path<(String, String, String)>,
+    db_pools: web::Data<Arc<[PgPool; 2]>>,
+    chains: web::Data<ChainRegistry>,
+) -> Result<HttpResponse, ApiError> {
+    let (contract_address, chain_id, token_id) = path.into_inner();
+    let normalized_address = parse_address("address", &contract_address)?;
+    let chain_id = resolve_chain_id(&chains, Some(&chain_id))?;
+    let token_id = parse_token_id(&token_id)?;
+
+    let params = parse_query::<PriceHistoryQueryParameters>(req.query_string())?;
+    if params.limit == Some(0) {
+        return Err(ApiError::invalid_parameter(
+            "limit",
+            "limit must be greater than 0",
+        ));
+    }
+
+    let db_access = &db_pools[0];
+    let ownership_stats =
+        get_token_ownership_stats(db_access, &normalized_address, &chain_id, &token_id)
+            .await
+            .map_err(ApiError::database("get_token_ownership_stats"))?;
+
+    let mut sales = get_token_sales_data(db_access, &normalized_address, &chain_id, &token_id)
+        .await
+        .map_err(ApiError::database("get_token_sales_data"))?;
+
+    let stats = TokenPriceStats {
+        all_time_high: sales
+            .iter()
+            .filter(|sale| sale.eth_price.is_some())
+            .max_by(|a, b| a.eth_price.cmp(&b.eth_price))
+            .cloned(),
+        all_time_low: sales
+            .iter()
+            .filter(|sale| sale.eth_price.is_some())
+            .min_by(|a, b| a.eth_price.cmp(&b.eth_price))
+            .cloned(),
+        sales_count: sales.len() as i64,
+        average_hold_time: ownership_stats.average_hold_time,
+        owner_count: ownership_stats.owner_count,
+    };
+
+    if let Some(limit) = params.limit {
+        sales.truncate(limit);
+    }
+
+    Ok(HttpResponse::Ok().json(json!({
+        "data": sales,
+        "stats": stats,
+    })))
 }
 
 #[derive(Deserialize, utoipa::ToSchema)]
 pub struct RefreshMetadataRequest {
     pub contract_address: String,
     pub token_id: String,
+    /// Defaults to the default chain
+    pub chain_id: Option<String>,
 }
 
 fn is_metadata_refreshing(token_data: &TokenInformationData) -> bool {
@@ -488,50 +642,38 @@ fn is_metadata_refreshing(token_data: &TokenInformationData) -> bool {
     tag = "Tokens",
     responses(
         (status = 200, description = "Metadata refresh has been requested", body = String),
-        (status = 400, description = "Data not found", body = String),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Token does not exist", body = ErrorResponse),
     ),
-    params(
-        ("contract_address" = String, Path, description = "The contract address of the collection"),
-        ("token_id" = String, Path, description = "The token ID"),
-    )
+    request_body = RefreshMetadataRequest,
 )]
 #[post("/metadata/refresh")]
 pub async fn post_refresh_token_metadata(
     body: web::Json<RefreshMetadataRequest>,
     db_pools: web::Data<Arc<[PgPool; 2]>>,
-) -> impl Responder {
+    chains: web::Data<ChainRegistry>,
+) -> Result<HttpResponse, ApiError> {
     let db_access = &db_pools[1];
-    let normalized_address = normalize_address(&body.contract_address);
-
-    match get_token_data(db_access, &normalized_address, CHAIN_ID, &body.token_id).await {
-        Err(e) => {
-            error!("error: {:?}", e);
-            HttpResponse::NotFound().json(json!({
-                "message": "Token does not exist"
-            }))
-        }
-        Ok(token_data) => {
-            if is_metadata_refreshing(&token_data) {
-                return HttpResponse::Ok().json(json!({
-                    "message": "Metadata refresh has already been requested"
-                }));
-            }
+    let normalized_address = parse_address("contract_address", &body.contract_address)?;
+    let chain_id = resolve_chain_id(&chains, body.chain_id.as_deref())?;
+    let token_id = parse_token_id(&body.token_id)?;
 
-            match refresh_token_metadata(db_access, &normalized_address, CHAIN_ID, &body.token_id)
-                .await
-            {
-                Ok(_) => HttpResponse::Ok().json(json!({
-                    "message": "Metadata refresh has been requested"
-                })),
-                Err(err) => {
-                    tracing::error!("Failed to refresh metadata: {}", err);
-                    HttpResponse::InternalServerError().json(json!({
-                        "message": "Failed to refresh metadata"
-                    }))
-                }
-            }
-        }
+    let token_data = get_token_data(db_access, &normalized_address, &chain_id, &token_id)
+        .await
+        .map_err(ApiError::database("get_token_data"))?;
+    if is_metadata_refreshing(&token_data) {
+        return Ok(HttpResponse::Ok().json(json!({
+            "message": "Metadata refresh has already been requested"
+        })));
     }
+
+    refresh_token_metadata(db_access, &normalized_address, &chain_id, &token_id)
+        .await
+        .map_err(|err| ApiError::internal("Failed to refresh metadata", err))?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "message": "Metadata refresh has been requested"
+    })))
 }
 
 pub async fn flush_all_data<D: DatabaseAccess + Sync>(
@@ -546,10 +688,12 @@ pub async fn flush_all_data<D: DatabaseAccess + Sync>(
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(get_tokens)
+        .service(get_tokens_batch)
         .service(get_token)
         .service(get_token_market)
         .service(get_tokens_portfolio)
         .service(get_token_offers)
         .service(get_token_activity)
+        .service(get_token_price_history)
         .service(post_refresh_token_metadata);
 }
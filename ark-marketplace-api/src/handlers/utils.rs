@@ -1,6 +1,104 @@
+use crate::managers::chain_registry::ChainRegistry;
+use crate::types::api_error::ApiError;
+use crate::types::chart_interval::{ChartInterval, MAX_CHART_BUCKETS};
+use crate::utils::cursor_utils::{Cursor, Keyset};
+use crate::utils::http_utils::normalize_address;
+use crate::utils::sql_utils::DIRECTIONS;
+use chrono::Utc;
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
 
-pub const CHAIN_ID: &str = "0x534e5f4d41494e";
+/// Most items a paginated route returns at once.
+pub const MAX_ITEMS_PER_PAGE: i64 = 1000;
+
+/// Deserializes the query string into the parameters of a route.
+pub fn parse_query<T: DeserializeOwned>(query_string: &str) -> Result<T, ApiError> {
+    serde_qs::from_str::<T>(query_string).map_err(|e| {
+        let err = ApiError::invalid_query(e);
+        tracing::error!("{}", err);
+        err
+    })
+}
+
+/// Checks that `address` is a hexadecimal Starknet address and returns it
+/// padded to 64 digits.
+pub fn parse_address(parameter: &str, address: &str) -> Result<String, ApiError> {
+    let digits = address
+        .strip_prefix("0x")
+        .or_else(|| address.strip_prefix("0X"));
+    match digits {
+        Some(digits)
+            if !digits.is_empty()
+                && digits.len() <= 64
+                && digits.chars().all(|c| c.is_ascii_hexdigit()) =>
+        {
+            Ok(normalize_address(address))
+        }
+        _ => Err(ApiError::InvalidAddress {
+            parameter: parameter.to_string(),
+            value: address.to_string(),
+        }),
+    }
+}
+
+/// Token ids are stored as decimal strings.
+pub fn parse_token_id(token_id: &str) -> Result<String, ApiError> {
+    if token_id.is_empty() || !token_id.chars().all(|c| c.is_ascii_digit()) {
+        return Err(ApiError::invalid_parameter(
+            "token_id",
+            format!("Invalid token id: {}, expected a decimal number", token_id),
+        ));
+    }
+    Ok(token_id.to_string())
+}
+
+/// Returns the allowed value matching `value`, or `default` when the
+/// parameter is missing.
+pub fn parse_one_of(
+    parameter: &str,
+    value: Option<&str>,
+    allowed: &[&'static str],
+    default: &'static str,
+) -> Result<&'static str, ApiError> {
+    match value {
+        None => Ok(default),
+        Some(value) => allowed
+            .iter()
+            .find(|allowed| **allowed == value)
+            .copied()
+            .ok_or_else(|| {
+                ApiError::invalid_parameter(
+                    parameter,
+                    format!(
+                        "Invalid {}: {}, expected one of {}",
+                        parameter,
+                        value,
+                        allowed.join(", ")
+                    ),
+                )
+            }),
+    }
+}
+
+pub fn parse_direction(
+    value: Option<&str>,
+    default: &'static str,
+) -> Result<&'static str, ApiError> {
+    parse_one_of("direction", value, DIRECTIONS, default)
+}
+
+fn check_items_per_page(items_per_page: i64) -> Result<i64, ApiError> {
+    if !(1..=MAX_ITEMS_PER_PAGE).contains(&items_per_page) {
+        return Err(ApiError::invalid_parameter(
+            "items_per_page",
+            format!(
+                "items_per_page must be between 1 and {}",
+                MAX_ITEMS_PER_PAGE
+            ),
+        ));
+    }
+    Ok(items_per_page)
+}
 
 #[derive(Deserialize)]
 pub struct PageParameters {
@@ -12,16 +110,118 @@ pub fn extract_page_params(
     query_string: &str,
     default_page: i64,
     default_items_per_page: i64,
-) -> Result<(i64, i64), String> {
-    match serde_qs::from_str::<PageParameters>(query_string) {
-        Err(e) => {
-            let msg = format!("Error when parsing page query parameters: {}", e);
-            tracing::error!(msg);
-            Err(msg)
-        }
-        Ok(params) => Ok((
-            params.page.unwrap_or(default_page),
-            params.items_per_page.unwrap_or(default_items_per_page),
-        )),
+) -> Result<(i64, i64), ApiError> {
+    let params = parse_query::<PageParameters>(query_string)?;
+    check_page_params(
+        params.page.unwrap_or(default_page),
+        params.items_per_page.unwrap_or(default_items_per_page),
+    )
+}
+
+/// Validates page parameters read along with the other parameters of a route.
+pub fn check_page_params(page: i64, items_per_page: i64) -> Result<(i64, i64), ApiError> {
+    if page < 1 {
+        return Err(ApiError::invalid_parameter(
+            "page",
+            "page must be greater than 0",
+        ));
     }
+    Ok((page, check_items_per_page(items_per_page)?))
+}
+
+#[derive(Deserialize)]
+pub struct CursorParameters {
+    cursor: Option<String>,
+    items_per_page: Option<i64>,
+}
+
+/// Reads the `cursor` and `items_per_page` query parameters of the routes
+/// paginated over `keyset`.
+pub fn extract_cursor_params(
+    query_string: &str,
+    keyset: &Keyset,
+    default_items_per_page: i64,
+) -> Result<(Option<Cursor>, i64), ApiError> {
+    let params = parse_query::<CursorParameters>(query_string)?;
+    check_cursor_params(
+        params.cursor.as_deref(),
+        params.items_per_page.unwrap_or(default_items_per_page),
+        keyset,
+    )
+}
+
+/// Validates cursor parameters read along with the other parameters of a
+/// route.
+pub fn check_cursor_params(
+    cursor: Option<&str>,
+    items_per_page: i64,
+    keyset: &Keyset,
+) -> Result<(Option<Cursor>, i64), ApiError> {
+    let items_per_page = check_items_per_page(items_per_page)?;
+    let cursor = cursor
+        .map(|cursor| keyset.decode_cursor(cursor))
+        .transpose()
+        .map_err(ApiError::InvalidCursor)?;
+
+    Ok((cursor, items_per_page))
+}
+
+/// Resolves a chain id given in the path or the body of a request.
+pub fn resolve_chain_id(
+    chains: &ChainRegistry,
+    chain_id: Option<&str>,
+) -> Result<String, ApiError> {
+    chains.resolve(chain_id).map_err(ApiError::UnsupportedChain)
+}
+
+#[derive(Deserialize)]
+pub struct ChainParameters {
+    chain_id: Option<String>,
+}
+
+/// Reads the optional `chain_id` query parameter of the routes that do not
+/// carry the chain in their path, falling back to the default chain.
+pub fn extract_chain_id(query_string: &str, chains: &ChainRegistry) -> Result<String, ApiError> {
+    let params = parse_query::<ChainParameters>(query_string)?;
+    resolve_chain_id(chains, params.chain_id.as_deref())
+}
+
+#[derive(Deserialize)]
+pub struct ChartParameters {
+    interval: Option<String>,
+    from: Option<i64>,
+    to: Option<i64>,
+}
+
+/// Reads the `interval`, `from` and `to` query parameters of the routes
+/// returning a time series, `to` defaulting to now and `from` to the default
+/// range of the interval.
+pub fn extract_chart_range(query_string: &str) -> Result<(ChartInterval, i64, i64), ApiError> {
+    let params = parse_query::<ChartParameters>(query_string)?;
+    let interval = params
+        .interval
+        .as_deref()
+        .unwrap_or("")
+        .parse::<ChartInterval>()
+        .map_err(|e| ApiError::invalid_parameter("interval", e.to_string()))?;
+    let to = params.to.unwrap_or_else(|| Utc::now().timestamp());
+    let from = params.from.unwrap_or(to - interval.default_range_seconds());
+
+    if from >= to {
+        return Err(ApiError::invalid_parameter(
+            "from",
+            "'from' must be lower than 'to'",
+        ));
+    }
+    if (to - from) / interval.seconds() >= MAX_CHART_BUCKETS {
+        return Err(ApiError::invalid_parameter(
+            "from",
+            format!(
+                "Range too large, at most {} buckets can be requested",
+                MAX_CHART_BUCKETS
+            ),
+        ));
+    }
+
+    Ok((interval, from, to))
 }
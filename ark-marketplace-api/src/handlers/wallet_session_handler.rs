@@ -0,0 +1,171 @@
+use super::utils::{parse_address, resolve_chain_id};
+use crate::db::wallet_session_query::{consume_wallet_nonce, create_wallet_nonce};
+use crate::managers::chain_registry::ChainRegistry;
+use crate::managers::wallet_session_manager::WalletSessionManager;
+use crate::models::wallet_session::{
+    SessionToken, SignInChallenge, SignInChallengeRequest, SignInRequest, WalletNonce,
+};
+use crate::routes::auth::bearer_token;
+use crate::types::api_error::ApiError;
+use crate::utils::typed_data_utils::{parse_felt, SignInMessage};
+use actix_web::{get, post, web, HttpRequest, HttpResponse};
+use chrono::Utc;
+use serde_json::json;
+use sqlx::postgres::PgPool;
+use std::sync::Arc;
+
+/// Time given to the wallet to sign the nonce.
+const NONCE_TTL_SECONDS: i64 = 300;
+
+#[utoipa::path(
+    tag = "Auth",
+    request_body = SignInChallengeRequest,
+    responses(
+        (status = 200, description = "Nonce and SNIP-12 typed data for the wallet to sign", body = SignInChallengeResponse),
+        (status = 400, description = "Malformed request", body = ErrorResponse),
+        (status = 422, description = "Invalid address", body = ErrorResponse),
+    )
+)]
+#[post("/auth/nonce")]
+pub async fn create_nonce(
+    body: web::Json<SignInChallengeRequest>,
+    db_pools: web::Data<Arc<[PgPool; 2]>>,
+    chains: web::Data<ChainRegistry>,
+) -> Result<HttpResponse, ApiError> {
+    let request = body.into_inner();
+    let address = parse_address("address", &request.address)
+        .map_err(|e| ApiError::InvalidBody(e.to_string()))?;
+    let chain_id = resolve_chain_id(&chains, request.chain_id.as_deref())?;
+
+    let issued_at = Utc::now().timestamp();
+    let nonce = WalletNonce {
+        nonce: format!("0x{:032x}", rand::random::<u128>()),
+        user_address: address.clone(),
+        chain_id: chain_id.clone(),
+        issued_at,
+        expiration_time: issued_at + NONCE_TTL_SECONDS,
+    };
+    create_wallet_nonce(&db_pools[1], &nonce)
+        .await
+        .map_err(ApiError::database("create_wallet_nonce"))?;
+
+    let message = SignInMessage {
+        chain_id,
+        address,
+        nonce: nonce.nonce.clone(),
+        issued_at,
+        expiration_time: nonce.expiration_time,
+    };
+    Ok(HttpResponse::Ok().json(json!({
+        "data": SignInChallenge {
+            nonce: nonce.nonce,
+            expiration_time: nonce.expiration_time,
+            typed_data: message.typed_data(),
+        }
+    })))
+}
+
+#[utoipa::path(
+    tag = "Auth",
+    request_body = SignInRequest,
+    responses(
+        (status = 200, description = "Session of the wallet that signed the nonce", body = SessionTokenResponse),
+        (status = 400, description = "Malformed request", body = ErrorResponse),
+        (status = 422, description = "Invalid address or signature", body = ErrorResponse),
+        (status = 401, description = "Unknown or expired nonce, or signature rejected by the account", body = ErrorResponse),
+    )
+)]
+#[post("/auth/verify")]
+pub async fn verify_signature(
+    body: web::Json<SignInRequest>,
+    db_pools: web::Data<Arc<[PgPool; 2]>>,
+    sessions: web::Data<WalletSessionManager>,
+) -> Result<HttpResponse, ApiError> {
+    let request = body.into_inner();
+    let address = parse_address("address", &request.address)
+        .map_err(|e| ApiError::InvalidBody(e.to_string()))?;
+    if request.signature.is_empty() {
+        return Err(ApiError::InvalidBody("The signature is empty".to_string()));
+    }
+    let signature = request
+        .signature
+        .iter()
+        .map(|value| parse_felt(value))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(ApiError::InvalidBody)?;
+
+    let nonce = consume_wallet_nonce(
+        &db_pools[1],
+        &request.nonce,
+        &address,
+        Utc::now().timestamp(),
+    )
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::RowNotFound => ApiError::Unauthorized("Unknown or expired nonce".to_string()),
+        e => ApiError::internal("error query consume_wallet_nonce", e),
+    })?;
+
+    let message = SignInMessage {
+        chain_id: nonce.chain_id.clone(),
+        address: address.clone(),
+        nonce: nonce.nonce,
+        issued_at: nonce.issued_at,
+        expiration_time: nonce.expiration_time,
+    };
+    let hash = message.message_hash().map_err(ApiError::InvalidBody)?;
+
+    let is_valid = sessions
+        .is_valid_signature(&address, hash, &signature)
+        .await
+        .map_err(|e| ApiError::internal("error calling is_valid_signature", e))?;
+    if !is_valid {
+        return Err(ApiError::Unauthorized("Invalid signature".to_string()));
+    }
+
+    let (token, session) = sessions
+        .issue_session(&address, &nonce.chain_id)
+        .map_err(|e| ApiError::internal("error issuing wallet session", e))?;
+    Ok(HttpResponse::Ok().json(json!({
+        "data": SessionToken {
+            token,
+            address: session.sub,
+            chain_id: session.chain_id,
+            expiration_time: session.exp,
+        }
+    })))
+}
+
+#[utoipa::path(
+    tag = "Auth",
+    responses(
+        (status = 200, description = "Session of the bearer token", body = SessionTokenResponse),
+        (status = 401, description = "Missing or invalid session", body = ErrorResponse),
+    )
+)]
+#[get("/auth/session")]
+pub async fn get_session(
+    req: HttpRequest,
+    sessions: web::Data<WalletSessionManager>,
+) -> Result<HttpResponse, ApiError> {
+    let token = bearer_token(req.headers())
+        .ok_or_else(|| ApiError::Unauthorized("Missing wallet session".to_string()))?;
+    let session = sessions
+        .decode_session(token)
+        .map_err(ApiError::Unauthorized)?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "data": SessionToken {
+            token: token.to_string(),
+            address: session.sub,
+            chain_id: session.chain_id,
+            expiration_time: session.exp,
+        }
+    })))
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(create_nonce)
+        .service(verify_signature)
+        .service(get_session);
+}
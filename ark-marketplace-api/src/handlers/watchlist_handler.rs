@@ -0,0 +1,201 @@
+use super::utils::{
+    extract_chain_id, extract_cursor_params, parse_address, parse_query, parse_token_id,
+    resolve_chain_id,
+};
+use crate::db::watchlist_query::{
+    add_watchlist_item, count_watchlist_items, delete_watchlist_item, get_watchlist_feed,
+    get_watchlist_items,
+};
+use crate::managers::chain_registry::ChainRegistry;
+use crate::models::watchlist::{WatchlistEventType, WatchlistItemRequest};
+use crate::routes::auth::wallet_session_guard;
+use crate::types::api_error::ApiError;
+use crate::utils::sql_utils::watchlist_feed_keyset;
+use actix_web::middleware::from_fn;
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse};
+use serde::Deserialize;
+use serde_json::json;
+use sqlx::postgres::PgPool;
+use std::sync::Arc;
+
+/// Most collections and tokens a wallet can follow, which bounds the feed
+/// query.
+const MAX_WATCHLIST_ITEMS: i64 = 500;
+
+#[derive(Deserialize, Debug)]
+struct FeedQueryParameters {
+    types: Option<Vec<WatchlistEventType>>,
+}
+
+#[utoipa::path(
+    tag = "Watchlists",
+    responses(
+        (status = 200, description = "Collections and tokens followed by a wallet", body = WatchlistResponse),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid wallet session", body = ErrorResponse),
+        (status = 403, description = "Session of another wallet", body = ErrorResponse),
+    ),
+    params(
+        ("user_address" = String, Path, description = "Address of the user"),
+        ("chain_id" = Option<String>, Query, description = "The blockchain chain ID, defaults to the default chain"),
+    )
+)]
+#[get("/watchlists/{user_address}", wrap = "from_fn(wallet_session_guard)")]
+pub async fn get_watchlist(
+    req: HttpRequest,
+    path: web::Path<String>,
+    db_pools: web::Data<Arc<[PgPool; 2]>>,
+    chains: web::Data<ChainRegistry>,
+) -> Result<HttpResponse, ApiError> {
+    let user_address = parse_address("user_address", &path.into_inner())?;
+    let chain_id = extract_chain_id(req.query_string(), &chains)?;
+
+    let items = get_watchlist_items(&db_pools[0], &chain_id, &user_address)
+        .await
+        .map_err(ApiError::database("get_watchlist"))?;
+    Ok(HttpResponse::Ok().json(json!({ "data": items })))
+}
+
+#[utoipa::path(
+    tag = "Watchlists",
+    request_body = WatchlistItemRequest,
+    responses(
+        (status = 200, description = "Follow a collection or a token, returning the existing item when it is already followed", body = WatchlistItemResponse),
+        (status = 400, description = "Malformed item", body = ErrorResponse),
+        (status = 422, description = "Invalid item or watchlist full", body = ErrorResponse),
+        (status = 404, description = "Collection or token not found", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid wallet session", body = ErrorResponse),
+        (status = 403, description = "Session of another wallet", body = ErrorResponse),
+    ),
+    params(
+        ("user_address" = String, Path, description = "Address of the user"),
+    )
+)]
+#[post("/watchlists/{user_address}", wrap = "from_fn(wallet_session_guard)")]
+pub async fn add_to_watchlist(
+    path: web::Path<String>,
+    body: web::Json<WatchlistItemRequest>,
+    db_pools: web::Data<Arc<[PgPool; 2]>>,
+    chains: web::Data<ChainRegistry>,
+) -> Result<HttpResponse, ApiError> {
+    let user_address = parse_address("user_address", &path.into_inner())?;
+    let item = body.into_inner();
+    let chain_id = resolve_chain_id(&chains, item.chain_id.as_deref())?;
+    let contract_address = parse_address("contract_address", &item.contract_address)
+        .map_err(|e| ApiError::InvalidBody(e.to_string()))?;
+    let token_id = item
+        .token_id
+        .as_deref()
+        .map(parse_token_id)
+        .transpose()
+        .map_err(|e| ApiError::InvalidBody(e.to_string()))?;
+
+    let count = count_watchlist_items(&db_pools[1], &user_address)
+        .await
+        .map_err(ApiError::database("count_watchlist_items"))?;
+    if count >= MAX_WATCHLIST_ITEMS {
+        return Err(ApiError::InvalidBody(format!(
+            "Watchlist full, at most {} items can be followed",
+            MAX_WATCHLIST_ITEMS
+        )));
+    }
+
+    let item = add_watchlist_item(
+        &db_pools[1],
+        &chain_id,
+        &user_address,
+        &contract_address,
+        token_id.as_deref(),
+    )
+    .await
+    .map_err(ApiError::database("add_watchlist_item"))?;
+    Ok(HttpResponse::Ok().json(json!({ "data": item })))
+}
+
+#[utoipa::path(
+    tag = "Watchlists",
+    responses(
+        (status = 204, description = "Item no longer followed"),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 404, description = "Data not found", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid wallet session", body = ErrorResponse),
+        (status = 403, description = "Session of another wallet", body = ErrorResponse),
+    ),
+    params(
+        ("user_address" = String, Path, description = "Address of the user"),
+        ("id" = i32, Path, description = "The watchlist item id"),
+    )
+)]
+#[delete(
+    "/watchlists/{user_address}/{id}",
+    wrap = "from_fn(wallet_session_guard)"
+)]
+pub async fn remove_from_watchlist(
+    path: web::Path<(String, i32)>,
+    db_pools: web::Data<Arc<[PgPool; 2]>>,
+) -> Result<HttpResponse, ApiError> {
+    let (user_address, id) = path.into_inner();
+    let user_address = parse_address("user_address", &user_address)?;
+
+    delete_watchlist_item(&db_pools[1], &user_address, id)
+        .await
+        .map_err(ApiError::database("delete_watchlist_item"))?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[utoipa::path(
+    tag = "Watchlists",
+    responses(
+        (status = 200, description = "Listings, price drops, sales and offers of the followed items over the last 30 days", body = WatchlistFeedResponse),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid wallet session", body = ErrorResponse),
+        (status = 403, description = "Session of another wallet", body = ErrorResponse),
+    ),
+    params(
+        ("user_address" = String, Path, description = "Address of the user"),
+        ("cursor" = Option<String>, Query, description = "Cursor of the page, as returned in `next_cursor`"),
+        ("items_per_page" = Option<i32>, Query, description = "Number of items per page, defaults to 100"),
+        ("types" = Option<Vec<WatchlistEventType>>, Query, description = "Only return events of these types"),
+        ("chain_id" = Option<String>, Query, description = "The blockchain chain ID, defaults to the default chain"),
+    )
+)]
+#[get(
+    "/watchlists/{user_address}/feed",
+    wrap = "from_fn(wallet_session_guard)"
+)]
+pub async fn get_feed(
+    req: HttpRequest,
+    path: web::Path<String>,
+    db_pools: web::Data<Arc<[PgPool; 2]>>,
+    chains: web::Data<ChainRegistry>,
+) -> Result<HttpResponse, ApiError> {
+    let user_address = parse_address("user_address", &path.into_inner())?;
+    let chain_id = extract_chain_id(req.query_string(), &chains)?;
+
+    let params = parse_query::<FeedQueryParameters>(req.query_string())?;
+    let (cursor, items_per_page) =
+        extract_cursor_params(req.query_string(), &watchlist_feed_keyset(), 100)?;
+
+    let (feed, next_cursor) = get_watchlist_feed(
+        &db_pools[0],
+        &chain_id,
+        &user_address,
+        cursor.as_ref(),
+        items_per_page,
+        &params.types,
+    )
+    .await
+    .map_err(ApiError::database("get_watchlist_feed"))?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "data": feed,
+        "next_cursor": next_cursor,
+    })))
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_watchlist)
+        .service(add_to_watchlist)
+        .service(remove_from_watchlist)
+        .service(get_feed);
+}
@@ -0,0 +1,210 @@
+use super::utils::{extract_page_params, parse_address};
+use crate::db::webhook_query::{
+    create_webhook_subscription, delete_webhook_subscription, get_webhook_dead_letters,
+    get_webhook_subscription, get_webhook_subscriptions, update_webhook_subscription,
+};
+use crate::models::webhook::WebhookSubscriptionRequest;
+use crate::types::api_error::ApiError;
+use actix_web::{web, HttpRequest, HttpResponse};
+use ark_sqlx::providers::marketplace::types::WebhookEventType;
+use serde::Deserialize;
+use serde_json::json;
+use sqlx::PgPool;
+use std::sync::Arc;
+
+#[derive(Deserialize)]
+pub struct WebhooksQueryParameters {
+    contract_address: Option<String>,
+}
+
+/// Normalizes the subscription and rejects unknown event types or urls that
+/// can't be delivered to.
+fn validate_subscription(
+    mut subscription: WebhookSubscriptionRequest,
+) -> Result<WebhookSubscriptionRequest, ApiError> {
+    if !(subscription.url.starts_with("https://") || subscription.url.starts_with("http://")) {
+        return Err(ApiError::InvalidBody(format!(
+            "Invalid webhook url: {}",
+            subscription.url
+        )));
+    }
+
+    subscription.contract_address =
+        parse_address("contract_address", &subscription.contract_address)
+            .map_err(|e| ApiError::InvalidBody(e.to_string()))?;
+
+    if let Some(event_types) = &subscription.event_types {
+        for event_type in event_types {
+            event_type
+                .parse::<WebhookEventType>()
+                .map_err(ApiError::InvalidBody)?;
+        }
+    }
+
+    Ok(subscription)
+}
+
+fn handle_webhook_error(err: sqlx::Error) -> ApiError {
+    ApiError::database("webhook subscription")(err)
+}
+
+#[utoipa::path(
+    post,
+    path = "/webhooks",
+    tag = "Webhooks",
+    request_body = WebhookSubscriptionRequest,
+    responses(
+        (status = 200, description = "Create a webhook subscription", body = WebhookResponse),
+        (status = 400, description = "Malformed subscription", body = ErrorResponse),
+        (status = 422, description = "Invalid subscription", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+    )
+)]
+pub async fn create_webhook(
+    body: web::Json<WebhookSubscriptionRequest>,
+    db_pools: web::Data<Arc<[PgPool; 2]>>,
+) -> Result<HttpResponse, ApiError> {
+    let subscription = validate_subscription(body.into_inner())?;
+
+    let webhook = create_webhook_subscription(&db_pools[1], &subscription)
+        .await
+        .map_err(handle_webhook_error)?;
+    Ok(HttpResponse::Ok().json(json!({ "data": webhook })))
+}
+
+#[utoipa::path(
+    get,
+    path = "/webhooks",
+    tag = "Webhooks",
+    responses(
+        (status = 200, description = "List webhook subscriptions", body = WebhooksResponse),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+    ),
+    params(
+        ("contract_address" = Option<String>, Query, description = "Only list the subscriptions of this collection"),
+    )
+)]
+pub async fn get_webhooks(
+    query_parameters: web::Query<WebhooksQueryParameters>,
+    db_pools: web::Data<Arc<[PgPool; 2]>>,
+) -> Result<HttpResponse, ApiError> {
+    let contract_address = query_parameters
+        .contract_address
+        .as_deref()
+        .map(|contract_address| parse_address("contract_address", contract_address))
+        .transpose()?;
+
+    let webhooks = get_webhook_subscriptions(&db_pools[1], contract_address.as_deref())
+        .await
+        .map_err(handle_webhook_error)?;
+    Ok(HttpResponse::Ok().json(json!({ "data": webhooks })))
+}
+
+#[utoipa::path(
+    get,
+    path = "/webhooks/{id}",
+    tag = "Webhooks",
+    responses(
+        (status = 200, description = "Get a webhook subscription", body = WebhookResponse),
+        (status = 404, description = "Data not found", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+    ),
+    params(
+        ("id" = i32, Path, description = "The webhook subscription id"),
+    )
+)]
+pub async fn get_webhook(
+    path: web::Path<i32>,
+    db_pools: web::Data<Arc<[PgPool; 2]>>,
+) -> Result<HttpResponse, ApiError> {
+    let webhook = get_webhook_subscription(&db_pools[1], path.into_inner())
+        .await
+        .map_err(handle_webhook_error)?;
+    Ok(HttpResponse::Ok().json(json!({ "data": webhook })))
+}
+
+#[utoipa::path(
+    put,
+    path = "/webhooks/{id}",
+    tag = "Webhooks",
+    request_body = WebhookSubscriptionRequest,
+    responses(
+        (status = 200, description = "Update a webhook subscription", body = WebhookResponse),
+        (status = 400, description = "Malformed subscription", body = ErrorResponse),
+        (status = 422, description = "Invalid subscription", body = ErrorResponse),
+        (status = 404, description = "Data not found", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+    ),
+    params(
+        ("id" = i32, Path, description = "The webhook subscription id"),
+    )
+)]
+pub async fn update_webhook(
+    path: web::Path<i32>,
+    body: web::Json<WebhookSubscriptionRequest>,
+    db_pools: web::Data<Arc<[PgPool; 2]>>,
+) -> Result<HttpResponse, ApiError> {
+    let subscription = validate_subscription(body.into_inner())?;
+
+    let webhook = update_webhook_subscription(&db_pools[1], path.into_inner(), &subscription)
+        .await
+        .map_err(handle_webhook_error)?;
+    Ok(HttpResponse::Ok().json(json!({ "data": webhook })))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/webhooks/{id}",
+    tag = "Webhooks",
+    responses(
+        (status = 204, description = "Webhook subscription deleted"),
+        (status = 404, description = "Data not found", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+    ),
+    params(
+        ("id" = i32, Path, description = "The webhook subscription id"),
+    )
+)]
+pub async fn delete_webhook(
+    path: web::Path<i32>,
+    db_pools: web::Data<Arc<[PgPool; 2]>>,
+) -> Result<HttpResponse, ApiError> {
+    delete_webhook_subscription(&db_pools[1], path.into_inner())
+        .await
+        .map_err(handle_webhook_error)?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[utoipa::path(
+    get,
+    path = "/webhooks/{id}/dead-letters",
+    tag = "Webhooks",
+    responses(
+        (status = 200, description = "Deliveries that failed after every retry", body = WebhookDeadLettersResponse),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+    ),
+    params(
+        ("id" = i32, Path, description = "The webhook subscription id"),
+        ("page" = Option<i32>, Query, description = "Page number for pagination, defaults to 1"),
+        ("items_per_page" = Option<i32>, Query, description = "Number of items per page, defaults to 100"),
+    )
+)]
+pub async fn get_webhook_dead_letter_log(
+    req: HttpRequest,
+    path: web::Path<i32>,
+    db_pools: web::Data<Arc<[PgPool; 2]>>,
+) -> Result<HttpResponse, ApiError> {
+    let (page, items_per_page) = extract_page_params(req.query_string(), 1, 100)?;
+
+    let (dead_letters, has_next_page, count) =
+        get_webhook_dead_letters(&db_pools[1], path.into_inner(), page, items_per_page)
+            .await
+            .map_err(handle_webhook_error)?;
+    Ok(HttpResponse::Ok().json(json!({
+        "data": dead_letters,
+        "count": count,
+        "next_page": if has_next_page { Some(page + 1) } else { None }
+    })))
+}
@@ -1,4 +1,5 @@
 pub mod db;
+pub mod graphql;
 pub mod handlers;
 pub mod managers;
 pub mod models;
@@ -1,10 +1,12 @@
 mod api_doc;
 
 use actix_cors::Cors;
-use actix_web::middleware::DefaultHeaders;
+use actix_web::middleware::{from_fn, DefaultHeaders};
 use actix_web::{web, App, HttpServer};
 use anyhow::Result;
-use ark_marketplace_api::routes::token;
+use ark_marketplace_api::routes::auth::{api_key_guard, AdminCredentials};
+use ark_marketplace_api::routes::{api_key, token, webhook};
+use ark_sqlx::providers::SqlxCtx;
 use aws_config::BehaviorVersion;
 use redis::{aio::MultiplexedConnection, Client};
 use serde::Deserialize;
@@ -16,9 +18,17 @@ use tokio::sync::Mutex;
 use tracing_subscriber::fmt;
 use tracing_subscriber::EnvFilter;
 
+use ark_marketplace_api::graphql::build_schema;
 use ark_marketplace_api::handlers::{
-    collection_handler, default_handler, portfolio_handler, token_handler,
+    chain_handler, collection_handler, default_handler, event_handler, export_handler,
+    graphql_handler, portfolio_handler, token_handler, wallet_session_handler, watchlist_handler,
 };
+use ark_marketplace_api::managers::api_key_manager::{parse_trusted_proxy, ApiKeyManager};
+use ark_marketplace_api::managers::chain_registry::ChainRegistry;
+use ark_marketplace_api::managers::event_stream_manager::EventStreamManager;
+use ark_marketplace_api::managers::price_manager::PriceManager;
+use ark_marketplace_api::managers::wallet_session_manager::WalletSessionManager;
+use ark_marketplace_api::types::api_error::{json_config, path_config, query_config};
 
 /// Initializes the logging, ensuring that the `RUST_LOG` environment
 /// variable is always considered first.
@@ -120,6 +130,10 @@ async fn main() -> std::io::Result<()> {
         .await
         .expect("Could not connect to the write database");
 
+    let chains = ChainRegistry::load(&db_pool)
+        .await
+        .expect("Could not load the supported chains");
+
     let redis_conn = match connect_redis().await {
         Ok(con) => con,
         Err(e) => {
@@ -131,6 +145,49 @@ async fn main() -> std::io::Result<()> {
         }
     };
 
+    let usage_ctx = SqlxCtx::new(&write_database_url)
+        .await
+        .expect("Could not connect to the usage database");
+    let is_api_key_required = std::env::var("API_KEY_REQUIRED").as_deref() == Ok("true");
+    let stage_name = std::env::var("STAGE_NAME").unwrap_or_else(|_| "local".to_string());
+    // Comma separated IPs or CIDR ranges of the load balancers allowed to set
+    // X-Forwarded-For, the subnets of an ALB since its node IPs change
+    let trusted_proxies: Vec<ipnet::IpNet> = std::env::var("TRUSTED_PROXIES")
+        .map(|proxies| {
+            proxies
+                .split(',')
+                .map(str::trim)
+                .filter(|proxy| !proxy.is_empty())
+                .map(|proxy| {
+                    parse_trusted_proxy(proxy).expect("Invalid IP or CIDR range in TRUSTED_PROXIES")
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let api_key_manager = web::Data::new(
+        ApiKeyManager::new(
+            db_pool.clone(),
+            usage_ctx,
+            redis_conn.clone(),
+            is_api_key_required,
+            stage_name,
+            trusted_proxies,
+        )
+        .await
+        .expect("Could not load the API key tiers"),
+    );
+
+    // Comma separated, any origin is allowed when missing
+    let cors_allowed_origins: Vec<String> = std::env::var("CORS_ALLOWED_ORIGINS")
+        .map(|origins| {
+            origins
+                .split(',')
+                .map(|origin| origin.trim().to_string())
+                .filter(|origin| !origin.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+
     let elasticsearch_url =
         std::env::var("ELASTICSEARCH_URL").expect("ELASTICSEARCH_URL must be set");
     let elasticsearch_username =
@@ -142,27 +199,90 @@ async fn main() -> std::io::Result<()> {
     es_config.insert("username".to_string(), elasticsearch_username);
     es_config.insert("password".to_string(), elasticsearch_password);
 
+    let price_engine_url =
+        std::env::var("PRICE_ENGINE_URL").unwrap_or_else(|_| "http://localhost:3030".to_string());
+    let price_manager = PriceManager::new(price_engine_url);
+
+    // Wallet sign-in needs an RPC provider to check the account signatures,
+    // without one the private user routes reject every request.
+    let wallet_session_manager = match std::env::var("RPC_PROVIDER") {
+        Ok(rpc_provider) => {
+            let jwt_secret = std::env::var("JWT_SECRET")
+                .expect("JWT_SECRET must be set when RPC_PROVIDER is set");
+            let session_ttl = std::env::var("WALLET_SESSION_TTL_SECONDS")
+                .ok()
+                .and_then(|ttl| ttl.parse::<i64>().ok())
+                .unwrap_or(86400);
+            Some(web::Data::new(WalletSessionManager::new(
+                rpc_provider,
+                jwt_secret.as_bytes(),
+                session_ttl,
+            )))
+        }
+        Err(_) => {
+            tracing::warn!("RPC_PROVIDER not set, wallet sign-in is disabled");
+            None
+        }
+    };
+
+    // The admin routes (API keys, webhooks, data flush) must never run with
+    // empty credentials
+    let admin_credentials = web::Data::new(
+        AdminCredentials::from_env().expect("The admin basic auth credentials are missing"),
+    );
+
+    let event_stream = EventStreamManager::new();
+    event_stream.start(write_db_pool.clone());
+
     let db_pools = Arc::new([db_pool.clone(), write_db_pool.clone()]);
+    let graphql_schema = build_schema(db_pool.clone(), chains.clone());
 
     HttpServer::new(move || {
-        let cors = Cors::default()
-            // Maybe we need to add some origin for security reason.
-            .allow_any_origin()
+        let mut cors = Cors::default()
             .allow_any_method()
             .allow_any_header()
             .max_age(3600);
+        if cors_allowed_origins.is_empty() {
+            cors = cors.allow_any_origin();
+        }
+        for origin in &cors_allowed_origins {
+            cors = cors.allowed_origin(origin);
+        }
 
         App::new()
+            .wrap(from_fn(api_key_guard))
             .wrap(cors)
             .wrap(DefaultHeaders::new().add(("X-GIT-REVISION", env!("GIT_HASH", "N/A"))))
             .app_data(web::Data::new(db_pools.clone()))
             .app_data(web::Data::new(redis_conn.clone()))
             .app_data(web::Data::new(es_config.clone()))
+            .app_data(web::Data::new(event_stream.clone()))
+            .app_data(web::Data::new(chains.clone()))
+            .app_data(web::Data::new(price_manager.clone()))
+            .app_data(web::Data::new(graphql_schema.clone()))
+            .app_data(api_key_manager.clone())
+            .app_data(admin_credentials.clone())
+            .app_data(query_config())
+            .app_data(path_config())
+            .app_data(json_config())
             .configure(token::config)
+            .configure(webhook::config)
+            .configure(api_key::config)
             .configure(default_handler::configure)
+            .configure(chain_handler::configure)
             .configure(collection_handler::configure)
             .configure(token_handler::configure)
             .configure(portfolio_handler::configure)
+            .configure(watchlist_handler::configure)
+            .configure(|cfg| {
+                if let Some(wallet_session_manager) = &wallet_session_manager {
+                    cfg.app_data(wallet_session_manager.clone());
+                    wallet_session_handler::configure(cfg);
+                }
+            })
+            .configure(export_handler::configure)
+            .configure(event_handler::configure)
+            .configure(graphql_handler::configure)
             .service(web::scope("/v1").service(default_handler::health_check_v1))
             .service(api_doc::configure())
     })
@@ -0,0 +1,132 @@
+use crate::db::api_key_query::{get_api_key_quota, get_tier_quota};
+use crate::managers::rate_limiter::{RateLimitDecision, RateLimiter};
+use crate::models::api_key::ApiQuota;
+use ark_sqlx::providers::metrics::{LambdaUsageData, LambdaUsageProvider};
+use ark_sqlx::providers::SqlxCtx;
+use ipnet::IpNet;
+use redis::aio::MultiplexedConnection;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Parses an entry of `TRUSTED_PROXIES`, either a CIDR range or a single IP.
+pub fn parse_trusted_proxy(proxy: &str) -> Option<IpNet> {
+    proxy
+        .parse::<IpNet>()
+        .or_else(|_| proxy.parse::<IpAddr>().map(IpNet::from))
+        .ok()
+}
+
+pub fn is_in_ranges(ranges: &[IpNet], ip: &IpAddr) -> bool {
+    ranges.iter().any(|range| range.contains(ip))
+}
+
+pub const ANONYMOUS_TIER: &str = "anonymous";
+const USAGE_TABLE_NAME: &str = "api_usage";
+/// A revoked key keeps working at most this long on each API instance.
+const QUOTA_CACHE_TTL: Duration = Duration::from_secs(60);
+const QUOTA_CACHE_MAX_ENTRIES: usize = 10_000;
+
+/// Resolves the quota of each request and enforces it.
+pub struct ApiKeyManager {
+    db_pool: PgPool,
+    usage_ctx: Arc<SqlxCtx>,
+    rate_limiter: RateLimiter,
+    anonymous_quota: ApiQuota,
+    is_key_required: bool,
+    stage_name: String,
+    trusted_proxies: Vec<IpNet>,
+    quota_cache: RwLock<HashMap<String, (Option<ApiQuota>, Instant)>>,
+}
+
+impl ApiKeyManager {
+    pub async fn new(
+        db_pool: PgPool,
+        usage_ctx: SqlxCtx,
+        redis: Arc<Mutex<MultiplexedConnection>>,
+        is_key_required: bool,
+        stage_name: String,
+        trusted_proxies: Vec<IpNet>,
+    ) -> Result<Self, sqlx::Error> {
+        let anonymous_quota = get_tier_quota(&db_pool, ANONYMOUS_TIER).await?;
+
+        Ok(Self {
+            db_pool,
+            usage_ctx: Arc::new(usage_ctx),
+            rate_limiter: RateLimiter::new(redis),
+            anonymous_quota,
+            is_key_required,
+            stage_name,
+            trusted_proxies,
+            quota_cache: RwLock::new(HashMap::new()),
+        })
+    }
+
+    pub fn is_key_required(&self) -> bool {
+        self.is_key_required
+    }
+
+    pub fn anonymous_quota(&self) -> &ApiQuota {
+        &self.anonymous_quota
+    }
+
+    /// Only the proxies in the ranges of `TRUSTED_PROXIES` may set the client
+    /// IP with `X-Forwarded-For`, other clients could rotate it freely.
+    pub fn is_trusted_proxy(&self, ip: &IpAddr) -> bool {
+        is_in_ranges(&self.trusted_proxies, ip)
+    }
+
+    /// Cached quota of a key, the inner `None` being an unknown or revoked
+    /// key. Returns `None` when the key has to be looked up.
+    pub fn cached_quota(&self, api_key: &str) -> Option<Option<ApiQuota>> {
+        self.quota_cache
+            .read()
+            .expect("quota cache lock poisoned")
+            .get(api_key)
+            .filter(|(_, cached_at)| cached_at.elapsed() < QUOTA_CACHE_TTL)
+            .map(|(quota, _)| quota.clone())
+    }
+
+    /// Quota of an active key, `None` for unknown or revoked keys. Both are
+    /// cached.
+    pub async fn quota_for_key(&self, api_key: &str) -> Result<Option<ApiQuota>, sqlx::Error> {
+        if let Some(quota) = self.cached_quota(api_key) {
+            return Ok(quota);
+        }
+
+        let quota = get_api_key_quota(&self.db_pool, api_key).await?;
+
+        let mut cache = self.quota_cache.write().expect("quota cache lock poisoned");
+        if cache.len() >= QUOTA_CACHE_MAX_ENTRIES {
+            cache.clear();
+        }
+        cache.insert(api_key.to_string(), (quota.clone(), Instant::now()));
+
+        Ok(quota)
+    }
+
+    pub async fn check_rate_limit(
+        &self,
+        subject: &str,
+        quota: &ApiQuota,
+    ) -> redis::RedisResult<RateLimitDecision> {
+        self.rate_limiter.check(subject, quota).await
+    }
+
+    /// Records the request in the usage table without delaying the response.
+    pub fn register_usage(&self, mut data: LambdaUsageData) {
+        data.stage_name = self.stage_name.clone();
+        let usage_ctx = self.usage_ctx.clone();
+
+        tokio::spawn(async move {
+            if let Err(e) =
+                LambdaUsageProvider::register_usage(&usage_ctx, USAGE_TABLE_NAME, &data).await
+            {
+                tracing::error!("Failed to register usage of {}: {}", data.request_id, e);
+            }
+        });
+    }
+}
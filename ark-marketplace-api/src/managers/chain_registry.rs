@@ -0,0 +1,63 @@
+use crate::db::chain_db_access;
+use crate::db::chain_query::get_enabled_chains;
+use crate::models::chain::Chain;
+
+/// Chains the API accepts, loaded from the `chain` table at startup.
+#[derive(Debug, Clone)]
+pub struct ChainRegistry {
+    chains: Vec<Chain>,
+}
+
+impl ChainRegistry {
+    /// Builds a registry from `chains`. The first chain is the default one
+    /// when none of them is flagged as such.
+    pub fn new(chains: Vec<Chain>) -> Option<Self> {
+        if chains.is_empty() {
+            return None;
+        }
+
+        Some(Self { chains })
+    }
+
+    pub async fn load<D: chain_db_access::DatabaseAccess + Sync>(
+        db_access: &D,
+    ) -> Result<Self, sqlx::Error> {
+        let chains = get_enabled_chains(db_access).await?;
+        Self::new(chains).ok_or(sqlx::Error::RowNotFound)
+    }
+
+    pub fn chains(&self) -> &[Chain] {
+        &self.chains
+    }
+
+    pub fn default_chain_id(&self) -> &str {
+        self.chains
+            .iter()
+            .find(|chain| chain.is_default)
+            .unwrap_or(&self.chains[0])
+            .chain_id
+            .as_str()
+    }
+
+    pub fn is_supported(&self, chain_id: &str) -> bool {
+        self.find(chain_id).is_some()
+    }
+
+    /// Returns the canonical id of `chain_id`, or the default chain id when
+    /// no chain is given.
+    pub fn resolve(&self, chain_id: Option<&str>) -> Result<String, String> {
+        match chain_id {
+            None => Ok(self.default_chain_id().to_string()),
+            Some(chain_id) => self
+                .find(chain_id)
+                .map(|chain| chain.chain_id.clone())
+                .ok_or_else(|| format!("Unsupported chain id: {}", chain_id)),
+        }
+    }
+
+    fn find(&self, chain_id: &str) -> Option<&Chain> {
+        self.chains
+            .iter()
+            .find(|chain| chain.chain_id.eq_ignore_ascii_case(chain_id))
+    }
+}
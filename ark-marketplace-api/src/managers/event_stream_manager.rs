@@ -0,0 +1,70 @@
+use crate::models::event::{MarketplaceEvent, TokenEventNotification};
+use sqlx::postgres::PgListener;
+use sqlx::PgPool;
+use tokio::sync::broadcast;
+
+const TOKEN_EVENT_CHANNEL: &str = "token_event";
+const EVENT_BUFFER_SIZE: usize = 1024;
+const RECONNECT_DELAY_SECONDS: u64 = 5;
+
+/// Relays the `token_event` notifications emitted by Postgres to every
+/// connected stream client.
+#[derive(Clone)]
+pub struct EventStreamManager {
+    sender: broadcast::Sender<MarketplaceEvent>,
+}
+
+impl Default for EventStreamManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventStreamManager {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_BUFFER_SIZE);
+        Self { sender }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<MarketplaceEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Spawns the listener task. Notifications are not forwarded to read
+    /// replicas, so `pool` must point to the primary database.
+    pub fn start(&self, pool: PgPool) {
+        let sender = self.sender.clone();
+        tokio::spawn(async move {
+            loop {
+                if let Err(err) = Self::listen(&pool, &sender).await {
+                    tracing::error!("token_event listener stopped: {}", err);
+                }
+                tokio::time::sleep(tokio::time::Duration::from_secs(RECONNECT_DELAY_SECONDS)).await;
+            }
+        });
+    }
+
+    async fn listen(
+        pool: &PgPool,
+        sender: &broadcast::Sender<MarketplaceEvent>,
+    ) -> Result<(), sqlx::Error> {
+        let mut listener = PgListener::connect_with(pool).await?;
+        listener.listen(TOKEN_EVENT_CHANNEL).await?;
+        tracing::info!("Listening to {} notifications", TOKEN_EVENT_CHANNEL);
+
+        loop {
+            let notification = listener.recv().await?;
+            let event = serde_json::from_str::<TokenEventNotification>(notification.payload())
+                .map_err(|e| e.to_string())
+                .and_then(MarketplaceEvent::try_from);
+
+            match event {
+                // An error only means that nobody is currently subscribed
+                Ok(event) => {
+                    let _ = sender.send(event);
+                }
+                Err(err) => tracing::warn!("Invalid token_event notification: {}", err),
+            }
+        }
+    }
+}
@@ -1 +1,7 @@
+pub mod api_key_manager;
+pub mod chain_registry;
 pub mod elasticsearch_manager;
+pub mod event_stream_manager;
+pub mod price_manager;
+pub mod rate_limiter;
+pub mod wallet_session_manager;
@@ -0,0 +1,51 @@
+use reqwest::Client as ReqwestClient;
+use serde::Deserialize;
+use std::time::Duration;
+
+/// USD prices published by `ark-price-engine`.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct UsdPrices {
+    pub eth_usd: f64,
+    pub strk_usd: f64,
+}
+
+#[derive(Clone)]
+pub struct PriceManager {
+    client: ReqwestClient,
+    price_engine_url: String,
+}
+
+impl PriceManager {
+    pub fn new(price_engine_url: String) -> Self {
+        // A slow price engine must not hold the routes that only use it to
+        // add USD values.
+        let client = ReqwestClient::builder()
+            .timeout(Duration::from_secs(2))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            client,
+            price_engine_url,
+        }
+    }
+
+    /// Latest prices, `None` when the price engine can't be reached or has
+    /// not received a price yet.
+    pub async fn get_usd_prices(&self) -> Option<UsdPrices> {
+        let response = self.client.get(&self.price_engine_url).send().await;
+        let prices = match response {
+            Ok(response) => response.json::<UsdPrices>().await,
+            Err(e) => Err(e),
+        };
+
+        match prices {
+            Ok(prices) if prices.eth_usd > 0.0 => Some(prices),
+            Ok(_) => None,
+            Err(e) => {
+                tracing::warn!("Failed to get prices from the price engine: {}", e);
+                None
+            }
+        }
+    }
+}
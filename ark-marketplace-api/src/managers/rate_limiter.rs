@@ -0,0 +1,107 @@
+use crate::models::api_key::ApiQuota;
+use redis::aio::MultiplexedConnection;
+use redis::Script;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+
+/// Token buckets refilled continuously over one minute and one day. A request
+/// takes a token from both buckets, or from none when either one is empty.
+const TOKEN_BUCKET_SCRIPT: &str = r#"
+local now = tonumber(ARGV[1])
+local windows = { 60000, 86400000 }
+local tokens = {}
+local allowed = 1
+local retry_after = 0
+
+for i = 1, 2 do
+    local capacity = tonumber(ARGV[i + 1])
+    local rate = capacity / windows[i]
+    local bucket = redis.call('HMGET', KEYS[i], 'tokens', 'updated_at')
+    local available = tonumber(bucket[1]) or capacity
+    local updated_at = tonumber(bucket[2]) or now
+    available = math.min(capacity, available + math.max(0, now - updated_at) * rate)
+    if available < 1 then
+        allowed = 0
+        retry_after = math.max(retry_after, math.ceil((1 - available) / rate))
+    end
+    tokens[i] = available
+end
+
+for i = 1, 2 do
+    if allowed == 1 then
+        tokens[i] = tokens[i] - 1
+    end
+    redis.call('HSET', KEYS[i], 'tokens', tokens[i], 'updated_at', now)
+    redis.call('PEXPIRE', KEYS[i], windows[i])
+end
+
+return { allowed, math.floor(tokens[1]), math.floor(tokens[2]), retry_after }
+"#;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    pub limit_per_minute: i32,
+    pub remaining_per_minute: i64,
+    pub remaining_per_day: i64,
+    /// Milliseconds until a token is available again, 0 when allowed
+    pub retry_after_ms: i64,
+}
+
+impl RateLimitDecision {
+    pub fn retry_after_seconds(&self) -> i64 {
+        (self.retry_after_ms + 999) / 1000
+    }
+}
+
+pub struct RateLimiter {
+    redis: Arc<Mutex<MultiplexedConnection>>,
+    script: Script,
+}
+
+impl RateLimiter {
+    pub fn new(redis: Arc<Mutex<MultiplexedConnection>>) -> Self {
+        Self {
+            redis,
+            script: Script::new(TOKEN_BUCKET_SCRIPT),
+        }
+    }
+
+    /// Takes one request from the buckets of `subject`, an API key id or a
+    /// client IP.
+    pub async fn check(
+        &self,
+        subject: &str,
+        quota: &ApiQuota,
+    ) -> redis::RedisResult<RateLimitDecision> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Error getting time")
+            .as_millis() as i64;
+
+        let mut conn = self.redis.lock().await.clone();
+        let (allowed, remaining_per_minute, remaining_per_day, retry_after_ms): (
+            i64,
+            i64,
+            i64,
+            i64,
+        ) = self
+            .script
+            .key(format!("rate_limit:{}:minute", subject))
+            .key(format!("rate_limit:{}:day", subject))
+            .arg(now)
+            .arg(quota.requests_per_minute)
+            .arg(quota.requests_per_day)
+            .invoke_async(&mut conn)
+            .await?;
+
+        Ok(RateLimitDecision {
+            allowed: allowed == 1,
+            limit_per_minute: quota.requests_per_minute,
+            remaining_per_minute,
+            remaining_per_day,
+            retry_after_ms,
+        })
+    }
+}
@@ -0,0 +1,118 @@
+use crate::models::wallet_session::WalletSession;
+use crate::utils::typed_data_utils::starknet_keccak;
+use chrono::Utc;
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use reqwest::Client as ReqwestClient;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use starknet_types_core::felt::Felt;
+use std::time::Duration;
+
+/// Value returned by `is_valid_signature` for a valid signature, the short
+/// string 'VALID'. Older accounts return 1 instead.
+const VALID: Felt = Felt::from_hex_unchecked("0x56414c4944");
+
+#[derive(Deserialize)]
+struct RpcResponse {
+    result: Option<Vec<String>>,
+    error: Option<Value>,
+}
+
+/// Verifies wallet signatures and issues the JWT sessions of signed in
+/// wallets.
+pub struct WalletSessionManager {
+    client: ReqwestClient,
+    rpc_provider: String,
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+    session_ttl: i64,
+}
+
+impl WalletSessionManager {
+    pub fn new(rpc_provider: String, jwt_secret: &[u8], session_ttl: i64) -> Self {
+        let client = ReqwestClient::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            client,
+            rpc_provider,
+            encoding_key: EncodingKey::from_secret(jwt_secret),
+            decoding_key: DecodingKey::from_secret(jwt_secret),
+            session_ttl,
+        }
+    }
+
+    pub fn issue_session(
+        &self,
+        address: &str,
+        chain_id: &str,
+    ) -> Result<(String, WalletSession), jsonwebtoken::errors::Error> {
+        let now = Utc::now().timestamp();
+        let session = WalletSession {
+            sub: address.to_string(),
+            chain_id: chain_id.to_string(),
+            iat: now,
+            exp: now + self.session_ttl,
+        };
+        let token = encode(&Header::default(), &session, &self.encoding_key)?;
+        Ok((token, session))
+    }
+
+    pub fn decode_session(&self, token: &str) -> Result<WalletSession, String> {
+        decode::<WalletSession>(token, &self.decoding_key, &Validation::default())
+            .map(|data| data.claims)
+            .map_err(|e| format!("Invalid session: {}", e))
+    }
+
+    /// Asks the account contract whether `signature` is a valid signature of
+    /// `hash`. A call rejected by the contract, such as an account that isn't
+    /// deployed, counts as an invalid signature.
+    pub async fn is_valid_signature(
+        &self,
+        address: &str,
+        hash: Felt,
+        signature: &[Felt],
+    ) -> Result<bool, reqwest::Error> {
+        let mut calldata = vec![
+            hash.to_hex_string(),
+            Felt::from(signature.len() as u64).to_hex_string(),
+        ];
+        calldata.extend(signature.iter().map(Felt::to_hex_string));
+
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "starknet_call",
+            "params": {
+                "request": {
+                    "contract_address": address,
+                    "entry_point_selector": starknet_keccak(b"is_valid_signature").to_hex_string(),
+                    "calldata": calldata,
+                },
+                "block_id": "latest",
+            },
+        });
+
+        let response = self
+            .client
+            .post(&self.rpc_provider)
+            .json(&request)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<RpcResponse>()
+            .await?;
+
+        if let Some(error) = response.error {
+            tracing::info!("is_valid_signature rejected for {}: {}", address, error);
+            return Ok(false);
+        }
+
+        let result = response
+            .result
+            .and_then(|result| result.first().and_then(|value| Felt::from_hex(value).ok()));
+        Ok(matches!(result, Some(value) if value == VALID || value == Felt::ONE))
+    }
+}
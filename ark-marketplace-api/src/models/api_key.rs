@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+#[derive(Debug, Deserialize, Serialize, FromRow, utoipa::ToSchema)]
+pub struct ApiKey {
+    pub api_key_id: i32,
+    /// First characters of the key, enough to recognize it
+    #[schema(example = "ark_1bc3d84f")]
+    pub key_prefix: String,
+    pub name: String,
+    #[schema(example = "free")]
+    pub tier: String,
+    /// Overrides the tier quota when set
+    pub requests_per_minute: Option<i32>,
+    /// Overrides the tier quota when set
+    pub requests_per_day: Option<i32>,
+    pub is_active: bool,
+    pub created_timestamp: i64,
+    pub revoked_timestamp: Option<i64>,
+}
+
+/// A newly created key. This is the only time the key itself is returned.
+#[derive(Debug, Deserialize, Serialize, FromRow, utoipa::ToSchema)]
+pub struct CreatedApiKey {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub key: ApiKey,
+    #[schema(example = "ark_1bc3d84f564247a1830a991340ae1808ac723d724fb24d28b6993330d6c9ce56")]
+    pub api_key: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct ApiKeyRequest {
+    pub name: String,
+    #[schema(example = "free")]
+    pub tier: String,
+    pub requests_per_minute: Option<i32>,
+    pub requests_per_day: Option<i32>,
+}
+
+/// Quotas enforced for a caller, from its key or from the anonymous tier.
+#[derive(Debug, Clone, FromRow)]
+pub struct ApiQuota {
+    pub api_key_id: Option<i32>,
+    pub tier: String,
+    pub requests_per_minute: i32,
+    pub requests_per_day: i32,
+}
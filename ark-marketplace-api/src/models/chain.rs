@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+#[derive(Debug, Clone, Deserialize, Serialize, FromRow, utoipa::ToSchema)]
+pub struct Chain {
+    #[schema(example = "0x534e5f4d41494e")]
+    pub chain_id: String,
+    #[schema(example = "SN_MAIN")]
+    pub name: String,
+    /// Chain used by the routes that do not receive a chain id
+    pub is_default: bool,
+}
@@ -44,7 +44,13 @@ pub struct CollectionFullData {
     pub is_verified: Option<bool>,
 }
 
-#[derive(Serialize, Deserialize, FromRow, Clone, utoipa::ToSchema)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CollectionKey {
+    pub contract_address: String,
+    pub chain_id: String,
+}
+
+#[derive(Serialize, Deserialize, FromRow, Clone, utoipa::ToSchema, async_graphql::SimpleObject)]
 pub struct CollectionData {
     #[schema(value_type = String, example = "0x02acee8c430f62333cf0e0e7a94b2347b5513b4c25f699461dd8d7b23c072478")]
     pub address: String,
@@ -61,6 +67,7 @@ pub struct CollectionData {
         serialize_with = "serialize_option_bigdecimal",
         deserialize_with = "deserialize_option_bigdecimal"
     )]
+    #[graphql(name = "volume7dEth")]
     pub volume_7d_eth: Option<BigDecimal>,
     #[schema(value_type = String, example = "1000000000000000")]
     #[serde(
@@ -68,6 +75,7 @@ pub struct CollectionData {
         deserialize_with = "deserialize_option_bigdecimal"
     )]
     pub top_offer: Option<BigDecimal>,
+    #[graphql(name = "sales7d")]
     pub sales_7d: Option<i64>,
     #[schema(value_type = String, example = "1000000000000000")]
     #[serde(
@@ -90,6 +98,7 @@ pub struct CollectionData {
         serialize_with = "serialize_option_bigdecimal",
         deserialize_with = "deserialize_option_bigdecimal"
     )]
+    #[graphql(name = "floor7dPercentage")]
     pub floor_7d_percentage: Option<BigDecimal>,
     pub is_verified: Option<bool>,
     pub deployed_timestamp: Option<i64>,
@@ -148,6 +157,7 @@ pub struct CollectionActivityDataDB {
     pub address: String,
     pub is_verified: Option<bool>,
     pub currency_address: Option<String>,
+    pub cursor_values: String,
 }
 
 #[derive(Deserialize, Serialize, FromRow, utoipa::ToSchema)]
@@ -195,3 +205,197 @@ pub struct OwnerDataCompleted {
     pub starknet_id: Option<String>,
     pub image: Option<String>,
 }
+
+/// One bucket of a collection chart. Prices are in the currency's smallest
+/// unit; the floor comes from the hourly `floor_collection` snapshots and the
+/// sale prices from the `Executed` events of the bucket.
+#[derive(Debug, Deserialize, Serialize, FromRow, Clone, utoipa::ToSchema)]
+pub struct CollectionChartData {
+    /// Start of the bucket, as a unix timestamp
+    #[schema(example = 1717200000)]
+    pub timestamp: i64,
+    #[schema(value_type = String, example = "1000000000000000")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub floor_open: Option<BigDecimal>,
+    #[schema(value_type = String, example = "1200000000000000")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub floor_high: Option<BigDecimal>,
+    #[schema(value_type = String, example = "900000000000000")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub floor_low: Option<BigDecimal>,
+    #[schema(value_type = String, example = "1100000000000000")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub floor_close: Option<BigDecimal>,
+    #[schema(value_type = String, example = "1500000000000000")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub sale_open: Option<BigDecimal>,
+    #[schema(value_type = String, example = "2000000000000000")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub sale_high: Option<BigDecimal>,
+    #[schema(value_type = String, example = "1000000000000000")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub sale_low: Option<BigDecimal>,
+    #[schema(value_type = String, example = "1200000000000000")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub sale_close: Option<BigDecimal>,
+    #[schema(value_type = String, example = "5700000000000000")]
+    pub volume: BigDecimal,
+    pub sales: i64,
+}
+
+#[derive(FromRow)]
+pub struct CollectionOfferDataDB {
+    pub offer_id: i32,
+    pub amount: Option<BigDecimal>,
+    pub quantity: Option<BigDecimal>,
+    pub currency_address: String,
+    pub source: String,
+    pub expire_at: i64,
+    pub hash: String,
+    pub cursor_values: String,
+}
+
+/// An offer any token of the collection can be sold to.
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CollectionOfferData {
+    pub offer_id: i32,
+    #[schema(value_type = String, example = "1000000000000000")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub price: Option<BigDecimal>,
+    /// Number of tokens the offer buys
+    #[schema(value_type = String, example = "1")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub quantity: Option<BigDecimal>,
+    pub currency: Currency,
+    #[schema(value_type = String, example = "-0.12")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub floor_difference: Option<BigDecimal>,
+    pub source: String,
+    pub expire_at: i64,
+    pub hash: String,
+    /// Tokens of the `user` given in the query the offer can be accepted
+    /// against
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub acceptable_token_ids: Option<Vec<String>>,
+}
+
+#[derive(FromRow)]
+pub struct OfferDepthLevelDB {
+    pub price: Option<BigDecimal>,
+    pub currency_address: String,
+    pub offer_count: i64,
+    pub quantity: Option<BigDecimal>,
+}
+
+/// Active collection-wide offers grouped by price.
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+pub struct OfferDepthLevel {
+    #[schema(value_type = String, example = "1000000000000000")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub price: Option<BigDecimal>,
+    pub currency: Currency,
+    #[schema(example = 3)]
+    pub offer_count: i64,
+    /// Number of tokens the offers of the level buy in total
+    #[schema(value_type = String, example = "5")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub quantity: Option<BigDecimal>,
+}
+
+/// Orders of one side of the order book at one price, in the smallest unit
+/// of the currency.
+#[derive(Serialize, Deserialize, FromRow, Clone)]
+pub struct OrderBookLevelDB {
+    pub is_bid: bool,
+    pub currency_address: String,
+    pub price: Option<BigDecimal>,
+    pub order_count: i64,
+    pub quantity: Option<BigDecimal>,
+}
+
+#[derive(Serialize, Deserialize, Clone, utoipa::ToSchema)]
+pub struct OrderBookLevel {
+    /// Price of the bucket in currency units
+    #[schema(value_type = String, example = "0.25")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub price: Option<BigDecimal>,
+    #[schema(example = 4)]
+    pub order_count: i64,
+    /// Number of tokens the orders of the bucket buy or sell
+    #[schema(value_type = String, example = "6")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub quantity: Option<BigDecimal>,
+}
+
+/// Active listings and bids of a collection in one currency. Bids are sorted
+/// from the highest price and asks from the lowest.
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+pub struct OrderBook {
+    pub currency: Currency,
+    pub bids: Vec<OrderBookLevel>,
+    pub asks: Vec<OrderBookLevel>,
+    #[schema(value_type = String, example = "0.24")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub best_bid: Option<BigDecimal>,
+    #[schema(value_type = String, example = "0.25")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub best_ask: Option<BigDecimal>,
+    /// Best ask minus best bid, empty when a side has no order
+    #[schema(value_type = String, example = "0.01")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub spread: Option<BigDecimal>,
+}
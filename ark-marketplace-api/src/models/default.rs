@@ -10,7 +10,7 @@ use crate::models::{deserialize_option_bigdecimal, serialize_option_bigdecimal};
 const CURRENCY_ADDRESS_ETH: &str =
     "0x049d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7";
 
-#[derive(Serialize, Deserialize, FromRow, ToSchema, Clone)]
+#[derive(Serialize, Deserialize, FromRow, ToSchema, Clone, async_graphql::SimpleObject)]
 #[schema(example = json!({
         "contract": "0x049d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7",
         "symbol": "ETH",
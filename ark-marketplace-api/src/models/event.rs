@@ -0,0 +1,82 @@
+use crate::models::token::TokenEventType;
+use crate::models::{deserialize_option_bigdecimal, serialize_option_bigdecimal};
+use ark_sqlx::providers::marketplace::types::TokenEventType as TokenEventTypeDB;
+use bigdecimal::BigDecimal;
+use num_bigint::BigInt;
+use serde::{Deserialize, Serialize};
+
+/// Payload published by the `token_event_notify` trigger on every
+/// `token_event` insert.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TokenEventNotification {
+    pub token_event_id: String,
+    pub contract_address: String,
+    pub chain_id: String,
+    pub token_id: String,
+    pub event_type: String,
+    pub block_timestamp: i64,
+    pub transaction_hash: Option<String>,
+    pub order_hash: Option<String>,
+    pub from_address: Option<String>,
+    pub to_address: Option<String>,
+    pub amount: Option<String>,
+    pub currency_address: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct MarketplaceEvent {
+    pub event_id: String,
+    pub activity_type: TokenEventType,
+    pub collection_address: String,
+    pub chain_id: String,
+    pub token_id: String,
+    #[schema(value_type = String, example = "12345.6789")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub price: Option<BigDecimal>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub time_stamp: i64,
+    pub transaction_hash: Option<String>,
+    pub order_hash: Option<String>,
+    pub currency_address: Option<String>,
+}
+
+impl TryFrom<TokenEventNotification> for MarketplaceEvent {
+    type Error = String;
+
+    fn try_from(notification: TokenEventNotification) -> Result<Self, Self::Error> {
+        let event_type = notification
+            .event_type
+            .parse::<TokenEventTypeDB>()
+            .map_err(|e| format!("{}: {}", e, notification.event_type))?;
+
+        // Activity endpoints expose executed orders as sales, keep the stream consistent
+        let activity_type = match TokenEventType::from(event_type) {
+            TokenEventType::Executed => TokenEventType::Sale,
+            activity_type => activity_type,
+        };
+
+        let price = notification.amount.as_deref().and_then(|amount| {
+            BigInt::parse_bytes(amount.trim_start_matches("0x").as_bytes(), 16)
+                .map(|value| BigDecimal::new(value, 0))
+        });
+
+        Ok(MarketplaceEvent {
+            event_id: notification.token_event_id,
+            activity_type,
+            collection_address: notification.contract_address,
+            chain_id: notification.chain_id,
+            token_id: notification.token_id,
+            price,
+            from: notification.from_address,
+            to: notification.to_address,
+            time_stamp: notification.block_timestamp,
+            transaction_hash: notification.transaction_hash,
+            order_hash: notification.order_hash,
+            currency_address: notification.currency_address,
+        })
+    }
+}
@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+use sqlx::types::BigDecimal;
+use sqlx::FromRow;
+
+use super::token::TokenEventType;
+use crate::models::{deserialize_option_bigdecimal, serialize_option_bigdecimal};
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Ndjson,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Ndjson => "ndjson",
+        }
+    }
+}
+
+/// Events exported, either every event of a collection or every event a
+/// wallet took part in.
+#[derive(Debug, Clone)]
+pub enum ActivityExportScope {
+    Collection { contract_address: String },
+    User { user_address: String },
+}
+
+#[derive(Debug, Clone)]
+pub struct ActivityExportFilter {
+    pub scope: ActivityExportScope,
+    pub chain_id: String,
+    /// Inclusive range of block timestamps
+    pub from: i64,
+    pub to: i64,
+    pub types: Option<Vec<TokenEventType>>,
+}
+
+#[derive(FromRow)]
+pub struct ActivityExportRowDB {
+    pub time_stamp: i64,
+    pub date: String,
+    pub activity_type: String,
+    pub collection_address: String,
+    pub collection_name: Option<String>,
+    pub token_id: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub amount: Option<BigDecimal>,
+    pub currency_address: Option<String>,
+    pub currency_symbol: Option<String>,
+    pub currency_decimals: Option<i16>,
+    pub currency_usd_price: Option<BigDecimal>,
+    pub transaction_hash: Option<String>,
+}
+
+/// A line of an activity export, amounts being in currency units rather than
+/// in the smallest unit of the currency.
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ActivityExportRow {
+    pub time_stamp: i64,
+    /// Date of the block, in UTC
+    #[schema(example = "2024-06-01T12:30:00Z")]
+    pub date: String,
+    pub activity_type: String,
+    pub collection_address: String,
+    pub collection_name: Option<String>,
+    pub token_id: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    #[schema(value_type = String, example = "1.25")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub price: Option<BigDecimal>,
+    #[schema(example = "ETH")]
+    pub currency: Option<String>,
+    pub currency_address: Option<String>,
+    /// Price in USD at the time of the trade, only known for the sales
+    /// indexed live
+    #[schema(value_type = String, example = "4375.12")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub usd_price: Option<BigDecimal>,
+    pub transaction_hash: Option<String>,
+}
+
+impl ActivityExportRow {
+    pub const CSV_HEADER: &'static str = "time_stamp,date,activity_type,collection_address,collection_name,token_id,from,to,price,currency,currency_address,usd_price,transaction_hash";
+
+    pub fn to_csv_record(&self) -> String {
+        let decimal = |value: &Option<BigDecimal>| {
+            value
+                .as_ref()
+                .map(BigDecimal::to_plain_string)
+                .unwrap_or_default()
+        };
+        let text = |value: &Option<String>| csv_field(value.as_deref().unwrap_or_default());
+
+        [
+            self.time_stamp.to_string(),
+            csv_field(&self.date),
+            csv_field(&self.activity_type),
+            csv_field(&self.collection_address),
+            text(&self.collection_name),
+            csv_field(&self.token_id),
+            text(&self.from),
+            text(&self.to),
+            decimal(&self.price),
+            text(&self.currency),
+            text(&self.currency_address),
+            decimal(&self.usd_price),
+            text(&self.transaction_hash),
+        ]
+        .join(",")
+    }
+}
+
+/// Quotes a CSV field when it contains a separator, a quote or a line break.
+/// Text starting like a formula, such as a collection name, is prefixed
+/// with a quote so that spreadsheets don't evaluate it.
+pub(crate) fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
@@ -1,7 +1,15 @@
+pub mod api_key;
+pub mod chain;
 pub mod collection;
 pub mod default;
+pub mod event;
+pub mod export;
 pub mod portfolio;
+pub mod tax_report;
 pub mod token;
+pub mod wallet_session;
+pub mod watchlist;
+pub mod webhook;
 
 use std::str::FromStr;
 
@@ -110,3 +110,119 @@ pub struct StatsData {
     )]
     pub total_value: Option<BigDecimal>,
 }
+
+/// Holdings of a user in one collection, amounts in wei.
+#[derive(FromRow)]
+pub struct CollectionValuationDB {
+    pub collection_address: String,
+    pub collection_name: Option<String>,
+    pub collection_image: Option<String>,
+    pub token_count: i64,
+    pub floor_price: Option<BigDecimal>,
+    pub top_bid: Option<BigDecimal>,
+    pub value_at_floor: Option<BigDecimal>,
+    pub value_at_top_bid: Option<BigDecimal>,
+    pub cost_basis: Option<BigDecimal>,
+    pub tokens_without_cost_basis: i64,
+    pub unrealized_pnl: Option<BigDecimal>,
+}
+
+/// Sales of a user, amounts in wei.
+#[derive(FromRow)]
+pub struct RealizedPnlDB {
+    pub sale_count: i64,
+    pub sales_without_cost_basis: i64,
+    pub realized_pnl: Option<BigDecimal>,
+    pub realized_pnl_usd: Option<BigDecimal>,
+}
+
+#[derive(FromRow)]
+pub struct PortfolioValueDB {
+    pub timestamp: i64,
+    pub token_count: i64,
+    pub value_at_floor: Option<BigDecimal>,
+}
+
+/// An amount in ETH along with its USD value at the current ETH price.
+#[derive(Serialize, Deserialize, Clone, utoipa::ToSchema)]
+pub struct PortfolioAmount {
+    #[schema(value_type = String, example = "1.25")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub eth: Option<BigDecimal>,
+    #[schema(value_type = String, example = "4210.57")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub usd: Option<BigDecimal>,
+}
+
+#[derive(Serialize, Deserialize, Clone, utoipa::ToSchema)]
+pub struct CollectionValuation {
+    pub collection_address: String,
+    pub collection_name: Option<String>,
+    pub collection_image: Option<String>,
+    pub token_count: i64,
+    #[schema(value_type = String, example = "0.05")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub floor_price: Option<BigDecimal>,
+    #[schema(value_type = String, example = "0.045")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub top_bid: Option<BigDecimal>,
+    pub value_at_floor: PortfolioAmount,
+    /// Each token valued at its best bid, a token offer or a collection bid
+    pub value_at_top_bid: PortfolioAmount,
+    pub cost_basis: PortfolioAmount,
+    /// Floor value minus cost basis of the tokens with a known cost basis
+    pub unrealized_pnl: PortfolioAmount,
+    /// Tokens received by transfer, whose acquisition price is unknown
+    pub tokens_without_cost_basis: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone, utoipa::ToSchema)]
+pub struct PortfolioValuePoint {
+    /// Start of the bucket, as a unix timestamp
+    #[schema(example = 1717200000)]
+    pub timestamp: i64,
+    pub token_count: i64,
+    /// Tokens held at the end of the bucket valued at the floor of the time,
+    /// in ETH
+    #[schema(value_type = String, example = "1.25")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub value_at_floor: Option<BigDecimal>,
+}
+
+#[derive(Serialize, Deserialize, Clone, utoipa::ToSchema)]
+pub struct PortfolioValuation {
+    pub value_at_floor: PortfolioAmount,
+    pub value_at_top_bid: PortfolioAmount,
+    pub cost_basis: PortfolioAmount,
+    pub unrealized_pnl: PortfolioAmount,
+    /// Sale price minus acquisition price of the tokens sold, valued in USD
+    /// at the ETH price of each sale
+    pub realized_pnl: PortfolioAmount,
+    pub sale_count: i64,
+    /// Sales of tokens received by transfer or whose price is unknown in ETH,
+    /// left out of the realized PnL
+    pub sales_without_cost_basis: i64,
+    #[schema(value_type = String, example = "3368.45")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub eth_usd: Option<BigDecimal>,
+    pub collections: Vec<CollectionValuation>,
+    pub history: Vec<PortfolioValuePoint>,
+}
@@ -0,0 +1,221 @@
+use serde::{Deserialize, Serialize};
+use sqlx::types::BigDecimal;
+use sqlx::FromRow;
+
+use super::export::csv_field;
+use crate::models::{deserialize_option_bigdecimal, serialize_option_bigdecimal};
+
+/// Acquisition consumed by each disposal among the tokens of the collection
+/// still held: the oldest, the most recent or the most expensive one.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LotMethod {
+    Fifo,
+    Lifo,
+    Hifo,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TaxReportFormat {
+    Json,
+    Csv,
+}
+
+/// A token received or sent by the user, `amount` being in the smallest
+/// unit of the currency.
+#[derive(FromRow)]
+pub struct TaxEventDB {
+    pub contract_address: String,
+    pub collection_name: Option<String>,
+    pub token_id: String,
+    pub event_type: String,
+    pub block_timestamp: i64,
+    pub is_acquisition: bool,
+    pub amount: Option<BigDecimal>,
+    pub royalty_amount: Option<BigDecimal>,
+    pub marketplace_fee_amount: Option<BigDecimal>,
+    pub currency_symbol: Option<String>,
+    pub currency_decimals: Option<i16>,
+    pub currency_usd_price: Option<BigDecimal>,
+    pub transaction_hash: Option<String>,
+}
+
+/// A token sold or burned during the year, matched with the acquisition
+/// picked by the lot method. Amounts are in currency units.
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct TaxDisposal {
+    pub collection_address: String,
+    pub collection_name: Option<String>,
+    pub token_id: String,
+    /// 'Sale' or 'Burn'
+    #[schema(example = "Sale")]
+    pub disposal_type: String,
+    pub disposed_at: i64,
+    #[schema(example = "2024-06-01T12:30:00Z")]
+    pub disposal_date: String,
+    /// 'Sale', 'Mint' or 'Transfer', empty when the token was acquired
+    /// before the indexed history
+    #[schema(example = "Sale")]
+    pub acquisition_type: Option<String>,
+    pub acquired_at: Option<i64>,
+    #[schema(example = "2024-01-15T08:00:00Z")]
+    pub acquisition_date: Option<String>,
+    pub holding_days: Option<i64>,
+    #[schema(example = "ETH")]
+    pub currency: Option<String>,
+    #[schema(value_type = String, example = "1.25")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub proceeds: Option<BigDecimal>,
+    #[schema(value_type = String, example = "4210.57")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub proceeds_usd: Option<BigDecimal>,
+    /// Price of the acquisition in USD at its date, nothing for a mint and
+    /// unknown for a transfer
+    #[schema(value_type = String, example = "3105.20")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub cost_basis_usd: Option<BigDecimal>,
+    /// Creator royalties paid out of the sale, empty when not recorded
+    #[schema(value_type = String, example = "0.0625")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub royalty: Option<BigDecimal>,
+    #[schema(value_type = String, example = "210.53")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub royalty_usd: Option<BigDecimal>,
+    /// Marketplace fees paid out of the sale, empty when not recorded
+    #[schema(value_type = String, example = "0.025")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub marketplace_fee: Option<BigDecimal>,
+    #[schema(value_type = String, example = "84.21")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub marketplace_fee_usd: Option<BigDecimal>,
+    /// Proceeds minus fees and cost basis, empty when either is unknown in
+    /// USD
+    #[schema(value_type = String, example = "810.63")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub gain_usd: Option<BigDecimal>,
+    pub transaction_hash: Option<String>,
+}
+
+impl TaxDisposal {
+    pub const CSV_HEADER: &'static str = "collection_address,collection_name,token_id,disposal_type,disposed_at,disposal_date,acquisition_type,acquired_at,acquisition_date,holding_days,currency,proceeds,proceeds_usd,cost_basis_usd,royalty,royalty_usd,marketplace_fee,marketplace_fee_usd,gain_usd,transaction_hash";
+
+    pub fn to_csv_record(&self) -> String {
+        let decimal = |value: &Option<BigDecimal>| {
+            value
+                .as_ref()
+                .map(BigDecimal::to_plain_string)
+                .unwrap_or_default()
+        };
+        let text = |value: &Option<String>| csv_field(value.as_deref().unwrap_or_default());
+        let number = |value: Option<i64>| value.map(|v| v.to_string()).unwrap_or_default();
+
+        [
+            csv_field(&self.collection_address),
+            text(&self.collection_name),
+            csv_field(&self.token_id),
+            csv_field(&self.disposal_type),
+            self.disposed_at.to_string(),
+            csv_field(&self.disposal_date),
+            text(&self.acquisition_type),
+            number(self.acquired_at),
+            text(&self.acquisition_date),
+            number(self.holding_days),
+            text(&self.currency),
+            decimal(&self.proceeds),
+            decimal(&self.proceeds_usd),
+            decimal(&self.cost_basis_usd),
+            decimal(&self.royalty),
+            decimal(&self.royalty_usd),
+            decimal(&self.marketplace_fee),
+            decimal(&self.marketplace_fee_usd),
+            decimal(&self.gain_usd),
+            text(&self.transaction_hash),
+        ]
+        .join(",")
+    }
+}
+
+/// Totals of the disposals of the year in USD, each leaving out the values
+/// that could not be priced.
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct TaxReportSummary {
+    #[schema(example = 2024)]
+    pub year: i32,
+    pub method: LotMethod,
+    #[schema(example = "USD")]
+    pub fiat: String,
+    /// Inclusive range of the year, as unix timestamps
+    pub from: i64,
+    pub to: i64,
+    pub disposal_count: i64,
+    #[schema(value_type = String, example = "4210.57")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub total_proceeds_usd: Option<BigDecimal>,
+    #[schema(value_type = String, example = "3105.20")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub total_cost_basis_usd: Option<BigDecimal>,
+    #[schema(value_type = String, example = "210.53")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub total_royalties_usd: Option<BigDecimal>,
+    #[schema(value_type = String, example = "84.21")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub total_marketplace_fees_usd: Option<BigDecimal>,
+    #[schema(value_type = String, example = "810.63")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub net_gain_usd: Option<BigDecimal>,
+    /// Disposals of tokens received by transfer or acquired before the
+    /// indexed history, left out of the net gain
+    pub disposals_without_cost_basis: i64,
+    /// Sales of the year, bought or sold, without a USD price for their
+    /// currency at their date
+    pub unpriced_trades: i64,
+    /// Tokens sent to another wallet during the year, which close a lot
+    /// without being a disposal
+    pub transfers_out: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct TaxReport {
+    pub summary: TaxReportSummary,
+    pub disposals: Vec<TaxDisposal>,
+}
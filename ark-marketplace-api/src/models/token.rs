@@ -61,6 +61,17 @@ pub struct TokenData {
     pub metadata: Option<JsonValue>,
     pub owner: Option<String>,
     pub buy_in_progress: Option<bool>,
+    /// Rarity rank in the collection, 1 being the rarest
+    #[serde(default)]
+    pub rarity_rank: Option<i64>,
+    /// OpenRarity score, higher is rarer
+    #[schema(value_type = String, example = "1.4139")]
+    #[serde(
+        default,
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub rarity_score: Option<BigDecimal>,
 }
 
 #[derive(Serialize, Deserialize, FromRow, utoipa::ToSchema)]
@@ -126,6 +137,17 @@ pub struct TokenInformationData {
     pub collection_image: Option<String>,
     pub metadata_updated_at: Option<i64>,
     pub metadata_status: Option<String>,
+    /// Rarity rank in the collection, 1 being the rarest
+    #[serde(default)]
+    pub rarity_rank: Option<i64>,
+    /// OpenRarity score, higher is rarer
+    #[schema(value_type = String, example = "1.4139")]
+    #[serde(
+        default,
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub rarity_score: Option<BigDecimal>,
 }
 
 #[derive(Serialize, Deserialize, FromRow, utoipa::ToSchema)]
@@ -161,7 +183,7 @@ pub struct TopOfferQueryResult {
     pub currency_address: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, FromRow, utoipa::ToSchema)]
+#[derive(Serialize, Deserialize, FromRow, Clone, utoipa::ToSchema, async_graphql::SimpleObject)]
 pub struct TopOffer {
     pub order_hash: Option<String>,
     #[schema(value_type = String, example = "12345.6789")]
@@ -185,7 +207,7 @@ pub struct ListingRaw {
     pub end_date: Option<i64>,
 }
 
-#[derive(Serialize, Deserialize, FromRow, utoipa::ToSchema, Clone)]
+#[derive(Serialize, Deserialize, FromRow, utoipa::ToSchema, Clone, async_graphql::SimpleObject)]
 pub struct Listing {
     pub is_auction: Option<bool>,
     pub order_hash: Option<String>,
@@ -196,6 +218,52 @@ pub struct Listing {
     pub currency: Currency,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TokenKey {
+    pub contract_address: String,
+    pub chain_id: String,
+    pub token_id: String,
+}
+
+/// A token with its market data, as returned by the batch lookup.
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+pub struct TokenBatchData {
+    pub chain_id: String,
+    pub token: TokenInformationData,
+    pub market_data: TokenMarketData,
+}
+
+/// A token with its listing and top offer, loaded in batches by `TokenKey`.
+#[derive(Serialize, Deserialize, Clone, async_graphql::SimpleObject)]
+#[graphql(complex, name = "Token")]
+pub struct TokenDetailsData {
+    pub collection_address: String,
+    pub chain_id: String,
+    pub token_id: String,
+    pub owner: Option<String>,
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub price: Option<BigDecimal>,
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub last_price: Option<BigDecimal>,
+    pub metadata: Option<JsonValue>,
+    pub metadata_updated_at: Option<i64>,
+    pub buy_in_progress: Option<bool>,
+    pub rarity_rank: Option<i64>,
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub rarity_score: Option<BigDecimal>,
+    pub listing: Option<Listing>,
+    pub top_offer: Option<TopOffer>,
+}
+
 #[derive(Serialize, Deserialize, FromRow, utoipa::ToSchema)]
 pub struct TokenPortfolioData {
     pub collection_address: Option<String>,
@@ -231,7 +299,7 @@ pub struct TokenPortfolioData {
     pub currency_address: Option<String>,
 }
 
-#[derive(FromRow)]
+#[derive(Clone, FromRow)]
 pub struct TokenOfferOneDataDB {
     pub offer_id: i32,
     pub amount: Option<BigDecimal>,
@@ -239,9 +307,19 @@ pub struct TokenOfferOneDataDB {
     pub source: Option<String>,
     pub expire_at: i64,
     pub hash: Option<String>,
+    pub cursor_values: String,
 }
 
-#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+/// First offers of a token in the order of `token_offers_keyset` with the
+/// number of its offers, as returned by the batch lookup.
+#[derive(Clone)]
+pub struct TokenOffersData {
+    pub offers: Vec<TokenOfferOneDataDB>,
+    pub total_count: i64,
+}
+
+#[derive(Serialize, Deserialize, utoipa::ToSchema, async_graphql::SimpleObject)]
+#[graphql(name = "Offer")]
 pub struct TokenOfferOneData {
     pub offer_id: i32,
     #[schema(value_type = String, example = "12345.6789")]
@@ -262,7 +340,7 @@ pub struct TokenOfferOneData {
     pub hash: Option<String>,
 }
 
-#[derive(Debug, utoipa::ToSchema)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, utoipa::ToSchema, async_graphql::Enum)]
 pub enum TokenEventType {
     Listing,
     CollectionOffer,
@@ -277,11 +355,16 @@ pub enum TokenEventType {
     Transfer,
     Rollback,
     // Cancel event type
+    #[graphql(name = "DELISTING")]
     ListingCancelled,
+    #[graphql(name = "CANCEL_AUCTION")]
     AuctionCancelled,
+    #[graphql(name = "CANCEL_OFFER")]
     OfferCancelled,
     // Expired event type
+    #[graphql(name = "EXPIRED_LISTING")]
     ListingExpired,
+    #[graphql(name = "EXPIRED_OFFER")]
     OfferExpired,
 }
 
@@ -461,6 +544,7 @@ pub struct TokenPortfolioActivityDataDB {
     pub collection_is_verified: Option<bool>,
     pub metadata: Option<JsonValue>,
     pub currency_address: Option<String>,
+    pub cursor_values: String,
 }
 
 #[derive(Deserialize, Serialize, FromRow, utoipa::ToSchema)]
@@ -494,3 +578,57 @@ pub struct TokenPortfolioActivityData {
     pub metadata: Option<JsonValue>,
     pub currency: Currency,
 }
+
+#[derive(Debug, FromRow)]
+pub struct TokenSaleDB {
+    pub amount: Option<BigDecimal>,
+    pub eth_amount: Option<BigDecimal>,
+    pub currency_address: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub time_stamp: i64,
+    pub transaction_hash: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, utoipa::ToSchema)]
+pub struct TokenSaleData {
+    /// Sale price in currency units, e.g. "1.5" for 1.5 ETH
+    #[schema(value_type = String, example = "1.5")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub price: Option<BigDecimal>,
+    /// Sale price converted to ETH at the time of the sale, null when the
+    /// currency could not be priced
+    #[schema(value_type = String, example = "1.5")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub eth_price: Option<BigDecimal>,
+    pub currency: Currency,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub time_stamp: i64,
+    pub transaction_hash: Option<String>,
+}
+
+#[derive(Debug, FromRow)]
+pub struct TokenOwnershipStatsDB {
+    pub average_hold_time: Option<i64>,
+    pub owner_count: i64,
+}
+
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+pub struct TokenPriceStats {
+    /// Highest sale, compared on the ETH price so that currencies mix
+    pub all_time_high: Option<TokenSaleData>,
+    /// Lowest sale, compared on the ETH price so that currencies mix
+    pub all_time_low: Option<TokenSaleData>,
+    pub sales_count: i64,
+    /// Average time in seconds an owner kept the token before it changed
+    /// hands, the current owner excluded
+    pub average_hold_time: Option<i64>,
+    pub owner_count: i64,
+}
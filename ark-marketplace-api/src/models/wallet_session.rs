@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use sqlx::FromRow;
+
+/// Claims of the JWT given to a wallet once signed in.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WalletSession {
+    /// Address of the wallet, padded to 64 digits
+    pub sub: String,
+    pub chain_id: String,
+    pub iat: i64,
+    pub exp: i64,
+}
+
+#[derive(Debug, FromRow)]
+pub struct WalletNonce {
+    pub nonce: String,
+    pub user_address: String,
+    pub chain_id: String,
+    pub issued_at: i64,
+    pub expiration_time: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct SignInChallengeRequest {
+    #[schema(example = "0x00e4769a4d2f7f69c70951a003eba5c32707cef3cdfb6b27ca63567f51cdd078")]
+    pub address: String,
+    /// The blockchain chain ID, defaults to the default chain
+    pub chain_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct SignInChallenge {
+    #[schema(example = "0x6f1c3a4e0b2d4c8a9e7f5b3d1c2a4e6f")]
+    pub nonce: String,
+    /// The nonce must be signed and sent back before this timestamp
+    pub expiration_time: i64,
+    /// SNIP-12 typed data to sign with the wallet
+    #[schema(value_type = Object)]
+    pub typed_data: JsonValue,
+}
+
+#[derive(Debug, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct SignInRequest {
+    pub address: String,
+    pub nonce: String,
+    /// Signature of the typed data, as returned by the wallet
+    #[schema(example = json!(["0x1d3b...", "0x4a2f..."]))]
+    pub signature: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct SessionToken {
+    /// JWT to send as `Authorization: Bearer <token>`
+    pub token: String,
+    pub address: String,
+    pub chain_id: String,
+    pub expiration_time: i64,
+}
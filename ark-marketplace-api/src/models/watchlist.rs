@@ -0,0 +1,123 @@
+use super::default::Currency;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use sqlx::types::BigDecimal;
+use sqlx::FromRow;
+use std::str::FromStr;
+
+use crate::models::{deserialize_option_bigdecimal, serialize_option_bigdecimal};
+
+#[derive(Debug, Deserialize, Serialize, FromRow, utoipa::ToSchema)]
+pub struct WatchlistItem {
+    pub watchlist_item_id: i32,
+    pub user_address: String,
+    pub contract_address: String,
+    pub chain_id: String,
+    /// Followed token, null when the whole collection is followed
+    pub token_id: Option<String>,
+    pub collection_name: Option<String>,
+    pub collection_image: Option<String>,
+    #[schema(value_type = String, example = "1200000000000000000")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub floor: Option<BigDecimal>,
+    #[schema(value_type = Option<Object>)]
+    pub metadata: Option<JsonValue>,
+    pub created_timestamp: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct WatchlistItemRequest {
+    pub contract_address: String,
+    /// The blockchain chain ID, defaults to the default chain
+    pub chain_id: Option<String>,
+    /// Token to follow, the whole collection is followed when missing
+    #[schema(example = "42")]
+    pub token_id: Option<String>,
+}
+
+/// Kinds of changes reported in the feed of a watchlist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum WatchlistEventType {
+    Listing,
+    /// A token listed below its previous listing by the same seller, or the
+    /// floor of a followed collection going down
+    PriceDrop,
+    Sale,
+    /// An offer on a followed token or on any token of a followed collection
+    NewOffer,
+}
+
+impl WatchlistEventType {
+    pub fn to_db_string(self) -> &'static str {
+        match self {
+            Self::Listing => "LISTING",
+            Self::PriceDrop => "PRICE_DROP",
+            Self::Sale => "SALE",
+            Self::NewOffer => "NEW_OFFER",
+        }
+    }
+}
+
+impl FromStr for WatchlistEventType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "LISTING" => Ok(Self::Listing),
+            "PRICE_DROP" => Ok(Self::PriceDrop),
+            "SALE" => Ok(Self::Sale),
+            "NEW_OFFER" => Ok(Self::NewOffer),
+            _ => Err(format!("Unknown watchlist event type: {}", s)),
+        }
+    }
+}
+
+#[derive(FromRow)]
+pub struct WatchlistFeedItemDB {
+    pub event_type: String,
+    pub contract_address: String,
+    pub token_id: Option<String>,
+    pub collection_name: Option<String>,
+    pub metadata: Option<JsonValue>,
+    pub price: Option<BigDecimal>,
+    pub previous_price: Option<BigDecimal>,
+    pub currency_address: Option<String>,
+    pub from_address: Option<String>,
+    pub to_address: Option<String>,
+    pub timestamp: i64,
+    pub transaction_hash: Option<String>,
+    pub cursor_values: String,
+}
+
+#[derive(Deserialize, Serialize, utoipa::ToSchema)]
+pub struct WatchlistFeedItem {
+    pub event_type: WatchlistEventType,
+    pub contract_address: String,
+    /// Null for the floor changes of a collection and its collection offers
+    pub token_id: Option<String>,
+    pub collection_name: Option<String>,
+    #[schema(value_type = Option<Object>)]
+    pub metadata: Option<JsonValue>,
+    #[schema(value_type = String, example = "1000000000000000000")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub price: Option<BigDecimal>,
+    /// Price before a price drop, empty for the other events
+    #[schema(value_type = String, example = "1200000000000000000")]
+    #[serde(
+        serialize_with = "serialize_option_bigdecimal",
+        deserialize_with = "deserialize_option_bigdecimal"
+    )]
+    pub previous_price: Option<BigDecimal>,
+    pub currency: Currency,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub timestamp: i64,
+    pub transaction_hash: Option<String>,
+}
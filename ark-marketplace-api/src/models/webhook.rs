@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use sqlx::FromRow;
+
+#[derive(Debug, Deserialize, Serialize, FromRow, utoipa::ToSchema)]
+pub struct WebhookSubscription {
+    pub webhook_subscription_id: i32,
+    #[schema(example = "https://example.com/ark-webhook")]
+    pub url: String,
+    /// Key used to compute the `X-Ark-Signature` HMAC of each delivery
+    pub secret: String,
+    pub contract_address: String,
+    pub chain_id: String,
+    #[schema(example = json!(["PLACED", "EXECUTED"]))]
+    pub event_types: Vec<String>,
+    pub is_active: bool,
+    pub created_timestamp: i64,
+    pub updated_timestamp: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct WebhookSubscriptionRequest {
+    #[schema(example = "https://example.com/ark-webhook")]
+    pub url: String,
+    pub contract_address: String,
+    pub chain_id: String,
+    /// Order events to deliver, all of them when empty or missing
+    #[schema(example = json!(["PLACED", "CANCELLED", "FULFILLED", "EXECUTED", "ROLLBACK"]))]
+    pub event_types: Option<Vec<String>>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, FromRow, utoipa::ToSchema)]
+pub struct WebhookDeadLetter {
+    pub webhook_dead_letter_id: i32,
+    pub webhook_subscription_id: i32,
+    pub event_type: String,
+    pub order_hash: String,
+    #[schema(value_type = Object)]
+    pub payload: JsonValue,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub created_timestamp: i64,
+}
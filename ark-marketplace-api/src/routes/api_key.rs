@@ -0,0 +1,16 @@
+use crate::handlers::api_key_handler;
+use crate::routes::auth::validator;
+use actix_web::web;
+use actix_web_httpauth::middleware::HttpAuthentication;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    let auth = HttpAuthentication::basic(validator);
+
+    cfg.service(
+        web::scope("/api-keys")
+            .wrap(auth)
+            .route("", web::post().to(api_key_handler::create_key))
+            .route("", web::get().to(api_key_handler::get_keys))
+            .route("/{id}", web::delete().to(api_key_handler::revoke_key)),
+    );
+}
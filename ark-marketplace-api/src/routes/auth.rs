@@ -1,9 +1,21 @@
-use actix_web::dev::ServiceRequest;
-use actix_web::{error, Error};
+use crate::managers::api_key_manager::ApiKeyManager;
+use crate::managers::rate_limiter::RateLimitDecision;
+use crate::managers::wallet_session_manager::WalletSessionManager;
+use crate::types::api_error::ApiError;
+use crate::utils::http_utils::normalize_address;
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{self, HeaderMap, HeaderName, HeaderValue};
+use actix_web::middleware::Next;
 use actix_web::{error::ResponseError, HttpResponse};
+use actix_web::{web, Error, HttpMessage};
 use actix_web_httpauth::extractors::basic::BasicAuth;
+use ark_sqlx::providers::metrics::LambdaUsageData;
 use serde::Serialize;
 use std::fmt;
+use std::net::IpAddr;
+use std::time::Instant;
+use subtle::ConstantTimeEq;
 
 #[derive(Debug, Serialize)]
 pub struct UnauthorizedError;
@@ -20,16 +32,373 @@ impl ResponseError for UnauthorizedError {
     }
 }
 
+/// Basic auth credentials of the admin routes, read once at startup.
+pub struct AdminCredentials {
+    user: String,
+    password: String,
+}
+
+impl AdminCredentials {
+    pub fn new(user: String, password: String) -> Self {
+        Self { user, password }
+    }
+
+    /// Reads `API_USER` and `API_PASSWORD`, both must be set and non-empty
+    /// so that the admin routes are never open with empty credentials.
+    pub fn from_env() -> Result<Self, String> {
+        let read = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .filter(|value| !value.is_empty())
+                .ok_or_else(|| format!("{} must be set", name))
+        };
+        Ok(Self::new(read("API_USER")?, read("API_PASSWORD")?))
+    }
+
+    /// Compares both fields in constant time and without short-circuiting,
+    /// so the response time doesn't tell which part was wrong.
+    pub fn matches(&self, user: &str, password: &str) -> bool {
+        let user_matches = self.user.as_bytes().ct_eq(user.as_bytes());
+        let password_matches = self.password.as_bytes().ct_eq(password.as_bytes());
+        (user_matches & password_matches).into()
+    }
+}
+
 pub async fn validator(
     req: ServiceRequest,
     credentials: BasicAuth,
 ) -> Result<ServiceRequest, (Error, ServiceRequest)> {
-    let user = std::env::var("API_USER").unwrap_or_default();
-    let password = std::env::var("API_PASSWORD").unwrap_or_default();
+    let is_valid = req
+        .app_data::<web::Data<AdminCredentials>>()
+        .is_some_and(|admin| {
+            admin.matches(
+                credentials.user_id(),
+                credentials.password().unwrap_or_default(),
+            )
+        });
 
-    if credentials.user_id() == user && credentials.password().unwrap_or_default() == password {
+    if is_valid {
         Ok(req)
     } else {
-        Err((error::ErrorUnauthorized("Unauthorized"), req))
+        Err((
+            ApiError::Unauthorized("Unauthorized".to_string()).into(),
+            req,
+        ))
+    }
+}
+
+/// Requests that are never rate limited.
+const UNMETERED_PATH_PREFIXES: [&str; 4] = ["/health", "/v1/health", "/swagger-ui", "/api-docs"];
+
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+fn set_rate_limit_headers(headers: &mut HeaderMap, decision: &RateLimitDecision) {
+    headers.insert(
+        HeaderName::from_static("x-ratelimit-limit"),
+        HeaderValue::from(decision.limit_per_minute),
+    );
+    headers.insert(
+        HeaderName::from_static("x-ratelimit-remaining"),
+        HeaderValue::from(decision.remaining_per_minute.max(0)),
+    );
+    if !decision.allowed {
+        headers.insert(
+            header::RETRY_AFTER,
+            HeaderValue::from(decision.retry_after_seconds()),
+        );
+    }
+}
+
+/// Address of the client. Behind trusted proxies, it is the last address of
+/// `X-Forwarded-For` that is not a trusted proxy: the addresses before it are
+/// sent by the client and can't be trusted.
+fn client_ip(req: &ServiceRequest, manager: &ApiKeyManager) -> String {
+    req.peer_addr()
+        .map(|addr| {
+            forwarded_client_ip(addr.ip(), req.headers(), |ip| manager.is_trusted_proxy(ip))
+        })
+        .map(|ip| ip.to_string())
+        .unwrap_or_default()
+}
+
+fn forwarded_client_ip(
+    peer_ip: IpAddr,
+    headers: &HeaderMap,
+    is_trusted_proxy: impl Fn(&IpAddr) -> bool,
+) -> IpAddr {
+    if !is_trusted_proxy(&peer_ip) {
+        return peer_ip;
+    }
+
+    headers
+        .get_all("x-forwarded-for")
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|address| address.trim().parse::<IpAddr>().ok())
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .find(|ip| !is_trusted_proxy(ip))
+        .unwrap_or(peer_ip)
+}
+
+/// Authenticates the `x-api-key` header and applies the quota of the key, or
+/// the anonymous quota of the client IP when no key is sent. Requests made
+/// with a key are recorded in the usage table.
+pub async fn api_key_guard<B: MessageBody + 'static>(
+    req: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<EitherBody<B>>, Error> {
+    let Some(manager) = req.app_data::<web::Data<ApiKeyManager>>().cloned() else {
+        return Ok(next.call(req).await?.map_into_left_body());
+    };
+    if UNMETERED_PATH_PREFIXES
+        .iter()
+        .any(|prefix| req.path().starts_with(prefix))
+    {
+        return Ok(next.call(req).await?.map_into_left_body());
+    }
+
+    let started_at = Instant::now();
+    let source_ip = client_ip(&req, &manager);
+    let api_key = req
+        .headers()
+        .get(API_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+
+    // Keys that are not cached yet cost a database query, their lookups are
+    // limited per client IP so that random keys can't flood the database.
+    if let Some(api_key) = &api_key {
+        if manager.cached_quota(api_key).is_none() {
+            let subject = format!("lookup:{}", source_ip);
+            match manager
+                .check_rate_limit(&subject, manager.anonymous_quota())
+                .await
+            {
+                Ok(decision) if !decision.allowed => {
+                    let response = ApiError::RateLimited("Too many API key lookups".to_string())
+                        .error_response();
+                    let mut response = req.into_response(response).map_into_right_body();
+                    set_rate_limit_headers(response.headers_mut(), &decision);
+                    return Ok(response);
+                }
+                Ok(_) => {}
+                Err(e) => tracing::error!("Failed to check the rate limit of {}: {}", subject, e),
+            }
+        }
+    }
+
+    let (subject, quota) = match &api_key {
+        Some(api_key) => match manager.quota_for_key(api_key).await {
+            Ok(Some(quota)) => (
+                format!("key:{}", quota.api_key_id.unwrap_or_default()),
+                quota,
+            ),
+            Ok(None) => {
+                let response =
+                    ApiError::Unauthorized("Invalid API key".to_string()).error_response();
+                return Ok(req.into_response(response).map_into_right_body());
+            }
+            Err(e) => {
+                let response =
+                    ApiError::internal("error query get_api_key_quota", e).error_response();
+                return Ok(req.into_response(response).map_into_right_body());
+            }
+        },
+        None if manager.is_key_required() => {
+            let response = ApiError::Unauthorized("Missing API key".to_string()).error_response();
+            return Ok(req.into_response(response).map_into_right_body());
+        }
+        None => (
+            format!("ip:{}", source_ip),
+            manager.anonymous_quota().clone(),
+        ),
+    };
+
+    // Redis being down must not take the API down with it.
+    let decision = match manager.check_rate_limit(&subject, &quota).await {
+        Ok(decision) => Some(decision),
+        Err(e) => {
+            tracing::error!("Failed to check the rate limit of {}: {}", subject, e);
+            None
+        }
+    };
+
+    // The key itself is never stored, only the id it resolved to
+    let usage = quota.api_key_id.map(|api_key_id| LambdaUsageData {
+        request_id: format!("{:032x}", rand::random::<u128>()),
+        api_key: api_key_id.to_string(),
+        stage_name: String::new(),
+        lambda_name: String::new(),
+        capacity: 1.0,
+        exec_time: 0,
+        response_status: 0,
+        params: serde_urlencoded::from_str(req.query_string()).unwrap_or_default(),
+        http_method: req.method().to_string(),
+        http_path: req.path().to_string(),
+        source_ip,
+    });
+
+    let mut response = match &decision {
+        Some(decision) if !decision.allowed => {
+            let response =
+                ApiError::RateLimited(format!("Rate limit of the {} tier exceeded", quota.tier))
+                    .error_response();
+            req.into_response(response).map_into_right_body()
+        }
+        _ => next.call(req).await?.map_into_left_body(),
+    };
+
+    if let Some(decision) = &decision {
+        set_rate_limit_headers(response.headers_mut(), decision);
+    }
+
+    if let Some(mut usage) = usage {
+        usage.exec_time = started_at.elapsed().as_millis();
+        usage.response_status = response.status().as_u16() as i32;
+        manager.register_usage(usage);
+    }
+
+    Ok(response)
+}
+
+/// Token of an `Authorization: Bearer <token>` header.
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+}
+
+/// Restricts a route having a `{user_address}` segment to the wallet signed
+/// in with the bearer token, the session being stored in the request
+/// extensions. Every request is rejected when wallet sign-in is disabled.
+pub async fn wallet_session_guard<B: MessageBody + 'static>(
+    req: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<EitherBody<B>>, Error> {
+    let Some(manager) = req.app_data::<web::Data<WalletSessionManager>>().cloned() else {
+        let response =
+            ApiError::Unauthorized("Wallet sign-in is disabled".to_string()).error_response();
+        return Ok(req.into_response(response).map_into_right_body());
+    };
+
+    let session = match bearer_token(req.headers()) {
+        Some(token) => match manager.decode_session(token) {
+            Ok(session) => session,
+            Err(e) => {
+                let response = ApiError::Unauthorized(e).error_response();
+                return Ok(req.into_response(response).map_into_right_body());
+            }
+        },
+        None => {
+            let response =
+                ApiError::Unauthorized("Missing wallet session".to_string()).error_response();
+            return Ok(req.into_response(response).map_into_right_body());
+        }
+    };
+
+    let user_address = req
+        .match_info()
+        .get("user_address")
+        .filter(|address| address.len() > 2)
+        .map(normalize_address);
+    if user_address.as_deref() != Some(session.sub.as_str()) {
+        let response = ApiError::Forbidden("The session belongs to another address".to_string())
+            .error_response();
+        return Ok(req.into_response(response).map_into_right_body());
+    }
+
+    req.extensions_mut().insert(session);
+    Ok(next.call(req).await?.map_into_left_body())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::managers::api_key_manager::{is_in_ranges, parse_trusted_proxy};
+    use actix_web::test::TestRequest;
+
+    fn alb_subnets() -> Vec<ipnet::IpNet> {
+        ["10.0.0.0/16", "192.0.2.10"]
+            .into_iter()
+            .map(|proxy| parse_trusted_proxy(proxy).unwrap())
+            .collect()
+    }
+
+    fn client_ip_of(peer: &str, forwarded_for: Option<&str>) -> IpAddr {
+        let mut request = TestRequest::default();
+        if let Some(forwarded_for) = forwarded_for {
+            request = request.insert_header(("x-forwarded-for", forwarded_for));
+        }
+        let req = request.to_srv_request();
+        let ranges = alb_subnets();
+        forwarded_client_ip(peer.parse().unwrap(), req.headers(), |ip| {
+            is_in_ranges(&ranges, ip)
+        })
+    }
+
+    #[test]
+    fn test_forwarded_header_from_trusted_range() {
+        assert_eq!(
+            client_ip_of("10.0.3.27", Some("203.0.113.7, 10.0.1.5")),
+            "203.0.113.7".parse::<IpAddr>().unwrap()
+        );
+        // A single trusted IP still works next to the ranges
+        assert_eq!(
+            client_ip_of("192.0.2.10", Some("198.51.100.4")),
+            "198.51.100.4".parse::<IpAddr>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_spoofed_forwarded_addresses_are_skipped() {
+        assert_eq!(
+            client_ip_of("10.0.3.27", Some("1.2.3.4, 203.0.113.7")),
+            "203.0.113.7".parse::<IpAddr>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_forwarded_header_from_untrusted_peer_is_ignored() {
+        assert_eq!(
+            client_ip_of("203.0.113.7", Some("1.2.3.4")),
+            "203.0.113.7".parse::<IpAddr>().unwrap()
+        );
+        assert_eq!(
+            client_ip_of("10.1.0.1", Some("1.2.3.4")),
+            "10.1.0.1".parse::<IpAddr>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_trusted_peer_without_header() {
+        assert_eq!(
+            client_ip_of("10.0.3.27", None),
+            "10.0.3.27".parse::<IpAddr>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_admin_credentials() {
+        let admin = AdminCredentials::new("admin".to_string(), "secret".to_string());
+        assert!(admin.matches("admin", "secret"));
+        assert!(!admin.matches("admin", "secreT"));
+        assert!(!admin.matches("admin", ""));
+        assert!(!admin.matches("", "secret"));
+        assert!(!admin.matches("", ""));
+    }
+
+    #[test]
+    fn test_parse_trusted_proxy() {
+        assert!(parse_trusted_proxy("10.0.0.0/16").is_some());
+        assert!(parse_trusted_proxy("10.0.0.1").is_some());
+        assert!(parse_trusted_proxy("fd00::/8").is_some());
+        assert!(parse_trusted_proxy("10.0.0.0/33").is_none());
+        assert!(parse_trusted_proxy("load-balancer").is_none());
     }
 }
@@ -1,2 +1,4 @@
+pub mod api_key;
 pub mod auth;
 pub mod token;
+pub mod webhook;
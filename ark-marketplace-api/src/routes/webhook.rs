@@ -0,0 +1,22 @@
+use crate::handlers::webhook_handler;
+use crate::routes::auth::validator;
+use actix_web::web;
+use actix_web_httpauth::middleware::HttpAuthentication;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    let auth = HttpAuthentication::basic(validator);
+
+    cfg.service(
+        web::scope("/webhooks")
+            .wrap(auth)
+            .route("", web::post().to(webhook_handler::create_webhook))
+            .route("", web::get().to(webhook_handler::get_webhooks))
+            .route("/{id}", web::get().to(webhook_handler::get_webhook))
+            .route("/{id}", web::put().to(webhook_handler::update_webhook))
+            .route("/{id}", web::delete().to(webhook_handler::delete_webhook))
+            .route(
+                "/{id}/dead-letters",
+                web::get().to(webhook_handler::get_webhook_dead_letter_log),
+            ),
+    );
+}
@@ -0,0 +1,78 @@
+use crate::models::api_key::ApiKey;
+use reqwest::Client;
+use serde_json::Value;
+
+#[tokio::test]
+async fn test_get_api_keys() {
+    let client = Client::new();
+    let user = std::env::var("API_USER").unwrap_or_default();
+    let password = std::env::var("API_PASSWORD").unwrap_or_default();
+
+    let url = "http://localhost:8080/api-keys".to_string();
+    let res = client
+        .get(&url)
+        .basic_auth(user, Some(password))
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert!(
+        res.status().is_success(),
+        "Request failed with status: {}",
+        res.status()
+    );
+
+    let body: Value = res.json().await.expect("Failed to parse response body");
+    let api_keys: Vec<ApiKey> =
+        serde_json::from_value(body["data"].clone()).expect("Failed to parse API keys");
+    assert!(api_keys
+        .iter()
+        .all(|api_key| api_key.key_prefix.starts_with("ark_")));
+}
+
+#[tokio::test]
+async fn test_get_api_keys_unauthorized() {
+    let client = Client::new();
+
+    let url = "http://localhost:8080/api-keys".to_string();
+    let res = client
+        .get(&url)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(res.status(), reqwest::StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_invalid_api_key() {
+    let client = Client::new();
+
+    let url = "http://localhost:8080/chains".to_string();
+    let res = client
+        .get(&url)
+        .header("x-api-key", "ark_invalid")
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(res.status(), reqwest::StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_rate_limit_headers() {
+    let client = Client::new();
+
+    let url = "http://localhost:8080/chains".to_string();
+    let res = client
+        .get(&url)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert!(
+        res.headers().contains_key("x-ratelimit-limit"),
+        "Missing rate limit headers"
+    );
+    assert!(res.headers().contains_key("x-ratelimit-remaining"));
+}
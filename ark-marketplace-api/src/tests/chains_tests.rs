@@ -0,0 +1,65 @@
+use crate::models::chain::Chain;
+use reqwest::{Client, StatusCode};
+
+const ADDRESS: &str = "0x05dbdedc203e92749e2e746e2d40a768d966bd243df04a6b712e222bc040a9af";
+const CHAIN_ID: &str = "0x534e5f4d41494e";
+const UNSUPPORTED_CHAIN_ID: &str = "0x1234";
+
+#[tokio::test]
+async fn test_get_chains() {
+    let client = Client::new();
+
+    let url = "http://localhost:8080/chains";
+    let res = client
+        .get(url)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert!(
+        res.status().is_success(),
+        "Request failed with status: {}",
+        res.status()
+    );
+
+    let body: serde_json::Value = res.json().await.expect("Failed to parse response body");
+    let chains: Vec<Chain> =
+        serde_json::from_value(body["data"].clone()).expect("Failed to deserialize data field");
+
+    assert!(chains.iter().any(|chain| chain.chain_id == CHAIN_ID));
+    assert_eq!(chains.iter().filter(|chain| chain.is_default).count(), 1);
+}
+
+#[tokio::test]
+async fn test_get_collection_tokens_unsupported_chain() {
+    let client = Client::new();
+
+    let url = format!(
+        "http://localhost:8080/collections/{}/{}/tokens",
+        ADDRESS, UNSUPPORTED_CHAIN_ID
+    );
+    let res = client
+        .get(&url)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_get_collection_activity_unsupported_chain() {
+    let client = Client::new();
+
+    let url = format!(
+        "http://localhost:8080/collections/{}/activity?chain_id={}",
+        ADDRESS, UNSUPPORTED_CHAIN_ID
+    );
+    let res = client
+        .get(&url)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
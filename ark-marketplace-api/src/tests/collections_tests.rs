@@ -1,4 +1,7 @@
-use crate::models::collection::CollectionFullData;
+use crate::models::collection::{
+    CollectionChartData, CollectionFullData, OfferDepthLevel, OrderBook,
+};
+use crate::models::export::ActivityExportRow;
 use reqwest::Client;
 use serde_json::Value;
 
@@ -52,6 +55,124 @@ async fn test_get_collection_activity() {
     println!("{:?}", body);
 }
 
+#[tokio::test]
+async fn test_get_collection_activity_cursor() {
+    let client = Client::new();
+
+    let url = format!(
+        "http://localhost:8080/collections/{}/activity?items_per_page=2",
+        ADDRESS
+    );
+    let first_page: Value = client
+        .get(&url)
+        .send()
+        .await
+        .expect("Failed to send request")
+        .json()
+        .await
+        .expect("Failed to parse response body");
+
+    let next_cursor = match first_page["next_cursor"].as_str() {
+        Some(next_cursor) => next_cursor,
+        // The collection has a single page of activity
+        None => return,
+    };
+
+    let res = client
+        .get(format!("{}&cursor={}", url, next_cursor))
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert!(
+        res.status().is_success(),
+        "Request failed with status: {}",
+        res.status()
+    );
+
+    let second_page: Value = res.json().await.expect("Failed to parse response body");
+    let first_timestamp = second_page["data"][0]["time_stamp"]
+        .as_i64()
+        .expect("second page should not be empty");
+    let last_timestamp = first_page["data"][1]["time_stamp"]
+        .as_i64()
+        .expect("time_stamp should be a number");
+    assert!(first_timestamp <= last_timestamp);
+}
+
+#[tokio::test]
+async fn test_get_collection_activity_invalid_cursor() {
+    let client = Client::new();
+
+    let url = format!(
+        "http://localhost:8080/collections/{}/activity?cursor=not-a-cursor",
+        ADDRESS
+    );
+    let res = client
+        .get(&url)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(res.status(), reqwest::StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_export_collection_activity() {
+    let client = Client::new();
+
+    let url = format!(
+        "http://localhost:8080/collections/{}/{}/activity/export?format=csv",
+        ADDRESS, CHAIN_ID
+    );
+    let res = client
+        .get(&url)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert!(
+        res.status().is_success(),
+        "Request failed with status: {}",
+        res.status()
+    );
+    assert_eq!(
+        res.headers()["content-type"].to_str().unwrap_or_default(),
+        "text/csv; charset=utf-8"
+    );
+
+    let body = res.text().await.expect("Failed to read response body");
+    let mut lines = body.lines();
+    assert_eq!(
+        lines.next(),
+        Some(ActivityExportRow::CSV_HEADER),
+        "the export should start with the header"
+    );
+    let columns = ActivityExportRow::CSV_HEADER.split(',').count();
+    for line in lines.filter(|line| !line.contains('"')) {
+        assert_eq!(line.split(',').count(), columns);
+    }
+}
+
+#[tokio::test]
+async fn test_export_collection_activity_invalid_range() {
+    let client = Client::new();
+
+    let url = format!(
+        "http://localhost:8080/collections/{}/{}/activity/export?from=20&to=10",
+        ADDRESS, CHAIN_ID
+    );
+    let res = client
+        .get(&url)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(res.status(), reqwest::StatusCode::BAD_REQUEST);
+
+    let body: Value = res.json().await.expect("Failed to parse response body");
+    assert_eq!(body["code"], "INVALID_PARAMETER");
+}
+
 #[tokio::test]
 async fn test_get_collection() {
     let client = Client::new();
@@ -117,3 +238,141 @@ async fn test_search_collections() {
     let body: Value = res.json().await.expect("Failed to parse response body");
     println!("{:?}", body);
 }
+
+#[tokio::test]
+async fn test_get_collection_charts() {
+    let client = Client::new();
+
+    let url = format!(
+        "http://localhost:8080/collections/{}/{}/charts?interval=1h",
+        ADDRESS, CHAIN_ID
+    );
+    let res = client
+        .get(&url)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert!(
+        res.status().is_success(),
+        "Request failed with status: {}",
+        res.status()
+    );
+
+    let body: Value = res.json().await.expect("Failed to parse response body");
+    let _charts_data: Vec<CollectionChartData> =
+        serde_json::from_value(body["data"].clone()).expect("Failed to deserialize data field");
+    assert_eq!(body["interval"], "1h");
+}
+
+#[tokio::test]
+async fn test_get_collection_charts_invalid_interval() {
+    let client = Client::new();
+
+    let url = format!(
+        "http://localhost:8080/collections/{}/{}/charts?interval=5m",
+        ADDRESS, CHAIN_ID
+    );
+    let res = client
+        .get(&url)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(res.status(), reqwest::StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_get_collection_offers() {
+    let client = Client::new();
+
+    let url = format!(
+        "http://localhost:8080/collections/{}/{}/offers?user={}",
+        ADDRESS, CHAIN_ID, USER_ADDRESS
+    );
+    let res = client
+        .get(&url)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert!(
+        res.status().is_success(),
+        "Request failed with status: {}",
+        res.status()
+    );
+
+    let body: Value = res.json().await.expect("Failed to parse response body");
+    let _depth: Vec<OfferDepthLevel> =
+        serde_json::from_value(body["depth"].clone()).expect("Failed to deserialize depth field");
+    let offers = body["data"].as_array().expect("data should be an array");
+    assert!(offers
+        .iter()
+        .all(|offer| offer["acceptable_token_ids"].is_array()));
+}
+
+#[tokio::test]
+async fn test_get_collection_order_book() {
+    let client = Client::new();
+
+    let url = format!(
+        "http://localhost:8080/collections/{}/{}/depth?levels=10&bucket_size=0.01",
+        ADDRESS, CHAIN_ID
+    );
+    let res = client
+        .get(&url)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert!(
+        res.status().is_success(),
+        "Request failed with status: {}",
+        res.status()
+    );
+
+    let body: Value = res.json().await.expect("Failed to parse response body");
+    let order_books: Vec<OrderBook> =
+        serde_json::from_value(body["data"].clone()).expect("Failed to deserialize data field");
+    for order_book in order_books {
+        assert!(order_book.bids.len() <= 10 && order_book.asks.len() <= 10);
+    }
+}
+
+#[tokio::test]
+async fn test_get_collections_invalid_sort() {
+    let client = Client::new();
+
+    let url = "http://localhost:8080/collections?sort=name";
+    let res = client
+        .get(url)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(res.status(), reqwest::StatusCode::BAD_REQUEST);
+
+    let body: Value = res.json().await.expect("Failed to parse response body");
+    assert_eq!(body["code"], "INVALID_PARAMETER");
+    assert_eq!(body["parameter"], "sort");
+}
+
+#[tokio::test]
+async fn test_get_collection_invalid_address() {
+    let client = Client::new();
+
+    let url = format!(
+        "http://localhost:8080/collections/not-an-address/{}",
+        CHAIN_ID
+    );
+    let res = client
+        .get(&url)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(res.status(), reqwest::StatusCode::BAD_REQUEST);
+
+    let body: Value = res.json().await.expect("Failed to parse response body");
+    assert_eq!(body["code"], "INVALID_ADDRESS");
+}
@@ -0,0 +1,26 @@
+use reqwest::Client;
+
+#[tokio::test]
+async fn test_stream_events() {
+    let client = Client::new();
+
+    let url = "http://localhost:8080/events/stream?types[0]=LISTING&types[1]=SALE".to_string();
+    let res = client
+        .get(&url)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert!(
+        res.status().is_success(),
+        "Request failed with status: {}",
+        res.status()
+    );
+
+    let content_type = res
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    assert_eq!(content_type, "text/event-stream");
+}
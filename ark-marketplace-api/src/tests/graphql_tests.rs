@@ -0,0 +1,177 @@
+use super::wallet_sessions_tests::test_wallet_session;
+use reqwest::Client;
+use serde_json::{json, Value};
+
+const ADDRESS: &str = "0x05dbdedc203e92749e2e746e2d40a768d966bd243df04a6b712e222bc040a9af";
+
+async fn post_query(query: &str) -> Value {
+    post_query_with_session(query, None).await
+}
+
+async fn post_query_with_session(query: &str, session: Option<&str>) -> Value {
+    let client = Client::new();
+
+    let mut request = client
+        .post("http://localhost:8080/graphql")
+        .json(&json!({ "query": query }));
+    if let Some(session) = session {
+        request = request.bearer_auth(session);
+    }
+    let res = request.send().await.expect("Failed to send request");
+
+    assert!(
+        res.status().is_success(),
+        "Request failed with status: {}",
+        res.status()
+    );
+
+    res.json().await.expect("Failed to parse response body")
+}
+
+#[tokio::test]
+async fn test_graphql_collection_tokens() {
+    let query = format!(
+        r#"{{
+            collection(address: "{}") {{
+                address
+                tokens(first: 5) {{
+                    nextCursor
+                    items {{
+                        tokenId
+                        listing {{ startAmount currency {{ symbol }} }}
+                        collection {{ address }}
+                    }}
+                }}
+                activity(first: 5) {{
+                    items {{ activityType token {{ tokenId }} }}
+                }}
+            }}
+        }}"#,
+        ADDRESS
+    );
+    let body = post_query(&query).await;
+
+    assert!(body["errors"].is_null(), "Unexpected errors: {}", body);
+    let collection = &body["data"]["collection"];
+    assert_eq!(collection["address"], ADDRESS);
+
+    let tokens = collection["tokens"]["items"]
+        .as_array()
+        .expect("tokens should be a list");
+    assert!(tokens.len() <= 5);
+    for token in tokens {
+        assert_eq!(token["collection"]["address"], ADDRESS);
+    }
+}
+
+#[tokio::test]
+async fn test_graphql_invalid_cursor() {
+    let query = format!(
+        r#"{{ collection(address: "{}") {{ tokens(after: "invalid") {{ nextCursor }} }} }}"#,
+        ADDRESS
+    );
+    let body = post_query(&query).await;
+
+    assert_eq!(body["errors"][0]["message"], "Invalid cursor");
+}
+
+#[tokio::test]
+async fn test_graphql_depth_limit() {
+    let query = format!(
+        r#"{{
+            token(address: "{}", tokenId: "1") {{
+                collection {{ tokens {{ items {{ collection {{ activity {{ items {{
+                    token {{ collection {{ tokens(first: 1) {{ totalCount }} }} }}
+                }} }} }} }} }} }}
+            }}
+        }}"#,
+        ADDRESS
+    );
+    let body = post_query(&query).await;
+
+    assert!(body["data"].is_null());
+    assert_eq!(body["errors"][0]["message"], "Query is nested too deep.");
+}
+
+#[tokio::test]
+async fn test_graphql_complexity_limit() {
+    let query = format!(
+        r#"{{
+            collection(address: "{}") {{
+                tokens(first: 100) {{
+                    items {{ collection {{ tokens(first: 100) {{ items {{ tokenId }} }} }} }}
+                }}
+            }}
+        }}"#,
+        ADDRESS
+    );
+    let body = post_query(&query).await;
+
+    assert!(body["data"].is_null());
+    assert_eq!(body["errors"][0]["message"], "Query is too complex.");
+}
+
+#[tokio::test]
+async fn test_graphql_portfolio_total_value_requires_session() {
+    let user_address = "0x00e4769a4d2f7f69c70951a003eba5c32707cef3cdfb6b27ca63567f51cdd078";
+    let query = format!(
+        r#"{{ portfolio(address: "{}") {{ address totalValue }} }}"#,
+        user_address
+    );
+
+    let body = post_query(&query).await;
+    assert_eq!(body["errors"][0]["message"], "Missing wallet session");
+
+    let session = test_wallet_session();
+    let body = post_query_with_session(&query, Some(&session)).await;
+    assert!(body["errors"].is_null(), "Unexpected errors: {}", body);
+}
+
+#[tokio::test]
+async fn test_graphql_token_offers_pages() {
+    let query = format!(
+        r#"{{
+            collection(address: "{}") {{
+                tokens(first: 10) {{
+                    items {{
+                        tokenId
+                        offers(first: 1) {{ nextCursor totalCount items {{ offerId currency {{ symbol }} }} }}
+                    }}
+                }}
+            }}
+        }}"#,
+        ADDRESS
+    );
+    let body = post_query(&query).await;
+    assert!(body["errors"].is_null(), "Unexpected errors: {}", body);
+
+    let tokens = body["data"]["collection"]["tokens"]["items"]
+        .as_array()
+        .expect("tokens should be a list");
+    for token in tokens {
+        let offers = &token["offers"];
+        let total_count = offers["totalCount"].as_i64().unwrap();
+        assert!(offers["items"].as_array().unwrap().len() as i64 <= total_count.min(1));
+        assert_eq!(offers["nextCursor"].is_string(), total_count > 1);
+
+        // The following page is read without the batch lookup
+        if let Some(next_cursor) = offers["nextCursor"].as_str() {
+            let query = format!(
+                r#"{{ token(address: "{}", tokenId: "{}") {{ offers(first: 1, after: "{}") {{ items {{ offerId }} }} }} }}"#,
+                ADDRESS,
+                token["tokenId"].as_str().unwrap(),
+                next_cursor
+            );
+            let next_page = post_query(&query).await;
+            assert!(
+                next_page["errors"].is_null(),
+                "Unexpected errors: {}",
+                next_page
+            );
+            assert_ne!(
+                next_page["data"]["token"]["offers"]["items"][0]["offerId"],
+                offers["items"][0]["offerId"]
+            );
+        }
+    }
+}
@@ -9,3 +9,24 @@ pub mod collections_tests;
 
 #[cfg(test)]
 mod default_tests;
+
+#[cfg(test)]
+mod events_tests;
+
+#[cfg(test)]
+mod webhooks_tests;
+
+#[cfg(test)]
+mod chains_tests;
+
+#[cfg(test)]
+mod api_keys_tests;
+
+#[cfg(test)]
+mod graphql_tests;
+
+#[cfg(test)]
+mod watchlists_tests;
+
+#[cfg(test)]
+mod wallet_sessions_tests;
@@ -1,4 +1,7 @@
-use crate::models::portfolio::{OfferApiData, StatsData};
+use super::wallet_sessions_tests::test_wallet_session;
+use crate::models::export::ActivityExportRow;
+use crate::models::portfolio::{OfferApiData, PortfolioValuation, StatsData};
+use crate::models::tax_report::{LotMethod, TaxDisposal, TaxReport};
 use reqwest::Client;
 use serde_json::Value;
 
@@ -24,6 +27,40 @@ async fn test_get_activity() {
     println!("{:?}", body);
 }
 
+#[tokio::test]
+async fn test_export_activity() {
+    let client = Client::new();
+    let user_address = "0x00e4769a4d2f7f69c70951a003eba5c32707cef3cdfb6b27ca63567f51cdd078";
+
+    let url = format!(
+        "http://localhost:8080/portfolio/{}/activity/export?format=ndjson",
+        user_address
+    );
+    let res = client
+        .get(&url)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert!(
+        res.status().is_success(),
+        "Request failed with status: {}",
+        res.status()
+    );
+
+    let body = res.text().await.expect("Failed to read response body");
+    let mut previous_time_stamp = i64::MIN;
+    for line in body.lines() {
+        let row: ActivityExportRow =
+            serde_json::from_str(line).expect("Failed to deserialize export row");
+        assert!(
+            row.time_stamp >= previous_time_stamp,
+            "rows should be sorted oldest first"
+        );
+        previous_time_stamp = row.time_stamp;
+    }
+}
+
 #[tokio::test]
 async fn test_get_tokens_portfolio() {
     let client = Client::new();
@@ -54,6 +91,7 @@ async fn test_get_tokens_portfolio_offers() {
     let url = format!("http://localhost:8080/portfolio/{}/offers", user_address);
     let res = client
         .get(&url)
+        .bearer_auth(test_wallet_session())
         .send()
         .await
         .expect("Failed to send request");
@@ -80,6 +118,7 @@ async fn test_get_tokens_portfolio_stats() {
     let url = format!("http://localhost:8080/portfolio/{}/stats", user_address);
     let res = client
         .get(&url)
+        .bearer_auth(test_wallet_session())
         .send()
         .await
         .expect("Failed to send request");
@@ -97,3 +136,103 @@ async fn test_get_tokens_portfolio_stats() {
     let _data: StatsData =
         serde_json::from_value(data.clone()).expect("Failed to deserialize data field");
 }
+
+#[tokio::test]
+async fn test_get_portfolio_valuation() {
+    let client = Client::new();
+    let user_address = "0x00e4769a4d2f7f69c70951a003eba5c32707cef3cdfb6b27ca63567f51cdd078";
+
+    let url = format!(
+        "http://localhost:8080/portfolio/{}/valuation?interval=1d",
+        user_address
+    );
+    let res = client
+        .get(&url)
+        .bearer_auth(test_wallet_session())
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert!(
+        res.status().is_success(),
+        "Request failed with status: {}",
+        res.status()
+    );
+
+    let body: serde_json::Value = res.json().await.expect("Failed to parse response body");
+    let valuation: PortfolioValuation =
+        serde_json::from_value(body["data"].clone()).expect("Failed to deserialize data field");
+    assert_eq!(body["interval"], "1d");
+    assert!(!valuation.history.is_empty());
+}
+
+#[tokio::test]
+async fn test_get_portfolio_valuation_missing_session() {
+    let client = Client::new();
+    let user_address = "0x00e4769a4d2f7f69c70951a003eba5c32707cef3cdfb6b27ca63567f51cdd078";
+
+    let url = format!("http://localhost:8080/portfolio/{}/valuation", user_address);
+    let res = client
+        .get(&url)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(res.status(), reqwest::StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_get_tax_report() {
+    let client = Client::new();
+    let user_address = "0x00e4769a4d2f7f69c70951a003eba5c32707cef3cdfb6b27ca63567f51cdd078";
+
+    let url = format!(
+        "http://localhost:8080/portfolio/{}/tax-report?year=2024&method=hifo",
+        user_address
+    );
+    let res = client
+        .get(&url)
+        .bearer_auth(test_wallet_session())
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert!(
+        res.status().is_success(),
+        "Request failed with status: {}",
+        res.status()
+    );
+
+    let body: Value = res.json().await.expect("Failed to parse response body");
+    let report: TaxReport =
+        serde_json::from_value(body["data"].clone()).expect("Failed to deserialize data field");
+    assert_eq!(report.summary.year, 2024);
+    assert_eq!(report.summary.method, LotMethod::Hifo);
+    assert_eq!(report.summary.disposal_count, report.disposals.len() as i64);
+}
+
+#[tokio::test]
+async fn test_get_tax_report_csv() {
+    let client = Client::new();
+    let user_address = "0x00e4769a4d2f7f69c70951a003eba5c32707cef3cdfb6b27ca63567f51cdd078";
+
+    let url = format!(
+        "http://localhost:8080/portfolio/{}/tax-report?year=2024&format=csv",
+        user_address
+    );
+    let res = client
+        .get(&url)
+        .bearer_auth(test_wallet_session())
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert!(
+        res.status().is_success(),
+        "Request failed with status: {}",
+        res.status()
+    );
+
+    let body = res.text().await.expect("Failed to read response body");
+    assert_eq!(body.lines().next(), Some(TaxDisposal::CSV_HEADER));
+}
@@ -1,4 +1,4 @@
-use crate::models::token::TokenMarketData;
+use crate::models::token::{TokenBatchData, TokenMarketData};
 use reqwest::Client;
 use serde_json::Value;
 
@@ -28,6 +28,88 @@ async fn test_get_tokens() {
     println!("{:?}", body);
 }
 
+#[tokio::test]
+async fn test_get_tokens_sorted_by_rarity() {
+    let client = Client::new();
+    let address = "0x05dbdedc203e92749e2e746e2d40a768d966bd243df04a6b712e222bc040a9af";
+    let chain_id = "0x534e5f4d41494e";
+
+    let url = format!(
+        "http://localhost:8080/collections/{}/{}/tokens?sort=rarity&direction=asc",
+        address, chain_id
+    );
+    let res = client
+        .get(&url)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert!(
+        res.status().is_success(),
+        "Request failed with status: {}",
+        res.status()
+    );
+
+    let body: Value = res.json().await.expect("Failed to parse response body");
+    let ranks: Vec<i64> = body["data"]
+        .as_array()
+        .expect("data should be an array")
+        .iter()
+        .filter_map(|token| token["rarity_rank"].as_i64())
+        .collect();
+    assert!(
+        ranks.windows(2).all(|pair| pair[0] <= pair[1]),
+        "Tokens are not sorted by rarity rank: {:?}",
+        ranks
+    );
+}
+
+#[tokio::test]
+async fn test_get_tokens_cursor_pagination() {
+    let client = Client::new();
+    let address = "0x05dbdedc203e92749e2e746e2d40a768d966bd243df04a6b712e222bc040a9af";
+    let chain_id = "0x534e5f4d41494e";
+
+    let url = format!(
+        "http://localhost:8080/collections/{}/{}/tokens?items_per_page=2",
+        address, chain_id
+    );
+    let mut token_ids = Vec::new();
+    let mut cursor: Option<String> = None;
+    for _ in 0..5 {
+        let page_url = match &cursor {
+            Some(cursor) => format!("{}&cursor={}", url, cursor),
+            None => url.clone(),
+        };
+        let res = client
+            .get(&page_url)
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert!(
+            res.status().is_success(),
+            "Request failed with status: {}",
+            res.status()
+        );
+
+        let body: Value = res.json().await.expect("Failed to parse response body");
+        for token in body["data"].as_array().expect("data should be an array") {
+            let token_id = token["token_id"].as_str().expect("token_id should be set");
+            assert!(
+                !token_ids.contains(&token_id.to_string()),
+                "token {} returned twice",
+                token_id
+            );
+            token_ids.push(token_id.to_string());
+        }
+
+        cursor = body["next_cursor"].as_str().map(String::from);
+        if cursor.is_none() {
+            break;
+        }
+    }
+}
+
 #[tokio::test]
 async fn test_get_token() {
     let client = Client::new();
@@ -86,6 +168,61 @@ async fn test_get_token_market() {
         serde_json::from_value(data.clone()).expect("Failed to deserialize data field");
 }
 
+#[tokio::test]
+async fn test_get_tokens_batch() {
+    let client = Client::new();
+    let address = "0x05dbdedc203e92749e2e746e2d40a768d966bd243df04a6b712e222bc040a9af";
+    let token_ids = ["445743458073", "0"];
+
+    let res = client
+        .post("http://localhost:8080/tokens/batch")
+        .json(&serde_json::json!({
+            "tokens": token_ids
+                .iter()
+                .map(|token_id| serde_json::json!({
+                    "contract_address": address,
+                    "token_id": token_id,
+                }))
+                .collect::<Vec<_>>(),
+        }))
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert!(
+        res.status().is_success(),
+        "Request failed with status: {}",
+        res.status()
+    );
+
+    let body: Value = res.json().await.expect("Failed to parse response body");
+    let tokens: Vec<TokenBatchData> =
+        serde_json::from_value(body["data"].clone()).expect("Failed to deserialize data field");
+    assert!(tokens.len() <= token_ids.len());
+    for token in &tokens {
+        assert!(token_ids.contains(&token.token.token_id.as_str()));
+    }
+}
+
+#[tokio::test]
+async fn test_get_tokens_batch_invalid_token() {
+    let client = Client::new();
+
+    let res = client
+        .post("http://localhost:8080/tokens/batch")
+        .json(&serde_json::json!({
+            "tokens": [{ "contract_address": "0x1", "token_id": "not-a-number" }],
+        }))
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(res.status(), reqwest::StatusCode::UNPROCESSABLE_ENTITY);
+
+    let body: Value = res.json().await.expect("Failed to parse response body");
+    assert_eq!(body["code"], "INVALID_BODY");
+}
+
 #[tokio::test]
 async fn test_get_token_offers() {
     let client = Client::new();
@@ -139,3 +276,61 @@ async fn test_get_token_activity() {
     let body: Value = res.json().await.expect("Failed to parse response body");
     println!("{:?}", body);
 }
+
+#[tokio::test]
+async fn test_get_token_price_history() {
+    let client = Client::new();
+    let address = "0x05dbdedc203e92749e2e746e2d40a768d966bd243df04a6b712e222bc040a9af";
+    let chain_id = "0x534e5f4d41494e";
+    let token_id = "445743458073";
+
+    let url = format!(
+        "http://localhost:8080/tokens/{}/{}/{}/price-history?limit=5",
+        address, chain_id, token_id
+    );
+    let res = client
+        .get(&url)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert!(
+        res.status().is_success(),
+        "Request failed with status: {}",
+        res.status()
+    );
+
+    let body: Value = res.json().await.expect("Failed to parse response body");
+    let sales = body["data"].as_array().expect("data should be an array");
+    assert!(
+        sales.len() <= 5,
+        "limit was not applied: {} sales",
+        sales.len()
+    );
+    assert!(
+        body["stats"]["sales_count"].as_i64().unwrap_or_default() >= sales.len() as i64,
+        "sales_count should cover every sale"
+    );
+}
+
+#[tokio::test]
+async fn test_get_tokens_invalid_filters() {
+    let client = Client::new();
+    let address = "0x05dbdedc203e92749e2e746e2d40a768d966bd243df04a6b712e222bc040a9af";
+    let chain_id = "0x534e5f4d41494e";
+
+    let url = format!(
+        "http://localhost:8080/collections/{}/{}/tokens?filters=%7B%22traits%22%3A1%7D",
+        address, chain_id
+    );
+    let res = client
+        .get(&url)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(res.status(), reqwest::StatusCode::UNPROCESSABLE_ENTITY);
+
+    let body: Value = res.json().await.expect("Failed to parse response body");
+    assert_eq!(body["code"], "INVALID_FILTERS");
+}
@@ -0,0 +1,84 @@
+use crate::models::wallet_session::SignInChallenge;
+use reqwest::Client;
+use serde_json::{json, Value};
+
+/// Session of the test wallet for the private routes, issued by
+/// `/auth/verify` and passed in `TEST_WALLET_SESSION`.
+pub(super) fn test_wallet_session() -> String {
+    std::env::var("TEST_WALLET_SESSION").expect("TEST_WALLET_SESSION must be set")
+}
+
+#[tokio::test]
+async fn test_create_nonce() {
+    let client = Client::new();
+    let address = "0x00e4769a4d2f7f69c70951a003eba5c32707cef3cdfb6b27ca63567f51cdd078";
+
+    let res = client
+        .post("http://localhost:8080/auth/nonce")
+        .json(&json!({ "address": address }))
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert!(
+        res.status().is_success(),
+        "Request failed with status: {}",
+        res.status()
+    );
+
+    let body: Value = res.json().await.expect("Failed to parse response body");
+    let challenge: SignInChallenge =
+        serde_json::from_value(body["data"].clone()).expect("Failed to parse challenge");
+    assert_eq!(challenge.typed_data["primaryType"], "SignIn");
+    assert_eq!(challenge.typed_data["message"]["nonce"], challenge.nonce);
+    assert_eq!(challenge.typed_data["message"]["address"], address);
+}
+
+#[tokio::test]
+async fn test_verify_unknown_nonce() {
+    let client = Client::new();
+
+    let res = client
+        .post("http://localhost:8080/auth/verify")
+        .json(&json!({
+            "address": "0x00e4769a4d2f7f69c70951a003eba5c32707cef3cdfb6b27ca63567f51cdd078",
+            "nonce": "0x0",
+            "signature": ["0x1", "0x2"],
+        }))
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(res.status(), reqwest::StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_private_route_invalid_session() {
+    let client = Client::new();
+    let user_address = "0x00e4769a4d2f7f69c70951a003eba5c32707cef3cdfb6b27ca63567f51cdd078";
+
+    let url = format!("http://localhost:8080/watchlists/{}", user_address);
+    let res = client
+        .get(&url)
+        .bearer_auth("not-a-session")
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(res.status(), reqwest::StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_private_route_missing_session() {
+    let client = Client::new();
+    let user_address = "0x00e4769a4d2f7f69c70951a003eba5c32707cef3cdfb6b27ca63567f51cdd078";
+
+    let url = format!("http://localhost:8080/watchlists/{}", user_address);
+    let res = client
+        .get(&url)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(res.status(), reqwest::StatusCode::UNAUTHORIZED);
+}
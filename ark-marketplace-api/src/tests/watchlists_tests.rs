@@ -0,0 +1,79 @@
+use super::wallet_sessions_tests::test_wallet_session;
+use crate::models::watchlist::{WatchlistEventType, WatchlistFeedItem, WatchlistItem};
+use reqwest::Client;
+use serde_json::Value;
+
+#[tokio::test]
+async fn test_get_watchlist() {
+    let client = Client::new();
+    let user_address = "0x00e4769a4d2f7f69c70951a003eba5c32707cef3cdfb6b27ca63567f51cdd078";
+
+    let url = format!("http://localhost:8080/watchlists/{}", user_address);
+    let res = client
+        .get(&url)
+        .bearer_auth(test_wallet_session())
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert!(
+        res.status().is_success(),
+        "Request failed with status: {}",
+        res.status()
+    );
+
+    let body: Value = res.json().await.expect("Failed to parse response body");
+    let items: Vec<WatchlistItem> =
+        serde_json::from_value(body["data"].clone()).expect("Failed to parse watchlist");
+    assert!(items.iter().all(|item| item.user_address == user_address));
+}
+
+#[tokio::test]
+async fn test_get_watchlist_feed() {
+    let client = Client::new();
+    let user_address = "0x00e4769a4d2f7f69c70951a003eba5c32707cef3cdfb6b27ca63567f51cdd078";
+
+    let url = format!(
+        "http://localhost:8080/watchlists/{}/feed?types[]=SALE&types[]=PRICE_DROP",
+        user_address
+    );
+    let res = client
+        .get(&url)
+        .bearer_auth(test_wallet_session())
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert!(
+        res.status().is_success(),
+        "Request failed with status: {}",
+        res.status()
+    );
+
+    let body: Value = res.json().await.expect("Failed to parse response body");
+    let feed: Vec<WatchlistFeedItem> =
+        serde_json::from_value(body["data"].clone()).expect("Failed to parse feed");
+    assert!(feed.iter().all(|item| matches!(
+        item.event_type,
+        WatchlistEventType::Sale | WatchlistEventType::PriceDrop
+    )));
+}
+
+#[tokio::test]
+async fn test_get_watchlist_feed_invalid_type() {
+    let client = Client::new();
+    let user_address = "0x00e4769a4d2f7f69c70951a003eba5c32707cef3cdfb6b27ca63567f51cdd078";
+
+    let url = format!(
+        "http://localhost:8080/watchlists/{}/feed?types[]=DELISTING",
+        user_address
+    );
+    let res = client
+        .get(&url)
+        .bearer_auth(test_wallet_session())
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(res.status(), reqwest::StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,45 @@
+use crate::models::webhook::WebhookSubscription;
+use reqwest::Client;
+use serde_json::Value;
+
+#[tokio::test]
+async fn test_get_webhooks() {
+    let client = Client::new();
+    let user = std::env::var("API_USER").unwrap_or_default();
+    let password = std::env::var("API_PASSWORD").unwrap_or_default();
+
+    let url = "http://localhost:8080/webhooks".to_string();
+    let res = client
+        .get(&url)
+        .basic_auth(user, Some(password))
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert!(
+        res.status().is_success(),
+        "Request failed with status: {}",
+        res.status()
+    );
+
+    let body: Value = res.json().await.expect("Failed to parse response body");
+    let webhooks: Vec<WebhookSubscription> =
+        serde_json::from_value(body["data"].clone()).expect("Failed to parse webhooks");
+    assert!(webhooks
+        .iter()
+        .all(|webhook| webhook.url.starts_with("http")));
+}
+
+#[tokio::test]
+async fn test_get_webhooks_unauthorized() {
+    let client = Client::new();
+
+    let url = "http://localhost:8080/webhooks".to_string();
+    let res = client
+        .get(&url)
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    assert_eq!(res.status(), reqwest::StatusCode::UNAUTHORIZED);
+}
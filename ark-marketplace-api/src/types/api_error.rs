@@ -0,0 +1,205 @@
+use actix_web::error::{JsonPayloadError, PathError, QueryPayloadError};
+use actix_web::http::StatusCode;
+use actix_web::{web, HttpResponse, ResponseError};
+use serde::Serialize;
+use std::fmt;
+
+/// Identifies the kind of error returned. Codes are part of the API contract,
+/// clients may match on them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    InvalidParameter,
+    InvalidAddress,
+    UnsupportedChain,
+    InvalidCursor,
+    MalformedBody,
+    InvalidBody,
+    InvalidFilters,
+    Unauthorized,
+    Forbidden,
+    NotFound,
+    RateLimited,
+    InternalError,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[schema(example = json!({
+    "code": "INVALID_PARAMETER",
+    "message": "Invalid direction: up, expected one of asc, desc",
+    "parameter": "direction"
+}))]
+pub struct ErrorResponse {
+    pub code: ErrorCode,
+    pub message: String,
+    /// Query or path parameter the error is about, when there is one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameter: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum ApiError {
+    /// A query or path parameter can't be parsed or has an unsupported value
+    InvalidParameter {
+        parameter: Option<String>,
+        message: String,
+    },
+    InvalidAddress {
+        parameter: String,
+        value: String,
+    },
+    UnsupportedChain(String),
+    InvalidCursor(String),
+    /// The body isn't JSON
+    MalformedBody(String),
+    /// The body is JSON but doesn't describe an acceptable request
+    InvalidBody(String),
+    /// The `filters` parameter can't be decoded into traits
+    InvalidFilters(String),
+    Unauthorized(String),
+    /// Authenticated, but not allowed to access the resource
+    Forbidden(String),
+    NotFound,
+    RateLimited(String),
+    /// The cause is logged where it happens and never sent to the client
+    Internal,
+}
+
+impl ApiError {
+    pub fn invalid_parameter(parameter: &str, message: impl Into<String>) -> Self {
+        ApiError::InvalidParameter {
+            parameter: Some(parameter.to_string()),
+            message: message.into(),
+        }
+    }
+
+    /// Error of a query string that doesn't deserialize into the parameters.
+    pub fn invalid_query(err: impl fmt::Display) -> Self {
+        ApiError::InvalidParameter {
+            parameter: None,
+            message: format!("Error when parsing query parameters: {}", err),
+        }
+    }
+
+    /// Maps the error of the query `context`, a missing row being a 404 and
+    /// anything else being logged and hidden behind a 500.
+    pub fn database(context: &'static str) -> impl FnOnce(sqlx::Error) -> Self {
+        move |err| match err {
+            sqlx::Error::RowNotFound => ApiError::NotFound,
+            err => ApiError::internal(&format!("error query {}", context), err),
+        }
+    }
+
+    pub fn internal(context: &str, err: impl fmt::Display) -> Self {
+        tracing::error!("{}: {}", context, err);
+        ApiError::Internal
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ApiError::InvalidParameter { .. } => ErrorCode::InvalidParameter,
+            ApiError::InvalidAddress { .. } => ErrorCode::InvalidAddress,
+            ApiError::UnsupportedChain(_) => ErrorCode::UnsupportedChain,
+            ApiError::InvalidCursor(_) => ErrorCode::InvalidCursor,
+            ApiError::MalformedBody(_) => ErrorCode::MalformedBody,
+            ApiError::InvalidBody(_) => ErrorCode::InvalidBody,
+            ApiError::InvalidFilters(_) => ErrorCode::InvalidFilters,
+            ApiError::Unauthorized(_) => ErrorCode::Unauthorized,
+            ApiError::Forbidden(_) => ErrorCode::Forbidden,
+            ApiError::NotFound => ErrorCode::NotFound,
+            ApiError::RateLimited(_) => ErrorCode::RateLimited,
+            ApiError::Internal => ErrorCode::InternalError,
+        }
+    }
+
+    fn parameter(&self) -> Option<&str> {
+        match self {
+            ApiError::InvalidParameter { parameter, .. } => parameter.as_deref(),
+            ApiError::InvalidAddress { parameter, .. } => Some(parameter),
+            ApiError::UnsupportedChain(_) => Some("chain_id"),
+            ApiError::InvalidCursor(_) => Some("cursor"),
+            ApiError::InvalidFilters(_) => Some("filters"),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ApiError::InvalidParameter { message, .. } => write!(f, "{}", message),
+            ApiError::InvalidAddress { parameter, value } => {
+                write!(f, "Invalid address for {}: {}", parameter, value)
+            }
+            ApiError::UnsupportedChain(message)
+            | ApiError::InvalidCursor(message)
+            | ApiError::MalformedBody(message)
+            | ApiError::InvalidBody(message)
+            | ApiError::InvalidFilters(message)
+            | ApiError::Unauthorized(message)
+            | ApiError::Forbidden(message)
+            | ApiError::RateLimited(message) => write!(f, "{}", message),
+            ApiError::NotFound => write!(f, "data not found"),
+            ApiError::Internal => write!(f, "Internal server error"),
+        }
+    }
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::InvalidParameter { .. }
+            | ApiError::InvalidAddress { .. }
+            | ApiError::UnsupportedChain(_)
+            | ApiError::InvalidCursor(_)
+            | ApiError::MalformedBody(_) => StatusCode::BAD_REQUEST,
+            ApiError::InvalidBody(_) | ApiError::InvalidFilters(_) => {
+                StatusCode::UNPROCESSABLE_ENTITY
+            }
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden(_) => StatusCode::FORBIDDEN,
+            ApiError::NotFound => StatusCode::NOT_FOUND,
+            ApiError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code()).json(ErrorResponse {
+            code: self.code(),
+            message: self.to_string(),
+            parameter: self.parameter().map(str::to_string),
+        })
+    }
+}
+
+/// Reports the query strings rejected by `web::Query` as `ApiError`s.
+pub fn query_config() -> web::QueryConfig {
+    web::QueryConfig::default()
+        .error_handler(|err: QueryPayloadError, _| ApiError::invalid_query(err).into())
+}
+
+/// Reports the path segments rejected by `web::Path` as `ApiError`s.
+pub fn path_config() -> web::PathConfig {
+    web::PathConfig::default().error_handler(|err: PathError, _| {
+        ApiError::InvalidParameter {
+            parameter: None,
+            message: format!("Error when parsing path parameters: {}", err),
+        }
+        .into()
+    })
+}
+
+/// Reports the bodies rejected by `web::Json` as `ApiError`s, a body that is
+/// valid JSON of the wrong shape being unprocessable rather than malformed.
+pub fn json_config() -> web::JsonConfig {
+    web::JsonConfig::default().error_handler(|err: JsonPayloadError, _| {
+        match err {
+            JsonPayloadError::Deserialize(err) if err.is_data() => {
+                ApiError::InvalidBody(err.to_string())
+            }
+            err => ApiError::MalformedBody(err.to_string()),
+        }
+        .into()
+    })
+}
@@ -0,0 +1,17 @@
+use crate::models::api_key::{ApiKey, CreatedApiKey};
+use serde::Serialize;
+
+#[derive(utoipa::ToSchema, Serialize)]
+pub struct CreatedApiKeyResponse {
+    data: CreatedApiKey,
+}
+
+#[derive(utoipa::ToSchema, Serialize)]
+pub struct ApiKeyResponse {
+    data: ApiKey,
+}
+
+#[derive(utoipa::ToSchema, Serialize)]
+pub struct ApiKeysResponse {
+    data: Vec<ApiKey>,
+}
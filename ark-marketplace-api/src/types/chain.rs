@@ -0,0 +1,7 @@
+use crate::models::chain::Chain;
+use serde::Serialize;
+
+#[derive(utoipa::ToSchema, Serialize)]
+pub struct ChainsResponse {
+    data: Vec<Chain>,
+}
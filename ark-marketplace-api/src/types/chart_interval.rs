@@ -0,0 +1,76 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Most buckets a single chart request may return.
+pub const MAX_CHART_BUCKETS: i64 = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartInterval {
+    Hour,
+    Day,
+    Week,
+}
+
+impl ChartInterval {
+    pub fn as_str(&self) -> &str {
+        match self {
+            ChartInterval::Hour => "1h",
+            ChartInterval::Day => "1d",
+            ChartInterval::Week => "1w",
+        }
+    }
+
+    /// Unit given to Postgres `date_trunc` to bucket the timestamps.
+    pub fn date_trunc_unit(&self) -> &str {
+        match self {
+            ChartInterval::Hour => "hour",
+            ChartInterval::Day => "day",
+            ChartInterval::Week => "week",
+        }
+    }
+
+    pub fn seconds(&self) -> i64 {
+        match self {
+            ChartInterval::Hour => 3600,
+            ChartInterval::Day => 86400,
+            ChartInterval::Week => 604800,
+        }
+    }
+
+    /// Range covered when the request does not give a `from` timestamp.
+    pub fn default_range_seconds(&self) -> i64 {
+        match self {
+            ChartInterval::Hour => 7 * 86400,
+            ChartInterval::Day => 90 * 86400,
+            ChartInterval::Week => 365 * 86400,
+        }
+    }
+}
+
+impl FromStr for ChartInterval {
+    type Err = ChartIntervalParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "1h" => Ok(ChartInterval::Hour),
+            "1d" | "" => Ok(ChartInterval::Day),
+            "1w" => Ok(ChartInterval::Week),
+            _ => Err(ChartIntervalParseError(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ChartIntervalParseError(String);
+
+impl fmt::Display for ChartIntervalParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Invalid chart interval: {}, expected one of 1h, 1d, 1w",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ChartIntervalParseError {}
@@ -1,6 +1,7 @@
 use crate::models::collection::{
-    CollectionActivityData, CollectionData, CollectionFullData, CollectionPortfolioData,
-    CollectionSearchData, OwnerData,
+    CollectionActivityData, CollectionChartData, CollectionData, CollectionFullData,
+    CollectionOfferData, CollectionPortfolioData, CollectionSearchData, OfferDepthLevel, OrderBook,
+    OwnerData,
 };
 use serde::Serialize;
 use std::collections::HashMap;
@@ -20,8 +21,7 @@ pub struct CollectionActivityResponse {
     data: Vec<CollectionActivityData>,
     #[schema(value_type = String, example = "777")]
     collection_count: i64,
-    #[schema(value_type = String, example = "3")]
-    next_page: i64,
+    next_cursor: Option<String>,
 }
 
 #[derive(utoipa::ToSchema, Serialize)]
@@ -48,3 +48,28 @@ pub struct AttributesResponse {
 pub struct AttributeValues {
     pub values: HashMap<String, usize>,
 }
+
+#[derive(utoipa::ToSchema, Serialize)]
+pub struct CollectionChartsResponse {
+    data: Vec<CollectionChartData>,
+    #[schema(example = "1d")]
+    interval: String,
+    from: i64,
+    to: i64,
+}
+
+#[derive(utoipa::ToSchema, Serialize)]
+pub struct CollectionOffersResponse {
+    data: Vec<CollectionOfferData>,
+    /// Best price levels of the collection-wide offers
+    depth: Vec<OfferDepthLevel>,
+    #[schema(value_type = String, example = "12")]
+    count: i64,
+    next_cursor: Option<String>,
+}
+
+#[derive(utoipa::ToSchema, Serialize)]
+pub struct CollectionOrderBookResponse {
+    /// One order book per currency the orders are placed in
+    data: Vec<OrderBook>,
+}
@@ -1,5 +1,12 @@
+pub mod api_error;
+pub mod api_key;
+pub mod chain;
+pub mod chart_interval;
 pub mod collection;
 pub mod default;
 pub mod offer_type;
 pub mod portfolio;
 pub mod token;
+pub mod wallet_session;
+pub mod watchlist;
+pub mod webhook;
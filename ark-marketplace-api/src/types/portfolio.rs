@@ -1,4 +1,5 @@
-use crate::models::portfolio::{OfferApiData, StatsData};
+use crate::models::portfolio::{OfferApiData, PortfolioValuation, StatsData};
+use crate::models::tax_report::TaxReport;
 use crate::models::token::{TokenPortfolioActivityData, TokenPortfolioData};
 use serde::Serialize;
 
@@ -20,10 +21,25 @@ pub struct PortfolioOffersResponse {
 pub struct PortfolioActivityResponse {
     data: Vec<TokenPortfolioActivityData>,
     token_count: i64,
-    next_page: i64,
+    next_cursor: Option<String>,
 }
 
 #[derive(utoipa::ToSchema, Serialize)]
 pub struct PortfolioStatsResponse {
     data: StatsData,
 }
+
+#[derive(utoipa::ToSchema, Serialize)]
+pub struct PortfolioValuationResponse {
+    data: PortfolioValuation,
+    /// Bucket size of the value history
+    #[schema(example = "1d")]
+    interval: String,
+    from: i64,
+    to: i64,
+}
+
+#[derive(utoipa::ToSchema, Serialize)]
+pub struct TaxReportResponse {
+    data: TaxReport,
+}
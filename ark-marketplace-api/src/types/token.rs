@@ -1,5 +1,6 @@
 use crate::models::token::{
-    TokenActivityData, TokenData, TokenInformationData, TokenMarketData, TokenOfferOneData,
+    TokenActivityData, TokenBatchData, TokenData, TokenInformationData, TokenMarketData,
+    TokenOfferOneData, TokenPriceStats, TokenSaleData,
 };
 use serde::Serialize;
 
@@ -7,7 +8,7 @@ use serde::Serialize;
 pub struct TokensResponse {
     data: Vec<TokenData>,
     token_count: i64,
-    next_page: i64,
+    next_cursor: Option<String>,
 }
 
 #[derive(utoipa::ToSchema, Serialize)]
@@ -15,6 +16,11 @@ pub struct TokenResponse {
     data: Vec<TokenInformationData>,
 }
 
+#[derive(utoipa::ToSchema, Serialize)]
+pub struct TokensBatchResponse {
+    data: Vec<TokenBatchData>,
+}
+
 #[derive(utoipa::ToSchema, Serialize)]
 pub struct TokenMarketDataResponse {
     data: Vec<TokenMarketData>,
@@ -24,7 +30,7 @@ pub struct TokenMarketDataResponse {
 pub struct TokenOffersResponse {
     data: Vec<TokenOfferOneData>,
     count: i64,
-    next_page: i64,
+    next_cursor: Option<String>,
 }
 
 #[derive(utoipa::ToSchema, Serialize)]
@@ -33,3 +39,9 @@ pub struct TokenActivitiesResponse {
     count: i64,
     next_page: i64,
 }
+
+#[derive(utoipa::ToSchema, Serialize)]
+pub struct TokenPriceHistoryResponse {
+    data: Vec<TokenSaleData>,
+    stats: TokenPriceStats,
+}
@@ -0,0 +1,12 @@
+use crate::models::wallet_session::{SessionToken, SignInChallenge};
+use serde::Serialize;
+
+#[derive(utoipa::ToSchema, Serialize)]
+pub struct SignInChallengeResponse {
+    data: SignInChallenge,
+}
+
+#[derive(utoipa::ToSchema, Serialize)]
+pub struct SessionTokenResponse {
+    data: SessionToken,
+}
@@ -0,0 +1,18 @@
+use crate::models::watchlist::{WatchlistFeedItem, WatchlistItem};
+use serde::Serialize;
+
+#[derive(utoipa::ToSchema, Serialize)]
+pub struct WatchlistItemResponse {
+    data: WatchlistItem,
+}
+
+#[derive(utoipa::ToSchema, Serialize)]
+pub struct WatchlistResponse {
+    data: Vec<WatchlistItem>,
+}
+
+#[derive(utoipa::ToSchema, Serialize)]
+pub struct WatchlistFeedResponse {
+    data: Vec<WatchlistFeedItem>,
+    next_cursor: Option<String>,
+}
@@ -0,0 +1,19 @@
+use crate::models::webhook::{WebhookDeadLetter, WebhookSubscription};
+use serde::Serialize;
+
+#[derive(utoipa::ToSchema, Serialize)]
+pub struct WebhookResponse {
+    data: WebhookSubscription,
+}
+
+#[derive(utoipa::ToSchema, Serialize)]
+pub struct WebhooksResponse {
+    data: Vec<WebhookSubscription>,
+}
+
+#[derive(utoipa::ToSchema, Serialize)]
+pub struct WebhookDeadLettersResponse {
+    data: Vec<WebhookDeadLetter>,
+    count: i64,
+    next_page: i64,
+}
@@ -30,3 +30,10 @@ pub fn compute_floor_difference(
         }
     }
 }
+
+/// Converts an amount of the smallest currency unit into currency units,
+/// e.g. 1500000000000000000 with 18 decimals gives 1.5.
+pub fn normalize_currency_amount(amount: BigDecimal, decimals: i16) -> BigDecimal {
+    let (digits, scale) = amount.into_bigint_and_exponent();
+    BigDecimal::new(digits, scale + i64::from(decimals)).normalized()
+}
@@ -0,0 +1,478 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Position of the last row of a page, handed to clients as an opaque token.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cursor {
+    /// Name of the ordering the cursor was read from
+    sort: String,
+    /// Sort key values of the row, as text
+    values: Vec<Option<String>>,
+}
+
+impl Cursor {
+    fn encode(&self) -> String {
+        let json = serde_json::to_vec(self).expect("Error serializing cursor");
+        URL_SAFE_NO_PAD.encode(json)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKeyType {
+    Numeric,
+    BigInt,
+    Text,
+}
+
+impl SortKeyType {
+    fn sql_type(&self) -> &'static str {
+        match self {
+            Self::Numeric => "NUMERIC",
+            Self::BigInt => "BIGINT",
+            Self::Text => "TEXT",
+        }
+    }
+
+    fn is_valid(&self, value: &str) -> bool {
+        match self {
+            Self::Numeric => BigDecimal::from_str(value).is_ok(),
+            Self::BigInt => value.parse::<i64>().is_ok(),
+            Self::Text => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Nulls {
+    First,
+    Last,
+}
+
+#[derive(Debug, Clone)]
+pub struct SortKey {
+    expression: String,
+    key_type: SortKeyType,
+    descending: bool,
+    /// `None` for expressions that are never null
+    nulls: Option<Nulls>,
+}
+
+impl SortKey {
+    /// Ascending key on a non null expression.
+    pub fn new(expression: impl Into<String>, key_type: SortKeyType) -> Self {
+        Self {
+            expression: expression.into(),
+            key_type,
+            descending: false,
+            nulls: None,
+        }
+    }
+
+    pub fn desc(mut self) -> Self {
+        self.descending = true;
+        self
+    }
+
+    /// Descending when `direction` is "desc", ascending otherwise.
+    pub fn direction(self, direction: &str) -> Self {
+        if direction == "desc" {
+            self.desc()
+        } else {
+            self
+        }
+    }
+
+    pub fn nulls_first(mut self) -> Self {
+        self.nulls = Some(Nulls::First);
+        self
+    }
+
+    pub fn nulls_last(mut self) -> Self {
+        self.nulls = Some(Nulls::Last);
+        self
+    }
+
+    fn order_by(&self) -> String {
+        let direction = if self.descending { "DESC" } else { "ASC" };
+        match self.nulls {
+            None => format!("{} {}", self.expression, direction),
+            Some(Nulls::First) => format!("{} {} NULLS FIRST", self.expression, direction),
+            Some(Nulls::Last) => format!("{} {} NULLS LAST", self.expression, direction),
+        }
+    }
+
+    fn equals(&self, param: Option<usize>) -> String {
+        match param {
+            Some(param) => format!(
+                "{} = ${}::{}",
+                self.expression,
+                param,
+                self.key_type.sql_type()
+            ),
+            None => format!("{} IS NULL", self.expression),
+        }
+    }
+
+    /// Condition on the rows sorted after the value, `None` when there is none.
+    fn after(&self, param: Option<usize>) -> Option<String> {
+        let operator = if self.descending { "<" } else { ">" };
+        match (param, self.nulls) {
+            (None, Some(Nulls::First)) => Some(format!("{} IS NOT NULL", self.expression)),
+            (None, _) => None,
+            (Some(param), Some(Nulls::Last)) => Some(format!(
+                "({expression} {operator} ${param}::{sql_type} OR {expression} IS NULL)",
+                expression = self.expression,
+                sql_type = self.key_type.sql_type(),
+            )),
+            (Some(param), _) => Some(format!(
+                "{} {} ${}::{}",
+                self.expression,
+                operator,
+                param,
+                self.key_type.sql_type()
+            )),
+        }
+    }
+}
+
+/// An ordering usable for keyset pagination. The last key must be unique so
+/// that no two rows share a position.
+#[derive(Debug, Clone)]
+pub struct Keyset {
+    name: String,
+    keys: Vec<SortKey>,
+}
+
+impl Keyset {
+    pub fn new(name: impl Into<String>, keys: Vec<SortKey>) -> Self {
+        Self {
+            name: name.into(),
+            keys,
+        }
+    }
+
+    pub fn order_by(&self) -> String {
+        self.keys
+            .iter()
+            .map(SortKey::order_by)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Select expression reading the sort key values of a row, to be
+    /// returned as `cursor_values` and passed to `next_cursor`.
+    pub fn cursor_values_select(&self) -> String {
+        format!(
+            "json_build_array({})::TEXT AS cursor_values",
+            self.keys
+                .iter()
+                .map(|key| format!("({})::TEXT", key.expression))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+
+    /// Decodes a cursor sent by a client, rejecting cursors of another
+    /// ordering.
+    pub fn decode_cursor(&self, token: &str) -> Result<Cursor, String> {
+        let invalid = || "Invalid cursor".to_string();
+        let json = URL_SAFE_NO_PAD.decode(token).map_err(|_| invalid())?;
+        let cursor: Cursor = serde_json::from_slice(&json).map_err(|_| invalid())?;
+
+        if cursor.sort != self.name {
+            return Err("Cursor doesn't match the requested sort".to_string());
+        }
+
+        let is_valid = cursor.values.len() == self.keys.len()
+            && self.keys.iter().zip(&cursor.values).all(|(key, value)| {
+                value
+                    .as_deref()
+                    .map_or(key.nulls.is_some(), |value| key.key_type.is_valid(value))
+            });
+        if !is_valid {
+            return Err(invalid());
+        }
+
+        Ok(cursor)
+    }
+
+    /// Condition keeping the rows after the cursor, starting with `AND`.
+    /// Values are bound from parameter `$first_param` on, in the returned order.
+    pub fn after_condition(
+        &self,
+        cursor: Option<&Cursor>,
+        first_param: usize,
+    ) -> (String, Vec<String>) {
+        let cursor = match cursor {
+            Some(cursor) => cursor,
+            None => return (String::new(), Vec::new()),
+        };
+
+        let mut binds = Vec::new();
+        let params: Vec<Option<usize>> = cursor
+            .values
+            .iter()
+            .map(|value| {
+                value.as_ref().map(|value| {
+                    binds.push(value.clone());
+                    first_param + binds.len() - 1
+                })
+            })
+            .collect();
+
+        let conditions: Vec<String> = self
+            .keys
+            .iter()
+            .enumerate()
+            .filter_map(|(i, key)| {
+                key.after(params[i]).map(|after| {
+                    let mut parts: Vec<String> = self.keys[..i]
+                        .iter()
+                        .zip(&params)
+                        .map(|(key, param)| key.equals(*param))
+                        .collect();
+                    parts.push(after);
+                    format!("({})", parts.join(" AND "))
+                })
+            })
+            .collect();
+
+        if conditions.is_empty() {
+            ("AND FALSE".to_string(), binds)
+        } else {
+            (format!("AND ({})", conditions.join(" OR ")), binds)
+        }
+    }
+
+    /// Cursor of the page following the row with these `cursor_values`.
+    pub fn next_cursor(&self, cursor_values: &str) -> Option<String> {
+        match serde_json::from_str::<Vec<Option<String>>>(cursor_values) {
+            Ok(values) => Some(
+                Cursor {
+                    sort: self.name.clone(),
+                    values,
+                }
+                .encode(),
+            ),
+            Err(e) => {
+                tracing::error!("Invalid cursor values {}: {}", cursor_values, e);
+                None
+            }
+        }
+    }
+
+    /// Splits rows fetched with a `LIMIT` of `items_per_page + 1`, returning
+    /// the page and the cursor of the next one, `None` on the last page.
+    pub fn paginate<T>(
+        &self,
+        mut rows: Vec<T>,
+        items_per_page: i64,
+        cursor_values: impl Fn(&T) -> &str,
+    ) -> (Vec<T>, Option<String>) {
+        let items_per_page = items_per_page.max(0) as usize;
+        if rows.len() <= items_per_page {
+            return (rows, None);
+        }
+
+        rows.truncate(items_per_page);
+        let next_cursor = rows
+            .last()
+            .and_then(|row| self.next_cursor(cursor_values(row)));
+        (rows, next_cursor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cursor(keyset: &Keyset, values: &[Option<&str>]) -> Cursor {
+        Cursor {
+            sort: keyset.name.clone(),
+            values: values.iter().map(|value| value.map(String::from)).collect(),
+        }
+    }
+
+    fn binds(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    fn mixed_directions() -> Keyset {
+        Keyset::new(
+            "mixed",
+            vec![
+                SortKey::new("a", SortKeyType::BigInt).desc(),
+                SortKey::new("id", SortKeyType::BigInt),
+            ],
+        )
+    }
+
+    fn nulls_last() -> Keyset {
+        Keyset::new(
+            "nulls_last",
+            vec![
+                SortKey::new("price", SortKeyType::Numeric)
+                    .desc()
+                    .nulls_last(),
+                SortKey::new("id", SortKeyType::BigInt),
+            ],
+        )
+    }
+
+    fn nulls_first() -> Keyset {
+        Keyset::new(
+            "nulls_first",
+            vec![
+                SortKey::new("name", SortKeyType::Text).nulls_first(),
+                SortKey::new("id", SortKeyType::BigInt),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_after_condition_without_cursor() {
+        assert_eq!(
+            mixed_directions().after_condition(None, 1),
+            (String::new(), vec![])
+        );
+    }
+
+    #[test]
+    fn test_after_condition_mixed_directions() {
+        let keyset = mixed_directions();
+        let cursor = cursor(&keyset, &[Some("5"), Some("7")]);
+
+        assert_eq!(
+            keyset.after_condition(Some(&cursor), 3),
+            (
+                "AND ((a < $3::BIGINT) OR (a = $3::BIGINT AND id > $4::BIGINT))".to_string(),
+                binds(&["5", "7"])
+            )
+        );
+    }
+
+    #[test]
+    fn test_after_condition_nulls_last() {
+        let keyset = nulls_last();
+
+        let cursor_with_value = cursor(&keyset, &[Some("1.5"), Some("9")]);
+        assert_eq!(
+            keyset.after_condition(Some(&cursor_with_value), 1),
+            (
+                "AND (((price < $1::NUMERIC OR price IS NULL)) OR (price = $1::NUMERIC AND id > $2::BIGINT))"
+                    .to_string(),
+                binds(&["1.5", "9"])
+            )
+        );
+
+        // Null values are sorted last, only the nulls after the id remain
+        let cursor_with_null = cursor(&keyset, &[None, Some("9")]);
+        assert_eq!(
+            keyset.after_condition(Some(&cursor_with_null), 1),
+            (
+                "AND ((price IS NULL AND id > $1::BIGINT))".to_string(),
+                binds(&["9"])
+            )
+        );
+    }
+
+    #[test]
+    fn test_after_condition_nulls_first() {
+        let keyset = nulls_first();
+
+        let cursor_with_value = cursor(&keyset, &[Some("bob"), Some("3")]);
+        assert_eq!(
+            keyset.after_condition(Some(&cursor_with_value), 1),
+            (
+                "AND ((name > $1::TEXT) OR (name = $1::TEXT AND id > $2::BIGINT))".to_string(),
+                binds(&["bob", "3"])
+            )
+        );
+
+        // Null values are sorted first, every non null value comes after
+        let cursor_with_null = cursor(&keyset, &[None, Some("3")]);
+        assert_eq!(
+            keyset.after_condition(Some(&cursor_with_null), 2),
+            (
+                "AND ((name IS NOT NULL) OR (name IS NULL AND id > $2::BIGINT))".to_string(),
+                binds(&["3"])
+            )
+        );
+    }
+
+    #[test]
+    fn test_after_condition_past_the_last_row() {
+        let keyset = Keyset::new(
+            "single",
+            vec![SortKey::new("price", SortKeyType::Numeric).nulls_last()],
+        );
+        let cursor = cursor(&keyset, &[None]);
+
+        assert_eq!(
+            keyset.after_condition(Some(&cursor), 1),
+            ("AND FALSE".to_string(), vec![])
+        );
+    }
+
+    #[test]
+    fn test_decode_cursor_round_trip() {
+        let keyset = nulls_last();
+        let token = keyset.next_cursor(r#"[null, "9"]"#).unwrap();
+
+        assert_eq!(
+            keyset.decode_cursor(&token),
+            Ok(cursor(&keyset, &[None, Some("9")]))
+        );
+    }
+
+    #[test]
+    fn test_decode_cursor_rejects_invalid_cursors() {
+        let keyset = mixed_directions();
+        let invalid = Err("Invalid cursor".to_string());
+
+        assert_eq!(keyset.decode_cursor("not base64!"), invalid);
+        assert_eq!(
+            keyset.decode_cursor(&URL_SAFE_NO_PAD.encode("not json")),
+            invalid
+        );
+        assert_eq!(
+            keyset.decode_cursor(&nulls_last().next_cursor(r#"["5", "7"]"#).unwrap()),
+            Err("Cursor doesn't match the requested sort".to_string())
+        );
+        // Wrong number of values
+        assert_eq!(
+            keyset.decode_cursor(&keyset.next_cursor(r#"["5"]"#).unwrap()),
+            invalid
+        );
+        // Null value of a key that is never null
+        assert_eq!(
+            keyset.decode_cursor(&keyset.next_cursor(r#"[null, "7"]"#).unwrap()),
+            invalid
+        );
+        // Value of the wrong type, which would fail the cast in SQL
+        assert_eq!(
+            keyset.decode_cursor(&keyset.next_cursor(r#"["1.5", "7"]"#).unwrap()),
+            invalid
+        );
+        assert!(nulls_last()
+            .decode_cursor(&nulls_last().next_cursor(r#"["1.5", "7"]"#).unwrap())
+            .is_ok());
+    }
+
+    #[test]
+    fn test_paginate() {
+        let keyset = mixed_directions();
+        let rows = vec![r#"["3", "1"]"#, r#"["2", "2"]"#, r#"["1", "3"]"#];
+
+        let (page, next_cursor) = keyset.paginate(rows.clone(), 2, |row| *row);
+        assert_eq!(page, rows[..2].to_vec());
+        assert_eq!(next_cursor, keyset.next_cursor(rows[1]));
+
+        let (page, next_cursor) = keyset.paginate(rows.clone(), 3, |row| *row);
+        assert_eq!(page, rows);
+        assert_eq!(next_cursor, None);
+    }
+}
@@ -40,6 +40,30 @@ impl TokenEventType {
     }
 }
 
+impl From<TokenEventTypeDB> for TokenEventType {
+    fn from(value: TokenEventTypeDB) -> Self {
+        match value {
+            TokenEventTypeDB::Listing => TokenEventType::Listing,
+            TokenEventTypeDB::Auction => TokenEventType::Auction,
+            TokenEventTypeDB::Offer => TokenEventType::Offer,
+            TokenEventTypeDB::CollectionOffer => TokenEventType::CollectionOffer,
+            TokenEventTypeDB::Fulfill => TokenEventType::Offer,
+            TokenEventTypeDB::Executed => TokenEventType::Executed,
+            TokenEventTypeDB::Cancelled => TokenEventType::Cancelled,
+            TokenEventTypeDB::Sale => TokenEventType::Sale,
+            TokenEventTypeDB::Mint => TokenEventType::Mint,
+            TokenEventTypeDB::Burn => TokenEventType::Burn,
+            TokenEventTypeDB::Transfer => TokenEventType::Transfer,
+            TokenEventTypeDB::Rollback => TokenEventType::Rollback,
+            TokenEventTypeDB::ListingCancelled => TokenEventType::ListingCancelled,
+            TokenEventTypeDB::AuctionCancelled => TokenEventType::AuctionCancelled,
+            TokenEventTypeDB::OfferCancelled => TokenEventType::OfferCancelled,
+            TokenEventTypeDB::ListingExpired => TokenEventType::ListingExpired,
+            TokenEventTypeDB::OfferExpired => TokenEventType::OfferExpired,
+        }
+    }
+}
+
 impl<DB> sqlx::Type<DB> for TokenEventType
 where
     DB: sqlx::Database,
@@ -58,28 +82,6 @@ where
     fn decode(
         value: <DB as sqlx::Database>::ValueRef<'r>,
     ) -> Result<Self, sqlx::error::BoxDynError> {
-        if let Ok(e) = TokenEventTypeDB::decode(value) {
-            match e {
-                TokenEventTypeDB::Listing => Ok(TokenEventType::Listing),
-                TokenEventTypeDB::Auction => Ok(TokenEventType::Auction),
-                TokenEventTypeDB::Offer => Ok(TokenEventType::Offer),
-                TokenEventTypeDB::CollectionOffer => Ok(TokenEventType::CollectionOffer),
-                TokenEventTypeDB::Fulfill => Ok(TokenEventType::Offer),
-                TokenEventTypeDB::Executed => Ok(TokenEventType::Executed),
-                TokenEventTypeDB::Cancelled => Ok(TokenEventType::Cancelled),
-                TokenEventTypeDB::Sale => Ok(TokenEventType::Sale),
-                TokenEventTypeDB::Mint => Ok(TokenEventType::Mint),
-                TokenEventTypeDB::Burn => Ok(TokenEventType::Burn),
-                TokenEventTypeDB::Transfer => Ok(TokenEventType::Transfer),
-                TokenEventTypeDB::Rollback => Ok(TokenEventType::Rollback),
-                TokenEventTypeDB::ListingCancelled => Ok(TokenEventType::ListingCancelled),
-                TokenEventTypeDB::AuctionCancelled => Ok(TokenEventType::AuctionCancelled),
-                TokenEventTypeDB::OfferCancelled => Ok(TokenEventType::OfferCancelled),
-                TokenEventTypeDB::ListingExpired => Ok(TokenEventType::ListingExpired),
-                TokenEventTypeDB::OfferExpired => Ok(TokenEventType::OfferExpired),
-            }
-        } else {
-            Err("Invalid event type".into())
-        }
+        TokenEventTypeDB::decode(value).map(TokenEventType::from)
     }
 }
@@ -1,4 +1,8 @@
 pub mod currency_utils;
+pub mod cursor_utils;
 pub mod db_utils;
 pub mod http_utils;
+pub mod order_book_utils;
 pub mod sql_utils;
+pub mod tax_utils;
+pub mod typed_data_utils;
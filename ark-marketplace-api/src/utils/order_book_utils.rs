@@ -0,0 +1,104 @@
+use crate::models::collection::{OrderBook, OrderBookLevel, OrderBookLevelDB};
+use crate::models::default::Currency;
+use crate::utils::currency_utils::normalize_currency_amount;
+use bigdecimal::{BigDecimal, RoundingMode};
+use std::collections::BTreeMap;
+
+/// Rounds `price` to a multiple of `bucket_size`, down for bids and up for
+/// asks so that a bucket never shows a better price than its orders.
+fn bucket_price(price: BigDecimal, bucket_size: &BigDecimal, is_bid: bool) -> BigDecimal {
+    let mode = if is_bid {
+        RoundingMode::Floor
+    } else {
+        RoundingMode::Ceiling
+    };
+    ((price / bucket_size).with_scale_round(0, mode) * bucket_size).normalized()
+}
+
+fn into_ladder(
+    buckets: BTreeMap<BigDecimal, (i64, BigDecimal)>,
+    is_bid: bool,
+    levels: usize,
+) -> Vec<OrderBookLevel> {
+    let to_level =
+        |(price, (order_count, quantity)): (BigDecimal, (i64, BigDecimal))| OrderBookLevel {
+            price: Some(price),
+            order_count,
+            quantity: Some(quantity),
+        };
+    if is_bid {
+        buckets
+            .into_iter()
+            .rev()
+            .take(levels)
+            .map(to_level)
+            .collect()
+    } else {
+        buckets.into_iter().take(levels).map(to_level).collect()
+    }
+}
+
+/// Groups the order book levels read from the database by currency,
+/// converting their prices into currency units and merging them into buckets
+/// of `bucket_size` when given. Each side keeps its best `levels` buckets.
+pub fn build_order_books(
+    rows: Vec<OrderBookLevelDB>,
+    currencies: &[Currency],
+    bucket_size: Option<&BigDecimal>,
+    levels: usize,
+) -> Vec<OrderBook> {
+    type Buckets = BTreeMap<BigDecimal, (i64, BigDecimal)>;
+    // Orders without a known currency are in the default currency, as
+    // everywhere else in the API.
+    let mut books: BTreeMap<Option<String>, (Currency, Buckets, Buckets)> = BTreeMap::new();
+
+    for row in rows {
+        let Some(price) = row.price else {
+            continue;
+        };
+        let currency = currencies
+            .iter()
+            .find(|c| c.contract.as_deref() == Some(row.currency_address.as_str()))
+            .cloned()
+            .unwrap_or_default();
+        let mut price = normalize_currency_amount(price, currency.decimals.unwrap_or(18));
+        if let Some(bucket_size) = bucket_size {
+            price = bucket_price(price, bucket_size, row.is_bid);
+        }
+
+        let (_, bids, asks) = books
+            .entry(currency.contract.clone())
+            .or_insert_with(|| (currency, BTreeMap::new(), BTreeMap::new()));
+        let side = if row.is_bid { bids } else { asks };
+        let bucket = side
+            .entry(price)
+            .or_insert_with(|| (0, BigDecimal::from(0)));
+        bucket.0 += row.order_count;
+        bucket.1 += row
+            .quantity
+            .unwrap_or_else(|| BigDecimal::from(row.order_count));
+    }
+
+    books
+        .into_values()
+        .map(|(currency, bids, asks)| {
+            let bids = into_ladder(bids, true, levels);
+            let asks = into_ladder(asks, false, levels);
+            let best_bid = bids.first().and_then(|level| level.price.clone());
+            let best_ask = asks.first().and_then(|level| level.price.clone());
+            let spread = match (&best_bid, &best_ask) {
+                (Some(bid), Some(ask)) => Some((ask - bid).normalized()),
+                _ => None,
+            };
+
+            OrderBook {
+                currency,
+                bids,
+                asks,
+                best_bid,
+                best_ask,
+                spread,
+            }
+        })
+        .collect()
+}
@@ -1,35 +1,128 @@
-pub fn generate_order_by_clause(
-    sort_field: &str,
-    sort_direction: &str,
-    sort_value: Option<&str>,
-) -> String {
+use crate::utils::cursor_utils::{Keyset, SortKey, SortKeyType};
+
+const TOKEN_ID_KEY: &str = "CAST(token.token_id AS NUMERIC)";
+
+pub fn tokens_keyset(sort_field: &str, sort_direction: &str, sort_value: Option<&str>) -> Keyset {
+    let token_id = SortKey::new(TOKEN_ID_KEY, SortKeyType::Numeric);
+    let name = format!("tokens:{}:{}", sort_field, sort_direction);
+
     match (sort_field, sort_direction, sort_value) {
-        ("price", "asc", _) => {
-            "token.listing_start_amount ASC NULLS LAST, CAST(token.token_id AS NUMERIC)".to_string()
-        }
-        ("price", "desc", _) => {
-            "token.listing_start_amount DESC NULLS FIRST, CAST(token.token_id AS NUMERIC)".to_string()
-        }
-        ("owner", "asc", Some(value)) if !value.is_empty() => format!(
-            "CASE
-                WHEN token.current_owner = '{}' THEN 0
-                ELSE 1
-            END, NULLIF(token.current_owner, '') ASC NULLS LAST, CAST(token.token_id AS NUMERIC) ASC",
-            value
+        ("price", "asc", _) => Keyset::new(
+            name,
+            vec![
+                SortKey::new("token.listing_start_amount", SortKeyType::Text).nulls_last(),
+                token_id,
+            ],
         ),
-        ("owner", "desc", Some(value)) if !value.is_empty() => format!(
-            "CASE
-                WHEN token.current_owner = '{}' THEN 0
-                ELSE 1
-            END, NULLIF(token.current_owner, '') DESC NULLS LAST, CAST(token.token_id AS NUMERIC) DESC",
-            value
+        ("price", "desc", _) => Keyset::new(
+            name,
+            vec![
+                SortKey::new("token.listing_start_amount", SortKeyType::Text)
+                    .desc()
+                    .nulls_first(),
+                token_id,
+            ],
         ),
-        (_, "asc", _) => "CAST(token.token_id AS NUMERIC) ASC".to_string(),
-        (_, "desc", _) => "CAST(token.token_id AS NUMERIC) DESC".to_string(),
-        _ => "CAST(token.token_id AS NUMERIC) ASC".to_string(),
+        ("rarity", "asc" | "desc", _) => Keyset::new(
+            name,
+            vec![
+                SortKey::new("token_rarity.rarity_rank", SortKeyType::BigInt)
+                    .direction(sort_direction)
+                    .nulls_last(),
+                token_id,
+            ],
+        ),
+        ("owner", "asc" | "desc", Some(value)) if !value.is_empty() => Keyset::new(
+            format!("{}:{}", name, value),
+            vec![
+                SortKey::new(
+                    format!(
+                        "CASE WHEN token.current_owner = '{}' THEN 0 ELSE 1 END",
+                        value.replace('\'', "''")
+                    ),
+                    SortKeyType::BigInt,
+                ),
+                SortKey::new("NULLIF(token.current_owner, '')", SortKeyType::Text)
+                    .direction(sort_direction)
+                    .nulls_last(),
+                token_id.direction(sort_direction),
+            ],
+        ),
+        (_, "desc", _) => Keyset::new("tokens:token_id:desc", vec![token_id.desc()]),
+        _ => Keyset::new("tokens:token_id:asc", vec![token_id]),
     }
 }
 
+/// Events sorted by block timestamp, the event id breaking ties.
+pub fn activity_keyset(direction: &str) -> Keyset {
+    let direction = if direction == "asc" { "asc" } else { "desc" };
+    Keyset::new(
+        format!("activity:{}", direction),
+        vec![
+            SortKey::new("te.block_timestamp", SortKeyType::BigInt).direction(direction),
+            SortKey::new("te.token_event_id", SortKeyType::Text).direction(direction),
+        ],
+    )
+}
+
+/// Best offers first, the soonest to expire breaking ties.
+pub fn token_offers_keyset() -> Keyset {
+    Keyset::new(
+        "token_offers",
+        vec![
+            SortKey::new(
+                "hex_to_decimal(token_offer.offer_amount)",
+                SortKeyType::Numeric,
+            )
+            .desc()
+            .nulls_last(),
+            SortKey::new("token_offer.end_date", SortKeyType::BigInt),
+            SortKey::new("token_offer.token_offer_id", SortKeyType::BigInt),
+        ],
+    )
+}
+
+pub fn collection_offers_keyset() -> Keyset {
+    Keyset::new(
+        "collection_offers",
+        vec![
+            SortKey::new(
+                "hex_to_decimal(collection_offer.offer_amount)",
+                SortKeyType::Numeric,
+            )
+            .desc()
+            .nulls_last(),
+            SortKey::new("collection_offer.end_date", SortKeyType::BigInt),
+            SortKey::new("collection_offer.collection_offer_id", SortKeyType::BigInt),
+        ],
+    )
+}
+
+pub const COLLECTION_SORTS: &[&str] = &[
+    "floor_price",
+    "floor_percentage",
+    "volume",
+    "top_bid",
+    "number_of_sales",
+    "marketcap",
+    "listed",
+];
+
+pub const TOKEN_SORTS: &[&str] = &["price", "owner", "rarity"];
+
+pub const DIRECTIONS: &[&str] = &["asc", "desc"];
+
+/// Ranges the collection market data is aggregated over.
+pub const TIME_RANGES: &[&str] = &["10m", "1h", "6h", "1d", "7d", "30d"];
+
+/// Escapes the wildcards of a user input matched with `LIKE`.
+pub fn escape_like_pattern(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
 pub fn generate_order_by_clause_collections(sort: &str, direction: &str) -> String {
     if sort == "floor_price" {
         format!("ORDER BY floor_price {} NULLS LAST", direction)
@@ -58,3 +151,32 @@ pub fn generate_order_by_clause_collections(sort: &str, direction: &str) -> Stri
         String::new()
     }
 }
+
+/// Latest changes of the followed items first.
+pub fn watchlist_feed_keyset() -> Keyset {
+    Keyset::new(
+        "watchlist_feed",
+        vec![
+            SortKey::new("feed.timestamp", SortKeyType::BigInt).desc(),
+            SortKey::new("feed.feed_id", SortKeyType::Text).desc(),
+        ],
+    )
+}
+
+/// Lateral join exposing `{alias}.usd_price`, the USD price of `asset` at
+/// `timestamp`: the close of its last minute candle recorded by
+/// ark-price-engine in the day before. `asset` and `timestamp` are SQL
+/// expressions, e.g. `currency_mapping.symbol` and `te.block_timestamp`.
+pub fn historical_usd_price_join(alias: &str, asset: &str, timestamp: &str) -> String {
+    format!(
+        "LEFT JOIN LATERAL (
+            SELECT price_candle.close AS usd_price
+            FROM price_candle
+            WHERE price_candle.asset = UPPER({asset})
+              AND price_candle.timestamp <= {timestamp}
+              AND price_candle.timestamp > {timestamp} - 86400
+            ORDER BY price_candle.timestamp DESC
+            LIMIT 1
+        ) {alias} ON true"
+    )
+}
@@ -0,0 +1,370 @@
+use crate::models::default::Currency;
+use crate::models::tax_report::{LotMethod, TaxDisposal, TaxEventDB, TaxReport, TaxReportSummary};
+use crate::utils::currency_utils::normalize_currency_amount;
+use bigdecimal::{BigDecimal, RoundingMode};
+use chrono::DateTime;
+use std::collections::HashMap;
+
+const SECONDS_PER_DAY: i64 = 86400;
+
+/// A token acquired and not yet disposed of, `cost_usd` being unknown for a
+/// transfer or an unpriced purchase.
+struct Lot {
+    acquisition_type: String,
+    acquired_at: i64,
+    cost_usd: Option<BigDecimal>,
+}
+
+/// Position in `lots` of the lot consumed by a disposal. Unknown costs rank
+/// below the known ones for HIFO, ties going to the oldest lot.
+fn select_lot(lots: &[Lot], method: LotMethod) -> Option<usize> {
+    if lots.is_empty() {
+        return None;
+    }
+    match method {
+        LotMethod::Fifo => Some(0),
+        LotMethod::Lifo => Some(lots.len() - 1),
+        LotMethod::Hifo => {
+            let mut selected = 0;
+            for (index, lot) in lots.iter().enumerate().skip(1) {
+                if lot.cost_usd > lots[selected].cost_usd {
+                    selected = index;
+                }
+            }
+            Some(selected)
+        }
+    }
+}
+
+fn activity_type(event_type: &str) -> String {
+    match event_type {
+        "Executed" => String::from("Sale"),
+        other => other.to_string(),
+    }
+}
+
+fn format_date(timestamp: i64) -> String {
+    DateTime::from_timestamp(timestamp, 0)
+        .map(|date| date.format("%Y-%m-%dT%H:%M:%SZ").to_string())
+        .unwrap_or_default()
+}
+
+fn to_usd(amount: &Option<BigDecimal>, rate: &Option<BigDecimal>) -> Option<BigDecimal> {
+    match (amount, rate) {
+        (Some(amount), Some(rate)) => {
+            Some((amount * rate).with_scale_round(2, RoundingMode::HalfEven))
+        }
+        _ => None,
+    }
+}
+
+fn add(total: &mut Option<BigDecimal>, value: &Option<BigDecimal>) {
+    if let Some(value) = value {
+        *total = Some(total.take().unwrap_or_else(|| BigDecimal::from(0)) + value);
+    }
+}
+
+/// Replays the events of the user in order, opening a lot for each token
+/// received and closing one for each token sold, burned or sent away. The
+/// tokens of a collection are pooled, so `method` decides which acquisition
+/// of the collection a disposal is matched with. Only the disposals between
+/// `from` and `to` are reported, the earlier ones only closing their lots.
+pub fn build_tax_report(
+    events: Vec<TaxEventDB>,
+    year: i32,
+    method: LotMethod,
+    from: i64,
+    to: i64,
+) -> TaxReport {
+    let mut lots: HashMap<String, Vec<Lot>> = HashMap::new();
+    let mut disposals = Vec::new();
+    let mut summary = TaxReportSummary {
+        year,
+        method,
+        fiat: String::from("USD"),
+        from,
+        to,
+        disposal_count: 0,
+        total_proceeds_usd: None,
+        total_cost_basis_usd: None,
+        total_royalties_usd: None,
+        total_marketplace_fees_usd: None,
+        net_gain_usd: None,
+        disposals_without_cost_basis: 0,
+        unpriced_trades: 0,
+        transfers_out: 0,
+    };
+
+    for event in events {
+        let in_year = event.block_timestamp >= from;
+        let is_sale = event.event_type == "Executed";
+        let (currency, decimals) = match event.currency_decimals {
+            Some(decimals) => (event.currency_symbol, decimals),
+            None => (Currency::default().symbol, 18),
+        };
+        let normalize = |amount: Option<BigDecimal>| {
+            amount.map(|amount| normalize_currency_amount(amount, decimals))
+        };
+        let price = normalize(event.amount);
+        let price_usd = to_usd(&price, &event.currency_usd_price);
+        if is_sale && in_year && price_usd.is_none() {
+            summary.unpriced_trades += 1;
+        }
+
+        let collection_lots = lots.entry(event.contract_address.clone()).or_default();
+        if event.is_acquisition {
+            let cost_usd = match event.event_type.as_str() {
+                "Executed" => price_usd,
+                "Mint" => Some(BigDecimal::from(0)),
+                _ => None,
+            };
+            collection_lots.push(Lot {
+                acquisition_type: activity_type(&event.event_type),
+                acquired_at: event.block_timestamp,
+                cost_usd,
+            });
+            continue;
+        }
+
+        let lot = select_lot(collection_lots, method).map(|index| collection_lots.remove(index));
+        if !in_year {
+            continue;
+        }
+        if event.event_type == "Transfer" {
+            summary.transfers_out += 1;
+            continue;
+        }
+
+        let (proceeds, proceeds_usd) = if is_sale {
+            (price, price_usd)
+        } else {
+            (None, Some(BigDecimal::from(0)))
+        };
+        let royalty = normalize(event.royalty_amount);
+        let royalty_usd = to_usd(&royalty, &event.currency_usd_price);
+        let marketplace_fee = normalize(event.marketplace_fee_amount);
+        let marketplace_fee_usd = to_usd(&marketplace_fee, &event.currency_usd_price);
+        let cost_basis_usd = lot.as_ref().and_then(|lot| lot.cost_usd.clone());
+        let acquisition_unknown = match &lot {
+            Some(lot) => lot.acquisition_type == "Transfer",
+            None => true,
+        };
+        if acquisition_unknown {
+            summary.disposals_without_cost_basis += 1;
+        }
+        // Fees that were not recorded count as nothing
+        let gain_usd = match (&proceeds_usd, &cost_basis_usd) {
+            (Some(proceeds_usd), Some(cost_basis_usd)) => {
+                let fees = royalty_usd.clone().unwrap_or_default()
+                    + marketplace_fee_usd.clone().unwrap_or_default();
+                Some(proceeds_usd - fees - cost_basis_usd)
+            }
+            _ => None,
+        };
+
+        summary.disposal_count += 1;
+        add(&mut summary.total_proceeds_usd, &proceeds_usd);
+        add(&mut summary.total_cost_basis_usd, &cost_basis_usd);
+        add(&mut summary.total_royalties_usd, &royalty_usd);
+        add(
+            &mut summary.total_marketplace_fees_usd,
+            &marketplace_fee_usd,
+        );
+        add(&mut summary.net_gain_usd, &gain_usd);
+
+        disposals.push(TaxDisposal {
+            collection_address: event.contract_address,
+            collection_name: event.collection_name,
+            token_id: event.token_id,
+            disposal_type: activity_type(&event.event_type),
+            disposed_at: event.block_timestamp,
+            disposal_date: format_date(event.block_timestamp),
+            acquisition_type: lot.as_ref().map(|lot| lot.acquisition_type.clone()),
+            acquired_at: lot.as_ref().map(|lot| lot.acquired_at),
+            acquisition_date: lot.as_ref().map(|lot| format_date(lot.acquired_at)),
+            holding_days: lot
+                .as_ref()
+                .map(|lot| (event.block_timestamp - lot.acquired_at) / SECONDS_PER_DAY),
+            currency: proceeds.as_ref().and(currency),
+            proceeds,
+            proceeds_usd,
+            cost_basis_usd,
+            royalty,
+            royalty_usd,
+            marketplace_fee,
+            marketplace_fee_usd,
+            gain_usd,
+            transaction_hash: event.transaction_hash,
+        });
+    }
+
+    TaxReport { summary, disposals }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const YEAR_START: i64 = 1704067200;
+    const YEAR_END: i64 = 1735689599;
+    const ETH_USD: i64 = 2000;
+
+    fn lot(cost_usd: Option<i64>) -> Lot {
+        Lot {
+            acquisition_type: String::from("Sale"),
+            acquired_at: 0,
+            cost_usd: cost_usd.map(BigDecimal::from),
+        }
+    }
+
+    fn wei(eth: i64) -> BigDecimal {
+        BigDecimal::from(eth) * BigDecimal::from(1_000_000_000_000_000_000u64)
+    }
+
+    /// Event of the token 1 of a single collection, trades being in ETH.
+    fn event(
+        event_type: &str,
+        timestamp: i64,
+        is_acquisition: bool,
+        eth: Option<i64>,
+    ) -> TaxEventDB {
+        TaxEventDB {
+            contract_address: String::from("0x1"),
+            collection_name: None,
+            token_id: String::from("1"),
+            event_type: event_type.to_string(),
+            block_timestamp: timestamp,
+            is_acquisition,
+            amount: eth.map(wei),
+            royalty_amount: None,
+            marketplace_fee_amount: None,
+            currency_symbol: None,
+            currency_decimals: None,
+            currency_usd_price: eth.map(|_| BigDecimal::from(ETH_USD)),
+            transaction_hash: None,
+        }
+    }
+
+    fn report(events: Vec<TaxEventDB>, method: LotMethod) -> TaxReport {
+        build_tax_report(events, 2024, method, YEAR_START, YEAR_END)
+    }
+
+    fn usd(value: i64) -> Option<BigDecimal> {
+        Some(BigDecimal::from(value))
+    }
+
+    #[test]
+    fn test_select_lot() {
+        let lots = vec![lot(Some(5)), lot(None), lot(Some(10)), lot(Some(10))];
+        assert_eq!(select_lot(&lots, LotMethod::Fifo), Some(0));
+        assert_eq!(select_lot(&lots, LotMethod::Lifo), Some(3));
+        assert_eq!(select_lot(&lots, LotMethod::Hifo), Some(2));
+        assert_eq!(
+            select_lot(&[lot(None), lot(None)], LotMethod::Hifo),
+            Some(0)
+        );
+        assert_eq!(select_lot(&[], LotMethod::Fifo), None);
+    }
+
+    #[test]
+    fn test_lot_methods() {
+        let events = || {
+            vec![
+                event("Mint", YEAR_START + 1, true, None),
+                event("Executed", YEAR_START + 2, true, Some(1)),
+                event("Executed", YEAR_START + 3, false, Some(2)),
+            ]
+        };
+
+        let fifo = report(events(), LotMethod::Fifo);
+        assert_eq!(fifo.disposals.len(), 1);
+        assert_eq!(fifo.disposals[0].acquisition_type.as_deref(), Some("Mint"));
+        assert_eq!(fifo.disposals[0].proceeds_usd, usd(4000));
+        assert_eq!(fifo.disposals[0].cost_basis_usd, usd(0));
+        assert_eq!(fifo.disposals[0].gain_usd, usd(4000));
+
+        let lifo = report(events(), LotMethod::Lifo);
+        assert_eq!(lifo.disposals[0].acquisition_type.as_deref(), Some("Sale"));
+        assert_eq!(lifo.disposals[0].cost_basis_usd, usd(2000));
+        assert_eq!(lifo.disposals[0].gain_usd, usd(2000));
+
+        let hifo = report(events(), LotMethod::Hifo);
+        assert_eq!(hifo.disposals[0].acquired_at, Some(YEAR_START + 2));
+        assert_eq!(hifo.summary.net_gain_usd, usd(2000));
+    }
+
+    #[test]
+    fn test_disposal_of_unknown_acquisition() {
+        let events = vec![
+            event("Executed", YEAR_START + 10, false, Some(2)),
+            event("Transfer", YEAR_START + 20, true, None),
+            event("Executed", YEAR_START + 30, false, Some(2)),
+        ];
+
+        let report = report(events, LotMethod::Fifo);
+        assert_eq!(report.summary.disposal_count, 2);
+        assert_eq!(report.summary.disposals_without_cost_basis, 2);
+        assert_eq!(report.summary.total_proceeds_usd, usd(8000));
+        assert_eq!(report.summary.net_gain_usd, None);
+
+        let before_history = &report.disposals[0];
+        assert_eq!(before_history.acquisition_type, None);
+        assert_eq!(before_history.holding_days, None);
+        assert_eq!(before_history.cost_basis_usd, None);
+        assert_eq!(before_history.gain_usd, None);
+
+        let received = &report.disposals[1];
+        assert_eq!(received.acquisition_type.as_deref(), Some("Transfer"));
+        assert_eq!(received.cost_basis_usd, None);
+        assert_eq!(received.gain_usd, None);
+    }
+
+    #[test]
+    fn test_mint_cost_basis() {
+        let events = vec![
+            event("Mint", YEAR_START + 1, true, None),
+            event("Burn", YEAR_START + 2, false, None),
+        ];
+
+        let report = report(events, LotMethod::Fifo);
+        let burn = &report.disposals[0];
+        assert_eq!(burn.disposal_type, "Burn");
+        assert_eq!(burn.proceeds, None);
+        assert_eq!(burn.proceeds_usd, usd(0));
+        assert_eq!(burn.cost_basis_usd, usd(0));
+        assert_eq!(burn.gain_usd, usd(0));
+        assert_eq!(report.summary.disposals_without_cost_basis, 0);
+    }
+
+    #[test]
+    fn test_year_boundaries() {
+        let events = vec![
+            event("Executed", YEAR_START - 200, true, Some(1)),
+            event("Executed", YEAR_START - 100, true, Some(3)),
+            // Closes the first lot without being reported
+            event("Executed", YEAR_START - 50, false, Some(2)),
+            event("Transfer", YEAR_START - 40, true, None),
+            event("Transfer", YEAR_START - 1, false, None),
+            event("Executed", YEAR_START, false, Some(2)),
+        ];
+
+        let report = report(events, LotMethod::Fifo);
+        assert_eq!(report.summary.disposal_count, 1);
+        assert_eq!(report.summary.transfers_out, 0);
+        assert_eq!(report.disposals[0].disposed_at, YEAR_START);
+        assert_eq!(report.disposals[0].acquired_at, Some(YEAR_START - 40));
+    }
+
+    #[test]
+    fn test_fees_reduce_gain() {
+        let mut sale = event("Executed", YEAR_START + 2, false, Some(2));
+        sale.royalty_amount = Some(wei(1) / BigDecimal::from(10));
+        sale.marketplace_fee_amount = Some(wei(1) / BigDecimal::from(20));
+        let events = vec![event("Executed", YEAR_START + 1, true, Some(1)), sale];
+
+        let report = report(events, LotMethod::Fifo);
+        assert_eq!(report.disposals[0].royalty_usd, usd(200));
+        assert_eq!(report.disposals[0].marketplace_fee_usd, usd(100));
+        assert_eq!(report.disposals[0].gain_usd, usd(1700));
+    }
+}
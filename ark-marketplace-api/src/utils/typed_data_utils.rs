@@ -0,0 +1,134 @@
+use serde_json::{json, Value};
+use sha3::{Digest, Keccak256};
+use starknet_types_core::felt::Felt;
+use starknet_types_core::hash::{Poseidon, StarkHash};
+
+const DOMAIN_NAME: &str = "ArkProject";
+const DOMAIN_VERSION: &str = "1";
+const REVISION: &str = "1";
+const SIGN_IN_STATEMENT: &str = "Sign in to ArkProject";
+
+/// Encoded types of SNIP-12 revision 1, where names are quoted.
+const STARKNET_DOMAIN_TYPE: &str = "\"StarknetDomain\"(\"name\":\"shortstring\",\"version\":\"shortstring\",\"chainId\":\"shortstring\",\"revision\":\"shortstring\")";
+const SIGN_IN_TYPE: &str = "\"SignIn\"(\"statement\":\"shortstring\",\"address\":\"ContractAddress\",\"nonce\":\"felt\",\"issuedAt\":\"timestamp\",\"expirationTime\":\"timestamp\")";
+
+/// Message a wallet signs to open a session, as SNIP-12 typed data.
+pub struct SignInMessage {
+    pub chain_id: String,
+    pub address: String,
+    pub nonce: String,
+    pub issued_at: i64,
+    pub expiration_time: i64,
+}
+
+impl SignInMessage {
+    /// Typed data to give to the `signMessage` method of the wallet.
+    pub fn typed_data(&self) -> Value {
+        json!({
+            "types": {
+                "StarknetDomain": [
+                    { "name": "name", "type": "shortstring" },
+                    { "name": "version", "type": "shortstring" },
+                    { "name": "chainId", "type": "shortstring" },
+                    { "name": "revision", "type": "shortstring" },
+                ],
+                "SignIn": [
+                    { "name": "statement", "type": "shortstring" },
+                    { "name": "address", "type": "ContractAddress" },
+                    { "name": "nonce", "type": "felt" },
+                    { "name": "issuedAt", "type": "timestamp" },
+                    { "name": "expirationTime", "type": "timestamp" },
+                ],
+            },
+            "primaryType": "SignIn",
+            "domain": {
+                "name": DOMAIN_NAME,
+                "version": DOMAIN_VERSION,
+                "chainId": decode_short_string(&self.chain_id).unwrap_or_else(|| self.chain_id.clone()),
+                "revision": REVISION,
+            },
+            "message": {
+                "statement": SIGN_IN_STATEMENT,
+                "address": self.address,
+                "nonce": self.nonce,
+                "issuedAt": self.issued_at,
+                "expirationTime": self.expiration_time,
+            },
+        })
+    }
+
+    /// Hash of the typed data for the signing account, the value its
+    /// `is_valid_signature` checks the signature against.
+    pub fn message_hash(&self) -> Result<Felt, String> {
+        let address = parse_felt(&self.address)?;
+
+        let domain_hash = Poseidon::hash_array(&[
+            starknet_keccak(STARKNET_DOMAIN_TYPE.as_bytes()),
+            encode_short_string(DOMAIN_NAME)?,
+            encode_short_string(DOMAIN_VERSION)?,
+            encode_short_string(&self.chain_id)?,
+            encode_short_string(REVISION)?,
+        ]);
+        let message_hash = Poseidon::hash_array(&[
+            starknet_keccak(SIGN_IN_TYPE.as_bytes()),
+            encode_short_string(SIGN_IN_STATEMENT)?,
+            address,
+            parse_felt(&self.nonce)?,
+            encode_timestamp(self.issued_at)?,
+            encode_timestamp(self.expiration_time)?,
+        ]);
+
+        Ok(Poseidon::hash_array(&[
+            encode_short_string("StarkNet Message")?,
+            domain_hash,
+            address,
+            message_hash,
+        ]))
+    }
+}
+
+/// Keccak-256 truncated to 250 bits, as used for selectors and type hashes.
+pub fn starknet_keccak(data: &[u8]) -> Felt {
+    let mut hash: [u8; 32] = Keccak256::digest(data).into();
+    hash[0] &= 0x03;
+    Felt::from_bytes_be(&hash)
+}
+
+/// Parses a felt given in hexadecimal or decimal.
+pub fn parse_felt(value: &str) -> Result<Felt, String> {
+    let felt = if value.starts_with("0x") || value.starts_with("0X") {
+        Felt::from_hex(value).ok()
+    } else {
+        Felt::from_dec_str(value).ok()
+    };
+    felt.ok_or_else(|| format!("Invalid felt: {}", value))
+}
+
+/// Encodes a `shortstring` the way starknet.js does: values that read as a
+/// number are taken as is, other values are ASCII encoded.
+fn encode_short_string(value: &str) -> Result<Felt, String> {
+    if let Ok(felt) = parse_felt(value) {
+        return Ok(felt);
+    }
+    if value.len() > 31 || !value.is_ascii() {
+        return Err(format!("Invalid short string: {}", value));
+    }
+    Ok(Felt::from_bytes_be_slice(value.as_bytes()))
+}
+
+fn encode_timestamp(timestamp: i64) -> Result<Felt, String> {
+    u64::try_from(timestamp)
+        .map(Felt::from)
+        .map_err(|_| format!("Invalid timestamp: {}", timestamp))
+}
+
+/// Reads a hexadecimal felt as an ASCII short string, such as the chain ids.
+fn decode_short_string(value: &str) -> Option<String> {
+    let bytes = parse_felt(value).ok()?.to_bytes_be();
+    let text: String = bytes
+        .iter()
+        .skip_while(|byte| **byte == 0)
+        .map(|byte| *byte as char)
+        .collect();
+    (!text.is_empty() && text.chars().all(|c| c.is_ascii_graphic())).then_some(text)
+}
@@ -6,10 +6,12 @@ use aws_config::BehaviorVersion;
 use clap::{App, Arg};
 use redis::aio::MultiplexedConnection;
 use redis::Client;
+use tasks::chains::get_enabled_chains;
 use tasks::collections::{
     empty_floor_price, insert_floor_price, update_collections_market_data,
     update_contract_marketdata, update_top_bid_collections,
 };
+use tasks::events::backfill_eth_amounts;
 use tasks::tokens::{cache_collection_pages, update_listed_tokens, update_top_bid_tokens};
 use tracing::info;
 use tracing_subscriber::fmt;
@@ -82,7 +84,9 @@ async fn main() -> std::io::Result<()> {
                 update_listed_tokens(&db_pool, &mut con).await;
                 update_top_bid_tokens(&db_pool, &mut con).await;
                 if should_cache_pages {
-                    let _ = cache_collection_pages(&db_pool, &mut con).await;
+                    for chain_id in get_enabled_chains(&db_pool).await {
+                        let _ = cache_collection_pages(&db_pool, &mut con, &chain_id).await;
+                    }
                 }
                 update_contract_marketdata(&db_pool).await;
             }
@@ -92,6 +96,7 @@ async fn main() -> std::io::Result<()> {
             update_top_bid_collections(&db_pool).await;
             update_collections_market_data(&db_pool).await;
             insert_floor_price(&db_pool).await;
+            backfill_eth_amounts(&db_pool).await;
         }
         "task_set3" => {
             empty_floor_price(&db_pool).await;
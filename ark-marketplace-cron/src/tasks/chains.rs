@@ -0,0 +1,19 @@
+use sqlx::PgPool;
+
+/// Ids of the chains the marketplace serves, default chain first.
+pub async fn get_enabled_chains(pool: &PgPool) -> Vec<String> {
+    let query = "
+        SELECT chain_id
+        FROM chain
+        WHERE is_enabled = TRUE
+        ORDER BY is_default DESC, chain_id
+    ";
+
+    match sqlx::query_scalar::<_, String>(query).fetch_all(pool).await {
+        Ok(chains) => chains,
+        Err(e) => {
+            tracing::error!("Failed to fetch enabled chains: {}", e);
+            Vec::new()
+        }
+    }
+}
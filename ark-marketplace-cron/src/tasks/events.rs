@@ -0,0 +1,89 @@
+use sqlx::PgPool;
+use tracing::info;
+
+/// Events priced per run, to keep each update short.
+const ETH_AMOUNT_BATCH_SIZE: i64 = 5000;
+
+/// Candles of ark-price-engine are looked up in the day before the event.
+const CANDLE_WINDOW_SECONDS: i64 = 86400;
+
+/// Prices the events that were indexed without a price, from the candles of
+/// ark-price-engine closest before the event in the same day: the amount in
+/// wei of ETH, and the USD price of the currency for sales. Events still
+/// unpriced once their candle window is over are marked and not looked at
+/// again.
+pub async fn backfill_eth_amounts(pool: &PgPool) {
+    let backfill_query = r#"
+        WITH candidate AS (
+            SELECT te.token_event_id, te.event_type, te.amount, te.block_timestamp,
+                currency_mapping.symbol, currency_mapping.decimals
+            FROM token_event te
+            LEFT JOIN currency_mapping ON currency_mapping.currency_address = te.currency_address
+                AND currency_mapping.chain_id = te.chain_id
+            WHERE te.amount IS NOT NULL
+              AND te.price_backfill_attempted_at IS NULL
+              AND (te.eth_amount IS NULL OR (te.event_type = 'Executed' AND te.currency_usd_price IS NULL))
+            ORDER BY te.block_timestamp
+            LIMIT $1
+        ),
+        priced AS (
+            SELECT
+                candidate.token_event_id,
+                candidate.block_timestamp,
+                CASE WHEN candidate.decimals IS NOT NULL AND eth_price.close > 0 THEN
+                    TRUNC(
+                        hex_to_decimal(candidate.amount)
+                        * POWER(10::NUMERIC, 18 - candidate.decimals)
+                        * currency_price.close / eth_price.close
+                    )::TEXT
+                END AS eth_amount,
+                CASE WHEN candidate.event_type = 'Executed' THEN currency_price.close END AS currency_usd_price
+            FROM candidate
+            LEFT JOIN LATERAL (
+                SELECT price_candle.close
+                FROM price_candle
+                WHERE price_candle.asset = UPPER(candidate.symbol)
+                  AND price_candle.timestamp <= candidate.block_timestamp
+                  AND price_candle.timestamp > candidate.block_timestamp - $2
+                ORDER BY price_candle.timestamp DESC
+                LIMIT 1
+            ) currency_price ON true
+            LEFT JOIN LATERAL (
+                SELECT price_candle.close
+                FROM price_candle
+                WHERE price_candle.asset = 'ETH'
+                  AND price_candle.timestamp <= candidate.block_timestamp
+                  AND price_candle.timestamp > candidate.block_timestamp - $2
+                ORDER BY price_candle.timestamp DESC
+                LIMIT 1
+            ) eth_price ON true
+        )
+        UPDATE token_event
+        SET eth_amount = COALESCE(token_event.eth_amount, priced.eth_amount),
+            currency_usd_price = COALESCE(token_event.currency_usd_price, priced.currency_usd_price),
+            price_backfill_attempted_at = CASE
+                WHEN priced.block_timestamp < EXTRACT(EPOCH FROM NOW())::BIGINT - $2
+                THEN EXTRACT(EPOCH FROM NOW())::BIGINT
+            END
+        FROM priced
+        WHERE token_event.token_event_id = priced.token_event_id
+          AND (
+            priced.eth_amount IS NOT NULL
+            OR priced.currency_usd_price IS NOT NULL
+            OR priced.block_timestamp < EXTRACT(EPOCH FROM NOW())::BIGINT - $2
+          )
+    "#;
+
+    match sqlx::query(backfill_query)
+        .bind(ETH_AMOUNT_BATCH_SIZE)
+        .bind(CANDLE_WINDOW_SECONDS)
+        .execute(pool)
+        .await
+    {
+        Ok(result) => info!(
+            "Backfilled the prices of {} events.",
+            result.rows_affected()
+        ),
+        Err(e) => tracing::error!("Failed to backfill the event prices: {}", e),
+    }
+}
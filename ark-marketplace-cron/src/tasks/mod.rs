@@ -1,2 +1,4 @@
+pub mod chains;
 pub mod collections;
+pub mod events;
 pub mod tokens;
@@ -12,7 +12,6 @@ use sqlx::Row;
 use std::collections::HashSet;
 use tracing::info;
 
-const CHAIN_ID: &str = "0x534e5f4d41494e";
 const ITEMS_PER_PAGE: i64 = 50;
 const REDIS_CACHE_TTL_SECONDS: u64 = 60;
 const MAX_PAGES_TO_CACHE: i64 = 5;
@@ -504,6 +503,7 @@ async fn update_collections_cache(
 pub async fn cache_collection_pages(
     db_pool: &PgPool,
     redis_conn: &mut MultiplexedConnection,
+    chain_id: &str,
 ) -> redis::RedisResult<()> {
     let collections_to_cache = vec![
         "0x05dbdedc203e92749e2e746e2d40a768d966bd243df04a6b712e222bc040a9af",
@@ -512,7 +512,8 @@ pub async fn cache_collection_pages(
     ];
 
     for contract_address in collections_to_cache {
-        if let Err(e) = cache_collection_page(db_pool, redis_conn, contract_address).await {
+        if let Err(e) = cache_collection_page(db_pool, redis_conn, contract_address, chain_id).await
+        {
             tracing::error!("Failed to cache collection page: {}", e);
         }
     }
@@ -528,6 +529,7 @@ async fn cache_collection_page(
     db_pool: &PgPool,
     redis_conn: &mut MultiplexedConnection,
     contract_address: &str,
+    chain_id: &str,
 ) -> redis::RedisResult<()> {
     let token_count_query = sqlx::query!(
         "
@@ -537,7 +539,7 @@ async fn cache_collection_page(
               AND token.chain_id = $2
             ",
         contract_address,
-        CHAIN_ID
+        chain_id
     )
     .fetch_one(db_pool)
     .await;
@@ -550,6 +552,11 @@ async fn cache_collection_page(
         }
     };
 
+    // The collection is not deployed on this chain.
+    if token_count == 0 {
+        return Ok(());
+    }
+
     let total_pages = calculate_total_pages(token_count, ITEMS_PER_PAGE);
 
     for page in 1..=MAX_PAGES_TO_CACHE {
@@ -576,7 +583,7 @@ async fn cache_collection_page(
             ITEMS_PER_PAGE,
             (page - 1) * ITEMS_PER_PAGE,
             contract_address,
-            CHAIN_ID,
+            chain_id,
         )
         .fetch_all(db_pool)
         .await
@@ -585,7 +592,7 @@ async fn cache_collection_page(
             Vec::new()
         });
         let json_data = json!((tokens_data, has_next_page, token_count));
-        let cache_key = format!("all_tokens_{}_page_{}", contract_address, page);
+        let cache_key = format!("all_tokens_{}_{}_page_{}", contract_address, chain_id, page);
         // Store the JSON data in Redis
         match redis_conn
             .set_ex::<_, _, ()>(&cache_key, json_data.to_string(), REDIS_CACHE_TTL_SECONDS)
@@ -4,6 +4,7 @@ extern crate openssl_probe;
 mod aws_s3_file_manager;
 mod elasticsearch_manager;
 mod metadata_storage;
+mod rarity;
 
 use crate::aws_s3_file_manager::AWSFileManager;
 use crate::elasticsearch_manager::EsManager;
@@ -19,7 +20,7 @@ use aws_config::BehaviorVersion;
 use dotenv::dotenv;
 use metadata_storage::MetadataSqlStorage;
 use serde::Deserialize;
-use std::{env, time::Duration};
+use std::{collections::HashSet, env, time::Duration};
 use tokio::time::sleep;
 use tracing::{debug, error, info, span, trace, warn, Level};
 use tracing_subscriber::{fmt, layer::SubscriberExt, EnvFilter, Registry};
@@ -195,6 +196,7 @@ async fn main() -> Result<()> {
                     info!("No tokens found that require metadata refresh");
                     sleep(config.loop_delay_duration).await;
                 } else {
+                    let mut refreshed_collections = HashSet::new();
                     for token in tokens {
                         total_tokens += 1;
 
@@ -220,6 +222,10 @@ async fn main() -> Result<()> {
                                     "✅ Metadata for Token ID: {} refreshed successfully",
                                     token.token_id
                                 );
+                                refreshed_collections.insert((
+                                    token.contract_address.clone(),
+                                    token.chain_id.clone(),
+                                ));
                             }
                             Err(metadata_error) => {
                                 match metadata_error {
@@ -242,6 +248,20 @@ async fn main() -> Result<()> {
                             }
                         }
                     }
+
+                    // Scores depend on the traits of the whole collection, so they
+                    // are recomputed once per batch rather than once per token.
+                    for (contract_address, chain_id) in refreshed_collections {
+                        if let Ok(scored_tokens) = storage
+                            .update_collection_rarity(&contract_address, &chain_id)
+                            .await
+                        {
+                            info!(
+                                "🏅 Rarity of {} tokens updated for collection {}",
+                                scored_tokens, contract_address
+                            );
+                        }
+                    }
                 }
             }
             Err(e) => {
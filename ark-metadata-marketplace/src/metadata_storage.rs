@@ -1,3 +1,4 @@
+use crate::rarity::update_collection_rarity;
 use arkproject::{
     metadata::{
         storage::Storage,
@@ -24,6 +25,19 @@ impl MetadataSqlStorage {
 
         Ok(Self { pool })
     }
+
+    pub async fn update_collection_rarity(
+        &self,
+        contract_address: &str,
+        chain_id: &str,
+    ) -> Result<u64, StorageError> {
+        update_collection_rarity(&self.pool, contract_address, chain_id)
+            .await
+            .map_err(|e| {
+                error!("Failed to update collection rarity. Error: {}", e);
+                StorageError::DatabaseError(e.to_string())
+            })
+    }
 }
 
 #[async_trait]
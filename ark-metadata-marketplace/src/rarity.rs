@@ -0,0 +1,119 @@
+use sqlx::PgPool;
+
+// The scores are described in the `token_rarity` migration.
+const INSERT_COLLECTION_RARITY_QUERY: &str = "
+    WITH tokens AS (
+        SELECT token_id, metadata
+        FROM token
+        WHERE contract_address = $1
+          AND chain_id = $2
+          AND is_burned = FALSE
+          AND metadata IS NOT NULL
+    ),
+    total AS (
+        SELECT COUNT(*)::NUMERIC AS token_count FROM tokens
+    ),
+    token_traits AS (
+        SELECT DISTINCT ON (tokens.token_id, attribute->>'trait_type')
+            tokens.token_id,
+            attribute->>'trait_type' AS trait_type,
+            COALESCE(attribute->>'value', 'null') AS trait_value
+        FROM tokens,
+            jsonb_array_elements(
+                CASE
+                    WHEN jsonb_typeof(tokens.metadata->'attributes') = 'array'
+                    THEN tokens.metadata->'attributes'
+                    ELSE '[]'::jsonb
+                END
+            ) AS attribute
+        WHERE attribute->>'trait_type' IS NOT NULL
+    ),
+    trait_types AS (
+        SELECT DISTINCT trait_type FROM token_traits
+    ),
+    full_traits AS (
+        SELECT tokens.token_id, trait_types.trait_type, COALESCE(token_traits.trait_value, 'null') AS trait_value
+        FROM tokens
+        CROSS JOIN trait_types
+        LEFT JOIN token_traits
+            ON token_traits.token_id = tokens.token_id
+            AND token_traits.trait_type = trait_types.trait_type
+        UNION ALL
+        SELECT tokens.token_id, 'meta_trait:trait_count', COUNT(token_traits.trait_type)::TEXT
+        FROM tokens
+        LEFT JOIN token_traits ON token_traits.token_id = tokens.token_id
+        GROUP BY tokens.token_id
+    ),
+    value_counts AS (
+        SELECT trait_type, trait_value, COUNT(*)::NUMERIC AS value_count
+        FROM full_traits
+        GROUP BY trait_type, trait_value
+    ),
+    type_counts AS (
+        SELECT trait_type, COUNT(*)::NUMERIC AS value_types
+        FROM value_counts
+        GROUP BY trait_type
+    ),
+    entropy AS (
+        SELECT SUM(-(value_count / token_count) * LOG(2, value_count / token_count)) AS collection_entropy
+        FROM value_counts, total
+    ),
+    scores AS (
+        SELECT
+            full_traits.token_id,
+            EXP(SUM(LN(value_counts.value_count / total.token_count))) AS statistical_score,
+            SUM((total.token_count / value_counts.value_count) / type_counts.value_types) AS trait_count_score,
+            COALESCE(
+                SUM(-LOG(2, value_counts.value_count / total.token_count)) / NULLIF(MAX(entropy.collection_entropy), 0),
+                0
+            ) AS information_content_score
+        FROM full_traits
+        INNER JOIN value_counts
+            ON value_counts.trait_type = full_traits.trait_type
+            AND value_counts.trait_value = full_traits.trait_value
+        INNER JOIN type_counts ON type_counts.trait_type = full_traits.trait_type
+        CROSS JOIN total
+        CROSS JOIN entropy
+        WHERE EXISTS (SELECT 1 FROM trait_types)
+        GROUP BY full_traits.token_id
+    )
+    INSERT INTO token_rarity (
+        contract_address, chain_id, token_id, statistical_score, trait_count_score,
+        information_content_score, rarity_rank
+    )
+    SELECT
+        $1,
+        $2,
+        token_id,
+        statistical_score,
+        trait_count_score,
+        information_content_score,
+        RANK() OVER (ORDER BY information_content_score DESC)
+    FROM scores
+";
+
+/// Recomputes the rarity of every token of a collection from the attributes of
+/// its metadata. Collections without any trait get no rarity.
+pub async fn update_collection_rarity(
+    pool: &PgPool,
+    contract_address: &str,
+    chain_id: &str,
+) -> Result<u64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM token_rarity WHERE contract_address = $1 AND chain_id = $2")
+        .bind(contract_address)
+        .bind(chain_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let result = sqlx::query(INSERT_COLLECTION_RARITY_QUERY)
+        .bind(contract_address)
+        .bind(chain_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(result.rows_affected())
+}
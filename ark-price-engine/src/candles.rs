@@ -0,0 +1,173 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+pub const CANDLE_SECONDS: i64 = 60;
+
+/// Size of the candles returned by `/history`, the minute candles being
+/// merged into larger ones.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum Interval {
+    #[serde(rename = "1m")]
+    OneMinute,
+    #[serde(rename = "5m")]
+    FiveMinutes,
+    #[serde(rename = "15m")]
+    FifteenMinutes,
+    #[serde(rename = "1h")]
+    OneHour,
+    #[serde(rename = "4h")]
+    FourHours,
+    #[serde(rename = "1d")]
+    OneDay,
+}
+
+impl Interval {
+    pub fn seconds(&self) -> i64 {
+        match self {
+            Interval::OneMinute => 60,
+            Interval::FiveMinutes => 300,
+            Interval::FifteenMinutes => 900,
+            Interval::OneHour => 3600,
+            Interval::FourHours => 14400,
+            Interval::OneDay => 86400,
+        }
+    }
+}
+
+/// USD prices of an asset during a period, `timestamp` being its start.
+#[derive(Debug, Clone, Serialize)]
+pub struct Candle {
+    pub timestamp: i64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    /// Number of prices received during the period
+    pub trade_count: i64,
+}
+
+impl Candle {
+    fn new(timestamp: i64, price: f64) -> Self {
+        Self {
+            timestamp,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            trade_count: 1,
+        }
+    }
+
+    fn add_price(&mut self, price: f64) {
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.close = price;
+        self.trade_count += 1;
+    }
+}
+
+/// Builds the minute candles of each asset, by symbol, from its prices. A
+/// candle is complete once a price of a later minute is received, and waits
+/// in `completed` until it is saved.
+#[derive(Debug, Default)]
+pub struct CandleBuilder {
+    current: HashMap<String, Candle>,
+    completed: Vec<(String, Candle)>,
+}
+
+impl CandleBuilder {
+    /// Adds a price observed at `time`, in milliseconds. Prices of a minute
+    /// already completed arrive late and update the current candle.
+    pub fn add_price(&mut self, asset: &str, price: f64, time: i64) {
+        let minute = (time / 1000).div_euclid(CANDLE_SECONDS) * CANDLE_SECONDS;
+        match self.current.get_mut(asset) {
+            Some(candle) if minute <= candle.timestamp => candle.add_price(price),
+            Some(candle) => {
+                let completed = std::mem::replace(candle, Candle::new(minute, price));
+                self.completed.push((asset.to_string(), completed));
+            }
+            None => {
+                self.current
+                    .insert(asset.to_string(), Candle::new(minute, price));
+            }
+        }
+    }
+
+    pub fn take_completed(&mut self) -> Vec<(String, Candle)> {
+        std::mem::take(&mut self.completed)
+    }
+
+    /// Puts back candles that could not be saved, to retry with the next
+    /// ones.
+    pub fn restore_completed(&mut self, mut candles: Vec<(String, Candle)>) {
+        candles.append(&mut self.completed);
+        self.completed = candles;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE_START: i64 = 1_700_000_040;
+
+    fn at(seconds: i64) -> i64 {
+        (MINUTE_START + seconds) * 1000
+    }
+
+    #[test]
+    fn test_candle_of_a_minute() {
+        let mut builder = CandleBuilder::default();
+        builder.add_price("ETH", 2000.0, at(0));
+        builder.add_price("ETH", 2010.0, at(10));
+        builder.add_price("ETH", 1990.0, at(20));
+        builder.add_price("ETH", 2005.0, at(59));
+        assert!(builder.take_completed().is_empty());
+
+        builder.add_price("ETH", 2020.0, at(60));
+        let completed = builder.take_completed();
+        assert_eq!(completed.len(), 1);
+        let (asset, candle) = &completed[0];
+        assert_eq!(asset, "ETH");
+        assert_eq!(candle.timestamp, MINUTE_START);
+        assert_eq!(candle.open, 2000.0);
+        assert_eq!(candle.high, 2010.0);
+        assert_eq!(candle.low, 1990.0);
+        assert_eq!(candle.close, 2005.0);
+        assert_eq!(candle.trade_count, 4);
+    }
+
+    #[test]
+    fn test_late_price_updates_current_candle() {
+        let mut builder = CandleBuilder::default();
+        builder.add_price("ETH", 2000.0, at(60));
+        builder.add_price("ETH", 1900.0, at(30));
+        builder.add_price("STRK", 0.5, at(0));
+        builder.add_price("ETH", 2010.0, at(120));
+
+        let completed = builder.take_completed();
+        assert_eq!(completed.len(), 1);
+        let (_, candle) = &completed[0];
+        assert_eq!(candle.timestamp, MINUTE_START + 60);
+        assert_eq!(candle.low, 1900.0);
+        assert_eq!(candle.close, 1900.0);
+        assert_eq!(candle.trade_count, 2);
+    }
+
+    #[test]
+    fn test_restore_completed() {
+        let mut builder = CandleBuilder::default();
+        builder.add_price("ETH", 2000.0, at(0));
+        builder.add_price("ETH", 2010.0, at(60));
+        let failed = builder.take_completed();
+        builder.add_price("ETH", 2020.0, at(120));
+
+        builder.restore_completed(failed);
+        let timestamps: Vec<i64> = builder
+            .take_completed()
+            .iter()
+            .map(|(_, candle)| candle.timestamp)
+            .collect();
+        assert_eq!(timestamps, vec![MINUTE_START, MINUTE_START + 60]);
+    }
+}
@@ -0,0 +1,142 @@
+use crate::candles::{Candle, Interval};
+use sqlx::{Error, FromRow, PgPool};
+
+#[derive(FromRow)]
+struct CandleDB {
+    timestamp: i64,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    trade_count: i64,
+}
+
+impl From<CandleDB> for Candle {
+    fn from(candle: CandleDB) -> Self {
+        Self {
+            timestamp: candle.timestamp,
+            open: candle.open,
+            high: candle.high,
+            low: candle.low,
+            close: candle.close,
+            trade_count: candle.trade_count,
+        }
+    }
+}
+
+/// Saves minute candles. A candle already saved for the minute, by an
+/// engine restarted during that minute, is merged with the new one.
+pub async fn insert_candles(pool: &PgPool, candles: &[(String, Candle)]) -> Result<(), Error> {
+    let query = "
+        INSERT INTO price_candle (asset, timestamp, open, high, low, close, trade_count)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT (asset, timestamp) DO UPDATE SET
+            high = GREATEST(price_candle.high, EXCLUDED.high),
+            low = LEAST(price_candle.low, EXCLUDED.low),
+            close = EXCLUDED.close,
+            trade_count = price_candle.trade_count + EXCLUDED.trade_count
+    ";
+
+    let mut tx = pool.begin().await?;
+    for (asset, candle) in candles {
+        sqlx::query(query)
+            .bind(asset)
+            .bind(candle.timestamp)
+            .bind(candle.open)
+            .bind(candle.high)
+            .bind(candle.low)
+            .bind(candle.close)
+            .bind(candle.trade_count)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await
+}
+
+/// Candles of `interval` between `from` and `to`, oldest first. Periods
+/// without trades have no candle.
+pub async fn get_candles(
+    pool: &PgPool,
+    asset: &str,
+    interval: Interval,
+    from: i64,
+    to: i64,
+) -> Result<Vec<Candle>, Error> {
+    let query = "
+        SELECT
+            timestamp - timestamp % $4 AS timestamp,
+            (array_agg(open ORDER BY timestamp))[1]::FLOAT8 AS open,
+            MAX(high)::FLOAT8 AS high,
+            MIN(low)::FLOAT8 AS low,
+            (array_agg(close ORDER BY timestamp DESC))[1]::FLOAT8 AS close,
+            SUM(trade_count)::BIGINT AS trade_count
+        FROM price_candle
+        WHERE asset = $1
+          AND timestamp BETWEEN $2 - $2 % $4 AND $3
+        GROUP BY 1
+        ORDER BY 1
+    ";
+
+    let candles = sqlx::query_as::<_, CandleDB>(query)
+        .bind(asset)
+        .bind(from)
+        .bind(to)
+        .bind(interval.seconds())
+        .fetch_all(pool)
+        .await?;
+    Ok(candles.into_iter().map(Candle::from).collect())
+}
+
+/// Last minute candle started at or before `timestamp`, no older than
+/// `max_age` seconds.
+pub async fn get_candle_at(
+    pool: &PgPool,
+    asset: &str,
+    timestamp: i64,
+    max_age: i64,
+) -> Result<Option<Candle>, Error> {
+    let query = "
+        SELECT
+            timestamp,
+            open::FLOAT8 AS open,
+            high::FLOAT8 AS high,
+            low::FLOAT8 AS low,
+            close::FLOAT8 AS close,
+            trade_count::BIGINT AS trade_count
+        FROM price_candle
+        WHERE asset = $1
+          AND timestamp <= $2
+          AND timestamp > $2 - $3
+        ORDER BY timestamp DESC
+        LIMIT 1
+    ";
+
+    let candle = sqlx::query_as::<_, CandleDB>(query)
+        .bind(asset)
+        .bind(timestamp)
+        .bind(max_age)
+        .fetch_optional(pool)
+        .await?;
+    Ok(candle.map(Candle::from))
+}
+
+/// Symbols of the currencies of `currency_mapping`, each with its address
+/// on `chain_id` when it has one.
+pub async fn get_currency_assets(
+    pool: &PgPool,
+    chain_id: &str,
+) -> Result<Vec<(String, Option<String>)>, Error> {
+    let query = "
+        SELECT DISTINCT ON (UPPER(symbol))
+            UPPER(symbol) AS symbol,
+            CASE WHEN chain_id = $1 THEN currency_address END AS currency_address
+        FROM currency_mapping
+        WHERE symbol IS NOT NULL AND symbol <> ''
+        ORDER BY UPPER(symbol), (chain_id = $1) DESC
+    ";
+
+    sqlx::query_as::<_, (String, Option<String>)>(query)
+        .bind(chain_id)
+        .fetch_all(pool)
+        .await
+}
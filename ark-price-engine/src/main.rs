@@ -1,22 +1,42 @@
 extern crate openssl;
 extern crate openssl_probe;
 // Modules déclaration
-use futures_util::StreamExt;
-use serde::Serialize;
-use serde_json::Value;
+mod candles;
+mod db;
+mod oracle;
+mod sources;
+
+use candles::Interval;
+use oracle::{now_millis, Prices};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sources::{build_sources, spawn_source, AssetConfig, PriceSink};
+use sqlx::postgres::{PgPool, PgPoolOptions};
+use std::convert::Infallible;
 use std::sync::{Arc, RwLock};
-use tokio_tungstenite::connect_async;
+use std::time::Duration;
+use tracing_subscriber::fmt;
+use tracing_subscriber::EnvFilter;
+use warp::http::StatusCode;
+use warp::reply::{Json, WithStatus};
 use warp::Filter;
 
 // Default alocator change
 #[global_allocator]
 static GLOBAL: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
 
-#[derive(Debug, Default)]
-struct Prices {
-    eth_usd: f64,
-    strk_usd: f64,
-}
+// Completed candles are saved at this pace
+const FLUSH_INTERVAL_SECONDS: u64 = 10;
+// Range of `/history` when `from` is not given
+const DEFAULT_HISTORY_SECONDS: i64 = 86400;
+const MAX_HISTORY_CANDLES: i64 = 10_000;
+// `/at` has no price when no trade was received in the hour before
+const MAX_PRICE_AGE_SECONDS: i64 = 3600;
+const DEFAULT_PRICE_SOURCES: &str = "binance,coinbase,pragma,avnu";
+// Served on `/`, so always priced
+const DEFAULT_ASSETS: [&str; 2] = ["ETH", "STRK"];
+// Addresses of the assets are the ones of Starknet mainnet
+const MAINNET_CHAIN_ID: &str = "0x534e5f4d41494e";
 
 #[derive(Serialize)]
 struct CombinedPriceResponse {
@@ -25,84 +45,257 @@ struct CombinedPriceResponse {
 }
 
 impl Prices {
-    fn new() -> Prices {
-        Prices {
-            eth_usd: 0.0,
-            strk_usd: 0.0,
+    /// Aggregated prices of ETH and STRK, 0 when no source has a fresh one.
+    fn get_combined(&self) -> CombinedPriceResponse {
+        let now = now_millis();
+        CombinedPriceResponse {
+            eth_usd: self.book.price("ETH", now).price.unwrap_or(0.0),
+            strk_usd: self.book.price("STRK", now).price.unwrap_or(0.0),
         }
     }
+}
 
-    fn update_eth_usd(&mut self, price: f64) {
-        self.eth_usd = price;
-    }
+#[derive(Deserialize)]
+struct HistoryQuery {
+    asset: String,
+    from: Option<i64>,
+    to: Option<i64>,
+    interval: Option<Interval>,
+}
 
-    fn update_strk_usd(&mut self, price: f64) {
-        self.strk_usd = price;
+#[derive(Deserialize)]
+struct AtQuery {
+    asset: String,
+    ts: i64,
+}
+
+fn now() -> i64 {
+    now_millis() / 1000
+}
+
+fn error_reply(status: StatusCode, message: &str) -> WithStatus<Json> {
+    warp::reply::with_status(warp::reply::json(&json!({ "error": message })), status)
+}
+
+/// Assets of `PRICE_ASSETS`, a comma separated list of symbols, or else
+/// every currency of `currency_mapping`.
+async fn load_assets(pool: &PgPool) -> Vec<AssetConfig> {
+    let currencies = match db::get_currency_assets(pool, MAINNET_CHAIN_ID).await {
+        Ok(currencies) => currencies,
+        Err(e) => {
+            tracing::error!("Failed to get currencies: {}", e);
+            Vec::new()
+        }
+    };
+    let symbols = std::env::var("PRICE_ASSETS")
+        .ok()
+        .filter(|symbols| !symbols.trim().is_empty());
+    let mut assets: Vec<AssetConfig> = match symbols {
+        Some(symbols) => symbols
+            .split(',')
+            .map(|symbol| symbol.trim().to_uppercase())
+            .filter(|symbol| !symbol.is_empty())
+            .map(|symbol| AssetConfig {
+                address: currencies
+                    .iter()
+                    .find(|(currency_symbol, _)| *currency_symbol == symbol)
+                    .and_then(|(_, address)| address.clone()),
+                symbol,
+            })
+            .collect(),
+        None => currencies
+            .into_iter()
+            .map(|(symbol, address)| AssetConfig { symbol, address })
+            .collect(),
+    };
+    for symbol in DEFAULT_ASSETS {
+        if !assets.iter().any(|asset| asset.symbol == symbol) {
+            assets.push(AssetConfig {
+                symbol: symbol.to_string(),
+                address: None,
+            });
+        }
     }
+    assets
+}
 
-    // fn get_eth_usd(&self) -> &f64 {
-    //     &self.eth_usd
-    // }
+async fn get_history(query: HistoryQuery, pool: PgPool) -> Result<WithStatus<Json>, Infallible> {
+    let asset = query.asset.to_uppercase();
+    if query.from.is_some_and(|from| from < 0) || query.to.is_some_and(|to| to < 0) {
+        return Ok(error_reply(
+            StatusCode::BAD_REQUEST,
+            "'from' and 'to' must be unix timestamps",
+        ));
+    }
+    let to = query.to.unwrap_or_else(now);
+    let from = query
+        .from
+        .unwrap_or_else(|| to.saturating_sub(DEFAULT_HISTORY_SECONDS).max(0));
+    let interval = query.interval.unwrap_or(Interval::OneMinute);
+    let Some(range) = to.checked_sub(from).filter(|range| *range >= 0) else {
+        return Ok(error_reply(
+            StatusCode::BAD_REQUEST,
+            "'from' must not be greater than 'to'",
+        ));
+    };
+    if range / interval.seconds() > MAX_HISTORY_CANDLES {
+        return Ok(error_reply(
+            StatusCode::BAD_REQUEST,
+            "Too many candles, use a larger interval or a shorter range",
+        ));
+    }
 
-    // fn get_strk_usd(&self) -> &f64 {
-    //     &self.strk_usd
-    // }
+    match db::get_candles(&pool, &asset, interval, from, to).await {
+        Ok(candles) => Ok(warp::reply::with_status(
+            warp::reply::json(&json!({
+                "asset": asset,
+                "interval": interval,
+                "from": from,
+                "to": to,
+                "data": candles,
+            })),
+            StatusCode::OK,
+        )),
+        Err(e) => {
+            tracing::error!("Failed to get price candles: {}", e);
+            Ok(error_reply(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to get the price history",
+            ))
+        }
+    }
+}
 
-    fn get_combined(&self) -> CombinedPriceResponse {
-        CombinedPriceResponse {
-            eth_usd: self.eth_usd,
-            strk_usd: self.strk_usd,
+/// Price of the asset at `ts`, the close of the last minute started at or
+/// before it.
+async fn get_price_at(query: AtQuery, pool: PgPool) -> Result<WithStatus<Json>, Infallible> {
+    let asset = query.asset.to_uppercase();
+    if query.ts < 0 {
+        return Ok(error_reply(
+            StatusCode::BAD_REQUEST,
+            "'ts' must be a unix timestamp",
+        ));
+    }
+    match db::get_candle_at(&pool, &asset, query.ts, MAX_PRICE_AGE_SECONDS).await {
+        Ok(Some(candle)) => Ok(warp::reply::with_status(
+            warp::reply::json(&json!({
+                "asset": asset,
+                "ts": query.ts,
+                "price": candle.close,
+                "candle_timestamp": candle.timestamp,
+            })),
+            StatusCode::OK,
+        )),
+        Ok(None) => Ok(error_reply(
+            StatusCode::NOT_FOUND,
+            "No price recorded at this time",
+        )),
+        Err(e) => {
+            tracing::error!("Failed to get price candle: {}", e);
+            Ok(error_reply(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to get the price",
+            ))
         }
     }
 }
 
 #[tokio::main]
 async fn main() {
-    let prices = Arc::new(RwLock::new(Prices::new()));
-    let prices_clone = Arc::clone(&prices);
+    dotenv::dotenv().ok();
+    init_logging();
+
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL not set");
+    let pool = PgPoolOptions::new()
+        .connect(&database_url)
+        .await
+        .expect("Could not connect to the database");
 
+    let prices = Arc::new(RwLock::new(Prices::default()));
+    let assets = Arc::new(load_assets(&pool).await);
+    let source_names =
+        std::env::var("PRICE_SOURCES").unwrap_or_else(|_| DEFAULT_PRICE_SOURCES.to_string());
+    for source in build_sources(&source_names) {
+        tracing::info!("Starting {} price source", source.name());
+        let sink: Arc<dyn PriceSink> = prices.clone();
+        spawn_source(source, Arc::clone(&assets), sink);
+    }
+
+    let flush_prices = Arc::clone(&prices);
+    let flush_pool = pool.clone();
     tokio::spawn(async move {
-        let binance_ws_url = "wss://stream.binance.com:9443/ws/ethusdt@trade/strkusdt@trade";
-        let url = url::Url::parse(binance_ws_url).unwrap();
-
-        let (ws_stream, _) = connect_async(url).await.expect("Failed to connect");
-
-        let (_write, mut read) = ws_stream.split();
-
-        while let Some(Ok(msg)) = read.next().await {
-            if let Ok(text) = msg.to_text() {
-                if let Ok(value) = serde_json::from_str::<Value>(text) {
-                    if let Some(price_str) = value["p"].as_str() {
-                        let price: f64 = price_str.parse().unwrap_or(0.0);
-                        match value["s"].as_str() {
-                            Some("ETHUSDT") => {
-                                let mut prices = prices_clone.write().unwrap();
-                                prices.update_eth_usd(price);
-                            }
-                            Some("STRKUSDT") => {
-                                let mut prices = prices_clone.write().unwrap();
-                                prices.update_strk_usd(price);
-                            }
-                            _ => {}
-                        }
-                    }
-                }
+        let mut interval = tokio::time::interval(Duration::from_secs(FLUSH_INTERVAL_SECONDS));
+        loop {
+            interval.tick().await;
+            let candles = flush_prices.write().unwrap().candles.take_completed();
+            if candles.is_empty() {
+                continue;
+            }
+            if let Err(e) = db::insert_candles(&flush_pool, &candles).await {
+                tracing::error!("Failed to save price candles: {}", e);
+                flush_prices
+                    .write()
+                    .unwrap()
+                    .candles
+                    .restore_completed(candles);
             }
         }
     });
 
     let price_filter = warp::any().map(move || Arc::clone(&prices));
+    let pool_filter = warp::any().map(move || pool.clone());
+
+    let root_route =
+        warp::path::end()
+            .and(price_filter.clone())
+            .map(|prices: Arc<RwLock<Prices>>| {
+                let prices = prices.read().unwrap();
+                let combined_prices = prices.get_combined();
+                drop(prices);
+                warp::reply::json(&combined_prices)
+            });
 
-    let root_route = warp::path::end()
+    let prices_route = warp::path("prices")
+        .and(warp::path::end())
         .and(price_filter)
-        .map(|prices: Arc<RwLock<Prices>>| {
+        .map(move |prices: Arc<RwLock<Prices>>| {
             let prices = prices.read().unwrap();
-            let combined_prices = prices.get_combined();
+            let now = now_millis();
+            let data: Vec<_> = assets
+                .iter()
+                .map(|asset| prices.book.price(&asset.symbol, now))
+                .collect();
             drop(prices);
-            warp::reply::json(&combined_prices)
+            warp::reply::json(&json!({ "data": data }))
         });
 
-    let routes = root_route;
+    let history_route = warp::path("history")
+        .and(warp::path::end())
+        .and(warp::query::<HistoryQuery>())
+        .and(pool_filter.clone())
+        .and_then(get_history);
+
+    let at_route = warp::path("at")
+        .and(warp::path::end())
+        .and(warp::query::<AtQuery>())
+        .and(pool_filter)
+        .and_then(get_price_at);
+
+    let routes = root_route.or(prices_route).or(history_route).or(at_route);
 
     warp::serve(routes).run(([0, 0, 0, 0], 3030)).await;
 }
+
+fn init_logging() {
+    const DEFAULT_LOG_FILTER: &str = "info";
+    tracing::subscriber::set_global_default(
+        fmt::Subscriber::builder()
+            .with_env_filter(
+                EnvFilter::try_from_default_env()
+                    .or(EnvFilter::try_new(DEFAULT_LOG_FILTER))
+                    .expect("Invalid RUST_LOG filters"),
+            )
+            .finish(),
+    )
+    .expect("Failed to set the global tracing subscriber");
+}
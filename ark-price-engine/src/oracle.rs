@@ -0,0 +1,219 @@
+use crate::candles::CandleBuilder;
+use crate::sources::PriceSink;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Quotes older than this are stale and left out of the price
+pub const STALE_AFTER_MS: i64 = 300_000;
+/// Quotes further than this ratio from the median of the sources are
+/// outliers
+const MAX_DEVIATION: f64 = 0.02;
+/// Fewest fresh quotes for a quote to be rejected as an outlier, with fewer
+/// there is no majority telling which source is wrong
+const MIN_QUOTES_FOR_OUTLIERS: usize = 3;
+
+/// Last price of an asset given by a source, `timestamp` being the time of
+/// the price in milliseconds.
+#[derive(Debug, Clone, Serialize)]
+pub struct Quote {
+    pub source: &'static str,
+    pub price: f64,
+    pub timestamp: i64,
+    pub is_stale: bool,
+    pub is_outlier: bool,
+}
+
+/// Price of an asset aggregated from the fresh quotes of the sources,
+/// `updated_at` being the time of the latest quote used.
+#[derive(Debug, Clone, Serialize)]
+pub struct AggregatedPrice {
+    pub asset: String,
+    pub price: Option<f64>,
+    pub updated_at: Option<i64>,
+    pub sources: Vec<Quote>,
+}
+
+fn median(mut values: Vec<f64>) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(f64::total_cmp);
+    let middle = values.len() / 2;
+    if values.len() % 2 == 1 {
+        Some(values[middle])
+    } else {
+        Some((values[middle - 1] + values[middle]) / 2.0)
+    }
+}
+
+/// Last quote of each source for each asset, by symbol.
+#[derive(Debug, Default)]
+pub struct PriceBook {
+    quotes: HashMap<String, HashMap<&'static str, (f64, i64)>>,
+}
+
+impl PriceBook {
+    pub fn update(&mut self, source: &'static str, asset: &str, price: f64, timestamp: i64) {
+        self.quotes
+            .entry(asset.to_string())
+            .or_default()
+            .insert(source, (price, timestamp));
+    }
+
+    /// Median of the fresh quotes once the ones too far from the median of
+    /// all of them are dropped. With fewer than three fresh quotes nothing
+    /// can be dropped, so there is no price when they disagree by more than
+    /// `MAX_DEVIATION`.
+    pub fn price(&self, asset: &str, now: i64) -> AggregatedPrice {
+        let mut sources: Vec<Quote> = self
+            .quotes
+            .get(asset)
+            .into_iter()
+            .flatten()
+            .map(|(source, (price, timestamp))| Quote {
+                source,
+                price: *price,
+                timestamp: *timestamp,
+                is_stale: now - timestamp > STALE_AFTER_MS,
+                is_outlier: false,
+            })
+            .collect();
+        sources.sort_by_key(|quote| quote.source);
+
+        let fresh: Vec<f64> = sources
+            .iter()
+            .filter(|quote| !quote.is_stale)
+            .map(|quote| quote.price)
+            .collect();
+        if let Some(fresh_median) = median(fresh.clone()) {
+            let max_deviation = fresh_median * MAX_DEVIATION;
+            let has_majority = fresh.len() >= MIN_QUOTES_FOR_OUTLIERS;
+            let (low, high) = fresh
+                .iter()
+                .fold((f64::MAX, f64::MIN), |(low, high), price| {
+                    (low.min(*price), high.max(*price))
+                });
+            for quote in sources.iter_mut().filter(|quote| !quote.is_stale) {
+                quote.is_outlier = if has_majority {
+                    (quote.price - fresh_median).abs() > max_deviation
+                } else {
+                    high - low > max_deviation
+                };
+            }
+        }
+
+        let used: Vec<&Quote> = sources
+            .iter()
+            .filter(|quote| !quote.is_stale && !quote.is_outlier)
+            .collect();
+        let price = median(used.iter().map(|quote| quote.price).collect());
+        let updated_at = used.iter().map(|quote| quote.timestamp).max();
+
+        AggregatedPrice {
+            asset: asset.to_string(),
+            price,
+            updated_at,
+            sources,
+        }
+    }
+}
+
+pub fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+/// Quotes of the sources and candles of the aggregated prices, shared by the
+/// sources and the routes.
+#[derive(Debug, Default)]
+pub struct Prices {
+    pub book: PriceBook,
+    pub candles: CandleBuilder,
+}
+
+impl PriceSink for RwLock<Prices> {
+    fn record(&self, source: &'static str, asset: &str, price: f64, timestamp: i64) {
+        let mut prices = self.write().unwrap();
+        prices.book.update(source, asset, price, timestamp);
+        let now = now_millis();
+        if let Some(price) = prices.book.price(asset, now).price {
+            prices.candles.add_price(asset, price, now);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000_000;
+
+    fn book(quotes: &[(&'static str, f64)]) -> PriceBook {
+        let mut book = PriceBook::default();
+        for (source, price) in quotes {
+            book.update(*source, "ETH", *price, NOW);
+        }
+        book
+    }
+
+    fn outliers(price: &AggregatedPrice) -> Vec<&'static str> {
+        price
+            .sources
+            .iter()
+            .filter(|quote| quote.is_outlier)
+            .map(|quote| quote.source)
+            .collect()
+    }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(median(vec![]), None);
+        assert_eq!(median(vec![3.0]), Some(3.0));
+        assert_eq!(median(vec![3.0, 1.0, 2.0]), Some(2.0));
+        assert_eq!(median(vec![4.0, 1.0, 3.0, 2.0]), Some(2.5));
+    }
+
+    #[test]
+    fn test_outlier_dropped_with_three_sources() {
+        let book = book(&[("avnu", 2000.0), ("binance", 2010.0), ("coinbase", 2500.0)]);
+
+        let price = book.price("ETH", NOW);
+        assert_eq!(outliers(&price), vec!["coinbase"]);
+        assert_eq!(price.price, Some(2005.0));
+        assert_eq!(price.updated_at, Some(NOW));
+    }
+
+    #[test]
+    fn test_two_sources_agreeing() {
+        let book = book(&[("binance", 2000.0), ("coinbase", 2020.0)]);
+
+        let price = book.price("ETH", NOW);
+        assert!(outliers(&price).is_empty());
+        assert_eq!(price.price, Some(2010.0));
+    }
+
+    #[test]
+    fn test_two_sources_disagreeing() {
+        let book = book(&[("binance", 2000.0), ("coinbase", 2100.0)]);
+
+        let price = book.price("ETH", NOW);
+        assert_eq!(outliers(&price), vec!["binance", "coinbase"]);
+        assert_eq!(price.price, None);
+        assert_eq!(price.updated_at, None);
+    }
+
+    #[test]
+    fn test_stale_quotes_ignored() {
+        let mut book = book(&[("binance", 2000.0)]);
+        book.update("coinbase", "ETH", 2500.0, NOW - STALE_AFTER_MS - 1);
+
+        let price = book.price("ETH", NOW);
+        assert!(outliers(&price).is_empty());
+        assert_eq!(price.price, Some(2000.0));
+        assert_eq!(book.price("STRK", NOW).price, None);
+    }
+}
@@ -0,0 +1,76 @@
+use super::{AssetConfig, PriceSink, PriceSource, SourceError, POLL_INTERVAL};
+use crate::oracle::now_millis;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+use std::time::Duration;
+
+const AVNU_PRICES_URL: &str = "https://starknet.impulse.avnu.fi/v1/tokens/prices";
+
+#[derive(Deserialize)]
+struct TokenPrice {
+    #[serde(rename = "priceInUSD")]
+    price_in_usd: Option<f64>,
+}
+
+/// Prices of the Starknet tokens on AVNU, for the assets with a mainnet
+/// address.
+pub struct Avnu {
+    client: Client,
+}
+
+impl Default for Avnu {
+    fn default() -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_default();
+        Self { client }
+    }
+}
+
+impl Avnu {
+    async fn get_price(&self, address: &str) -> Result<Option<f64>, reqwest::Error> {
+        let prices = self
+            .client
+            .get(AVNU_PRICES_URL)
+            .query(&[("token", address)])
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Vec<TokenPrice>>()
+            .await?;
+        Ok(prices
+            .into_iter()
+            .next()
+            .and_then(|price| price.price_in_usd)
+            .filter(|price| *price > 0.0))
+    }
+}
+
+#[async_trait]
+impl PriceSource for Avnu {
+    fn name(&self) -> &'static str {
+        "avnu"
+    }
+
+    /// Each asset has its own request, one that fails is skipped until the
+    /// next poll without holding up the others.
+    async fn run(&self, assets: &[AssetConfig], sink: &dyn PriceSink) -> Result<(), SourceError> {
+        loop {
+            for asset in assets {
+                let Some(address) = &asset.address else {
+                    continue;
+                };
+                match self.get_price(address).await {
+                    Ok(Some(price)) => sink.record(self.name(), &asset.symbol, price, now_millis()),
+                    Ok(None) => {}
+                    Err(e) => {
+                        tracing::warn!("Failed to get the AVNU price of {}: {}", asset.symbol, e)
+                    }
+                }
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use super::{AssetConfig, PriceSink, PriceSource, SourceError, READ_TIMEOUT};
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use serde_json::Value;
+use tokio::time::timeout;
+use tokio_tungstenite::connect_async;
+
+const BINANCE_WS_URL: &str = "wss://stream.binance.com:9443/ws";
+
+/// Trades of the USDT pairs, USDT being taken for USD.
+pub struct Binance;
+
+#[async_trait]
+impl PriceSource for Binance {
+    fn name(&self) -> &'static str {
+        "binance"
+    }
+
+    async fn run(&self, assets: &[AssetConfig], sink: &dyn PriceSink) -> Result<(), SourceError> {
+        let streams: Vec<String> = assets
+            .iter()
+            .filter(|asset| asset.symbol != "USDT")
+            .map(|asset| format!("{}usdt@trade", asset.symbol.to_lowercase()))
+            .collect();
+        if streams.is_empty() {
+            return Ok(());
+        }
+        let url = url::Url::parse(&format!("{}/{}", BINANCE_WS_URL, streams.join("/")))?;
+
+        let (ws_stream, _) = connect_async(url).await?;
+        let (_write, mut read) = ws_stream.split();
+
+        while let Some(msg) = timeout(READ_TIMEOUT, read.next()).await? {
+            let msg = msg?;
+            let Ok(text) = msg.to_text() else {
+                continue;
+            };
+            let Ok(value) = serde_json::from_str::<Value>(text) else {
+                continue;
+            };
+            let asset = value["s"]
+                .as_str()
+                .and_then(|pair| pair.strip_suffix("USDT"));
+            let price = value["p"]
+                .as_str()
+                .and_then(|price| price.parse::<f64>().ok());
+            // Trade time in milliseconds
+            let trade_time = value["T"].as_i64();
+            if let (Some(asset), Some(price), Some(trade_time)) = (asset, price, trade_time) {
+                if price > 0.0 {
+                    sink.record(self.name(), asset, price, trade_time);
+                }
+            }
+        }
+        Ok(())
+    }
+}
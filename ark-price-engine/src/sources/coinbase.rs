@@ -0,0 +1,68 @@
+use super::{AssetConfig, PriceSink, PriceSource, SourceError, READ_TIMEOUT};
+use crate::oracle::now_millis;
+use async_trait::async_trait;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio::time::timeout;
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::Message;
+
+const COINBASE_WS_URL: &str = "wss://ws-feed.exchange.coinbase.com";
+
+/// Ticker of the USD pairs.
+pub struct Coinbase;
+
+#[async_trait]
+impl PriceSource for Coinbase {
+    fn name(&self) -> &'static str {
+        "coinbase"
+    }
+
+    async fn run(&self, assets: &[AssetConfig], sink: &dyn PriceSink) -> Result<(), SourceError> {
+        let url = url::Url::parse(COINBASE_WS_URL)?;
+        let (ws_stream, _) = connect_async(url).await?;
+        let (mut write, mut read) = ws_stream.split();
+
+        // A product Coinbase doesn't list fails its whole subscription, so
+        // each product is subscribed on its own.
+        for asset in assets.iter().filter(|asset| asset.symbol != "USD") {
+            let subscription = json!({
+                "type": "subscribe",
+                "product_ids": [format!("{}-USD", asset.symbol)],
+                "channels": ["ticker"],
+            });
+            write.send(Message::Text(subscription.to_string())).await?;
+        }
+
+        while let Some(msg) = timeout(READ_TIMEOUT, read.next()).await? {
+            let msg = msg?;
+            let Ok(text) = msg.to_text() else {
+                continue;
+            };
+            let Ok(value) = serde_json::from_str::<Value>(text) else {
+                continue;
+            };
+            match value["type"].as_str() {
+                Some("ticker") => {
+                    let asset = value["product_id"]
+                        .as_str()
+                        .and_then(|product| product.strip_suffix("-USD"));
+                    let price = value["price"]
+                        .as_str()
+                        .and_then(|price| price.parse::<f64>().ok());
+                    if let (Some(asset), Some(price)) = (asset, price) {
+                        if price > 0.0 {
+                            sink.record(self.name(), asset, price, now_millis());
+                        }
+                    }
+                }
+                Some("error") => tracing::warn!(
+                    "Coinbase subscription failed: {}",
+                    value["reason"].as_str().unwrap_or_default()
+                ),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,106 @@
+mod avnu;
+mod binance;
+mod coinbase;
+mod pragma;
+
+use async_trait::async_trait;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+pub use avnu::Avnu;
+pub use binance::Binance;
+pub use coinbase::Coinbase;
+pub use pragma::Pragma;
+
+pub type SourceError = Box<dyn std::error::Error + Send + Sync>;
+
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// A websocket without any message for this long is considered dropped
+const READ_TIMEOUT: Duration = Duration::from_secs(60);
+/// Pace of the sources polled over HTTP
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// An asset to price, by symbol, with the address of its token on Starknet
+/// mainnet when known.
+#[derive(Debug, Clone)]
+pub struct AssetConfig {
+    pub symbol: String,
+    pub address: Option<String>,
+}
+
+/// Receives the USD prices given by the sources, `timestamp` being the time
+/// of the price in milliseconds.
+pub trait PriceSink: Send + Sync {
+    fn record(&self, source: &'static str, asset: &str, price: f64, timestamp: i64);
+}
+
+#[async_trait]
+pub trait PriceSource: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Sends the prices of `assets` to `sink` until the connection is lost.
+    /// Assets the source doesn't list are skipped.
+    async fn run(&self, assets: &[AssetConfig], sink: &dyn PriceSink) -> Result<(), SourceError>;
+}
+
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+/// Sources enabled in `names`, a comma separated list. Pragma is read
+/// through the RPC of `STARKNET_RPC_URL` and left out when it is not set.
+pub fn build_sources(names: &str) -> Vec<Box<dyn PriceSource>> {
+    let mut sources: Vec<Box<dyn PriceSource>> = Vec::new();
+    for name in names
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    {
+        match name {
+            "binance" => sources.push(Box::new(Binance)),
+            "coinbase" => sources.push(Box::new(Coinbase)),
+            "avnu" => sources.push(Box::<Avnu>::default()),
+            "pragma" => match env_var("STARKNET_RPC_URL") {
+                Some(rpc_url) => sources.push(Box::new(Pragma::new(
+                    rpc_url,
+                    env_var("PRAGMA_ORACLE_ADDRESS"),
+                ))),
+                None => tracing::warn!("STARKNET_RPC_URL not set, Pragma is disabled"),
+            },
+            _ => tracing::warn!("Unknown price source: {}", name),
+        }
+    }
+    sources
+}
+
+/// Runs `source` for as long as the engine runs, reconnecting after each
+/// failure with a backoff doubling up to a minute. The backoff starts over
+/// once a connection has lasted longer than that.
+pub fn spawn_source(
+    source: Box<dyn PriceSource>,
+    assets: Arc<Vec<AssetConfig>>,
+    sink: Arc<dyn PriceSink>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut backoff = MIN_BACKOFF;
+        loop {
+            let started_at = Instant::now();
+            match source.run(&assets, sink.as_ref()).await {
+                Ok(()) => tracing::warn!("{} price source disconnected", source.name()),
+                Err(e) => tracing::error!("{} price source failed: {}", source.name(), e),
+            }
+            if started_at.elapsed() > MAX_BACKOFF {
+                backoff = MIN_BACKOFF;
+            }
+            tracing::info!(
+                "Reconnecting to {} in {}s",
+                source.name(),
+                backoff.as_secs()
+            );
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    })
+}
@@ -0,0 +1,108 @@
+use super::{AssetConfig, PriceSink, PriceSource, SourceError, POLL_INTERVAL};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// Pragma oracle on Starknet mainnet
+const PRAGMA_ORACLE_ADDRESS: &str =
+    "0x2a85bd616f912537c50a49a4076db02c00b29b2cdc8a197ce92ed1837fa875b";
+/// Selector of `get_data_median`
+const GET_DATA_MEDIAN_SELECTOR: &str =
+    "0x24b869ce68dd257b370701ca16e4aaf9c6483ff6805d04ba7661f3a0b6ce59";
+
+/// Median of the spot prices published on-chain to the Pragma oracle, read
+/// with `starknet_call`.
+pub struct Pragma {
+    client: Client,
+    rpc_url: String,
+    oracle_address: String,
+}
+
+impl Pragma {
+    pub fn new(rpc_url: String, oracle_address: Option<String>) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_default();
+        Self {
+            client,
+            rpc_url,
+            oracle_address: oracle_address.unwrap_or_else(|| PRAGMA_ORACLE_ADDRESS.to_string()),
+        }
+    }
+
+    /// Price of the `{symbol}/USD` pair and the time it was last updated, in
+    /// seconds. `None` when Pragma doesn't publish the pair.
+    async fn get_price(&self, symbol: &str) -> Result<Option<(f64, i64)>, SourceError> {
+        let pair = format!("{}/USD", symbol);
+        if pair.len() > 31 {
+            return Ok(None);
+        }
+        // The pair as a short string, in a `DataType::SpotEntry`
+        let pair_id = format!("0x{}", hex_encode(pair.as_bytes()));
+        let request = json!({
+            "jsonrpc": "2.0",
+            "method": "starknet_call",
+            "params": {
+                "request": {
+                    "contract_address": self.oracle_address,
+                    "entry_point_selector": GET_DATA_MEDIAN_SELECTOR,
+                    "calldata": ["0x0", pair_id],
+                },
+                "block_id": "latest",
+            },
+            "id": 1,
+        });
+
+        let response = self
+            .client
+            .post(&self.rpc_url)
+            .json(&request)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Value>()
+            .await?;
+        if let Some(error) = response.get("error") {
+            tracing::debug!("Pragma has no price for {}: {}", pair, error);
+            return Ok(None);
+        }
+
+        // PragmaPricesResponse: price, decimals, last_updated_timestamp, ...
+        let felt = |index: usize| {
+            response["result"][index]
+                .as_str()
+                .and_then(|value| u128::from_str_radix(value.trim_start_matches("0x"), 16).ok())
+        };
+        match (felt(0), felt(1), felt(2)) {
+            (Some(price), Some(decimals), Some(updated_at)) if price > 0 => Ok(Some((
+                price as f64 / 10f64.powi(decimals as i32),
+                updated_at as i64,
+            ))),
+            _ => Ok(None),
+        }
+    }
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[async_trait]
+impl PriceSource for Pragma {
+    fn name(&self) -> &'static str {
+        "pragma"
+    }
+
+    async fn run(&self, assets: &[AssetConfig], sink: &dyn PriceSink) -> Result<(), SourceError> {
+        loop {
+            for asset in assets {
+                if let Some((price, updated_at)) = self.get_price(&asset.symbol).await? {
+                    sink.record(self.name(), &asset.symbol, price, updated_at * 1000);
+                }
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+}
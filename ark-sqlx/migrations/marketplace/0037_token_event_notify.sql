@@ -0,0 +1,30 @@
+-- Publish every new token_event on the `token_event` channel so that the
+-- marketplace API can stream activity to clients without polling.
+CREATE OR REPLACE FUNCTION notify_token_event()
+    RETURNS trigger
+    LANGUAGE plpgsql AS $$
+BEGIN
+    PERFORM pg_notify(
+        'token_event',
+        json_build_object(
+            'token_event_id', NEW.token_event_id,
+            'contract_address', NEW.contract_address,
+            'chain_id', NEW.chain_id,
+            'token_id', NEW.token_id,
+            'event_type', NEW.event_type,
+            'block_timestamp', NEW.block_timestamp,
+            'transaction_hash', NEW.transaction_hash,
+            'order_hash', NEW.order_hash,
+            'from_address', NEW.from_address,
+            'to_address', NEW.to_address,
+            'amount', NEW.amount,
+            'currency_address', NEW.currency_address
+        )::text
+    );
+    RETURN NEW;
+END
+$$;
+
+CREATE TRIGGER token_event_notify
+AFTER INSERT ON token_event
+FOR EACH ROW EXECUTE FUNCTION notify_token_event();
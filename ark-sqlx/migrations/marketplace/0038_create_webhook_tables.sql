@@ -0,0 +1,33 @@
+CREATE TABLE webhook_subscription (
+  webhook_subscription_id SERIAL PRIMARY KEY,
+  url TEXT NOT NULL,
+  secret TEXT NOT NULL DEFAULT replace(gen_random_uuid()::text || gen_random_uuid()::text, '-', ''),
+  contract_address VARCHAR(66) NOT NULL,
+  chain_id TEXT NOT NULL,
+  -- empty means every order lifecycle event
+  event_types TEXT[] NOT NULL DEFAULT '{}',
+  is_active BOOLEAN NOT NULL DEFAULT TRUE,
+  created_timestamp BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW())::BIGINT),
+  updated_timestamp BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW())::BIGINT)
+);
+
+CREATE INDEX idx_webhook_subscription_contract_chain ON webhook_subscription (contract_address, chain_id);
+
+CREATE TABLE webhook_dead_letter (
+  webhook_dead_letter_id SERIAL PRIMARY KEY,
+  webhook_subscription_id INTEGER NOT NULL,
+  event_type TEXT NOT NULL,
+  order_hash TEXT NOT NULL,
+  payload JSONB NOT NULL,
+  attempts INTEGER NOT NULL,
+  last_error TEXT,
+  created_timestamp BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW())::BIGINT),
+  FOREIGN KEY (webhook_subscription_id) REFERENCES webhook_subscription(webhook_subscription_id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_webhook_dead_letter_subscription ON webhook_dead_letter (webhook_subscription_id);
+
+GRANT ALL PRIVILEGES ON TABLE webhook_subscription TO "arkproject";
+GRANT ALL PRIVILEGES ON TABLE webhook_dead_letter TO "arkproject";
+GRANT USAGE, SELECT ON SEQUENCE webhook_subscription_webhook_subscription_id_seq TO "arkproject";
+GRANT USAGE, SELECT ON SEQUENCE webhook_dead_letter_webhook_dead_letter_id_seq TO "arkproject";
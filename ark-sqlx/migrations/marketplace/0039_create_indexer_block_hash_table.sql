@@ -0,0 +1,14 @@
+-- Hashes of the most recently indexed blocks, used by the head of chain
+-- indexer to detect reorgs.
+CREATE TABLE indexer_block_hash (
+  chain_id TEXT NOT NULL,
+  block_number BIGINT NOT NULL,
+  block_hash VARCHAR(66) NOT NULL,
+  parent_hash VARCHAR(66) NOT NULL,
+  block_timestamp BIGINT NOT NULL,
+  indexed_timestamp BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW())::BIGINT),
+
+  PRIMARY KEY (chain_id, block_number)
+);
+
+GRANT ALL PRIVILEGES ON TABLE indexer_block_hash TO "arkproject";
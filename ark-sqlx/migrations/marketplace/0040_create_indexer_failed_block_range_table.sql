@@ -0,0 +1,21 @@
+-- Block ranges the indexer failed to index, retried with exponential backoff
+-- until they succeed.
+CREATE TABLE indexer_failed_block_range (
+  failed_block_range_id SERIAL PRIMARY KEY,
+  chain_id TEXT NOT NULL,
+  from_block BIGINT NOT NULL,
+  to_block BIGINT NOT NULL,
+  attempts INTEGER NOT NULL DEFAULT 1,
+  last_error TEXT,
+  is_resolved BOOLEAN NOT NULL DEFAULT FALSE,
+  next_retry_timestamp BIGINT NOT NULL,
+  created_timestamp BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW())::BIGINT),
+  updated_timestamp BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW())::BIGINT),
+
+  UNIQUE (chain_id, from_block, to_block)
+);
+
+CREATE INDEX idx_indexer_failed_block_range_retry ON indexer_failed_block_range (chain_id, next_retry_timestamp) WHERE is_resolved = FALSE;
+
+GRANT ALL PRIVILEGES ON TABLE indexer_failed_block_range TO "arkproject";
+GRANT USAGE, SELECT ON SEQUENCE indexer_failed_block_range_failed_block_range_id_seq TO "arkproject";
@@ -0,0 +1,9 @@
+-- Last block fully indexed by each indexer, replaces the local checkpoint
+-- files that were lost whenever a task was replaced.
+CREATE TABLE indexer_checkpoint (
+  indexer_identifier TEXT PRIMARY KEY,
+  block_number BIGINT NOT NULL,
+  updated_timestamp BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW())::BIGINT)
+);
+
+GRANT ALL PRIVILEGES ON TABLE indexer_checkpoint TO "arkproject";
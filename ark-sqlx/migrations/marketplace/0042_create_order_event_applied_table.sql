@@ -0,0 +1,13 @@
+-- Order events already applied by the marketplace indexer. The key is
+-- inserted in the transaction that applies the event, so a replayed block
+-- finds it and leaves the token and offer tables untouched.
+CREATE TABLE order_event_applied (
+  block_number BIGINT NOT NULL,
+  order_hash TEXT NOT NULL,
+  event_kind VARCHAR(16) NOT NULL,
+  block_timestamp BIGINT NOT NULL,
+  applied_timestamp BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW())::BIGINT),
+  PRIMARY KEY (block_number, order_hash, event_kind)
+);
+
+GRANT ALL PRIVILEGES ON TABLE order_event_applied TO "arkproject";
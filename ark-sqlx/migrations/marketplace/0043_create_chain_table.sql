@@ -0,0 +1,18 @@
+-- Chains served by the marketplace API and cron. Only enabled chains are
+-- accepted in requests; the default one is used when a route does not carry
+-- a chain id.
+CREATE TABLE chain (
+  chain_id TEXT PRIMARY KEY,
+  name TEXT NOT NULL,
+  is_default BOOLEAN NOT NULL DEFAULT FALSE,
+  is_enabled BOOLEAN NOT NULL DEFAULT TRUE,
+  created_timestamp BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW())::BIGINT)
+);
+
+CREATE UNIQUE INDEX chain_single_default_idx ON chain (is_default) WHERE is_default;
+
+INSERT INTO chain (chain_id, name, is_default, is_enabled) VALUES
+  ('0x534e5f4d41494e', 'SN_MAIN', TRUE, TRUE),
+  ('0x534e5f5345504f4c4941', 'SN_SEPOLIA', FALSE, FALSE);
+
+GRANT ALL PRIVILEGES ON TABLE chain TO "arkproject";
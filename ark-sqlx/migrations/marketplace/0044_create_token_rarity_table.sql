@@ -0,0 +1,27 @@
+-- Rarity of the tokens of a collection, recomputed from the `attributes` of
+-- the token metadata each time metadata is refreshed.
+--   statistical_score: product of the probabilities of the token traits,
+--     lower is rarer.
+--   trait_count_score: sum of the inverse probabilities of the token traits,
+--     each divided by the number of values of its trait type, higher is rarer.
+--   information_content_score: OpenRarity score, the information content of
+--     the token traits divided by the collection entropy, higher is rarer.
+-- Missing traits count as a "null" value and the number of traits of the
+-- token is scored as an extra trait. The rank follows the information
+-- content score, 1 being the rarest.
+CREATE TABLE token_rarity (
+  contract_address VARCHAR(66) NOT NULL,
+  chain_id TEXT NOT NULL,
+  token_id TEXT NOT NULL,
+  statistical_score NUMERIC NOT NULL,
+  trait_count_score NUMERIC NOT NULL,
+  information_content_score NUMERIC NOT NULL,
+  rarity_rank BIGINT NOT NULL,
+  updated_timestamp BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW())::BIGINT),
+  PRIMARY KEY (contract_address, chain_id, token_id),
+  FOREIGN KEY (contract_address, chain_id, token_id) REFERENCES token(contract_address, chain_id, token_id) ON DELETE CASCADE
+);
+
+CREATE INDEX token_rarity_rank_idx ON token_rarity (contract_address, chain_id, rarity_rank);
+
+GRANT ALL PRIVILEGES ON TABLE token_rarity TO "arkproject";
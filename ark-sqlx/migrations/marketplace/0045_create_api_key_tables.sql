@@ -0,0 +1,51 @@
+-- API keys of the marketplace API. Only a SHA-256 hash of each key is stored,
+-- the key itself is returned once when it is created.
+CREATE TABLE api_key_tier (
+  tier TEXT PRIMARY KEY,
+  requests_per_minute INTEGER NOT NULL,
+  requests_per_day INTEGER NOT NULL
+);
+
+-- `anonymous` applies to requests sent without a key, per client IP.
+INSERT INTO api_key_tier (tier, requests_per_minute, requests_per_day) VALUES
+  ('anonymous', 30, 5000),
+  ('free', 60, 20000),
+  ('pro', 600, 500000),
+  ('enterprise', 3000, 5000000);
+
+CREATE TABLE api_key (
+  api_key_id SERIAL PRIMARY KEY,
+  key_hash TEXT NOT NULL UNIQUE,
+  key_prefix TEXT NOT NULL,
+  name TEXT NOT NULL,
+  tier TEXT NOT NULL REFERENCES api_key_tier(tier),
+  -- Per key overrides of the tier quotas
+  requests_per_minute INTEGER,
+  requests_per_day INTEGER,
+  is_active BOOLEAN NOT NULL DEFAULT TRUE,
+  created_timestamp BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW())::BIGINT),
+  revoked_timestamp BIGINT
+);
+
+-- Same layout as ark_lambda_usage so that LambdaUsageProvider can record it.
+CREATE TABLE api_usage (
+  request_id TEXT NOT NULL,
+  api_key TEXT NOT NULL,
+  timestamp BIGINT NOT NULL,
+  capacity FLOAT8 NOT NULL,
+  execution_time_in_ms BIGINT NOT NULL,
+  response_status_code INT NOT NULL,
+  request_method TEXT NOT NULL,
+  request_path TEXT NOT NULL,
+  request_params TEXT NOT NULL,
+  ip VARCHAR(64) NOT NULL,
+  stage_name TEXT NOT NULL,
+  PRIMARY KEY (request_id)
+);
+
+CREATE INDEX idx_api_usage_api_key_timestamp ON api_usage (api_key, timestamp);
+
+GRANT ALL PRIVILEGES ON TABLE api_key_tier TO "arkproject";
+GRANT ALL PRIVILEGES ON TABLE api_key TO "arkproject";
+GRANT USAGE, SELECT ON SEQUENCE api_key_api_key_id_seq TO "arkproject";
+GRANT ALL PRIVILEGES ON TABLE api_usage TO "arkproject";
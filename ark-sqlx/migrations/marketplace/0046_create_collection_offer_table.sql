@@ -0,0 +1,27 @@
+-- Offers placed on a whole collection rather than on a token, any token of
+-- the collection can be sold to them. `traits` restricts a trait offer to
+-- the tokens having one of the listed values for every trait type, e.g.
+-- {"Background": ["Blue", "Red"]}, and is NULL for collection-wide offers.
+CREATE TABLE collection_offer (
+  collection_offer_id SERIAL PRIMARY KEY,
+  contract_address VARCHAR(66) NOT NULL,
+  chain_id TEXT NOT NULL,
+  broker_id TEXT,
+  order_hash TEXT NOT NULL UNIQUE,
+  offer_maker TEXT NOT NULL,
+  offer_amount TEXT NOT NULL,
+  offer_quantity TEXT NOT NULL,
+  offer_timestamp BIGINT NOT NULL,
+  currency_chain_id TEXT NOT NULL DEFAULT '',
+  currency_address TEXT NOT NULL DEFAULT '',
+  start_date BIGINT NOT NULL DEFAULT 0,
+  end_date BIGINT NOT NULL DEFAULT 0,
+  status TEXT NOT NULL DEFAULT 'PLACED',
+  traits JSONB,
+  FOREIGN KEY (contract_address, chain_id) REFERENCES contract(contract_address, chain_id)
+);
+
+CREATE INDEX collection_offer_contract_status_idx ON collection_offer (contract_address, chain_id, status, end_date);
+
+GRANT ALL PRIVILEGES ON TABLE collection_offer TO "arkproject";
+GRANT USAGE, SELECT ON SEQUENCE collection_offer_collection_offer_id_seq TO "arkproject";
@@ -0,0 +1,16 @@
+-- Collections and tokens followed by a wallet, `token_id` being NULL when the
+-- whole collection is followed.
+CREATE TABLE watchlist_item (
+  watchlist_item_id SERIAL PRIMARY KEY,
+  user_address VARCHAR(66) NOT NULL,
+  contract_address VARCHAR(66) NOT NULL,
+  chain_id TEXT NOT NULL,
+  token_id TEXT,
+  created_timestamp BIGINT NOT NULL DEFAULT EXTRACT(EPOCH FROM NOW())::BIGINT,
+  FOREIGN KEY (contract_address, chain_id) REFERENCES contract(contract_address, chain_id)
+);
+
+CREATE UNIQUE INDEX watchlist_item_unique_idx ON watchlist_item (user_address, chain_id, contract_address, COALESCE(token_id, ''));
+
+GRANT ALL PRIVILEGES ON TABLE watchlist_item TO "arkproject";
+GRANT USAGE, SELECT ON SEQUENCE watchlist_item_watchlist_item_id_seq TO "arkproject";
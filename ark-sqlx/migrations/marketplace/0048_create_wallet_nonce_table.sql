@@ -0,0 +1,13 @@
+-- Nonces handed to wallets signing in, each one can be exchanged once for a
+-- session before `expiration_time`.
+CREATE TABLE wallet_nonce (
+  nonce TEXT PRIMARY KEY,
+  user_address VARCHAR(66) NOT NULL,
+  chain_id TEXT NOT NULL,
+  issued_at BIGINT NOT NULL,
+  expiration_time BIGINT NOT NULL
+);
+
+CREATE INDEX wallet_nonce_expiration_time_idx ON wallet_nonce (expiration_time);
+
+GRANT ALL PRIVILEGES ON TABLE wallet_nonce TO "arkproject";
@@ -0,0 +1,4 @@
+-- USD price of one unit of the event currency when the trade happened. Only
+-- known for the sales indexed live, NULL otherwise.
+ALTER TABLE token_event
+ADD COLUMN currency_usd_price NUMERIC;
@@ -0,0 +1,11 @@
+-- USD price of the currencies at the start of each hour, used to value the
+-- trades made before `token_event.currency_usd_price` was recorded.
+CREATE TABLE currency_price_history (
+  currency_address TEXT NOT NULL,
+  chain_id TEXT NOT NULL,
+  timestamp BIGINT NOT NULL,
+  usd_price NUMERIC NOT NULL,
+  PRIMARY KEY (currency_address, chain_id, timestamp)
+);
+
+GRANT ALL PRIVILEGES ON TABLE currency_price_history TO "arkproject";
@@ -0,0 +1,5 @@
+-- Parts of the amount of a sale paid as creator royalties and marketplace
+-- fees, in the smallest unit of the sale currency. NULL for the sales indexed
+-- without them.
+ALTER TABLE token_event ADD COLUMN royalty_amount TEXT;
+ALTER TABLE token_event ADD COLUMN marketplace_fee_amount TEXT;
@@ -0,0 +1,14 @@
+-- Minute candles of the USD price of the assets followed by ark-price-engine,
+-- `timestamp` being the start of the minute.
+CREATE TABLE price_candle (
+  asset TEXT NOT NULL,
+  timestamp BIGINT NOT NULL,
+  open NUMERIC NOT NULL,
+  high NUMERIC NOT NULL,
+  low NUMERIC NOT NULL,
+  close NUMERIC NOT NULL,
+  trade_count INTEGER NOT NULL,
+  PRIMARY KEY (asset, timestamp)
+);
+
+GRANT ALL PRIVILEGES ON TABLE price_candle TO "arkproject";
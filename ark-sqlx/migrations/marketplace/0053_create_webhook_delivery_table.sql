@@ -0,0 +1,17 @@
+-- deliveries still being retried, resumed by the indexer after a restart
+CREATE TABLE webhook_delivery (
+  webhook_delivery_id SERIAL PRIMARY KEY,
+  webhook_subscription_id INTEGER NOT NULL,
+  event_type TEXT NOT NULL,
+  order_hash TEXT NOT NULL,
+  payload JSONB NOT NULL,
+  attempts INTEGER NOT NULL DEFAULT 0,
+  last_error TEXT,
+  created_timestamp BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW())::BIGINT),
+  FOREIGN KEY (webhook_subscription_id) REFERENCES webhook_subscription(webhook_subscription_id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_webhook_delivery_subscription ON webhook_delivery (webhook_subscription_id);
+
+GRANT ALL PRIVILEGES ON TABLE webhook_delivery TO "arkproject";
+GRANT USAGE, SELECT ON SEQUENCE webhook_delivery_webhook_delivery_id_seq TO "arkproject";
@@ -0,0 +1,8 @@
+-- api_usage.api_key now holds the id of the key, replace the raw keys
+-- recorded until now.
+UPDATE api_usage
+SET api_key = api_key.api_key_id::TEXT
+FROM api_key
+WHERE api_key.key_hash = encode(sha256(convert_to(api_usage.api_key, 'UTF8')), 'hex');
+
+DELETE FROM api_usage WHERE api_key !~ '^[0-9]+$';
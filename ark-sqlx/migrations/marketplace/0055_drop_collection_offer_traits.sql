@@ -0,0 +1,2 @@
+-- Diri orders carry no trait criteria, so trait offers can't be indexed.
+ALTER TABLE collection_offer DROP COLUMN traits;
@@ -0,0 +1 @@
+CREATE INDEX idx_token_event_token_type_timestamp ON token_event (contract_address, chain_id, token_id, event_type, block_timestamp);
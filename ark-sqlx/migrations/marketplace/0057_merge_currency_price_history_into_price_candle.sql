@@ -0,0 +1,19 @@
+-- Historical USD prices are only read from price_candle, the hourly prices
+-- recorded before are kept as candles of their hour.
+INSERT INTO price_candle (asset, timestamp, open, high, low, close, trade_count)
+SELECT DISTINCT ON (UPPER(currency_mapping.symbol), currency_price_history.timestamp)
+    UPPER(currency_mapping.symbol),
+    currency_price_history.timestamp,
+    currency_price_history.usd_price,
+    currency_price_history.usd_price,
+    currency_price_history.usd_price,
+    currency_price_history.usd_price,
+    0
+FROM currency_price_history
+JOIN currency_mapping ON currency_mapping.currency_address = currency_price_history.currency_address
+    AND currency_mapping.chain_id = currency_price_history.chain_id
+WHERE currency_mapping.symbol IS NOT NULL
+ORDER BY UPPER(currency_mapping.symbol), currency_price_history.timestamp
+ON CONFLICT (asset, timestamp) DO NOTHING;
+
+DROP TABLE currency_price_history;
@@ -0,0 +1,5 @@
+-- Rates in basis points used to split the executed sales into creator
+-- royalties and marketplace fees. NULL when the rate is unknown, the part
+-- then being left NULL on the sale.
+ALTER TABLE contract ADD COLUMN royalty_bps INTEGER;
+ALTER TABLE broker ADD COLUMN fee_bps INTEGER;
@@ -0,0 +1,13 @@
+-- Executed collection offers whose sold token is not known yet. Diri's
+-- executed data doesn't name the token, it is read from the transfer from the
+-- seller to the buyer indexed by Sana, which can come after the execution.
+-- The sale is recorded and its row deleted once the transfer is found.
+CREATE TABLE pending_collection_offer_sale (
+  order_hash TEXT PRIMARY KEY,
+  seller TEXT,
+  buyer TEXT NOT NULL,
+  block_timestamp BIGINT NOT NULL,
+  created_timestamp BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW())::BIGINT)
+);
+
+GRANT ALL PRIVILEGES ON TABLE pending_collection_offer_sale TO "arkproject";
@@ -0,0 +1,10 @@
+-- Events the cron could not price from the candles. Their candle window
+-- (the day before the event) is over, so they are not looked at again.
+ALTER TABLE token_event
+ADD COLUMN price_backfill_attempted_at BIGINT;
+
+-- Events still waiting for an ETH amount, or for the USD price of a sale.
+CREATE INDEX idx_token_event_price_backfill ON token_event (block_timestamp)
+WHERE amount IS NOT NULL
+  AND price_backfill_attempted_at IS NULL
+  AND (eth_amount IS NULL OR (event_type = 'Executed' AND currency_usd_price IS NULL));
@@ -0,0 +1,125 @@
+use crate::providers::{ProviderError, SqlxCtxPg};
+
+const RETRY_INITIAL_BACKOFF_SECONDS: i64 = 30;
+const RETRY_MAX_BACKOFF_SECONDS: i64 = 3600;
+/// Keeps `2^attempts` within BIGINT, the backoff is capped well before anyway.
+const RETRY_MAX_BACKOFF_EXPONENT: i32 = 20;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct FailedBlockRange {
+    pub failed_block_range_id: i32,
+    pub chain_id: String,
+    pub from_block: i64,
+    pub to_block: i64,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub next_retry_timestamp: i64,
+    pub created_timestamp: i64,
+}
+
+pub struct FailedBlockRangeProvider {}
+
+impl FailedBlockRangeProvider {
+    /// Records a failed indexation of `from_block..=to_block`. A range that
+    /// fails again gets its attempt count increased and its next retry pushed
+    /// back exponentially.
+    pub async fn register_failure(
+        client: &SqlxCtxPg,
+        chain_id: &str,
+        from_block: u64,
+        to_block: u64,
+        error: &str,
+    ) -> Result<FailedBlockRange, ProviderError> {
+        let query = "
+            INSERT INTO indexer_failed_block_range (chain_id, from_block, to_block, last_error, next_retry_timestamp)
+            VALUES ($1, $2, $3, $4, EXTRACT(EPOCH FROM NOW())::BIGINT + $5)
+            ON CONFLICT (chain_id, from_block, to_block) DO UPDATE SET
+                attempts = indexer_failed_block_range.attempts + 1,
+                last_error = EXCLUDED.last_error,
+                is_resolved = FALSE,
+                next_retry_timestamp = EXTRACT(EPOCH FROM NOW())::BIGINT
+                    + LEAST($5 * POWER(2, LEAST(indexer_failed_block_range.attempts, $7))::BIGINT, $6),
+                updated_timestamp = EXTRACT(EPOCH FROM NOW())::BIGINT
+            RETURNING failed_block_range_id, chain_id, from_block, to_block, attempts, last_error,
+                next_retry_timestamp, created_timestamp
+        ";
+
+        let failed_range = sqlx::query_as::<_, FailedBlockRange>(query)
+            .bind(chain_id)
+            .bind(from_block as i64)
+            .bind(to_block as i64)
+            .bind(error)
+            .bind(RETRY_INITIAL_BACKOFF_SECONDS)
+            .bind(RETRY_MAX_BACKOFF_SECONDS)
+            .bind(RETRY_MAX_BACKOFF_EXPONENT)
+            .fetch_one(&client.pool)
+            .await?;
+
+        Ok(failed_range)
+    }
+
+    /// Open ranges whose backoff has elapsed, oldest first.
+    pub async fn get_due_ranges(
+        client: &SqlxCtxPg,
+        chain_id: &str,
+        limit: i64,
+    ) -> Result<Vec<FailedBlockRange>, ProviderError> {
+        let query = "
+            SELECT failed_block_range_id, chain_id, from_block, to_block, attempts, last_error,
+                next_retry_timestamp, created_timestamp
+            FROM indexer_failed_block_range
+            WHERE chain_id = $1
+              AND is_resolved = FALSE
+              AND next_retry_timestamp <= EXTRACT(EPOCH FROM NOW())::BIGINT
+            ORDER BY from_block
+            LIMIT $2
+        ";
+
+        let ranges = sqlx::query_as::<_, FailedBlockRange>(query)
+            .bind(chain_id)
+            .bind(limit)
+            .fetch_all(&client.pool)
+            .await?;
+
+        Ok(ranges)
+    }
+
+    /// Every range that has not been indexed successfully yet.
+    pub async fn get_open_ranges(
+        client: &SqlxCtxPg,
+        chain_id: &str,
+    ) -> Result<Vec<FailedBlockRange>, ProviderError> {
+        let query = "
+            SELECT failed_block_range_id, chain_id, from_block, to_block, attempts, last_error,
+                next_retry_timestamp, created_timestamp
+            FROM indexer_failed_block_range
+            WHERE chain_id = $1 AND is_resolved = FALSE
+            ORDER BY from_block
+        ";
+
+        let ranges = sqlx::query_as::<_, FailedBlockRange>(query)
+            .bind(chain_id)
+            .fetch_all(&client.pool)
+            .await?;
+
+        Ok(ranges)
+    }
+
+    pub async fn resolve(
+        client: &SqlxCtxPg,
+        failed_block_range_id: i32,
+    ) -> Result<(), ProviderError> {
+        let query = "
+            UPDATE indexer_failed_block_range
+            SET is_resolved = TRUE, updated_timestamp = EXTRACT(EPOCH FROM NOW())::BIGINT
+            WHERE failed_block_range_id = $1
+        ";
+
+        sqlx::query(query)
+            .bind(failed_block_range_id)
+            .execute(&client.pool)
+            .await?;
+
+        Ok(())
+    }
+}
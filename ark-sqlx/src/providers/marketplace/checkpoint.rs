@@ -0,0 +1,59 @@
+use crate::providers::{ProviderError, SqlxCtxPg};
+use sqlx::PgExecutor;
+
+pub struct CheckpointProvider {}
+
+impl CheckpointProvider {
+    /// Last block fully indexed by `indexer_identifier`, if any.
+    pub async fn get_checkpoint(
+        client: &SqlxCtxPg,
+        indexer_identifier: &str,
+    ) -> Result<Option<u64>, ProviderError> {
+        let block_number = sqlx::query_scalar::<_, i64>(
+            "SELECT block_number FROM indexer_checkpoint WHERE indexer_identifier = $1",
+        )
+        .bind(indexer_identifier)
+        .fetch_optional(&client.pool)
+        .await?;
+
+        Ok(block_number.map(|block_number| block_number as u64))
+    }
+
+    /// Must only be called once every event of `block_number` is committed.
+    /// Diri writes each order event in its own transaction, so the checkpoint
+    /// can trail the data but never run ahead of it: replaying a block is
+    /// harmless since applied order events are skipped.
+    pub async fn save_checkpoint<'e, E: PgExecutor<'e>>(
+        executor: E,
+        indexer_identifier: &str,
+        block_number: u64,
+    ) -> Result<(), ProviderError> {
+        let query = "
+            INSERT INTO indexer_checkpoint (indexer_identifier, block_number)
+            VALUES ($1, $2)
+            ON CONFLICT (indexer_identifier) DO UPDATE SET
+                block_number = EXCLUDED.block_number,
+                updated_timestamp = EXTRACT(EPOCH FROM NOW())::BIGINT
+        ";
+
+        sqlx::query(query)
+            .bind(indexer_identifier)
+            .bind(block_number as i64)
+            .execute(executor)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_checkpoint(
+        client: &SqlxCtxPg,
+        indexer_identifier: &str,
+    ) -> Result<(), ProviderError> {
+        sqlx::query("DELETE FROM indexer_checkpoint WHERE indexer_identifier = $1")
+            .bind(indexer_identifier)
+            .execute(&client.pool)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -1,3 +1,13 @@
+pub mod block_range;
+pub use block_range::FailedBlockRangeProvider;
+pub mod checkpoint;
+pub use checkpoint::CheckpointProvider;
 pub mod order;
 pub use order::OrderProvider;
+pub mod price;
+pub use price::{FixturePriceProvider, PriceEngineProvider, PriceProvider};
+pub mod reorg;
+pub use reorg::ReorgProvider;
 pub mod types;
+pub mod webhook;
+pub use webhook::WebhookProvider;
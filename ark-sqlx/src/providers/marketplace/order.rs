@@ -1,31 +1,32 @@
 use crate::providers::marketplace::types::{
-    TokenEventType, AUCTION_CANCELLED_STR, AUCTION_STR, BURN_STR, CANCELLED_STR,
+    TokenEventType, WebhookEventType, AUCTION_CANCELLED_STR, AUCTION_STR, BURN_STR, CANCELLED_STR,
     COLLECTION_OFFER_STR, EXECUTED_STR, FULFILL_STR, LISTING_CANCELLED_STR, LISTING_EXPIRED_STR,
     LISTING_STR, MINT_STR, OFFER_CANCELLED_STR, OFFER_EXPIRED_STR, OFFER_STR, ROLLBACK_STR,
     SALE_STR, TRANSFER_STR,
 };
+use crate::providers::marketplace::webhook::PendingDelivery;
+use crate::providers::marketplace::{PriceProvider, WebhookProvider};
 use crate::providers::{ContractProvider, ProviderError, SqlxCtxPg};
 use anyhow::Result;
 use arkproject::diri::storage::types::{
     CancelledData, ExecutedData, FulfilledData, PlacedData, RollbackStatusData,
 };
 use async_std::stream::StreamExt;
-use num_bigint::BigInt;
-use num_traits::Num;
+use num_bigint::{BigInt, BigUint};
+use num_traits::{FromPrimitive, Num};
 use redis::aio::MultiplexedConnection;
 use redis::AsyncCommands;
-use reqwest::Client;
-use serde::Deserialize;
 use sqlx::types::BigDecimal;
-use sqlx::Row;
+use sqlx::{Connection, PgConnection, Row};
 use starknet::core::types::Felt;
 use starknet::providers::jsonrpc::HttpTransport;
 use starknet::providers::JsonRpcClient;
+use std::collections::HashMap;
 use std::fmt;
 use std::str::FromStr;
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use tracing::{error, info, trace};
+use tracing::{error, info, trace, warn};
 
 // conversion from Diri string
 impl FromStr for TokenEventType {
@@ -92,6 +93,30 @@ enum RollbackStatus {
     CancelledOwnership,
 }
 
+/// Diri callback an order event was received through, part of the key that
+/// makes replayed events no-ops.
+#[derive(Debug, Copy, Clone)]
+enum OrderEventKind {
+    Placed,
+    Cancelled,
+    Fulfilled,
+    Executed,
+    Rollback,
+}
+
+impl fmt::Display for OrderEventKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let string = match self {
+            OrderEventKind::Placed => "PLACED",
+            OrderEventKind::Cancelled => "CANCELLED",
+            OrderEventKind::Fulfilled => "FULFILLED",
+            OrderEventKind::Executed => "EXECUTED",
+            OrderEventKind::Rollback => "ROLLBACK",
+        };
+        write!(f, "{}", string)
+    }
+}
+
 #[derive(sqlx::FromRow)]
 struct TokenInfo {
     token_id: String,
@@ -110,6 +135,64 @@ struct OrginalOrder {
     event_type: TokenEventType,
 }
 
+#[derive(sqlx::FromRow)]
+struct ExecutedCollectionOffer {
+    contract_address: String,
+    chain_id: String,
+    offer_maker: String,
+    offer_amount: String,
+    offer_timestamp: i64,
+    currency_chain_id: String,
+    currency_address: String,
+}
+
+/// USD prices of the assets of an event at the time of its block, resolved
+/// before the transaction of the event so that no call to the price engine
+/// runs while the transaction holds row locks. Assets missing from it are
+/// recorded without prices, the cron pricing them later from the candles.
+#[derive(Debug, Default)]
+struct EventPrices {
+    usd_prices: HashMap<String, f64>,
+}
+
+impl EventPrices {
+    async fn resolve(prices: &dyn PriceProvider, assets: &[String], timestamp: i64) -> Self {
+        let mut usd_prices = HashMap::with_capacity(assets.len());
+        for asset in assets {
+            if let Some(price) = OrderProvider::get_usd_price(prices, asset, timestamp).await {
+                usd_prices.insert(asset.clone(), price);
+            }
+        }
+        Self { usd_prices }
+    }
+
+    fn usd_price(&self, asset: &str) -> Option<f64> {
+        self.usd_prices.get(asset).copied()
+    }
+}
+
+/// Sale of a collection offer, pending in `pending_collection_offer_sale`
+/// until the token it sold is known.
+#[derive(sqlx::FromRow)]
+struct CollectionOfferSale {
+    order_hash: String,
+    seller: Option<String>,
+    buyer: String,
+    block_timestamp: i64,
+}
+
+#[derive(sqlx::FromRow)]
+struct SoldToken {
+    token_id: String,
+    token_id_hex: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct SaleFeeRates {
+    royalty_bps: Option<i32>,
+    marketplace_fee_bps: Option<i32>,
+}
+
 #[derive(sqlx::FromRow)]
 struct Offer {
     offer_amount: Option<f64>,
@@ -223,25 +306,10 @@ pub struct TokenData {
     currency_address: Option<String>,
 }
 
-#[derive(Deserialize, Debug)]
-#[serde(rename_all = "camelCase")]
-struct TokenPrice {
-    #[allow(dead_code)]
-    address: String,
-
-    #[allow(dead_code)]
-    #[serde(rename = "priceInUSD")]
-    price_in_usd: Option<f64>,
-
-    #[serde(rename = "priceInETH")]
-    price_in_eth: Option<f64>,
-
-    #[allow(dead_code)]
-    decimals: u8,
-}
-
-fn eth_to_wei(price_in_eth: f64) -> u128 {
-    (price_in_eth * 1e18) as u128
+#[derive(sqlx::FromRow)]
+struct CurrencyData {
+    symbol: Option<String>,
+    decimals: Option<i16>,
 }
 
 fn hex_to_wei(hex_str: Option<String>) -> Option<u128> {
@@ -253,8 +321,29 @@ fn hex_to_wei(hex_str: Option<String>) -> Option<u128> {
     }
 }
 
+/// Converts a hex amount of a currency with `decimals` into wei, given the
+/// price of one unit of the currency in ETH.
+fn convert_to_wei(hex_str: &str, decimals: i16, price_in_eth: f64) -> Option<String> {
+    let amount = BigInt::from_str_radix(hex_str.trim_start_matches("0x"), 16).ok()?;
+    let price_in_eth = BigDecimal::from_f64(price_in_eth)?;
+    let wei =
+        BigDecimal::new(amount, i64::from(decimals)) * price_in_eth * BigDecimal::from(WEI_PER_ETH);
+    Some(wei.with_scale(0).into_bigint_and_exponent().0.to_string())
+}
+
+/// Part `bps` basis points of a hex amount, as hex.
+fn hex_share(hex_str: &str, bps: i32) -> Option<String> {
+    let amount = BigUint::from_str_radix(hex_str.trim_start_matches("0x"), 16).ok()?;
+    let bps = u32::try_from(bps).ok()?;
+    Some(format!("0x{:x}", amount * bps / 10_000u32))
+}
+
 const CURRENCY_ADDRESS_ETH: &str =
     "0x049d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7";
+const ETH_SYMBOL: &str = "ETH";
+const WEI_PER_ETH: u64 = 1_000_000_000_000_000_000;
+/// Pending sales of collection offers looked up on each resolution pass.
+const PENDING_SALES_BATCH_SIZE: i64 = 100;
 
 impl OrderProvider {
     async fn clear_tokens_cache(
@@ -287,7 +376,7 @@ impl OrderProvider {
     }
 
     async fn token_exists(
-        client: &SqlxCtxPg,
+        conn: &mut PgConnection,
         contract_address: &str,
         token_id: &str,
         chain_id: &str,
@@ -304,13 +393,13 @@ impl OrderProvider {
             .bind(contract_address)
             .bind(token_id)
             .bind(chain_id)
-            .fetch_one(&client.pool)
+            .fetch_one(&mut *conn)
             .await?;
         Ok(exists)
     }
 
     pub async fn get_contract(
-        client: &SqlxCtxPg,
+        conn: &mut PgConnection,
         contract_address: &str,
         chain_id: &str,
     ) -> Result<Option<String>, ProviderError> {
@@ -322,19 +411,19 @@ impl OrderProvider {
         let result = sqlx::query(query)
             .bind(contract_address)
             .bind(chain_id)
-            .fetch_optional(&client.pool)
+            .fetch_optional(&mut *conn)
             .await?;
 
         Ok(result.map(|row| row.get("contract_address")))
     }
 
     pub async fn get_or_create_contract(
-        client: &SqlxCtxPg,
+        conn: &mut PgConnection,
         contract_address: &str,
         chain_id: &str,
         block_timestamp: u64,
     ) -> Result<String, ProviderError> {
-        match Self::get_contract(client, contract_address, chain_id).await? {
+        match Self::get_contract(conn, contract_address, chain_id).await? {
             Some(contract_address) => Ok(contract_address),
             None => {
                 let insert_query = "
@@ -347,7 +436,7 @@ impl OrderProvider {
                     .bind(chain_id)
                     .bind("ERC721".to_string())
                     .bind(block_timestamp as i64)
-                    .fetch_one(&client.pool)
+                    .fetch_one(&mut *conn)
                     .await?;
                 Ok(result.get::<String, _>("contract_address"))
             }
@@ -355,7 +444,7 @@ impl OrderProvider {
     }
 
     pub async fn get_offer_data_by_order_hash(
-        client: &SqlxCtxPg,
+        conn: &mut PgConnection,
         order_hash: &str,
     ) -> Result<Option<OfferData>, sqlx::Error> {
         let query = "
@@ -415,7 +504,7 @@ impl OrderProvider {
             ),
         >(query)
         .bind(order_hash)
-        .fetch_optional(&client.pool)
+        .fetch_optional(&mut *conn)
         .await?
         {
             Ok(Some(OfferData {
@@ -441,7 +530,7 @@ impl OrderProvider {
     }
 
     pub async fn get_token_data_by_order_hash(
-        client: &SqlxCtxPg,
+        conn: &mut PgConnection,
         order_hash: &str,
     ) -> Result<Option<TokenData>, sqlx::Error> {
         let query = "
@@ -471,7 +560,7 @@ impl OrderProvider {
             ),
         >(query)
         .bind(order_hash)
-        .fetch_optional(&client.pool)
+        .fetch_optional(&mut *conn)
         .await?
         {
             Ok(Some(TokenData {
@@ -489,7 +578,7 @@ impl OrderProvider {
     }
 
     pub async fn get_current_owner(
-        client: &SqlxCtxPg,
+        conn: &mut PgConnection,
         contract_address: &String,
         token_id: &str,
         chain_id: &str,
@@ -503,7 +592,7 @@ impl OrderProvider {
             .bind(contract_address)
             .bind(token_id)
             .bind(chain_id)
-            .fetch_one(&client.pool)
+            .fetch_one(&mut *conn)
             .await?;
 
         let current_owner: Option<String> = result.try_get::<String, _>("current_owner").ok();
@@ -512,7 +601,7 @@ impl OrderProvider {
     }
 
     pub async fn get_fulfiller_address_from_event(
-        client: &SqlxCtxPg,
+        conn: &mut PgConnection,
         contract_address: &String,
         token_id: &str,
         chain_id: &str,
@@ -529,7 +618,7 @@ impl OrderProvider {
             .bind(chain_id)
             .bind(order_hash)
             .bind(TokenEventType::Fulfill.to_string())
-            .fetch_optional(&client.pool)
+            .fetch_optional(&mut *conn)
             .await?;
 
         match result {
@@ -542,7 +631,7 @@ impl OrderProvider {
     }
 
     pub async fn get_token_data_by_id(
-        client: &SqlxCtxPg,
+        conn: &mut PgConnection,
         contract_address: &String,
         token_id: &str,
         chain_id: &str,
@@ -576,7 +665,7 @@ impl OrderProvider {
         .bind(contract_address)
         .bind(token_id)
         .bind(chain_id)
-        .fetch_optional(&client.pool)
+        .fetch_optional(&mut *conn)
         .await?
         {
             Ok(Some(TokenData {
@@ -594,7 +683,7 @@ impl OrderProvider {
     }
 
     pub async fn update_token_status(
-        client: &SqlxCtxPg,
+        conn: &mut PgConnection,
         contract_address: &String,
         token_id: &str,
         status: OrderStatus,
@@ -615,14 +704,14 @@ impl OrderProvider {
             .bind(token_id)
             .bind(status.to_string())
             .bind(buy_in_progress)
-            .execute(&client.pool)
+            .execute(&mut *conn)
             .await?;
 
         Ok(())
     }
 
     pub async fn update_offer_status(
-        client: &SqlxCtxPg,
+        conn: &mut PgConnection,
         order_hash: &str,
         status: OrderStatus,
     ) -> Result<(), ProviderError> {
@@ -636,7 +725,7 @@ impl OrderProvider {
         let token_info: Option<TokenInfo> = sqlx::query_as(select_query)
             .bind(order_hash)
             .bind(status.to_string())
-            .fetch_optional(&client.pool)
+            .fetch_optional(&mut *conn)
             .await?;
 
         if let Some(ref info) = token_info {
@@ -645,7 +734,7 @@ impl OrderProvider {
             sqlx::query(query)
                 .bind(order_hash)
                 .bind(status.to_string())
-                .execute(&client.pool)
+                .execute(&mut *conn)
                 .await?;
 
             // special case for cancelled orders
@@ -676,7 +765,7 @@ impl OrderProvider {
                     .bind(contract_address)
                     .bind(token_id)
                     .bind(chain_id)
-                    .fetch_one(&client.pool)
+                    .fetch_one(&mut *conn)
                     .await;
 
                 // Update top_bid fields based on whether a valid offer exists
@@ -704,7 +793,7 @@ impl OrderProvider {
                         .bind(contract_address)
                         .bind(chain_id)
                         .bind(token_id)
-                        .execute(&client.pool)
+                        .execute(&mut *conn)
                         .await
                         .map_err(|e| {
                             error!(
@@ -733,7 +822,7 @@ impl OrderProvider {
                         .bind(contract_address)
                         .bind(chain_id)
                         .bind(token_id)
-                        .execute(&client.pool)
+                        .execute(&mut *conn)
                         .await
                         .map_err(|e| {
                             error!(
@@ -751,7 +840,7 @@ impl OrderProvider {
     }
 
     pub async fn clear_token_data_if_listing(
-        client: &SqlxCtxPg,
+        conn: &mut PgConnection,
         contract_address: &String,
         token_id: &str,
     ) -> Result<(), ProviderError> {
@@ -768,13 +857,13 @@ impl OrderProvider {
         sqlx::query(query)
             .bind(contract_address)
             .bind(token_id)
-            .execute(&client.pool)
+            .execute(&mut *conn)
             .await?;
         Ok(())
     }
 
     pub async fn recalculate_floor_price(
-        client: &SqlxCtxPg,
+        conn: &mut PgConnection,
         contract_address: &str,
         chain_id: &str,
     ) -> Result<(), ProviderError> {
@@ -791,7 +880,7 @@ impl OrderProvider {
         match sqlx::query_scalar::<_, BigDecimal>(recalculate_query)
             .bind(contract_address)
             .bind(chain_id)
-            .fetch_optional(&client.pool)
+            .fetch_optional(&mut *conn)
             .await
         {
             Ok(new_floor_price) => {
@@ -805,7 +894,7 @@ impl OrderProvider {
                     match sqlx::query_scalar::<_, BigDecimal>(current_floor_query)
                         .bind(contract_address)
                         .bind(chain_id)
-                        .fetch_optional(&client.pool)
+                        .fetch_optional(&mut *conn)
                         .await
                     {
                         Ok(current_floor_opt) => {
@@ -822,7 +911,7 @@ impl OrderProvider {
                                     .bind(contract_address)
                                     .bind(min_price)
                                     .bind(chain_id)
-                                    .execute(&client.pool)
+                                    .execute(&mut *conn)
                                     .await
                                 {
                                     Ok(_) => {
@@ -865,7 +954,7 @@ impl OrderProvider {
                     match sqlx::query(update_query)
                         .bind(contract_address)
                         .bind(chain_id)
-                        .execute(&client.pool)
+                        .execute(&mut *conn)
                         .await
                     {
                         Ok(_) => {
@@ -899,7 +988,7 @@ impl OrderProvider {
 
     // Common function for updating best offer
     pub async fn update_best_offer(
-        client: &SqlxCtxPg,
+        conn: &mut PgConnection,
         info: &OrderExecutedInfo,
     ) -> Result<(), ProviderError> {
         let select_query = "
@@ -918,7 +1007,7 @@ impl OrderProvider {
                 .bind(&info.token_id)
                 .bind(info.block_timestamp as i64)
                 .bind(&info.to_address)
-                .fetch_optional(&client.pool)
+                .fetch_optional(&mut *conn)
                 .await?;
 
         match best_offer {
@@ -941,7 +1030,7 @@ impl OrderProvider {
                 .bind(end)
                 .bind(curr_addr)
                 .bind(order_hash)
-                .execute(&client.pool)
+                .execute(&mut *conn)
                 .await?;
             }
             None => {
@@ -958,7 +1047,7 @@ impl OrderProvider {
                 )
                 .bind(&info.contract_address)
                 .bind(&info.token_id)
-                .execute(&client.pool)
+                .execute(&mut *conn)
                 .await?;
             }
         }
@@ -966,7 +1055,7 @@ impl OrderProvider {
     }
 
     pub async fn verify_token_status(
-        client: &SqlxCtxPg,
+        conn: &mut PgConnection,
         contract_address: &str,
         token_id: &str,
     ) -> Result<(), ProviderError> {
@@ -982,7 +1071,7 @@ impl OrderProvider {
         let result = sqlx::query(verify_query)
             .bind(contract_address)
             .bind(token_id)
-            .fetch_one(&client.pool)
+            .fetch_one(&mut *conn)
             .await?;
 
         info!(
@@ -1039,11 +1128,11 @@ impl OrderProvider {
     }
 
     pub async fn update_token_data_on_listing_executed(
-        client: &SqlxCtxPg,
+        conn: &mut PgConnection,
         info: &OrderExecutedInfo,
     ) -> Result<(), ProviderError> {
         // 1. Update token with new owner and clean listing data
-        Self::verify_token_status(client, &info.contract_address, &info.token_id).await?;
+        Self::verify_token_status(conn, &info.contract_address, &info.token_id).await?;
 
         let base_update = "
         UPDATE token
@@ -1078,7 +1167,7 @@ impl OrderProvider {
             .bind(&info.currency_chain_id)
             .bind(&info.currency_address)
             .bind(OrderStatus::Executed.to_string())
-            .execute(&client.pool)
+            .execute(&mut *conn)
             .await?;
 
         info!("Updated token status: {:?}", result.rows_affected());
@@ -1090,7 +1179,7 @@ impl OrderProvider {
             );
         }
 
-        Self::verify_token_status(client, &info.contract_address, &info.token_id).await?;
+        Self::verify_token_status(conn, &info.contract_address, &info.token_id).await?;
         // 2. Remove buyer's offers only
         let delete_buyer_offers = "
         DELETE FROM token_offer 
@@ -1102,17 +1191,17 @@ impl OrderProvider {
             .bind(&info.to_address)
             .bind(&info.contract_address)
             .bind(&info.token_id)
-            .execute(&client.pool)
+            .execute(&mut *conn)
             .await?;
 
         // 3. Update best offer (excluding buyer)
-        Self::update_best_offer(client, info).await?;
+        Self::update_best_offer(conn, info).await?;
 
         Ok(())
     }
 
     pub async fn update_token_data_on_offer_executed(
-        client: &SqlxCtxPg,
+        conn: &mut PgConnection,
         info: &OrderExecutedInfo,
     ) -> Result<(), ProviderError> {
         // 1. Update token and clean listing/offer data
@@ -1149,7 +1238,7 @@ impl OrderProvider {
             .bind(&info.currency_chain_id)
             .bind(&info.currency_address)
             .bind(OrderStatus::Executed.to_string())
-            .execute(&client.pool)
+            .execute(&mut *conn)
             .await?;
 
         // 2. Remove all offers from the new owner
@@ -1163,45 +1252,161 @@ impl OrderProvider {
             .bind(&info.to_address)
             .bind(&info.contract_address)
             .bind(&info.token_id)
-            .execute(&client.pool)
+            .execute(&mut *conn)
             .await?;
 
         // 3. Update best offer (excluding new owner)
-        Self::update_best_offer(client, info).await?;
+        Self::update_best_offer(conn, info).await?;
 
         Ok(())
     }
 
-    async fn insert_event_history(
+    async fn get_currency_data(
+        conn: &mut PgConnection,
+        chain_id: &str,
+        currency_address: &str,
+    ) -> Result<Option<CurrencyData>, ProviderError> {
+        let query = "
+            SELECT symbol, decimals
+            FROM currency_mapping
+            WHERE currency_address = $1 AND chain_id = $2
+        ";
+        Ok(sqlx::query_as::<_, CurrencyData>(query)
+            .bind(currency_address)
+            .bind(chain_id)
+            .fetch_optional(&mut *conn)
+            .await?)
+    }
+
+    /// Failures of the price service are logged only, an event is then
+    /// recorded without its prices rather than not indexed.
+    async fn get_usd_price(prices: &dyn PriceProvider, asset: &str, timestamp: i64) -> Option<f64> {
+        match prices.usd_price(asset, timestamp).await {
+            Ok(price) => price.filter(|price| *price > 0.0),
+            Err(e) => {
+                error!(
+                    "Failed to get the USD price of {} at {}: {}",
+                    asset, timestamp, e
+                );
+                None
+            }
+        }
+    }
+
+    /// Converts `amount`, in hex, of the currency `symbol` with `decimals`
+    /// into wei of ETH at the USD prices of the currency and of ETH. Also
+    /// gives the USD price of the currency. The amount is unknown when
+    /// either price is missing, the events recorded before the price engine
+    /// being priced later by the cron from the candles.
+    fn convert_amount_to_wei(
+        prices: &EventPrices,
+        symbol: &str,
+        decimals: i16,
+        amount: &str,
+    ) -> (Option<String>, Option<f64>) {
+        let usd_price = prices.usd_price(symbol);
+        let eth_amount = match (usd_price, prices.usd_price(ETH_SYMBOL)) {
+            (Some(usd_price), Some(eth_usd_price)) => {
+                convert_to_wei(amount, decimals, usd_price / eth_usd_price)
+            }
+            _ => None,
+        };
+        (eth_amount, usd_price)
+    }
+
+    /// Resolves the prices needed by the events of `order_hash`, from the
+    /// currencies it was recorded with and `currency_address` for an order
+    /// being placed. ETH is needed to convert the other currencies and to
+    /// price a sale.
+    async fn resolve_event_prices(
         client: &SqlxCtxPg,
+        prices: &dyn PriceProvider,
+        order_hash: &str,
+        currency_address: Option<&str>,
+        block_timestamp: u64,
+        is_sale: bool,
+    ) -> Result<EventPrices, ProviderError> {
+        let query = "
+            SELECT DISTINCT symbol
+            FROM currency_mapping
+            WHERE symbol IS NOT NULL
+              AND currency_address IN (
+                  SELECT currency_address FROM token_event WHERE order_hash = $1
+                  UNION
+                  SELECT currency_address FROM collection_offer WHERE order_hash = $1
+                  UNION
+                  SELECT $2::text
+              );
+        ";
+        let mut assets: Vec<String> = sqlx::query_scalar(query)
+            .bind(order_hash)
+            .bind(currency_address)
+            .fetch_all(&client.pool)
+            .await?;
+
+        let needs_eth = is_sale || assets.iter().any(|asset| asset != ETH_SYMBOL);
+        assets.retain(|asset| asset != ETH_SYMBOL);
+        if needs_eth {
+            assets.push(ETH_SYMBOL.to_string());
+        }
+
+        Ok(EventPrices::resolve(prices, &assets, block_timestamp as i64).await)
+    }
+
+    /// Records the event with its amount in wei of ETH and, for a sale, the
+    /// USD price of its currency. Both are computed from the prices at the
+    /// time of the block, resolved before the transaction, so that
+    /// reindexing gives the same values. Amounts without a currency are in
+    /// ETH.
+    async fn insert_event_history(
+        conn: &mut PgConnection,
+        prices: &EventPrices,
         event_data: &EventHistoryData,
     ) -> Result<(), ProviderError> {
         let token_event_id = format!("{}_{}", &event_data.order_hash, event_data.block_timestamp);
-        let eth_amount: Option<String>;
-        if event_data.currency_address == Some(CURRENCY_ADDRESS_ETH.to_string())
-            || event_data.currency_address.is_none()
-        {
-            eth_amount = hex_to_wei(event_data.amount.clone()).map(|value| value.to_string());
+        let is_sale = event_data.event_type == TokenEventType::Executed;
+        let currency_address = event_data
+            .currency_address
+            .as_deref()
+            .filter(|address| !address.is_empty())
+            .unwrap_or(CURRENCY_ADDRESS_ETH);
+
+        let (eth_amount, currency_usd_price) = if currency_address == CURRENCY_ADDRESS_ETH {
+            let eth_amount = hex_to_wei(event_data.amount.clone()).map(|value| value.to_string());
+            let eth_usd_price = if is_sale {
+                prices.usd_price(ETH_SYMBOL)
+            } else {
+                None
+            };
+            (eth_amount, eth_usd_price)
         } else {
-            match Self::get_token_price(event_data.currency_address.as_ref().unwrap()).await {
-                Ok(price) => {
-                    if let Some(amount_in_wei) = hex_to_wei(event_data.amount.clone()) {
-                        let price_in_wei = eth_to_wei(price);
-                        let eth_value = price_in_wei * amount_in_wei;
-                        eth_amount = Some(eth_value.to_string());
-                    } else {
-                        eth_amount = None;
-                    }
+            let currency =
+                Self::get_currency_data(conn, &event_data.chain_id, currency_address).await?;
+            match (currency, event_data.amount.as_deref()) {
+                (
+                    Some(CurrencyData {
+                        symbol: Some(symbol),
+                        decimals: Some(decimals),
+                    }),
+                    Some(amount),
+                ) => {
+                    let (eth_amount, usd_price) =
+                        Self::convert_amount_to_wei(prices, &symbol, decimals, amount);
+                    (eth_amount, usd_price.filter(|_| is_sale))
                 }
-                Err(_e) => {
-                    eth_amount =
-                        hex_to_wei(event_data.amount.clone()).map(|value| value.to_string());
+                _ => {
+                    trace!(
+                        "No currency data for {}, recording the event without prices",
+                        currency_address
+                    );
+                    (None, None)
                 }
             }
-        }
+        };
+
         let q = "
-            INSERT INTO token_event (token_event_id, order_hash, token_id, token_id_hex, contract_address, chain_id, event_type, block_timestamp, from_address, to_address, amount, canceled_reason, currency_address, eth_amount)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14);
+            INSERT INTO token_event (token_event_id, order_hash, token_id, token_id_hex, contract_address, chain_id, event_type, block_timestamp, from_address, to_address, amount, canceled_reason, currency_address, eth_amount, currency_usd_price)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15);
         ";
 
         let _r = sqlx::query(q)
@@ -1219,14 +1424,16 @@ impl OrderProvider {
             .bind(event_data.canceled_reason.as_ref())
             .bind(event_data.currency_address.clone())
             .bind(eth_amount.as_ref())
-            .execute(&client.pool)
+            .bind(currency_usd_price)
+            .execute(&mut *conn)
             .await?;
 
         Ok(())
     }
 
     async fn insert_cancel_event(
-        client: &SqlxCtxPg,
+        conn: &mut PgConnection,
+        prices: &EventPrices,
         order_hash: String,
         block_timestamp: i64,
         reason: String,
@@ -1253,7 +1460,7 @@ impl OrderProvider {
         ";
         if let Ok(mut event_history) = sqlx::query_as::<_, EventHistoryData>(query)
             .bind(order_hash)
-            .fetch_one(&client.pool)
+            .fetch_one(&mut *conn)
             .await
         {
             event_history.block_timestamp = block_timestamp;
@@ -1280,17 +1487,17 @@ impl OrderProvider {
                     .bind(&event_history.contract_address)
                     .bind(&event_history.token_id)
                     .bind(&event_history.chain_id)
-                    .execute(&client.pool)
+                    .execute(&mut *conn)
                     .await?;
             }
 
-            Self::insert_event_history(client, &event_history).await?;
+            Self::insert_event_history(conn, prices, &event_history).await?;
         }
         Ok(())
     }
 
     async fn offer_exists(
-        client: &SqlxCtxPg,
+        conn: &mut PgConnection,
         order_hash: &str,
         offer_timestamp: i64,
     ) -> Result<bool, ProviderError> {
@@ -1306,14 +1513,14 @@ impl OrderProvider {
         let exists: bool = sqlx::query_scalar(query)
             .bind(order_hash)
             .bind(offer_timestamp)
-            .fetch_one(&client.pool)
+            .fetch_one(&mut *conn)
             .await?;
 
         Ok(exists)
     }
 
     async fn handle_broker_foreign_key_violation(
-        client: &SqlxCtxPg,
+        conn: &mut PgConnection,
         broker_id: &str,
         chain_id: &str,
     ) -> Result<(), ProviderError> {
@@ -1327,23 +1534,23 @@ impl OrderProvider {
             .bind(broker_id)
             .bind(chain_id)
             .bind("Inserted by indexer")
-            .execute(&client.pool)
+            .execute(&mut *conn)
             .await?;
 
         Ok(())
     }
 
     async fn insert_offers(
-        client: &SqlxCtxPg,
+        conn: &mut PgConnection,
         offer_data: &OfferData,
     ) -> Result<(), ProviderError> {
-        if Self::offer_exists(client, &offer_data.order_hash, offer_data.timestamp).await? {
+        if Self::offer_exists(conn, &offer_data.order_hash, offer_data.timestamp).await? {
             trace!("Offer already exists in database.");
             return Ok(());
         }
 
         if !Self::token_exists(
-            client,
+            conn,
             &offer_data.contract_address,
             &offer_data.token_id,
             &offer_data.chain_id,
@@ -1364,7 +1571,7 @@ impl OrderProvider {
             .bind(&offer_data.contract_address)
             .bind(&offer_data.token_id)
             .bind(&offer_data.chain_id)
-            .fetch_optional(&client.pool)
+            .fetch_optional(&mut *conn)
             .await?;
 
         // If topbid_amount is filled and the offer is better, update topbid fields
@@ -1392,22 +1599,31 @@ impl OrderProvider {
                     .bind(&offer_data.order_hash)
                     .bind(&offer_data.broker_id);
 
-                let result = update_query_binded.execute(&client.pool).await;
+                // the top bid is best effort, the savepoints keep a failed
+                // update from aborting the transaction of the event
+                let mut savepoint = conn.begin().await?;
+                let result = update_query_binded.execute(&mut *savepoint).await;
 
                 match result {
-                    Ok(_) => trace!("Update query executed successfully."),
+                    Ok(_) => {
+                        savepoint.commit().await?;
+                        trace!("Update query executed successfully.")
+                    }
                     Err(sqlx::Error::Database(ref e))
                         if e.code() == Some(std::borrow::Cow::Borrowed("23503"))
                             && e.message().contains("token_top_bid_broker_id_fkey") =>
                     {
+                        savepoint.rollback().await?;
+
                         // Handle Foreign Key violation for broker_id
                         Self::handle_broker_foreign_key_violation(
-                            client,
+                            conn,
                             &offer_data.broker_id,
                             &offer_data.chain_id,
                         )
                         .await?;
 
+                        let mut savepoint = conn.begin().await?;
                         let retry_result = sqlx::query(update_query)
                             .bind(&offer_data.contract_address)
                             .bind(&offer_data.token_id)
@@ -1418,20 +1634,27 @@ impl OrderProvider {
                             .bind(&offer_data.currency_address)
                             .bind(&offer_data.order_hash)
                             .bind(&offer_data.broker_id)
-                            .execute(&client.pool)
+                            .execute(&mut *savepoint)
                             .await;
 
                         match retry_result {
                             Ok(_) => {
+                                savepoint.commit().await?;
                                 trace!("Update query executed successfully after inserting broker.")
                             }
-                            Err(e) => error!(
-                                "Error executing update query after inserting broker: {:?}",
-                                e
-                            ),
+                            Err(e) => {
+                                savepoint.rollback().await?;
+                                error!(
+                                    "Error executing update query after inserting broker: {:?}",
+                                    e
+                                )
+                            }
                         }
                     }
-                    Err(e) => error!("Error executing update query: {:?}", e),
+                    Err(e) => {
+                        savepoint.rollback().await?;
+                        error!("Error executing update query: {:?}", e)
+                    }
                 }
             }
         }
@@ -1458,7 +1681,7 @@ impl OrderProvider {
             .bind(offer_data.end_date)
             .bind(&offer_data.broker_id)
             .bind(&offer_data.to_address)
-            .execute(&client.pool)
+            .execute(&mut *conn)
             .await?;
 
         let update_query = "
@@ -1471,22 +1694,116 @@ impl OrderProvider {
             .bind(&offer_data.contract_address)
             .bind(&offer_data.token_id)
             .bind(&offer_data.chain_id)
-            .execute(&client.pool)
+            .execute(&mut *conn)
             .await?;
 
         Ok(())
     }
 
-    pub async fn register_placed(
-        client: &SqlxCtxPg,
-        redis_conn: Arc<Mutex<MultiplexedConnection>>,
-        provider: &JsonRpcClient<HttpTransport>,
-        _block_id: u64,
+    /// Records an offer placed on a whole collection, which has no token to
+    /// attach it to in `token_offer`.
+    async fn insert_collection_offer(
+        conn: &mut PgConnection,
+        contract_address: &str,
         block_timestamp: u64,
         data: &PlacedData,
     ) -> Result<(), ProviderError> {
-        trace!("Registering placed order {:?}", data);
+        let insert_query = "
+            INSERT INTO collection_offer
+            (contract_address, chain_id, broker_id, order_hash, offer_maker, offer_amount, offer_quantity, offer_timestamp, currency_chain_id, currency_address, start_date, end_date, status)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            ON CONFLICT (order_hash) DO NOTHING;
+        ";
+
+        sqlx::query(insert_query)
+            .bind(contract_address)
+            .bind(&data.token_chain_id)
+            .bind(&data.broker_id)
+            .bind(&data.order_hash)
+            .bind(&data.offerer)
+            .bind(&data.start_amount)
+            .bind(&data.quantity)
+            .bind(block_timestamp as i64)
+            .bind(&data.currency_chain_id)
+            .bind(&data.currency_address)
+            .bind(data.start_date as i64)
+            .bind(data.end_date as i64)
+            .bind(OrderStatus::Placed.to_string())
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Returns the collection of the collection offer `order_hash`, `None`
+    /// when the order isn't a collection offer.
+    async fn update_collection_offer_status(
+        conn: &mut PgConnection,
+        order_hash: &str,
+        status: OrderStatus,
+    ) -> Result<Option<String>, ProviderError> {
+        let update_query = "
+            UPDATE collection_offer
+            SET status = $2
+            WHERE order_hash = $1
+            RETURNING contract_address;
+        ";
+
+        let contract_address = sqlx::query_scalar(update_query)
+            .bind(order_hash)
+            .bind(status.to_string())
+            .fetch_optional(&mut *conn)
+            .await?;
+
+        Ok(contract_address)
+    }
+
+    /// Registers the symbol and decimals of a currency the first time an
+    /// order uses it.
+    async fn ensure_currency_mapping(
+        conn: &mut PgConnection,
+        provider: &JsonRpcClient<HttpTransport>,
+        currency_chain_id: &str,
+        currency_address: &str,
+    ) -> Result<(), ProviderError> {
+        if currency_chain_id.is_empty() || currency_address.is_empty() {
+            return Ok(());
+        }
 
+        // Checking if currency mapping exists in the `currency_mapping` table
+        if Self::check_currency_mapping_exists(conn, currency_chain_id, currency_address).await? {
+            return Ok(());
+        }
+
+        // Call method to interact with the contract address
+        let tst_token_address = Felt::from_str(currency_address)
+            .map_err(|_| ProviderError::ParsingError("Invalid currency address".to_string()))?;
+        let decimals = provider.retrieve_decimals(tst_token_address).await?;
+        let decimals: i16 = decimals
+            .parse::<i16>()
+            .map_err(|_| ProviderError::ParsingError("Failed to parse decimals".to_string()))?;
+
+        let symbol = provider.retrieve_symbol(tst_token_address).await?;
+        sqlx::query(
+            "INSERT INTO currency_mapping (currency_address, chain_id, symbol, decimals) VALUES ($1, $2, $3, $4)"
+        )
+            .bind(currency_address)
+            .bind(currency_chain_id)
+            .bind(&symbol)
+            .bind(decimals)
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn apply_placed(
+        conn: &mut PgConnection,
+        prices: &EventPrices,
+        provider: &JsonRpcClient<HttpTransport>,
+        block_timestamp: u64,
+        data: &PlacedData,
+    ) -> Result<Option<String>, ProviderError> {
         let check_existing_query = "
             SELECT EXISTS (
                 SELECT 1 
@@ -1502,7 +1819,7 @@ impl OrderProvider {
         let already_exists: bool = sqlx::query_scalar(check_existing_query)
             .bind(&data.order_hash)
             .bind(event_type.to_string())
-            .fetch_one(&client.pool)
+            .fetch_one(&mut *conn)
             .await?;
 
         if already_exists {
@@ -1511,7 +1828,26 @@ impl OrderProvider {
                 data.order_hash,
                 event_type.to_string()
             );
-            return Ok(());
+            return Ok(None);
+        }
+
+        if event_type == TokenEventType::CollectionOffer && data.token_id.is_none() {
+            let contract_address = Self::get_or_create_contract(
+                conn,
+                &data.token_address,
+                &data.token_chain_id,
+                block_timestamp,
+            )
+            .await?;
+            Self::insert_collection_offer(conn, &contract_address, block_timestamp, data).await?;
+            Self::ensure_currency_mapping(
+                conn,
+                provider,
+                &data.currency_chain_id,
+                &data.currency_address,
+            )
+            .await?;
+            return Ok(Some(contract_address));
         }
 
         let mut currency_chain_id = "".to_string();
@@ -1534,20 +1870,13 @@ impl OrderProvider {
 
         let event_type = TokenEventType::from_str(&data.order_type).map_err(ProviderError::from)?;
         let contract_address = Self::get_or_create_contract(
-            client,
+            conn,
             &data.token_address,
             &data.token_chain_id,
             block_timestamp,
         )
         .await?;
 
-        match Self::clear_tokens_cache(redis_conn.clone(), &contract_address).await {
-            Ok(_) => {}
-            Err(e) => {
-                println!("Error when deleting cache : {}", e);
-            }
-        }
-
         if event_type == TokenEventType::Offer || event_type == TokenEventType::CollectionOffer {
             // create token without listing information
             let upsert_query = "
@@ -1569,15 +1898,15 @@ impl OrderProvider {
                 .bind(block_timestamp as i64)
                 .bind(block_timestamp as i64)
                 .bind(OrderStatus::Placed.to_string())
-                .execute(&client.pool)
+                .execute(&mut *conn)
                 .await?;
 
             to_address =
-                Self::get_current_owner(client, &contract_address, &token_id, &data.token_chain_id)
+                Self::get_current_owner(conn, &contract_address, &token_id, &data.token_chain_id)
                     .await?;
 
             Self::insert_offers(
-                client,
+                conn,
                 &OfferData {
                     token_id: token_id.clone(),
                     contract_address: contract_address.clone(),
@@ -1667,25 +1996,30 @@ impl OrderProvider {
 
             currency_chain_id = data.currency_chain_id.clone();
             currency_address = data.currency_address.clone();
-            let result = upsert_query_binded.execute(&client.pool).await;
+            // a failed statement aborts the whole transaction, the savepoint
+            // allows to insert the missing broker and retry
+            let mut savepoint = conn.begin().await?;
+            let result = upsert_query_binded.execute(&mut *savepoint).await;
 
             // check if the broker is missing
-            let _ = match result {
-                Ok(_) => Ok(()),
+            match result {
+                Ok(_) => savepoint.commit().await?,
                 Err(sqlx::Error::Database(ref e))
                     if e.code() == Some(std::borrow::Cow::Borrowed("23503"))
                         && e.message().contains("token_listing_broker_id_fkey") =>
                 {
+                    savepoint.rollback().await?;
+
                     // Handle Foreign Key violation for broker_id
                     Self::handle_broker_foreign_key_violation(
-                        client,
+                        conn,
                         &data.broker_id,
                         &data.token_chain_id,
                     )
                     .await?;
 
                     // Retry the upsert operation
-                    let _ = sqlx::query(upsert_query)
+                    sqlx::query(upsert_query)
                         .bind(contract_address.clone())
                         .bind(token_id.clone())
                         .bind(data.token_chain_id.clone())
@@ -1706,16 +2040,14 @@ impl OrderProvider {
                         .bind(block_timestamp as i64)
                         .bind(OrderStatus::Placed.to_string())
                         .bind(event_type.to_string())
-                        .execute(&client.pool)
-                        .await;
-
-                    Ok(())
+                        .execute(&mut *conn)
+                        .await?;
                 }
                 Err(e) => {
                     error!("Error executing update query because of broker : {:?}", e);
-                    Err(ProviderError::from(e))
+                    return Err(ProviderError::from(e));
                 }
-            };
+            }
 
             // update the floor :
             let current_floor_query = "
@@ -1727,7 +2059,7 @@ impl OrderProvider {
             let current_floor: Option<BigDecimal> = sqlx::query_scalar(current_floor_query)
                 .bind(&contract_address)
                 .bind(&data.token_chain_id)
-                .fetch_optional(&client.pool)
+                .fetch_optional(&mut *conn)
                 .await?
                 .unwrap_or_else(|| Some(BigDecimal::from(0)));
 
@@ -1749,14 +2081,15 @@ impl OrderProvider {
                     .bind(&contract_address)
                     .bind(&data.token_chain_id)
                     .bind(&listing_amount)
-                    .execute(&client.pool)
+                    .execute(&mut *conn)
                     .await?;
             }
         }
 
         if let Some(token_id_hex) = data.token_id.clone() {
             Self::insert_event_history(
-                client,
+                conn,
+                prices,
                 &EventHistoryData {
                     order_hash: data.order_hash.clone(),
                     token_id: token_id.clone(),
@@ -1776,60 +2109,27 @@ impl OrderProvider {
         }
 
         // manage currency
-        if !currency_chain_id.is_empty() && !currency_address.is_empty() {
-            // Checking if currency mapping exists in the `currency_mapping` table
-            let currency_mapping_exists =
-                Self::check_currency_mapping_exists(client, &currency_chain_id, &currency_address)
-                    .await?;
-
-            if !currency_mapping_exists {
-                // Call method to interact with the contract address
-                let tst_token_address = Felt::from_str(&currency_address).map_err(|_| {
-                    ProviderError::ParsingError("Invalid currency address".to_string())
-                })?;
-                let decimals = provider.retrieve_decimals(tst_token_address).await?;
-                let decimals: i16 = decimals.parse::<i16>().map_err(|_| {
-                    ProviderError::ParsingError("Failed to parse decimals".to_string())
-                })?;
-
-                let symbol = provider.retrieve_symbol(tst_token_address).await?;
-                sqlx::query(
-                    "INSERT INTO currency_mapping (currency_address, chain_id, symbol, decimals) VALUES ($1, $2, $3, $4)"
-                )
-                    .bind(&currency_address)
-                    .bind(&currency_chain_id)
-                    .bind(&symbol)
-                    .bind(decimals)
-                    .execute(&client.pool)
-                    .await?;
-            }
-        }
+        Self::ensure_currency_mapping(conn, provider, &currency_chain_id, &currency_address)
+            .await?;
 
-        Ok(())
+        Ok(Some(contract_address))
     }
 
-    pub async fn register_cancelled(
-        client: &SqlxCtxPg,
-        redis_conn: Arc<Mutex<MultiplexedConnection>>,
-        _block_id: u64,
+    async fn apply_cancelled(
+        conn: &mut PgConnection,
+        prices: &EventPrices,
         block_timestamp: u64,
         data: &CancelledData,
-    ) -> Result<(), ProviderError> {
-        trace!("Registering cancelled order {:?}", data);
+    ) -> Result<Option<String>, ProviderError> {
         let mut is_listing = true;
+        let mut updated_contract = None;
         // if the order hash exists in token table, then it is a listing
-        if let Some(token_data) =
-            Self::get_token_data_by_order_hash(client, &data.order_hash).await?
+        if let Some(token_data) = Self::get_token_data_by_order_hash(conn, &data.order_hash).await?
         {
-            match Self::clear_tokens_cache(redis_conn.clone(), &token_data.contract_address).await {
-                Ok(_) => {}
-                Err(e) => {
-                    println!("Error when deleting cache : {}", e);
-                }
-            }
+            updated_contract = Some(token_data.contract_address.clone());
 
             Self::update_token_status(
-                client,
+                conn,
                 &token_data.contract_address,
                 &token_data.token_id,
                 OrderStatus::Cancelled,
@@ -1837,31 +2137,34 @@ impl OrderProvider {
             .await?;
 
             Self::clear_token_data_if_listing(
-                client,
+                conn,
                 &token_data.contract_address,
                 &token_data.token_id,
             )
             .await?;
 
-            Self::recalculate_floor_price(
-                client,
-                &token_data.contract_address,
-                &token_data.chain_id,
-            )
-            .await?;
+            Self::recalculate_floor_price(conn, &token_data.contract_address, &token_data.chain_id)
+                .await?;
         }
 
         // if the order hash exists in token_offer table, then it is an offer
-        if Self::get_offer_data_by_order_hash(client, &data.order_hash)
+        if Self::get_offer_data_by_order_hash(conn, &data.order_hash)
             .await?
             .is_some()
         {
-            Self::update_offer_status(client, &data.order_hash, OrderStatus::Cancelled).await?;
+            Self::update_offer_status(conn, &data.order_hash, OrderStatus::Cancelled).await?;
             is_listing = false;
+        } else if let Some(contract_address) =
+            Self::update_collection_offer_status(conn, &data.order_hash, OrderStatus::Cancelled)
+                .await?
+        {
+            // collection offers have no token to record a cancel event on
+            return Ok(Some(contract_address));
         }
         // insert cancelled event
         Self::insert_cancel_event(
-            client,
+            conn,
+            prices,
             data.order_hash.clone(),
             block_timestamp as i64,
             data.reason.clone(),
@@ -1869,16 +2172,16 @@ impl OrderProvider {
         )
         .await?;
 
-        Ok(())
+        Ok(updated_contract)
     }
 
-    pub async fn register_fulfilled(
-        client: &SqlxCtxPg,
-        redis_conn: Arc<Mutex<MultiplexedConnection>>,
-        _block_id: u64,
+    async fn apply_fulfilled(
+        conn: &mut PgConnection,
+        prices: &EventPrices,
         block_timestamp: u64,
         data: &FulfilledData,
-    ) -> Result<(), ProviderError> {
+    ) -> Result<Option<String>, ProviderError> {
+        let mut updated_contract = None;
         // First check if an executed event exists with a later timestamp
         let check_executed_query = "
             SELECT block_timestamp 
@@ -1892,11 +2195,10 @@ impl OrderProvider {
         let executed_exists = sqlx::query_scalar::<_, i64>(check_executed_query)
             .bind(&data.order_hash)
             .bind(block_timestamp as i64)
-            .fetch_optional(&client.pool)
+            .fetch_optional(&mut *conn)
             .await?;
 
-        if let Some(token_data) =
-            Self::get_token_data_by_order_hash(client, &data.order_hash).await?
+        if let Some(token_data) = Self::get_token_data_by_order_hash(conn, &data.order_hash).await?
         {
             let token_id = match BigInt::from_str(&token_data.token_id) {
                 Ok(token_id) => token_id.to_string(),
@@ -1906,16 +2208,12 @@ impl OrderProvider {
                 }
             };
 
-            match Self::clear_tokens_cache(redis_conn.clone(), &token_data.contract_address).await {
-                Ok(_) => {}
-                Err(e) => {
-                    println!("Error when deleting cache : {}", e);
-                }
-            }
+            updated_contract = Some(token_data.contract_address.clone());
 
             // Always record the fulfill event
             Self::insert_event_history(
-                client,
+                conn,
+                prices,
                 &EventHistoryData {
                     order_hash: data.order_hash.clone(),
                     token_id: token_id.clone(),
@@ -1937,14 +2235,14 @@ impl OrderProvider {
             if executed_exists.is_none() {
                 info!("Updating token status to Fulfilled as no later Executed event exists");
                 Self::update_token_status(
-                    client,
+                    conn,
                     &token_data.contract_address,
                     &token_data.token_id,
                     OrderStatus::Fulfilled,
                 )
                 .await?;
 
-                Self::update_offer_status(client, &data.order_hash, OrderStatus::Fulfilled).await?;
+                Self::update_offer_status(conn, &data.order_hash, OrderStatus::Fulfilled).await?;
             } else {
                 info!(
                     "Skipping status update for order {} as it was already executed",
@@ -1952,26 +2250,33 @@ impl OrderProvider {
                 );
             }
         } else if let Some(offer_data) =
-            Self::get_offer_data_by_order_hash(client, &data.order_hash).await?
+            Self::get_offer_data_by_order_hash(conn, &data.order_hash).await?
         {
             // Only update status if no later executed event exists
             if executed_exists.is_none() {
                 Self::update_token_status(
-                    client,
+                    conn,
                     &offer_data.contract_address,
                     &offer_data.token_id,
                     OrderStatus::Fulfilled,
                 )
                 .await?;
             }
+        } else if executed_exists.is_none() {
+            updated_contract = Self::update_collection_offer_status(
+                conn,
+                &data.order_hash,
+                OrderStatus::Fulfilled,
+            )
+            .await?;
         }
 
-        Ok(())
+        Ok(updated_contract)
     }
 
     /// This function checks if a currency mapping exists in the database
     pub async fn check_currency_mapping_exists(
-        client: &SqlxCtxPg,
+        conn: &mut PgConnection,
         currency_chain_id: &str,
         currency_address: &str,
     ) -> Result<bool, ProviderError> {
@@ -1981,21 +2286,19 @@ impl OrderProvider {
         let count: i64 = sqlx::query_scalar(query)
             .bind(currency_chain_id)
             .bind(currency_address)
-            .fetch_one(&client.pool)
+            .fetch_one(&mut *conn)
             .await?;
 
         Ok(count > 0)
     }
 
-    pub async fn register_executed(
-        client: &SqlxCtxPg,
-        redis_conn: Arc<Mutex<MultiplexedConnection>>,
-        _block_id: u64,
+    async fn apply_executed(
+        conn: &mut PgConnection,
+        prices: &EventPrices,
         block_timestamp: u64,
         data: &ExecutedData,
-    ) -> Result<(), ProviderError> {
+    ) -> Result<Option<String>, ProviderError> {
         println!("executed event {}", data.order_hash);
-        trace!("Registering executed order {:?}", data);
         let check_executed_query = "
         SELECT EXISTS (
             SELECT 1 
@@ -2008,12 +2311,12 @@ impl OrderProvider {
 
         let already_executed: bool = sqlx::query_scalar(check_executed_query)
             .bind(&data.order_hash)
-            .fetch_one(&client.pool)
+            .fetch_one(&mut *conn)
             .await?;
 
         if already_executed {
             trace!("Order {} was already executed, skipping", data.order_hash);
-            return Ok(());
+            return Ok(None);
         }
 
         // 1. Get the original order event (Listing or Offer)
@@ -2028,22 +2331,15 @@ impl OrderProvider {
 
         let original_order: Option<OrginalOrder> = sqlx::query_as(select_query)
             .bind(data.order_hash.clone())
-            .fetch_optional(&client.pool)
+            .fetch_optional(&mut *conn)
             .await?;
 
         // Always record the executed event, with or without original order data
         if let Some(order) = original_order.as_ref() {
-            // Clear redis cache for this token
-            match Self::clear_tokens_cache(redis_conn.clone(), &order.contract_address).await {
-                Ok(_) => {}
-                Err(e) => {
-                    println!("Error when deleting cache : {}", e);
-                }
-            }
-
             // Insert the execution event with full data
             Self::insert_event_history(
-                client,
+                conn,
+                prices,
                 &EventHistoryData {
                     order_hash: data.order_hash.clone(),
                     block_timestamp: block_timestamp as i64,
@@ -2060,6 +2356,14 @@ impl OrderProvider {
                 },
             )
             .await?;
+            Self::record_sale_fees(
+                conn,
+                &data.order_hash,
+                &order.contract_address,
+                &order.chain_id,
+                order.amount.as_deref(),
+            )
+            .await?;
 
             println!("register_executed: {:?}", data);
 
@@ -2077,7 +2381,7 @@ impl OrderProvider {
             // Update token data based on event type
             match order.event_type.to_db_string().as_str() {
                 "Listing" => {
-                    Self::update_token_data_on_listing_executed(client, &params).await?;
+                    Self::update_token_data_on_listing_executed(conn, &params).await?;
                 }
                 "Auction" => {
                     // First delete all offers for this token
@@ -2091,59 +2395,374 @@ impl OrderProvider {
                         .bind(&order.contract_address)
                         .bind(&order.token_id)
                         .bind(&order.chain_id)
-                        .execute(&client.pool)
+                        .execute(&mut *conn)
                         .await?;
 
                     // Then handle it like a listing
-                    Self::update_token_data_on_listing_executed(client, &params).await?;
+                    Self::update_token_data_on_listing_executed(conn, &params).await?;
                 }
                 "Offer" => {
-                    Self::update_token_data_on_offer_executed(client, &params).await?;
-                    Self::update_offer_status(client, &data.order_hash, OrderStatus::Executed)
+                    Self::update_token_data_on_offer_executed(conn, &params).await?;
+                    Self::update_offer_status(conn, &data.order_hash, OrderStatus::Executed)
                         .await?;
                 }
                 _ => {
                     error!("Unknown event type: {}", order.event_type);
                 }
             }
-        } else {
-            error!(
-                "No original Listing or Offer found for order hash: {}",
-                data.order_hash
+        } else if let Some(offer) =
+            Self::get_collection_offer_by_order_hash(conn, &data.order_hash).await?
+        {
+            Self::update_collection_offer_status(conn, &data.order_hash, OrderStatus::Executed)
+                .await?;
+
+            let buyer = data.to.clone().unwrap_or(offer.offer_maker.clone());
+            let sale = CollectionOfferSale {
+                order_hash: data.order_hash.clone(),
+                seller: data.from.clone(),
+                buyer,
+                block_timestamp: block_timestamp as i64,
+            };
+            match Self::get_collection_offer_sold_token(conn, &offer, &sale).await? {
+                Some(token) => {
+                    Self::record_collection_offer_sale(conn, prices, &offer, &sale, token).await?;
+                }
+                None => {
+                    warn!(
+                        "No transfer found yet for the token sold to collection offer {}, the sale is kept pending",
+                        data.order_hash
+                    );
+                    Self::insert_pending_collection_offer_sale(conn, &sale).await?;
+                }
+            }
+
+            return Ok(Some(offer.contract_address));
+        } else {
+            error!(
+                "No original Listing or Offer found for order hash: {}",
+                data.order_hash
             );
+            Self::insert_executed_without_order(conn, prices, block_timestamp, data).await?;
+        }
 
-            // Still record the executed event with available data
-            Self::insert_event_history(
+        Ok(original_order.map(|order| order.contract_address))
+    }
+
+    /// Splits the executed sale of `order_hash` into the creator royalties
+    /// and the marketplace fee, from the rates of the collection and of the
+    /// broker of the order. Must run before the order is cleared from the
+    /// token, a part staying NULL when its rate is unknown.
+    async fn record_sale_fees(
+        conn: &mut PgConnection,
+        order_hash: &str,
+        contract_address: &str,
+        chain_id: &str,
+        amount: Option<&str>,
+    ) -> Result<(), ProviderError> {
+        let Some(amount) = amount.filter(|amount| !amount.is_empty()) else {
+            return Ok(());
+        };
+
+        let query = "
+            SELECT
+                (SELECT royalty_bps FROM contract WHERE contract_address = $2 AND chain_id = $3) AS royalty_bps,
+                (
+                    SELECT fee_bps FROM broker
+                    WHERE id = COALESCE(
+                        (SELECT listing_broker_id FROM token WHERE listing_orderhash = $1 LIMIT 1),
+                        (SELECT broker_id FROM token_offer WHERE order_hash = $1 LIMIT 1),
+                        (SELECT broker_id FROM collection_offer WHERE order_hash = $1 LIMIT 1)
+                    )
+                ) AS marketplace_fee_bps;
+        ";
+        let rates = sqlx::query_as::<_, SaleFeeRates>(query)
+            .bind(order_hash)
+            .bind(contract_address)
+            .bind(chain_id)
+            .fetch_one(&mut *conn)
+            .await?;
+
+        let royalty_amount = rates.royalty_bps.and_then(|bps| hex_share(amount, bps));
+        let marketplace_fee_amount = rates
+            .marketplace_fee_bps
+            .and_then(|bps| hex_share(amount, bps));
+        if royalty_amount.is_none() && marketplace_fee_amount.is_none() {
+            return Ok(());
+        }
+
+        let update_query = "
+            UPDATE token_event
+            SET royalty_amount = $2, marketplace_fee_amount = $3
+            WHERE order_hash = $1 AND event_type = $4;
+        ";
+        sqlx::query(update_query)
+            .bind(order_hash)
+            .bind(royalty_amount)
+            .bind(marketplace_fee_amount)
+            .bind(EXECUTED_STR)
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Still records the executed event when its order is unknown, so that
+    /// the sale is not lost.
+    async fn insert_executed_without_order(
+        conn: &mut PgConnection,
+        prices: &EventPrices,
+        block_timestamp: u64,
+        data: &ExecutedData,
+    ) -> Result<(), ProviderError> {
+        Self::insert_event_history(
+            conn,
+            prices,
+            &EventHistoryData {
+                order_hash: data.order_hash.clone(),
+                block_timestamp: block_timestamp as i64,
+                token_id: "MISSING_DATA".to_string(),
+                token_id_hex: "MISSING_DATA".to_string(),
+                contract_address: "MISSING_DATA".to_string(),
+                chain_id: "MISSING_DATA".to_string(),
+                event_type: TokenEventType::Executed,
+                canceled_reason: Some("No original order found".to_string()),
+                to_address: data.to.clone(),
+                from_address: data.from.clone(),
+                amount: None,
+                currency_address: None,
+            },
+        )
+        .await?;
+
+        error!(
+            "Recorded execution event with missing data for order hash: {:?}",
+            data
+        );
+
+        Ok(())
+    }
+
+    async fn get_collection_offer_by_order_hash(
+        conn: &mut PgConnection,
+        order_hash: &str,
+    ) -> Result<Option<ExecutedCollectionOffer>, ProviderError> {
+        let query = "
+            SELECT contract_address, chain_id, offer_maker, offer_amount, offer_timestamp, currency_chain_id, currency_address
+            FROM collection_offer
+            WHERE order_hash = $1;
+        ";
+
+        let offer = sqlx::query_as::<_, ExecutedCollectionOffer>(query)
+            .bind(order_hash)
+            .fetch_optional(&mut *conn)
+            .await?;
+
+        Ok(offer)
+    }
+
+    /// The executed data of a collection offer doesn't name the token, it is
+    /// the one of the collection transferred from the seller to the buyer
+    /// between the placement of the offer and its execution. Transfers
+    /// followed by a recorded sale of their token belong to that sale, the
+    /// oldest remaining one is matched so that successive sales between the
+    /// same addresses are paired in order.
+    async fn get_collection_offer_sold_token(
+        conn: &mut PgConnection,
+        offer: &ExecutedCollectionOffer,
+        sale: &CollectionOfferSale,
+    ) -> Result<Option<SoldToken>, ProviderError> {
+        // Addresses are compared without their padding, Sana and Diri don't
+        // format them the same way. The seller is matched only when Diri
+        // gives it.
+        let query = "
+            SELECT transfer.token_id, transfer.token_id_hex
+            FROM token_event transfer
+            WHERE transfer.contract_address = $1
+              AND transfer.chain_id = $2
+              AND transfer.event_type = $3
+              AND ($4::text IS NULL OR ltrim(lower(substring(transfer.from_address from 3)), '0') = ltrim(lower(substring($4 from 3)), '0'))
+              AND ltrim(lower(substring(transfer.to_address from 3)), '0') = ltrim(lower(substring($5 from 3)), '0')
+              AND transfer.block_timestamp BETWEEN $6 AND $7
+              AND NOT EXISTS (
+                  SELECT 1
+                  FROM token_event sold
+                  WHERE sold.contract_address = transfer.contract_address
+                    AND sold.chain_id = transfer.chain_id
+                    AND sold.token_id = transfer.token_id
+                    AND sold.event_type = $8
+                    AND sold.block_timestamp >= transfer.block_timestamp
+              )
+            ORDER BY transfer.block_timestamp ASC
+            LIMIT 1;
+        ";
+
+        let token = sqlx::query_as::<_, SoldToken>(query)
+            .bind(&offer.contract_address)
+            .bind(&offer.chain_id)
+            .bind(TRANSFER_STR)
+            .bind(sale.seller.as_deref())
+            .bind(&sale.buyer)
+            .bind(offer.offer_timestamp)
+            .bind(sale.block_timestamp)
+            .bind(EXECUTED_STR)
+            .fetch_optional(&mut *conn)
+            .await?;
+
+        Ok(token)
+    }
+
+    /// Records the sale of `token` to a collection offer and gives the token
+    /// to the buyer.
+    async fn record_collection_offer_sale(
+        conn: &mut PgConnection,
+        prices: &EventPrices,
+        offer: &ExecutedCollectionOffer,
+        sale: &CollectionOfferSale,
+        token: SoldToken,
+    ) -> Result<(), ProviderError> {
+        Self::insert_event_history(
+            conn,
+            prices,
+            &EventHistoryData {
+                order_hash: sale.order_hash.clone(),
+                block_timestamp: sale.block_timestamp,
+                token_id: token.token_id.clone(),
+                token_id_hex: token.token_id_hex,
+                contract_address: offer.contract_address.clone(),
+                chain_id: offer.chain_id.clone(),
+                event_type: TokenEventType::Executed,
+                canceled_reason: None,
+                to_address: Some(sale.buyer.clone()),
+                from_address: sale.seller.clone(),
+                amount: Some(offer.offer_amount.clone()),
+                currency_address: Some(offer.currency_address.clone()),
+            },
+        )
+        .await?;
+        Self::record_sale_fees(
+            conn,
+            &sale.order_hash,
+            &offer.contract_address,
+            &offer.chain_id,
+            Some(&offer.offer_amount),
+        )
+        .await?;
+
+        let params = OrderExecutedInfo {
+            block_timestamp: sale.block_timestamp as u64,
+            contract_address: offer.contract_address.clone(),
+            token_id: token.token_id,
+            to_address: Some(sale.buyer.clone()),
+            price: offer.offer_amount.clone(),
+            currency_chain_id: offer.currency_chain_id.clone(),
+            currency_address: offer.currency_address.clone(),
+        };
+        Self::update_token_data_on_offer_executed(conn, &params).await?;
+
+        Ok(())
+    }
+
+    async fn insert_pending_collection_offer_sale(
+        conn: &mut PgConnection,
+        sale: &CollectionOfferSale,
+    ) -> Result<(), ProviderError> {
+        let query = "
+            INSERT INTO pending_collection_offer_sale (order_hash, seller, buyer, block_timestamp)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (order_hash) DO NOTHING;
+        ";
+
+        sqlx::query(query)
+            .bind(&sale.order_hash)
+            .bind(sale.seller.as_deref())
+            .bind(&sale.buyer)
+            .bind(sale.block_timestamp)
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Records the pending sales of collection offers whose transfer has
+    /// been indexed since they were executed. Returns the number of sales
+    /// recorded, the others staying pending for the next call.
+    pub async fn resolve_pending_collection_offer_sales(
+        client: &SqlxCtxPg,
+        redis_conn: Arc<Mutex<MultiplexedConnection>>,
+        prices: &dyn PriceProvider,
+    ) -> Result<usize, ProviderError> {
+        let query = "
+            SELECT order_hash, seller, buyer, block_timestamp
+            FROM pending_collection_offer_sale
+            ORDER BY block_timestamp, order_hash
+            LIMIT $1;
+        ";
+        let pending_sales = sqlx::query_as::<_, CollectionOfferSale>(query)
+            .bind(PENDING_SALES_BATCH_SIZE)
+            .fetch_all(&client.pool)
+            .await?;
+
+        let mut resolved = 0;
+        for sale in pending_sales {
+            // The transfer is looked up first so that prices are only read
+            // for the sales that can be recorded, then again in the
+            // transaction.
+            let mut conn = client.pool.acquire().await?;
+            let Some(offer) =
+                Self::get_collection_offer_by_order_hash(&mut conn, &sale.order_hash).await?
+            else {
+                error!(
+                    "Collection offer {} of a pending sale not found",
+                    sale.order_hash
+                );
+                continue;
+            };
+            if Self::get_collection_offer_sold_token(&mut conn, &offer, &sale)
+                .await?
+                .is_none()
+            {
+                continue;
+            }
+            drop(conn);
+
+            let event_prices = Self::resolve_event_prices(
                 client,
-                &EventHistoryData {
-                    order_hash: data.order_hash.clone(),
-                    block_timestamp: block_timestamp as i64,
-                    token_id: "MISSING_DATA".to_string(),
-                    token_id_hex: "MISSING_DATA".to_string(),
-                    contract_address: "MISSING_DATA".to_string(),
-                    chain_id: "MISSING_DATA".to_string(),
-                    event_type: TokenEventType::Executed,
-                    canceled_reason: Some("No original order found".to_string()),
-                    to_address: data.to.clone(),
-                    from_address: data.from.clone(),
-                    amount: None,
-                    currency_address: None,
-                },
+                prices,
+                &sale.order_hash,
+                None,
+                sale.block_timestamp as u64,
+                true,
             )
             .await?;
 
-            error!(
-                "Recorded execution event with missing data for order hash: {:?}",
-                data
+            let mut tx = client.pool.begin().await?;
+            let Some(token) = Self::get_collection_offer_sold_token(&mut tx, &offer, &sale).await?
+            else {
+                continue;
+            };
+
+            Self::record_collection_offer_sale(&mut tx, &event_prices, &offer, &sale, token)
+                .await?;
+            sqlx::query("DELETE FROM pending_collection_offer_sale WHERE order_hash = $1")
+                .bind(&sale.order_hash)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+
+            trace!(
+                "Recorded pending sale of collection offer {}",
+                sale.order_hash
             );
+            Self::clear_cache_of(redis_conn.clone(), Some(offer.contract_address)).await;
+            resolved += 1;
         }
 
-        Ok(())
+        Ok(resolved)
     }
 
-    pub async fn status_back_to_open(
-        client: &SqlxCtxPg,
-        _block_id: u64,
+    async fn apply_rollback(
+        conn: &mut PgConnection,
+        prices: &EventPrices,
         block_timestamp: u64,
         data: &RollbackStatusData,
     ) -> Result<(), ProviderError> {
@@ -2155,20 +2774,20 @@ impl OrderProvider {
             }
         }
 
-        if let Some(token_data) =
-            Self::get_token_data_by_order_hash(client, &data.order_hash).await?
+        if let Some(token_data) = Self::get_token_data_by_order_hash(conn, &data.order_hash).await?
         {
             Self::update_token_status(
-                client,
+                conn,
                 &token_data.contract_address,
                 &token_data.token_id,
                 OrderStatus::Cancelled,
             )
             .await?;
-            Self::update_offer_status(client, &data.order_hash, OrderStatus::Cancelled).await?;
+            Self::update_offer_status(conn, &data.order_hash, OrderStatus::Cancelled).await?;
 
             Self::insert_event_history(
-                client,
+                conn,
+                prices,
                 &EventHistoryData {
                     order_hash: data.order_hash.clone(),
                     block_timestamp: block_timestamp as i64,
@@ -2186,37 +2805,417 @@ impl OrderProvider {
             )
             .await?;
         } else if let Some(offer_data) =
-            Self::get_offer_data_by_order_hash(client, &data.order_hash).await?
+            Self::get_offer_data_by_order_hash(conn, &data.order_hash).await?
         {
             Self::update_token_status(
-                client,
+                conn,
                 &offer_data.contract_address,
                 &offer_data.token_id,
                 OrderStatus::Cancelled,
             )
             .await?;
+        } else {
+            Self::update_collection_offer_status(conn, &data.order_hash, OrderStatus::Cancelled)
+                .await?;
         }
 
         Ok(())
     }
 
-    async fn get_token_price(token_address: &str) -> Result<f64, reqwest::Error> {
-        let client = Client::new();
-        let url = format!(
-            "https://starknet.impulse.avnu.fi/v1/tokens/prices?token={}",
-            token_address
-        );
-        let response = client
-            .get(&url)
-            .header("accept", "*/*")
-            .send()
-            .await?
-            .json::<Vec<TokenPrice>>()
+    /// Records that the `kind` event of `order_hash` in block `block_id` is
+    /// being applied. Returns `false` if it already was, in which case the
+    /// transaction must not write anything else.
+    ///
+    /// Diri's storage callbacks only carry the block, neither the transaction
+    /// hash nor the index of the event, so an event is identified by its
+    /// block, order hash and kind: an order goes through a given transition
+    /// once per block.
+    async fn claim_order_event(
+        conn: &mut PgConnection,
+        block_id: u64,
+        block_timestamp: u64,
+        order_hash: &str,
+        kind: OrderEventKind,
+    ) -> Result<bool, ProviderError> {
+        let query = "
+            INSERT INTO order_event_applied (block_number, order_hash, event_kind, block_timestamp)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (block_number, order_hash, event_kind) DO NOTHING;
+        ";
+
+        let result = sqlx::query(query)
+            .bind(block_id as i64)
+            .bind(order_hash)
+            .bind(kind.to_string())
+            .bind(block_timestamp as i64)
+            .execute(&mut *conn)
             .await?;
-        if let Some(token_price) = response.first() {
-            Ok(token_price.price_in_eth.unwrap())
-        } else {
-            Ok(0f64)
+
+        if result.rows_affected() == 0 {
+            trace!(
+                "{} event of order {} in block {} was already applied, skipping",
+                kind,
+                order_hash,
+                block_id
+            );
+            return Ok(false);
+        }
+
+        Ok(true)
+    }
+
+    /// Queues the webhooks of an applied event in its transaction. Webhook
+    /// failures are logged only, they must never stop the indexation, and
+    /// the savepoint keeps them from aborting the transaction.
+    async fn enqueue_webhooks(
+        conn: &mut PgConnection,
+        event_type: WebhookEventType,
+        order_hash: &str,
+        block_timestamp: u64,
+    ) -> Result<Vec<PendingDelivery>, ProviderError> {
+        let mut savepoint = conn.begin().await?;
+        match WebhookProvider::enqueue(&mut savepoint, event_type, order_hash, block_timestamp)
+            .await
+        {
+            Ok(deliveries) => {
+                savepoint.commit().await?;
+                Ok(deliveries)
+            }
+            Err(e) => {
+                savepoint.rollback().await?;
+                error!(
+                    "Failed to queue {:?} webhooks for order {}: {}",
+                    event_type, order_hash, e
+                );
+                Ok(vec![])
+            }
+        }
+    }
+
+    async fn clear_cache_of(
+        redis_conn: Arc<Mutex<MultiplexedConnection>>,
+        contract_address: Option<String>,
+    ) {
+        if let Some(contract_address) = contract_address {
+            if let Err(e) = Self::clear_tokens_cache(redis_conn, &contract_address).await {
+                println!("Error when deleting cache : {}", e);
+            }
+        }
+    }
+
+    /// Applies a placed order and queues its webhooks in a single
+    /// transaction. Returns `false` when the event had already been applied
+    /// and nothing was written.
+    pub async fn register_placed(
+        client: &SqlxCtxPg,
+        redis_conn: Arc<Mutex<MultiplexedConnection>>,
+        prices: &dyn PriceProvider,
+        provider: &JsonRpcClient<HttpTransport>,
+        block_id: u64,
+        block_timestamp: u64,
+        data: &PlacedData,
+    ) -> Result<bool, ProviderError> {
+        trace!("Registering placed order {:?}", data);
+        let event_prices = Self::resolve_event_prices(
+            client,
+            prices,
+            &data.order_hash,
+            Some(&data.currency_address),
+            block_timestamp,
+            false,
+        )
+        .await?;
+        let mut tx = client.pool.begin().await?;
+        if !Self::claim_order_event(
+            &mut tx,
+            block_id,
+            block_timestamp,
+            &data.order_hash,
+            OrderEventKind::Placed,
+        )
+        .await?
+        {
+            return Ok(false);
+        }
+
+        let updated_contract =
+            Self::apply_placed(&mut tx, &event_prices, provider, block_timestamp, data).await?;
+        let deliveries = Self::enqueue_webhooks(
+            &mut tx,
+            WebhookEventType::Placed,
+            &data.order_hash,
+            block_timestamp,
+        )
+        .await?;
+        tx.commit().await?;
+        WebhookProvider::deliver_committed(client, deliveries);
+
+        Self::clear_cache_of(redis_conn, updated_contract).await;
+        Ok(true)
+    }
+
+    pub async fn register_cancelled(
+        client: &SqlxCtxPg,
+        redis_conn: Arc<Mutex<MultiplexedConnection>>,
+        prices: &dyn PriceProvider,
+        block_id: u64,
+        block_timestamp: u64,
+        data: &CancelledData,
+    ) -> Result<bool, ProviderError> {
+        trace!("Registering cancelled order {:?}", data);
+        let event_prices = Self::resolve_event_prices(
+            client,
+            prices,
+            &data.order_hash,
+            None,
+            block_timestamp,
+            false,
+        )
+        .await?;
+        let mut tx = client.pool.begin().await?;
+        if !Self::claim_order_event(
+            &mut tx,
+            block_id,
+            block_timestamp,
+            &data.order_hash,
+            OrderEventKind::Cancelled,
+        )
+        .await?
+        {
+            return Ok(false);
+        }
+
+        let updated_contract =
+            Self::apply_cancelled(&mut tx, &event_prices, block_timestamp, data).await?;
+        let deliveries = Self::enqueue_webhooks(
+            &mut tx,
+            WebhookEventType::Cancelled,
+            &data.order_hash,
+            block_timestamp,
+        )
+        .await?;
+        tx.commit().await?;
+        WebhookProvider::deliver_committed(client, deliveries);
+
+        Self::clear_cache_of(redis_conn, updated_contract).await;
+        Ok(true)
+    }
+
+    pub async fn register_fulfilled(
+        client: &SqlxCtxPg,
+        redis_conn: Arc<Mutex<MultiplexedConnection>>,
+        prices: &dyn PriceProvider,
+        block_id: u64,
+        block_timestamp: u64,
+        data: &FulfilledData,
+    ) -> Result<bool, ProviderError> {
+        trace!("Registering fulfilled order {:?}", data);
+        let event_prices = Self::resolve_event_prices(
+            client,
+            prices,
+            &data.order_hash,
+            None,
+            block_timestamp,
+            false,
+        )
+        .await?;
+        let mut tx = client.pool.begin().await?;
+        if !Self::claim_order_event(
+            &mut tx,
+            block_id,
+            block_timestamp,
+            &data.order_hash,
+            OrderEventKind::Fulfilled,
+        )
+        .await?
+        {
+            return Ok(false);
+        }
+
+        let updated_contract =
+            Self::apply_fulfilled(&mut tx, &event_prices, block_timestamp, data).await?;
+        let deliveries = Self::enqueue_webhooks(
+            &mut tx,
+            WebhookEventType::Fulfilled,
+            &data.order_hash,
+            block_timestamp,
+        )
+        .await?;
+        tx.commit().await?;
+        WebhookProvider::deliver_committed(client, deliveries);
+
+        Self::clear_cache_of(redis_conn, updated_contract).await;
+        Ok(true)
+    }
+
+    pub async fn register_executed(
+        client: &SqlxCtxPg,
+        redis_conn: Arc<Mutex<MultiplexedConnection>>,
+        prices: &dyn PriceProvider,
+        block_id: u64,
+        block_timestamp: u64,
+        data: &ExecutedData,
+    ) -> Result<bool, ProviderError> {
+        trace!("Registering executed order {:?}", data);
+        let event_prices = Self::resolve_event_prices(
+            client,
+            prices,
+            &data.order_hash,
+            None,
+            block_timestamp,
+            true,
+        )
+        .await?;
+        let mut tx = client.pool.begin().await?;
+        if !Self::claim_order_event(
+            &mut tx,
+            block_id,
+            block_timestamp,
+            &data.order_hash,
+            OrderEventKind::Executed,
+        )
+        .await?
+        {
+            return Ok(false);
+        }
+
+        let updated_contract =
+            Self::apply_executed(&mut tx, &event_prices, block_timestamp, data).await?;
+        let deliveries = Self::enqueue_webhooks(
+            &mut tx,
+            WebhookEventType::Executed,
+            &data.order_hash,
+            block_timestamp,
+        )
+        .await?;
+        tx.commit().await?;
+        WebhookProvider::deliver_committed(client, deliveries);
+
+        Self::clear_cache_of(redis_conn, updated_contract).await;
+        Ok(true)
+    }
+
+    pub async fn status_back_to_open(
+        client: &SqlxCtxPg,
+        prices: &dyn PriceProvider,
+        block_id: u64,
+        block_timestamp: u64,
+        data: &RollbackStatusData,
+    ) -> Result<bool, ProviderError> {
+        trace!("Registering rollback of order {:?}", data);
+        let event_prices = Self::resolve_event_prices(
+            client,
+            prices,
+            &data.order_hash,
+            None,
+            block_timestamp,
+            false,
+        )
+        .await?;
+        let mut tx = client.pool.begin().await?;
+        if !Self::claim_order_event(
+            &mut tx,
+            block_id,
+            block_timestamp,
+            &data.order_hash,
+            OrderEventKind::Rollback,
+        )
+        .await?
+        {
+            return Ok(false);
         }
+
+        Self::apply_rollback(&mut tx, &event_prices, block_timestamp, data).await?;
+        let deliveries = Self::enqueue_webhooks(
+            &mut tx,
+            WebhookEventType::Rollback,
+            &data.order_hash,
+            block_timestamp,
+        )
+        .await?;
+        tx.commit().await?;
+        WebhookProvider::deliver_committed(client, deliveries);
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::marketplace::FixturePriceProvider;
+
+    const TIMESTAMP: i64 = 1_700_000_000;
+    const WEI: u128 = 1_000_000_000_000_000_000;
+
+    fn prices() -> FixturePriceProvider {
+        FixturePriceProvider::new()
+            .with_price(ETH_SYMBOL, TIMESTAMP, 2048.0)
+            .with_price("STRK", TIMESTAMP, 0.5)
+            .with_price("USDC", TIMESTAMP, 1.0)
+    }
+
+    fn hex(amount: u128) -> String {
+        format!("0x{:x}", amount)
+    }
+
+    #[test]
+    fn test_convert_to_wei() {
+        assert_eq!(
+            convert_to_wei(&hex(4 * WEI), 18, 0.25),
+            Some(WEI.to_string())
+        );
+        assert_eq!(convert_to_wei("0xinvalid", 18, 0.25), None);
+    }
+
+    async fn resolve(prices: &FixturePriceProvider, asset: &str, timestamp: i64) -> EventPrices {
+        EventPrices::resolve(
+            prices,
+            &[asset.to_string(), ETH_SYMBOL.to_string()],
+            timestamp,
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_convert_strk_amount_to_wei() {
+        let event_prices = resolve(&prices(), "STRK", TIMESTAMP).await;
+        let (eth_amount, usd_price) =
+            OrderProvider::convert_amount_to_wei(&event_prices, "STRK", 18, &hex(4096 * WEI));
+        assert_eq!(eth_amount, Some(WEI.to_string()));
+        assert_eq!(usd_price, Some(0.5));
+    }
+
+    #[tokio::test]
+    async fn test_convert_amount_with_decimals_to_wei() {
+        let event_prices = resolve(&prices(), "USDC", TIMESTAMP + 60).await;
+        let (eth_amount, _) =
+            OrderProvider::convert_amount_to_wei(&event_prices, "USDC", 6, &hex(2048 * 1_000_000));
+        assert_eq!(eth_amount, Some(WEI.to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_convert_amount_without_prices() {
+        let amount = hex(4096 * WEI);
+
+        let before_prices = resolve(&prices(), "STRK", TIMESTAMP - 1).await;
+        assert_eq!(
+            OrderProvider::convert_amount_to_wei(&before_prices, "STRK", 18, &amount),
+            (None, None)
+        );
+
+        // Assets that were not resolved before the transaction have no price
+        let other_asset = resolve(&prices(), "STRK", TIMESTAMP).await;
+        assert_eq!(
+            OrderProvider::convert_amount_to_wei(&other_asset, "LORDS", 18, &amount),
+            (None, None)
+        );
+
+        let without_eth = FixturePriceProvider::new().with_price("STRK", TIMESTAMP, 0.5);
+        let without_eth_price = resolve(&without_eth, "STRK", TIMESTAMP).await;
+        assert_eq!(
+            OrderProvider::convert_amount_to_wei(&without_eth_price, "STRK", 18, &amount),
+            (None, Some(0.5))
+        );
     }
 }
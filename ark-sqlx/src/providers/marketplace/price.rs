@@ -0,0 +1,140 @@
+use crate::providers::ProviderError;
+use async_trait::async_trait;
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::Duration;
+
+const PRICE_REQUEST_TIMEOUT_SECONDS: u64 = 10;
+// The price engine has one price per minute and per asset
+const PRICE_RESOLUTION_SECONDS: i64 = 60;
+// The candles of the last minutes may not be saved by the price engine yet
+const UNSETTLED_PRICE_SECONDS: i64 = 180;
+const MAX_CACHED_PRICES: usize = 10_000;
+
+/// USD prices of the currencies, by symbol, at a given time.
+#[async_trait]
+pub trait PriceProvider: Send + Sync {
+    /// USD price of one unit of `asset` at `timestamp`, in seconds. `None`
+    /// when no price is known at that time.
+    async fn usd_price(&self, asset: &str, timestamp: i64) -> Result<Option<f64>, ProviderError>;
+}
+
+#[derive(Deserialize)]
+struct PriceAtResponse {
+    price: f64,
+}
+
+/// Prices recorded by ark-price-engine, read from its `/at` route. Settled
+/// prices never change, so they are kept in memory to serve the many events
+/// of a block or of a reindexation with a single request.
+pub struct PriceEngineProvider {
+    client: Client,
+    base_url: String,
+    cache: Mutex<HashMap<(String, i64), f64>>,
+}
+
+impl PriceEngineProvider {
+    pub fn new(base_url: &str) -> Result<Self, ProviderError> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(PRICE_REQUEST_TIMEOUT_SECONDS))
+            .build()
+            .map_err(|e| ProviderError::PriceError(e.to_string()))?;
+
+        Ok(Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            cache: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn cached_price(&self, key: &(String, i64)) -> Option<f64> {
+        self.cache.lock().ok()?.get(key).copied()
+    }
+
+    fn cache_price(&self, key: (String, i64), price: f64) {
+        if let Ok(mut cache) = self.cache.lock() {
+            if cache.len() >= MAX_CACHED_PRICES {
+                cache.clear();
+            }
+            cache.insert(key, price);
+        }
+    }
+
+    async fn fetch_price(&self, asset: &str, timestamp: i64) -> Result<Option<f64>, ProviderError> {
+        let response = self
+            .client
+            .get(format!("{}/at", self.base_url))
+            .query(&[("asset", asset.to_string()), ("ts", timestamp.to_string())])
+            .send()
+            .await
+            .map_err(|e| ProviderError::PriceError(e.to_string()))?;
+
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => {
+                let body = response
+                    .json::<PriceAtResponse>()
+                    .await
+                    .map_err(|e| ProviderError::PriceError(e.to_string()))?;
+                Ok(Some(body.price))
+            }
+            status => Err(ProviderError::PriceError(format!(
+                "price engine returned {} for {} at {}",
+                status, asset, timestamp
+            ))),
+        }
+    }
+}
+
+#[async_trait]
+impl PriceProvider for PriceEngineProvider {
+    async fn usd_price(&self, asset: &str, timestamp: i64) -> Result<Option<f64>, ProviderError> {
+        let asset = asset.to_uppercase();
+        let minute = timestamp.div_euclid(PRICE_RESOLUTION_SECONDS) * PRICE_RESOLUTION_SECONDS;
+        let key = (asset, minute);
+        if let Some(price) = self.cached_price(&key) {
+            return Ok(Some(price));
+        }
+
+        let price = self.fetch_price(&key.0, timestamp).await?;
+        let settled = timestamp < chrono::Utc::now().timestamp() - UNSETTLED_PRICE_SECONDS;
+        if let (Some(price), true) = (price, settled) {
+            self.cache_price(key, price);
+        }
+        Ok(price)
+    }
+}
+
+/// Fixed prices, for tests and local setups without a price engine. The
+/// price of an asset at a time is the last one set at or before it.
+#[derive(Debug, Default, Clone)]
+pub struct FixturePriceProvider {
+    prices: HashMap<String, BTreeMap<i64, f64>>,
+}
+
+impl FixturePriceProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_price(mut self, asset: &str, timestamp: i64, price: f64) -> Self {
+        self.prices
+            .entry(asset.to_uppercase())
+            .or_default()
+            .insert(timestamp, price);
+        self
+    }
+}
+
+#[async_trait]
+impl PriceProvider for FixturePriceProvider {
+    async fn usd_price(&self, asset: &str, timestamp: i64) -> Result<Option<f64>, ProviderError> {
+        Ok(self
+            .prices
+            .get(&asset.to_uppercase())
+            .and_then(|prices| prices.range(..=timestamp).next_back())
+            .map(|(_, price)| *price))
+    }
+}
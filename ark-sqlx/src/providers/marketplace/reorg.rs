@@ -0,0 +1,259 @@
+use crate::providers::marketplace::types::{BURN_STR, MINT_STR, TRANSFER_STR};
+use crate::providers::marketplace::OrderProvider;
+use crate::providers::{ProviderError, SqlxCtxPg};
+use tracing::info;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedBlockHash {
+    pub block_number: u64,
+    pub block_hash: String,
+    pub parent_hash: String,
+    pub block_timestamp: u64,
+}
+
+#[derive(sqlx::FromRow)]
+struct IndexedBlockHashRow {
+    block_number: i64,
+    block_hash: String,
+    parent_hash: String,
+    block_timestamp: i64,
+}
+
+impl From<IndexedBlockHashRow> for IndexedBlockHash {
+    fn from(row: IndexedBlockHashRow) -> Self {
+        Self {
+            block_number: row.block_number as u64,
+            block_hash: row.block_hash,
+            parent_hash: row.parent_hash,
+            block_timestamp: row.block_timestamp as u64,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct AffectedToken {
+    contract_address: String,
+    token_id: String,
+}
+
+pub struct ReorgProvider {}
+
+impl ReorgProvider {
+    pub async fn get_block_hash(
+        client: &SqlxCtxPg,
+        chain_id: &str,
+        block_number: u64,
+    ) -> Result<Option<IndexedBlockHash>, ProviderError> {
+        let query = "
+            SELECT block_number, block_hash, parent_hash, block_timestamp
+            FROM indexer_block_hash
+            WHERE chain_id = $1 AND block_number = $2
+        ";
+
+        let row = sqlx::query_as::<_, IndexedBlockHashRow>(query)
+            .bind(chain_id)
+            .bind(block_number as i64)
+            .fetch_optional(&client.pool)
+            .await?;
+
+        Ok(row.map(IndexedBlockHash::from))
+    }
+
+    pub async fn insert_block_hash(
+        client: &SqlxCtxPg,
+        chain_id: &str,
+        block: &IndexedBlockHash,
+    ) -> Result<(), ProviderError> {
+        let query = "
+            INSERT INTO indexer_block_hash (chain_id, block_number, block_hash, parent_hash, block_timestamp)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (chain_id, block_number) DO UPDATE SET
+                block_hash = EXCLUDED.block_hash,
+                parent_hash = EXCLUDED.parent_hash,
+                block_timestamp = EXCLUDED.block_timestamp,
+                indexed_timestamp = EXTRACT(EPOCH FROM NOW())::BIGINT
+        ";
+
+        sqlx::query(query)
+            .bind(chain_id)
+            .bind(block.block_number as i64)
+            .bind(&block.block_hash)
+            .bind(&block.parent_hash)
+            .bind(block.block_timestamp as i64)
+            .execute(&client.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Only the last blocks are needed to find a common ancestor, older
+    /// hashes are dropped.
+    pub async fn prune_block_hashes(
+        client: &SqlxCtxPg,
+        chain_id: &str,
+        before_block: u64,
+    ) -> Result<(), ProviderError> {
+        sqlx::query("DELETE FROM indexer_block_hash WHERE chain_id = $1 AND block_number < $2")
+            .bind(chain_id)
+            .bind(before_block as i64)
+            .execute(&client.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Removes what Sana indexed from `block_number` (included) so that the
+    /// blocks of the canonical chain can be indexed again. Token events carry
+    /// no block number, the orphaned ones are found from the timestamps
+    /// recorded for the reverted blocks.
+    ///
+    /// Only the Mint, Transfer and Burn events are deleted: orderbook events,
+    /// offers and listings are written by Diri and are not recreated by the
+    /// Sana reindex. Token ownership is restored from the remaining transfer
+    /// events. The applied order event markers are left alone: they are keyed
+    /// by arkchain blocks, which a Starknet reorg doesn't revert.
+    pub async fn revert_from_block(
+        client: &SqlxCtxPg,
+        chain_id: &str,
+        block_number: u64,
+    ) -> Result<(), ProviderError> {
+        let mut tx = client.pool.begin().await?;
+
+        let orphaned_timestamp: Option<i64> = sqlx::query_scalar(
+            "
+            SELECT MIN(block_timestamp) FROM (
+                SELECT block_timestamp FROM indexer_block_hash
+                WHERE chain_id = $1 AND block_number >= $2
+                UNION ALL
+                SELECT block_timestamp FROM block WHERE block_number >= $2
+            ) orphaned
+            ",
+        )
+        .bind(chain_id)
+        .bind(block_number as i64)
+        .fetch_one(&mut *tx)
+        .await?;
+        // Nothing was recorded for these blocks, so Sana indexed nothing there
+        let block_timestamp = orphaned_timestamp.unwrap_or(i64::MAX);
+
+        let affected_tokens = sqlx::query_as::<_, AffectedToken>(
+            "
+            SELECT DISTINCT contract_address, token_id
+            FROM token_event
+            WHERE chain_id = $1 AND block_timestamp >= $2 AND event_type IN ($3, $4, $5)
+            ",
+        )
+        .bind(chain_id)
+        .bind(block_timestamp)
+        .bind(MINT_STR)
+        .bind(BURN_STR)
+        .bind(TRANSFER_STR)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let deleted_events = sqlx::query(
+            "
+            DELETE FROM token_event
+            WHERE chain_id = $1 AND block_timestamp >= $2 AND event_type IN ($3, $4, $5)
+            ",
+        )
+        .bind(chain_id)
+        .bind(block_timestamp)
+        .bind(MINT_STR)
+        .bind(BURN_STR)
+        .bind(TRANSFER_STR)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        let (contract_addresses, token_ids): (Vec<String>, Vec<String>) = affected_tokens
+            .into_iter()
+            .map(|token| (token.contract_address, token.token_id))
+            .unzip();
+
+        let restore_owner_query = "
+            UPDATE token t
+            SET
+                current_owner = last_transfer.to_address,
+                held_timestamp = last_transfer.block_timestamp,
+                is_burned = last_transfer.event_type = $5,
+                updated_timestamp = EXTRACT(EPOCH FROM NOW())::BIGINT
+            FROM (
+                SELECT DISTINCT ON (te.contract_address, te.token_id)
+                    te.contract_address, te.token_id, te.to_address, te.block_timestamp, te.event_type
+                FROM token_event te
+                JOIN UNNEST($2::text[], $3::text[]) AS affected(contract_address, token_id)
+                    ON te.contract_address = affected.contract_address
+                    AND te.token_id = affected.token_id
+                WHERE te.chain_id = $1 AND te.event_type IN ($4, $5, $6)
+                ORDER BY te.contract_address, te.token_id, te.block_timestamp DESC
+            ) last_transfer
+            WHERE t.contract_address = last_transfer.contract_address
+              AND t.chain_id = $1
+              AND t.token_id = last_transfer.token_id
+        ";
+
+        sqlx::query(restore_owner_query)
+            .bind(chain_id)
+            .bind(&contract_addresses)
+            .bind(&token_ids)
+            .bind(MINT_STR)
+            .bind(BURN_STR)
+            .bind(TRANSFER_STR)
+            .execute(&mut *tx)
+            .await?;
+
+        // Tokens minted in an orphaned block have no history left
+        let delete_tokens_query = "
+            DELETE FROM token
+            WHERE chain_id = $1
+              AND block_timestamp >= $2
+              AND NOT EXISTS (
+                  SELECT 1 FROM token_event te
+                  WHERE te.contract_address = token.contract_address
+                    AND te.chain_id = token.chain_id
+                    AND te.token_id = token.token_id
+              )
+              AND NOT EXISTS (
+                  SELECT 1 FROM token_offer o
+                  WHERE o.contract_address = token.contract_address
+                    AND o.chain_id = token.chain_id
+                    AND o.token_id = token.token_id
+              )
+        ";
+
+        sqlx::query(delete_tokens_query)
+            .bind(chain_id)
+            .bind(block_timestamp)
+            .execute(&mut *tx)
+            .await?;
+
+        // Sana skips the blocks it already knows when force mode is disabled
+        sqlx::query("DELETE FROM block WHERE block_number >= $1")
+            .bind(block_number as i64)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("DELETE FROM indexer_block_hash WHERE chain_id = $1 AND block_number >= $2")
+            .bind(chain_id)
+            .bind(block_number as i64)
+            .execute(&mut *tx)
+            .await?;
+
+        let mut contract_addresses = contract_addresses;
+        contract_addresses.sort();
+        contract_addresses.dedup();
+        for contract_address in contract_addresses {
+            OrderProvider::recalculate_floor_price(&mut tx, &contract_address, chain_id).await?;
+        }
+
+        tx.commit().await?;
+
+        info!(
+            "Reverted {} token events from block {}",
+            deleted_events, block_number
+        );
+
+        Ok(())
+    }
+}
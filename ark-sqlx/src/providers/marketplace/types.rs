@@ -105,3 +105,46 @@ impl TokenEventType {
         }
     }
 }
+
+/// Order lifecycle events that can be delivered to webhook subscribers.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum WebhookEventType {
+    Placed,
+    Cancelled,
+    Fulfilled,
+    Executed,
+    Rollback,
+}
+
+pub const WEBHOOK_PLACED_STR: &str = "PLACED";
+pub const WEBHOOK_CANCELLED_STR: &str = "CANCELLED";
+pub const WEBHOOK_FULFILLED_STR: &str = "FULFILLED";
+pub const WEBHOOK_EXECUTED_STR: &str = "EXECUTED";
+pub const WEBHOOK_ROLLBACK_STR: &str = "ROLLBACK";
+
+impl WebhookEventType {
+    pub fn to_db_string(&self) -> String {
+        match self {
+            WebhookEventType::Placed => WEBHOOK_PLACED_STR.to_string(),
+            WebhookEventType::Cancelled => WEBHOOK_CANCELLED_STR.to_string(),
+            WebhookEventType::Fulfilled => WEBHOOK_FULFILLED_STR.to_string(),
+            WebhookEventType::Executed => WEBHOOK_EXECUTED_STR.to_string(),
+            WebhookEventType::Rollback => WEBHOOK_ROLLBACK_STR.to_string(),
+        }
+    }
+}
+
+impl std::str::FromStr for WebhookEventType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            WEBHOOK_PLACED_STR => Ok(WebhookEventType::Placed),
+            WEBHOOK_CANCELLED_STR => Ok(WebhookEventType::Cancelled),
+            WEBHOOK_FULFILLED_STR => Ok(WebhookEventType::Fulfilled),
+            WEBHOOK_EXECUTED_STR => Ok(WebhookEventType::Executed),
+            WEBHOOK_ROLLBACK_STR => Ok(WebhookEventType::Rollback),
+            _ => Err(format!("Invalid webhook event type: {}", s)),
+        }
+    }
+}
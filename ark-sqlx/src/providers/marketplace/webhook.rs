@@ -0,0 +1,344 @@
+use crate::providers::marketplace::types::WebhookEventType;
+use crate::providers::{ProviderError, SqlxCtxPg};
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use serde::Serialize;
+use sha2::Sha256;
+use sqlx::{PgConnection, PgPool};
+use std::time::Duration;
+use tracing::{error, trace, warn};
+
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "X-Ark-Signature";
+pub const WEBHOOK_TIMESTAMP_HEADER: &str = "X-Ark-Timestamp";
+const WEBHOOK_MAX_ATTEMPTS: u32 = 5;
+const WEBHOOK_INITIAL_BACKOFF_SECONDS: u64 = 2;
+const WEBHOOK_REQUEST_TIMEOUT_SECONDS: u64 = 10;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookPayload {
+    pub event_type: String,
+    pub order_hash: String,
+    pub contract_address: String,
+    pub chain_id: String,
+    pub token_id: Option<String>,
+    pub block_timestamp: i64,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct OrderTarget {
+    contract_address: String,
+    chain_id: String,
+    token_id: Option<String>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct WebhookSubscription {
+    webhook_subscription_id: i32,
+}
+
+/// A delivery persisted in `webhook_delivery` until it succeeds or is moved to
+/// the dead letters, so that retries survive an indexer restart.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct PendingDelivery {
+    webhook_delivery_id: i32,
+    webhook_subscription_id: i32,
+    url: String,
+    secret: String,
+    order_hash: String,
+    event_type: String,
+    body: String,
+    attempts: i32,
+    last_error: Option<String>,
+}
+
+pub struct WebhookProvider {}
+
+impl WebhookProvider {
+    /// Queues `event_type` of the given order for every active subscription
+    /// of its collection, in the transaction of the order event so that the
+    /// deliveries are committed with it. The returned deliveries are sent by
+    /// `deliver_committed` once the transaction is committed.
+    pub async fn enqueue(
+        conn: &mut PgConnection,
+        event_type: WebhookEventType,
+        order_hash: &str,
+        block_timestamp: u64,
+    ) -> Result<Vec<PendingDelivery>, ProviderError> {
+        let Some(target) = Self::get_order_target(conn, order_hash).await? else {
+            trace!(
+                "No token event found for order {}, skipping webhooks",
+                order_hash
+            );
+            return Ok(vec![]);
+        };
+
+        let subscriptions = Self::get_subscriptions(conn, &target, event_type).await?;
+        if subscriptions.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let payload = WebhookPayload {
+            event_type: event_type.to_db_string(),
+            order_hash: order_hash.to_string(),
+            contract_address: target.contract_address,
+            chain_id: target.chain_id,
+            token_id: target.token_id,
+            block_timestamp: block_timestamp as i64,
+        };
+        let body = serde_json::to_string(&payload)
+            .map_err(|e| ProviderError::ParsingError(e.to_string()))?;
+
+        let subscription_ids: Vec<i32> = subscriptions
+            .iter()
+            .map(|s| s.webhook_subscription_id)
+            .collect();
+        let deliveries = Self::insert_deliveries(conn, &subscription_ids, &payload, &body).await?;
+
+        Ok(deliveries)
+    }
+
+    /// Sends committed deliveries in the background so that the indexer is
+    /// never slowed down by a subscriber. Deliveries lost by a crash before
+    /// they succeed are picked up by `resume_pending`.
+    pub fn deliver_committed(client: &SqlxCtxPg, deliveries: Vec<PendingDelivery>) {
+        Self::spawn_deliveries(&client.pool, deliveries);
+    }
+
+    /// Restarts the deliveries left pending by a previous run. Deliveries of
+    /// the subscriptions deactivated since then are not sent.
+    pub async fn resume_pending(client: &SqlxCtxPg) -> Result<(), ProviderError> {
+        let query = "
+            SELECT d.webhook_delivery_id, d.webhook_subscription_id, s.url, s.secret,
+                   d.order_hash, d.event_type, d.payload::text AS body, d.attempts, d.last_error
+            FROM webhook_delivery d
+            INNER JOIN webhook_subscription s ON s.webhook_subscription_id = d.webhook_subscription_id
+            WHERE s.is_active = true
+            ORDER BY d.webhook_delivery_id
+        ";
+
+        let deliveries = sqlx::query_as::<_, PendingDelivery>(query)
+            .fetch_all(&client.pool)
+            .await?;
+        if !deliveries.is_empty() {
+            trace!("Resuming {} pending webhook deliveries", deliveries.len());
+        }
+        Self::spawn_deliveries(&client.pool, deliveries);
+
+        Ok(())
+    }
+
+    /// Hex encoded HMAC-SHA256 of `{timestamp}.{body}`, subscribers recompute it
+    /// with their secret to authenticate the delivery.
+    pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+            .expect("HMAC can take key of any size");
+        mac.update(format!("{}.{}", timestamp, body).as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    fn spawn_deliveries(pool: &PgPool, deliveries: Vec<PendingDelivery>) {
+        for delivery in deliveries {
+            let pool = pool.clone();
+            tokio::spawn(async move {
+                Self::deliver(&pool, delivery).await;
+            });
+        }
+    }
+
+    async fn get_order_target(
+        conn: &mut PgConnection,
+        order_hash: &str,
+    ) -> Result<Option<OrderTarget>, sqlx::Error> {
+        let query = "
+            SELECT contract_address, chain_id, NULLIF(token_id, '') AS token_id
+            FROM token_event
+            WHERE order_hash = $1
+            ORDER BY block_timestamp DESC
+            LIMIT 1
+        ";
+
+        sqlx::query_as::<_, OrderTarget>(query)
+            .bind(order_hash)
+            .fetch_optional(&mut *conn)
+            .await
+    }
+
+    async fn get_subscriptions(
+        conn: &mut PgConnection,
+        target: &OrderTarget,
+        event_type: WebhookEventType,
+    ) -> Result<Vec<WebhookSubscription>, sqlx::Error> {
+        let query = "
+            SELECT webhook_subscription_id
+            FROM webhook_subscription
+            WHERE is_active = true
+              AND contract_address = $1
+              AND chain_id = $2
+              AND (cardinality(event_types) = 0 OR $3 = ANY(event_types))
+        ";
+
+        sqlx::query_as::<_, WebhookSubscription>(query)
+            .bind(&target.contract_address)
+            .bind(&target.chain_id)
+            .bind(event_type.to_db_string())
+            .fetch_all(&mut *conn)
+            .await
+    }
+
+    async fn insert_deliveries(
+        conn: &mut PgConnection,
+        subscription_ids: &[i32],
+        payload: &WebhookPayload,
+        body: &str,
+    ) -> Result<Vec<PendingDelivery>, sqlx::Error> {
+        let query = "
+            WITH inserted AS (
+                INSERT INTO webhook_delivery (webhook_subscription_id, event_type, order_hash, payload)
+                SELECT subscription_id, $2, $3, $4::text::jsonb
+                FROM UNNEST($1::int[]) AS subscription_id
+                RETURNING webhook_delivery_id, webhook_subscription_id, order_hash, event_type, attempts, last_error
+            )
+            SELECT i.webhook_delivery_id, i.webhook_subscription_id, s.url, s.secret,
+                   i.order_hash, i.event_type, $4::text AS body, i.attempts, i.last_error
+            FROM inserted i
+            INNER JOIN webhook_subscription s ON s.webhook_subscription_id = i.webhook_subscription_id
+        ";
+
+        sqlx::query_as::<_, PendingDelivery>(query)
+            .bind(subscription_ids)
+            .bind(&payload.event_type)
+            .bind(&payload.order_hash)
+            .bind(body)
+            .fetch_all(&mut *conn)
+            .await
+    }
+
+    async fn deliver(pool: &PgPool, delivery: PendingDelivery) {
+        let http_client = match Client::builder()
+            .timeout(Duration::from_secs(WEBHOOK_REQUEST_TIMEOUT_SECONDS))
+            .build()
+        {
+            Ok(http_client) => http_client,
+            Err(e) => {
+                error!("Failed to build webhook HTTP client: {}", e);
+                return;
+            }
+        };
+
+        let mut last_error = delivery.last_error.clone().unwrap_or_default();
+        let first_attempt = delivery.attempts.max(0) as u32 + 1;
+        for attempt in first_attempt..=WEBHOOK_MAX_ATTEMPTS {
+            let timestamp = chrono::Utc::now().timestamp();
+            let signature = Self::sign(&delivery.secret, timestamp, &delivery.body);
+
+            let result = http_client
+                .post(&delivery.url)
+                .header("Content-Type", "application/json")
+                .header(WEBHOOK_TIMESTAMP_HEADER, timestamp.to_string())
+                .header(WEBHOOK_SIGNATURE_HEADER, format!("sha256={}", signature))
+                .body(delivery.body.clone())
+                .send()
+                .await;
+
+            match result {
+                Ok(response) if response.status().is_success() => {
+                    trace!(
+                        "Webhook {} delivered for order {}",
+                        delivery.webhook_subscription_id,
+                        delivery.order_hash
+                    );
+                    if let Err(e) = Self::delete_delivery(pool, delivery.webhook_delivery_id).await
+                    {
+                        error!(
+                            "Failed to clear delivered webhook {}: {}",
+                            delivery.webhook_delivery_id, e
+                        );
+                    }
+                    return;
+                }
+                Ok(response) => last_error = format!("HTTP status {}", response.status()),
+                Err(e) => last_error = e.to_string(),
+            }
+
+            warn!(
+                "Webhook {} delivery attempt {}/{} failed: {}",
+                delivery.webhook_subscription_id, attempt, WEBHOOK_MAX_ATTEMPTS, last_error
+            );
+
+            if let Err(e) = Self::record_attempt(pool, &delivery, attempt, &last_error).await {
+                error!(
+                    "Failed to record attempt for webhook delivery {}: {}",
+                    delivery.webhook_delivery_id, e
+                );
+            }
+
+            if attempt < WEBHOOK_MAX_ATTEMPTS {
+                let backoff = WEBHOOK_INITIAL_BACKOFF_SECONDS * 2u64.pow(attempt - 1);
+                tokio::time::sleep(Duration::from_secs(backoff)).await;
+            }
+        }
+
+        if let Err(e) = Self::move_to_dead_letter(pool, &delivery, &last_error).await {
+            error!(
+                "Failed to record dead letter for webhook {}: {}",
+                delivery.webhook_subscription_id, e
+            );
+        }
+    }
+
+    async fn record_attempt(
+        pool: &PgPool,
+        delivery: &PendingDelivery,
+        attempt: u32,
+        last_error: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE webhook_delivery SET attempts = $2, last_error = $3 WHERE webhook_delivery_id = $1",
+        )
+        .bind(delivery.webhook_delivery_id)
+        .bind(attempt as i32)
+        .bind(last_error)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete_delivery(pool: &PgPool, webhook_delivery_id: i32) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM webhook_delivery WHERE webhook_delivery_id = $1")
+            .bind(webhook_delivery_id)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn move_to_dead_letter(
+        pool: &PgPool,
+        delivery: &PendingDelivery,
+        last_error: &str,
+    ) -> Result<(), sqlx::Error> {
+        let query = "
+            INSERT INTO webhook_dead_letter (webhook_subscription_id, event_type, order_hash, payload, attempts, last_error)
+            VALUES ($1, $2, $3, $4::jsonb, $5, $6);
+        ";
+
+        let mut tx = pool.begin().await?;
+        sqlx::query(query)
+            .bind(delivery.webhook_subscription_id)
+            .bind(&delivery.event_type)
+            .bind(&delivery.order_hash)
+            .bind(&delivery.body)
+            .bind(WEBHOOK_MAX_ATTEMPTS as i32)
+            .bind(last_error)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM webhook_delivery WHERE webhook_delivery_id = $1")
+            .bind(delivery.webhook_delivery_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
@@ -21,14 +21,7 @@ pub struct LambdaUsageData {
 
 impl LambdaUsageData {
     pub fn params_to_string(&self) -> String {
-        let mut s = String::from("{");
-
-        for (key, value) in &self.params {
-            s.push_str(&format!("\"{}\": \"{}\"", key, value));
-        }
-
-        s.push('}');
-        s
+        serde_json::to_string(&self.params).unwrap_or_else(|_| String::from("{}"))
     }
 }
 
@@ -1,4 +1,5 @@
 use crate::providers::marketplace::OrderProvider as MarketplaceOrderProvider;
+use crate::providers::marketplace::{PriceEngineProvider, PriceProvider, WebhookProvider};
 use crate::providers::orderbook::OrderProvider;
 use arkproject::diri::storage::types::{
     CancelledData, ExecutedData, FulfilledData, PlacedData, RollbackStatusData,
@@ -20,7 +21,7 @@ use std::error::Error;
 use std::future::Future;
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use tracing::error;
+use tracing::{error, trace};
 
 pub mod marketplace;
 pub mod metrics;
@@ -83,6 +84,8 @@ pub enum ProviderError {
     PaginationCacheError(String),
     #[error("Parsing error: {0}")]
     ParsingError(String),
+    #[error("Price error: {0}")]
+    PriceError(String),
 }
 
 impl From<SqlxError> for ProviderError {
@@ -176,6 +179,7 @@ pub struct SqlxMarketplaceProvider {
     client: SqlxCtxPg,
     redis_conn: Arc<Mutex<MultiplexedConnection>>,
     provider: JsonRpcClient<HttpTransport>,
+    prices: Box<dyn PriceProvider>,
 }
 
 pub trait ContractProvider {
@@ -239,7 +243,17 @@ impl ContractProvider for JsonRpcClient<HttpTransport> {
 }
 
 impl SqlxMarketplaceProvider {
+    /// Prices the events with ark-price-engine, at `PRICE_ENGINE_URL`.
     pub async fn new(sqlx_conn_str: &str) -> Result<Self, ProviderError> {
+        let price_engine_url = std::env::var("PRICE_ENGINE_URL").expect("PRICE_ENGINE_URL not set");
+        let prices = Box::new(PriceEngineProvider::new(&price_engine_url)?);
+        Self::with_price_provider(sqlx_conn_str, prices).await
+    }
+
+    pub async fn with_price_provider(
+        sqlx_conn_str: &str,
+        prices: Box<dyn PriceProvider>,
+    ) -> Result<Self, ProviderError> {
         let redis_conn = match connect_redis().await {
             Ok(con) => con,
             Err(e) => {
@@ -254,12 +268,34 @@ impl SqlxMarketplaceProvider {
         let provider = JsonRpcClient::new(HttpTransport::new(Url::parse(&rpc_url).unwrap()));
         let sqlx = SqlxCtxPg::new(sqlx_conn_str).await?;
 
+        if let Err(e) = WebhookProvider::resume_pending(&sqlx).await {
+            error!("Failed to resume pending webhook deliveries: {}", e);
+        }
+
         Ok(Self {
             client: sqlx,
             redis_conn,
             provider,
+            prices,
         })
     }
+
+    /// Records the sales of collection offers that were waiting for the
+    /// transfer of their token. Failures are logged only, the sales stay
+    /// pending until the next call.
+    pub async fn resolve_pending_sales(&self) {
+        match MarketplaceOrderProvider::resolve_pending_collection_offer_sales(
+            &self.client,
+            self.redis_conn.clone(),
+            self.prices.as_ref(),
+        )
+        .await
+        {
+            Ok(0) => {}
+            Ok(resolved) => trace!("Recorded {} pending collection offer sales", resolved),
+            Err(e) => error!("Failed to resolve pending collection offer sales: {}", e),
+        }
+    }
 }
 
 #[async_trait]
@@ -270,15 +306,17 @@ impl Storage for SqlxMarketplaceProvider {
         block_timestamp: u64,
         data: &PlacedData,
     ) -> StorageResult<()> {
-        Ok(MarketplaceOrderProvider::register_placed(
+        MarketplaceOrderProvider::register_placed(
             &self.client,
             self.redis_conn.clone(),
+            self.prices.as_ref(),
             &self.provider,
             block_id,
             block_timestamp,
             data,
         )
-        .await?)
+        .await?;
+        Ok(())
     }
 
     async fn register_cancelled(
@@ -287,14 +325,16 @@ impl Storage for SqlxMarketplaceProvider {
         block_timestamp: u64,
         data: &CancelledData,
     ) -> StorageResult<()> {
-        Ok(MarketplaceOrderProvider::register_cancelled(
+        MarketplaceOrderProvider::register_cancelled(
             &self.client,
             self.redis_conn.clone(),
+            self.prices.as_ref(),
             block_id,
             block_timestamp,
             data,
         )
-        .await?)
+        .await?;
+        Ok(())
     }
 
     async fn register_fulfilled(
@@ -303,14 +343,16 @@ impl Storage for SqlxMarketplaceProvider {
         block_timestamp: u64,
         data: &FulfilledData,
     ) -> StorageResult<()> {
-        Ok(MarketplaceOrderProvider::register_fulfilled(
+        MarketplaceOrderProvider::register_fulfilled(
             &self.client,
             self.redis_conn.clone(),
+            self.prices.as_ref(),
             block_id,
             block_timestamp,
             data,
         )
-        .await?)
+        .await?;
+        Ok(())
     }
 
     async fn register_executed(
@@ -319,14 +361,16 @@ impl Storage for SqlxMarketplaceProvider {
         block_timestamp: u64,
         data: &ExecutedData,
     ) -> StorageResult<()> {
-        Ok(MarketplaceOrderProvider::register_executed(
+        MarketplaceOrderProvider::register_executed(
             &self.client,
             self.redis_conn.clone(),
+            self.prices.as_ref(),
             block_id,
             block_timestamp,
             data,
         )
-        .await?)
+        .await?;
+        Ok(())
     }
 
     async fn status_back_to_open(
@@ -335,12 +379,14 @@ impl Storage for SqlxMarketplaceProvider {
         block_timestamp: u64,
         data: &RollbackStatusData,
     ) -> StorageResult<()> {
-        Ok(MarketplaceOrderProvider::status_back_to_open(
+        MarketplaceOrderProvider::status_back_to_open(
             &self.client,
+            self.prices.as_ref(),
             block_id,
             block_timestamp,
             data,
         )
-        .await?)
+        .await?;
+        Ok(())
     }
 }
@@ -1,15 +1,17 @@
 use anyhow::Result;
-use ark_sqlx::providers::SqlxMarketplaceProvider;
+use ark_sqlx::providers::marketplace::CheckpointProvider;
+use ark_sqlx::providers::{SqlxCtxPg, SqlxMarketplaceProvider};
 use arkproject::diri::{event_handler::EventHandler, Diri};
 use async_trait::async_trait;
 use aws_config::BehaviorVersion;
+use clap::{App, Arg};
 use dotenv::dotenv;
 use serde::Deserialize;
 use starknet::{
     core::types::BlockId,
     providers::{jsonrpc::HttpTransport, AnyProvider, JsonRpcClient, Provider},
 };
-use std::{env, fs, path::Path, sync::Arc};
+use std::{env, sync::Arc};
 use tracing::{error, info, trace, warn};
 use tracing_subscriber::fmt;
 use tracing_subscriber::EnvFilter;
@@ -24,37 +26,51 @@ struct DatabaseCredentials {
     host: String,
 }
 
-#[derive(Debug)]
+const DEFAULT_INDEXER_IDENTIFIER: &str = "arkchain-indexer-marketplace";
+
+/// Progress of the indexer, stored in Postgres so that it survives the
+/// replacement of the task.
 struct Checkpoint {
-    file_path: String,
-    current_block: u64,
+    client: SqlxCtxPg,
+    indexer_identifier: String,
 }
 
 impl Checkpoint {
-    async fn new(file_path: String) -> Result<Self> {
-        let current_block = if Path::new(&file_path).exists() {
-            let contents = fs::read_to_string(&file_path)?;
-            contents.trim().parse::<u64>()?
-        } else {
-            fs::write(&file_path, "0")?;
-            trace!("Created new checkpoint file at {}", file_path);
-            0
-        };
+    fn new(client: SqlxCtxPg, indexer_identifier: String) -> Self {
+        Checkpoint {
+            client,
+            indexer_identifier,
+        }
+    }
 
-        Ok(Checkpoint {
-            file_path,
-            current_block,
-        })
+    /// First block that still has to be indexed.
+    async fn get_next_block(&self) -> Result<u64> {
+        let checkpoint =
+            CheckpointProvider::get_checkpoint(&self.client, &self.indexer_identifier).await?;
+        Ok(checkpoint.map_or(0, |block_number| block_number + 1))
     }
 
     async fn save(&self, block_number: u64) -> Result<()> {
-        fs::write(&self.file_path, block_number.to_string())?;
+        CheckpointProvider::save_checkpoint(
+            &self.client.pool,
+            &self.indexer_identifier,
+            block_number,
+        )
+        .await?;
         trace!("Saved checkpoint: block {}", block_number);
         Ok(())
     }
 
-    fn get_block(&self) -> u64 {
-        self.current_block
+    /// Makes the indexer start again from `from_block`.
+    async fn reset(&self, from_block: u64) -> Result<()> {
+        match from_block.checked_sub(1) {
+            Some(block_number) => self.save(block_number).await,
+            None => {
+                CheckpointProvider::delete_checkpoint(&self.client, &self.indexer_identifier)
+                    .await?;
+                Ok(())
+            }
+        }
     }
 }
 
@@ -90,6 +106,31 @@ async fn main() -> Result<()> {
     init_logging();
     trace!("Starting...");
 
+    let matches = App::new("arkchain-indexer-marketplace")
+        .arg(
+            Arg::with_name("indexer-id")
+                .long("indexer-id")
+                .takes_value(true)
+                .help("Identifier the checkpoint is stored under, defaults to INDEXER_IDENTIFIER"),
+        )
+        .arg(
+            Arg::with_name("from-block")
+                .long("from-block")
+                .takes_value(true)
+                .help("Resets the checkpoint and starts indexing from this block"),
+        )
+        .get_matches();
+
+    let indexer_identifier = matches
+        .value_of("indexer-id")
+        .map(String::from)
+        .or_else(|| env::var("INDEXER_IDENTIFIER").ok())
+        .unwrap_or_else(|| DEFAULT_INDEXER_IDENTIFIER.to_string());
+    let from_block = matches
+        .value_of("from-block")
+        .map(|value| value.parse::<u64>())
+        .transpose()?;
+
     let rpc_url = env::var("ARKCHAIN_RPC_PROVIDER").expect("ARKCHAIN_RPC_PROVIDER must be set");
     let rpc_url_converted = Url::parse(&rpc_url).unwrap();
 
@@ -99,29 +140,43 @@ async fn main() -> Result<()> {
         HttpTransport::new(rpc_url_converted.clone()),
     )));
 
-    let storage = SqlxMarketplaceProvider::new(&database_uri).await?;
+    let storage = Arc::new(SqlxMarketplaceProvider::new(&database_uri).await?);
     let handler = DefaultEventHandler {};
 
     let indexer = Arc::new(Diri::new(
         provider.clone(),
-        Arc::new(storage),
+        storage.clone(),
         Arc::new(handler),
     ));
 
     let sleep_secs = 1;
-    let checkpoint = Checkpoint::new("checkpoint.txt".to_string()).await?;
-    let mut from = checkpoint.get_block();
+    let checkpoint = Checkpoint::new(
+        SqlxCtxPg::new(&database_uri).await?,
+        indexer_identifier.clone(),
+    );
+    if let Some(from_block) = from_block {
+        warn!(
+            "Resetting checkpoint of {} to block {}",
+            indexer_identifier, from_block
+        );
+        checkpoint.reset(from_block).await?;
+    }
+    let mut from = checkpoint.get_next_block().await?;
     let range = 1;
 
     // Set to None to keep polling the head of chain.
     let to = None;
 
     info!(
-        "Starting arkchain indexer: from:{} to:{:?} range:{}",
-        from, to, range,
+        "Starting arkchain indexer {}: from:{} to:{:?} range:{}",
+        indexer_identifier, from, to, range,
     );
 
     loop {
+        // Sales of collection offers wait for the transfer indexed by Sana,
+        // which can land at any time, so they are retried on every pass.
+        storage.resolve_pending_sales().await;
+
         let latest_block = match provider.block_number().await {
             Ok(block_number) => block_number,
             Err(e) => {
@@ -155,35 +210,38 @@ async fn main() -> Result<()> {
             Ok(_) => {
                 trace!("Blocks successfully indexed");
 
+                // Known limitation: the checkpoint is not written in the
+                // transactions of the block. Diri applies each event through
+                // `Storage` in its own transaction and doesn't expose one for
+                // the whole block, so a crash before this point leaves the
+                // events of the range committed and the checkpoint behind.
+                // The range is then indexed again on restart, and the
+                // `order_event_applied` markers turn the events already
+                // applied into no-ops.
+                //
+                // A checkpoint that can't be saved stops the indexer: going
+                // on would resume from a stale block after the next restart.
+                if let Err(e) = checkpoint.save(end).await {
+                    error!("Failed to save checkpoint at block {}: {}", end, e);
+                    return Err(e);
+                }
+
                 if let Some(to) = to {
                     if end >= to {
-                        // Save final checkpoint before exiting
-                        if let Err(e) = checkpoint.save(end).await {
-                            warn!("Failed to save final checkpoint: {}", e);
-                        }
                         trace!("`to` block was reached, exit.");
                         return Ok(());
                     }
                 }
 
-                // Save the checkpoint after successful indexing
-                if let Err(e) = checkpoint.save(end).await {
-                    warn!("Failed to save checkpoint: {}", e);
-                }
-
                 // +1 to not re-index the end block.
                 from = end + 1;
             }
             Err(e) => {
                 error!("Blocks indexing error: {}", e);
 
-                // Save checkpoint even on error to track progress
-                if let Err(ce) = checkpoint.save(end).await {
-                    warn!("Failed to save checkpoint after error: {}", ce);
-                }
-
-                warn!("Skipping blocks range: {} - {}", start, end);
-                from = end + 1;
+                // The checkpoint is left untouched so that the range is
+                // indexed again instead of being skipped.
+                warn!("Retrying blocks range: {} - {}", start, end);
             }
         };
 
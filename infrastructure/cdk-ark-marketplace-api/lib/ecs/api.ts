@@ -108,6 +108,12 @@ function deployApiServices(
         process.env.REDIS_USERNAME || "defaultRedisUsername",
       REDIS_PASSWORD:
         process.env.REDIS_PASSWORD || "defaultRedisPassword",
+      STAGE_NAME: isProductionEnvironment ? "production" : "staging",
+      API_USER: process.env.API_USER || "",
+      API_PASSWORD: process.env.API_PASSWORD || "",
+      // The load balancer nodes get addresses of the VPC subnets, which
+      // change over time, so the whole VPC range may set X-Forwarded-For
+      TRUSTED_PROXIES: vpc.vpcCidrBlock,
     },
   });
 